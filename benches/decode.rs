@@ -0,0 +1,138 @@
+//! The latency-critical path from a fetched transaction to a serialized
+//! event: `cargo bench --bench decode`.
+
+use base64::Engine;
+use borsh::BorshDeserialize;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use raydium_v4_monitor::decode::{compiled_instructions, initialize2_instructions, Initialize2Data};
+use raydium_v4_monitor::events::{Event, PairType, PoolEvent, QuoteSide};
+use raydium_v4_monitor::raydium::INITIALIZE2_DISCRIMINATOR;
+use raydium_v4_monitor::tokens::parse_metadata;
+use raydium_v4_monitor::RAYDIUM_V4_PROGRAM_ID;
+use rust_decimal::Decimal;
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    instruction::{AccountMeta, Instruction},
+    message::Message,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::Transaction,
+};
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction, EncodedTransactionWithStatusMeta,
+    TransactionBinaryEncoding,
+};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+fn initialize2_data() -> Initialize2Data {
+    Initialize2Data {
+        discriminator: INITIALIZE2_DISCRIMINATOR,
+        nonce: 254,
+        open_time: 1_700_000_000,
+        init_pc_amount: 5_000_000_000,
+        init_coin_amount: 200_000_000_000,
+    }
+}
+
+/// A pool creation as launch tools send it: compute budget instructions
+/// followed by initialize2.
+fn launch_transaction() -> EncodedConfirmedTransactionWithStatusMeta {
+    let accounts = (0..21).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+    let initialize2 = Instruction::new_with_borsh(
+        Pubkey::from_str(RAYDIUM_V4_PROGRAM_ID).unwrap(),
+        &initialize2_data(),
+        accounts.iter().map(|key| AccountMeta::new(*key, false)).collect(),
+    );
+    let instructions = [
+        ComputeBudgetInstruction::set_compute_unit_limit(300_000),
+        ComputeBudgetInstruction::set_compute_unit_price(100_000),
+        initialize2,
+    ];
+    let tx = Transaction::new_unsigned(Message::new(&instructions, Some(&accounts[17])));
+    let blob = base64::engine::general_purpose::STANDARD.encode(bincode::serialize(&tx).unwrap());
+    EncodedConfirmedTransactionWithStatusMeta {
+        slot: 250_000_000,
+        block_time: Some(1_700_000_000),
+        transaction: EncodedTransactionWithStatusMeta {
+            transaction: EncodedTransaction::Binary(blob, TransactionBinaryEncoding::Base64),
+            meta: None,
+            version: None,
+        },
+    }
+}
+
+/// A Metaplex metadata account with the name, symbol and uri padded the way
+/// the program stores them.
+fn metadata_account() -> Vec<u8> {
+    let padded = |text: &str, len: usize| {
+        let mut bytes = (len as u32).to_le_bytes().to_vec();
+        bytes.extend(text.as_bytes());
+        bytes.resize(4 + len, 0);
+        bytes
+    };
+    let mut data = vec![4];
+    data.extend([0; 64]);
+    data.extend(padded("Dog Coin", 32));
+    data.extend(padded("DOG", 10));
+    data.extend(padded("https://ipfs.io/ipfs/QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG", 200));
+    data.resize(679, 0);
+    data
+}
+
+fn pool_event() -> Event {
+    Event::NewPool(Box::new(PoolEvent {
+        signature: Signature::new_unique(),
+        amm: Pubkey::new_unique(),
+        creator: Pubkey::new_unique(),
+        slot: 250_000_000,
+        coin_mint: Pubkey::new_unique(),
+        coin_name: Some("Dog Coin".to_string()),
+        coin_symbol: Some("DOG".to_string()),
+        coin_image: Some("https://ipfs.io/ipfs/QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG".to_string()),
+        pc_mint: spl_token::native_mint::id(),
+        pc_name: Some("Wrapped SOL".to_string()),
+        pc_symbol: Some("SOL".to_string()),
+        pc_image: None,
+        init_coin_amount: Decimal::new(200_000_000_000, 6),
+        init_pc_amount: Decimal::new(5_000_000_000, 9),
+        quote_side: QuoteSide::Pc,
+        pair_type: PairType::Sol,
+        liquidity_sol: Some(5.0),
+        price_impact: Some(0.1653),
+        flags: vec!["mint"],
+        open_time: 1_700_000_000,
+        block_delay_secs: Some(1),
+        launch_pool: None,
+        token_age_secs: None,
+        deployer: None,
+        enrichment: BTreeMap::new(),
+    }))
+}
+
+fn instruction_matching(c: &mut Criterion) {
+    let tx = launch_transaction();
+    c.bench_function("compiled_instructions", |b| b.iter(|| compiled_instructions(black_box(&tx)).unwrap()));
+    let instructions = compiled_instructions(&tx).unwrap().unwrap();
+    c.bench_function("initialize2_instructions", |b| {
+        b.iter(|| initialize2_instructions(black_box(&instructions)).count())
+    });
+}
+
+fn initialize2_parsing(c: &mut Criterion) {
+    let data = borsh::to_vec(&initialize2_data()).unwrap();
+    c.bench_function("initialize2_data", |b| b.iter(|| Initialize2Data::try_from_slice(black_box(&data)).unwrap()));
+}
+
+fn metadata_parsing(c: &mut Criterion) {
+    let data = metadata_account();
+    c.bench_function("parse_metadata", |b| b.iter(|| parse_metadata(black_box(&data))));
+}
+
+fn event_serialization(c: &mut Criterion) {
+    let event = pool_event();
+    c.bench_function("serialize_pool_event", |b| b.iter(|| serde_json::to_string(black_box(&event)).unwrap()));
+}
+
+criterion_group!(benches, instruction_matching, initialize2_parsing, metadata_parsing, event_serialization);
+criterion_main!(benches);
@@ -0,0 +1,17 @@
+//! Arbitrary instruction data and accounts into the initialize2 decoder.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use raydium_v4_monitor::decode::{initialize2, ResolvedInstruction};
+use solana_sdk::pubkey::Pubkey;
+
+fuzz_target!(|input: (Vec<u8>, Vec<[u8; 32]>)| {
+    let (data, keys) = input;
+    let ix = ResolvedInstruction {
+        program_id: Pubkey::new_unique(),
+        accounts: keys.into_iter().map(Pubkey::new_from_array).collect(),
+        data,
+    };
+    let _ = initialize2(&ix);
+});
@@ -0,0 +1,10 @@
+//! Arbitrary account data into the Metaplex metadata parser.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use raydium_v4_monitor::tokens::parse_metadata;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = parse_metadata(data);
+});
@@ -0,0 +1,32 @@
+//! Arbitrary bytes as a base64 transaction through instruction resolution
+//! and initialize2 matching, as fetched transactions are decoded.
+
+#![no_main]
+
+use base64::Engine;
+use libfuzzer_sys::fuzz_target;
+use raydium_v4_monitor::decode::{compiled_instructions, initialize2, initialize2_instructions};
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction, EncodedTransactionWithStatusMeta,
+    TransactionBinaryEncoding,
+};
+
+fuzz_target!(|data: &[u8]| {
+    let tx = EncodedConfirmedTransactionWithStatusMeta {
+        slot: 0,
+        block_time: None,
+        transaction: EncodedTransactionWithStatusMeta {
+            transaction: EncodedTransaction::Binary(
+                base64::engine::general_purpose::STANDARD.encode(data),
+                TransactionBinaryEncoding::Base64,
+            ),
+            meta: None,
+            version: None,
+        },
+    };
+    if let Ok(Some(instructions)) = compiled_instructions(&tx) {
+        for ix in initialize2_instructions(&instructions) {
+            let _ = initialize2(ix);
+        }
+    }
+});
@@ -0,0 +1,187 @@
+use crate::config::AdminWatchConfig;
+use crate::decode::{self, AdminInstruction, SetParams};
+use crate::events::{Event, PoolAdminEvent};
+use crate::raydium::{self, AmmInfo, NewPool};
+use crate::rpc::KeyRing;
+use crate::sinks::Sinks;
+use anyhow::{anyhow, Result};
+use solana_client::{
+    pubsub_client::PubsubClient,
+    rpc_client::RpcClient,
+    rpc_config::{RpcTransactionConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter},
+};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Subscribes to the logs of each new pool for a while and alerts on the
+/// Raydium admin instructions sent to it: setParams changing its status,
+/// fees, open time or another parameter, protocol fee withdrawals and order
+/// cancels. Swaps, deposits and withdrawals log a `ray_log` line and are
+/// skipped without fetching the transaction.
+pub struct AdminWatcher {
+    keys: Arc<KeyRing>,
+    rpc_client: Arc<RpcClient>,
+    sinks: Sinks,
+    config: AdminWatchConfig,
+}
+
+impl AdminWatcher {
+    pub fn new(keys: Arc<KeyRing>, rpc_client: Arc<RpcClient>, sinks: Sinks, config: AdminWatchConfig) -> Self {
+        Self { keys, rpc_client, sinks, config }
+    }
+
+    /// `name` is the pool's display name used in alerts.
+    pub async fn watch(self: Arc<Self>, pool: NewPool, name: String) {
+        let amm = pool.accounts.amm;
+        // the pubsub client blocks
+        let watcher = self.clone();
+        match tokio::task::spawn_blocking(move || watcher.run(&amm, &name)).await {
+            Ok(Ok(changes)) => info!(pool = %amm, changes, "Finished watching pool admin instructions"),
+            Ok(Err(e)) => warn!(pool = %amm, error = %e, "Stopped watching pool admin instructions"),
+            Err(e) => warn!(pool = %amm, error = %e, "Pool admin watcher panicked"),
+        }
+    }
+
+    fn run(&self, amm: &Pubkey, name: &str) -> Result<u64> {
+        // the pool as launched, updated with each change for the next one's
+        // previous value
+        let (mut info, _) = raydium::fetch_pool(&self.rpc_client, amm)?;
+        let (mut subscription, receiver) = PubsubClient::logs_subscribe(
+            &self.keys.ws_url("logsSubscribe"),
+            RpcTransactionLogsFilter::Mentions(vec![amm.to_string()]),
+            RpcTransactionLogsConfig { commitment: Some(CommitmentConfig::confirmed()) },
+        )?;
+        info!(pool = %amm, window_mins = self.config.window_mins, "Watching pool admin instructions");
+
+        let end = Instant::now() + Duration::from_secs(self.config.window_mins * 60);
+        let mut changes = 0;
+        let result = loop {
+            let Some(remaining) = end.checked_duration_since(Instant::now()) else {
+                break Ok(changes);
+            };
+            let log = match receiver.recv_timeout(remaining) {
+                Ok(log) => log,
+                Err(e) if e.is_timeout() => break Ok(changes),
+                Err(_) => break Err(anyhow!("log subscription closed")),
+            };
+            if log.value.err.is_some() || log.value.logs.iter().any(|line| line.contains("ray_log:")) {
+                continue;
+            }
+            let Ok(signature) = Signature::from_str(&log.value.signature) else {
+                continue;
+            };
+            let instructions = match self.instructions(&signature) {
+                Ok(instructions) => instructions,
+                Err(e) => {
+                    warn!(pool = %amm, signature = %signature, error = %e, "Failed to fetch transaction");
+                    continue;
+                }
+            };
+            for admin in instructions.iter().filter_map(|ix| decode::admin_instruction(ix, amm)) {
+                let event = change(&mut info, amm, name, signature, &admin);
+                warn!(pool = %amm, instruction = admin.name(), param = ?event.param, to = ?event.to, "Pool changed by admin");
+                changes += 1;
+                let sinks = self.sinks.clone();
+                tokio::spawn(async move { sinks.dispatch(&Event::PoolAdmin(event)).await });
+            }
+        };
+        let _ = subscription.shutdown();
+        result
+    }
+
+    fn instructions(&self, signature: &Signature) -> Result<Vec<decode::ResolvedInstruction>> {
+        match decode::compiled_instructions(&self.fetch(signature, UiTransactionEncoding::Base64)?)? {
+            Some(instructions) => Ok(instructions),
+            // lookup tables the node didn't resolve
+            None => decode::parsed_instructions(&self.fetch(signature, UiTransactionEncoding::JsonParsed)?),
+        }
+    }
+
+    fn fetch(&self, signature: &Signature, encoding: UiTransactionEncoding) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+        Ok(self.rpc_client.get_transaction_with_config(
+            signature,
+            RpcTransactionConfig {
+                encoding: Some(encoding),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )?)
+    }
+}
+
+/// The event for `admin`, applying the change to `info`.
+fn change(
+    info: &mut AmmInfo,
+    amm: &Pubkey,
+    name: &str,
+    signature: Signature,
+    admin: &AdminInstruction,
+) -> PoolAdminEvent {
+    let (param, from, to) = match admin {
+        AdminInstruction::SetParams(set) => {
+            let (from, to) = values(info, set);
+            (Some(set.param_name().to_string()), from, to)
+        }
+        AdminInstruction::WithdrawPnl | AdminInstruction::AdminCancelOrders => (None, None, None),
+    };
+    PoolAdminEvent {
+        amm: *amm,
+        name: name.to_string(),
+        signature,
+        instruction: admin.name().to_string(),
+        param,
+        from,
+        to,
+    }
+}
+
+/// The parameter's value before and after `set`, for those the pool state
+/// holds.
+fn values(info: &mut AmmInfo, set: &SetParams) -> (Option<String>, Option<String>) {
+    match (set.param_name(), set.value, set.swap_fee) {
+        ("status", Some(status), _) => {
+            let from = info.status_name().to_string();
+            info.status = status;
+            (Some(from), Some(raydium::status_name(status).to_string()))
+        }
+        ("fees", _, Some((numerator, denominator))) => {
+            let from = format!("{}%", info.swap_fee_percent());
+            (info.swap_fee_numerator, info.swap_fee_denominator) = (numerator, denominator);
+            (Some(from), Some(format!("{}%", raydium::fee_percent(numerator, denominator))))
+        }
+        ("open_time", Some(open_time), _) => {
+            let from = info.pool_open_time.to_string();
+            info.pool_open_time = open_time;
+            (Some(from), Some(open_time.to_string()))
+        }
+        ("amm_owner", _, _) => (None, set.new_owner.map(|owner| owner.to_string())),
+        (_, value, _) => (None, value.map(|value| value.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info() -> AmmInfo {
+        AmmInfo::unpack(&[0; raydium::AMM_INFO_LEN]).unwrap()
+    }
+
+    #[test]
+    fn status_and_fee_changes_report_the_previous_value() {
+        let mut info = AmmInfo { status: 6, swap_fee_numerator: 25, swap_fee_denominator: 10_000, ..info() };
+        let disable = SetParams { param: 0, value: Some(3), swap_fee: None, new_owner: None };
+        assert_eq!(values(&mut info, &disable), (Some("swap_only".to_string()), Some("withdraw_only".to_string())));
+        assert_eq!(info.status, 3);
+
+        let fees = SetParams { param: 9, value: None, swap_fee: Some((100, 10_000)), new_owner: None };
+        assert_eq!(values(&mut info, &fees), (Some("0.25%".to_string()), Some("1%".to_string())));
+
+        let depth = SetParams { param: 3, value: Some(5), swap_fee: None, new_owner: None };
+        assert_eq!(values(&mut info, &depth), (None, Some("5".to_string())));
+    }
+}
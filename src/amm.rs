@@ -0,0 +1,231 @@
+use rust_decimal::Decimal;
+
+/// Default Raydium V4 trade fee, 0.25%.
+pub const RAYDIUM_FEE_BPS: u64 = 25;
+const BPS_DENOMINATOR: u128 = 10_000;
+
+/// Estimated result of a swap against a constant-product pool.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SwapEstimate {
+    pub amount_out: u64,
+    // change of the marginal price caused by the swap, 0.032 means 3.2%
+    pub price_impact: f64,
+}
+
+/// Output amount by x * y = k after deducting the fee.
+pub fn constant_product_out(amount_in: u64, reserve_in: u64, reserve_out: u64, fee_bps: u64) -> u64 {
+    let amount_in = amount_in as u128 * (BPS_DENOMINATOR - fee_bps.min(10_000) as u128) / BPS_DENOMINATOR;
+    let denominator = reserve_in as u128 + amount_in;
+    if denominator == 0 {
+        return 0;
+    }
+    (reserve_out as u128 * amount_in / denominator) as u64
+}
+
+/// Estimates the output amount and price impact.
+pub fn estimate_swap(amount_in: u64, reserve_in: u64, reserve_out: u64, fee_bps: u64) -> SwapEstimate {
+    let amount_out = constant_product_out(amount_in, reserve_in, reserve_out, fee_bps);
+    // price of the output token in units of the input token
+    let spot_before = reserve_in as f64 / reserve_out as f64;
+    let spot_after = (reserve_in as f64 + amount_in as f64) / (reserve_out as f64 - amount_out as f64);
+    let price_impact = if reserve_in == 0 || reserve_out == 0 || amount_out >= reserve_out {
+        f64::INFINITY
+    } else {
+        spot_after / spot_before - 1.0
+    };
+    SwapEstimate { amount_out, price_impact }
+}
+
+/// Swap direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapDirection {
+    CoinToPc,
+    PcToCoin,
+}
+
+/// Pool state needed for quoting: tradable reserves and the swap fee.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolState {
+    pub coin_reserve: u64,
+    pub pc_reserve: u64,
+    pub swap_fee_numerator: u64,
+    pub swap_fee_denominator: u64,
+}
+
+impl PoolState {
+    /// Uses the default Raydium V4 fee.
+    pub fn from_reserves(coin_reserve: u64, pc_reserve: u64) -> Self {
+        Self {
+            coin_reserve,
+            pc_reserve,
+            swap_fee_numerator: RAYDIUM_FEE_BPS,
+            swap_fee_denominator: BPS_DENOMINATOR as u64,
+        }
+    }
+}
+
+/// Result of [`quote_swap`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SwapQuote {
+    pub amount_in: u64,
+    pub fee_amount: u64,
+    pub amount_out: u64,
+    pub price_impact: f64,
+}
+
+impl SwapQuote {
+    /// Minimum output after applying `slippage_bps`.
+    pub fn minimum_out(&self, slippage_bps: u64) -> u64 {
+        (self.amount_out as u128 * (BPS_DENOMINATOR - slippage_bps.min(10_000) as u128) / BPS_DENOMINATOR) as u64
+    }
+}
+
+/// Quotes a swap the way Raydium V4 `swap_base_in` does: the fee is rounded
+/// up and deducted, then the output follows the constant-product curve.
+pub fn quote_swap(pool: &PoolState, amount_in: u64, direction: SwapDirection) -> SwapQuote {
+    let (reserve_in, reserve_out) = match direction {
+        SwapDirection::CoinToPc => (pool.coin_reserve, pool.pc_reserve),
+        SwapDirection::PcToCoin => (pool.pc_reserve, pool.coin_reserve),
+    };
+    let fee_amount = if pool.swap_fee_denominator == 0 {
+        0
+    } else {
+        (amount_in as u128 * pool.swap_fee_numerator as u128).div_ceil(pool.swap_fee_denominator as u128) as u64
+    };
+    let amount_in_less_fee = amount_in.saturating_sub(fee_amount);
+    let estimate = estimate_swap(amount_in_less_fee, reserve_in, reserve_out, 0);
+    SwapQuote {
+        amount_in,
+        fee_amount,
+        amount_out: estimate.amount_out,
+        price_impact: estimate.price_impact,
+    }
+}
+
+/// Converts a raw token amount to UI units without going through f64, which
+/// loses precision above 2^53. Decimal holds at most 28 fractional digits;
+/// further digits are truncated.
+pub fn ui_amount(amount: u64, decimals: u8) -> Decimal {
+    let scale = u32::from(decimals);
+    if scale <= Decimal::MAX_SCALE {
+        return Decimal::from_i128_with_scale(amount.into(), scale).normalize();
+    }
+    let divisor = 10u128.checked_pow(scale - Decimal::MAX_SCALE).unwrap_or(u128::MAX);
+    Decimal::from_i128_with_scale((u128::from(amount) / divisor) as i128, Decimal::MAX_SCALE).normalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// A raw reserve for a token with random decimals: 1 to 10^9 whole
+    /// tokens scaled by 0 to 9 decimals.
+    fn reserve() -> impl Strategy<Value = u64> {
+        (1..=1_000_000_000u64, 0..=9u32).prop_map(|(ui, decimals)| ui * 10u64.pow(decimals))
+    }
+
+    fn amount() -> impl Strategy<Value = u64> {
+        0..=1_000_000_000_000_000_000u64
+    }
+
+    fn pool() -> impl Strategy<Value = PoolState> {
+        (reserve(), reserve(), 0..=100u64).prop_map(|(coin_reserve, pc_reserve, fee_bps)| PoolState {
+            coin_reserve,
+            pc_reserve,
+            swap_fee_numerator: fee_bps,
+            swap_fee_denominator: BPS_DENOMINATOR as u64,
+        })
+    }
+
+    fn direction() -> impl Strategy<Value = SwapDirection> {
+        prop_oneof![Just(SwapDirection::CoinToPc), Just(SwapDirection::PcToCoin)]
+    }
+
+    proptest! {
+        #[test]
+        fn output_never_reaches_reserve(amount_in in amount(), reserve_in in reserve(), reserve_out in reserve(), fee_bps in 0..=10_000u64) {
+            prop_assert!(constant_product_out(amount_in, reserve_in, reserve_out, fee_bps) < reserve_out);
+        }
+
+        #[test]
+        fn output_grows_with_input(a in amount(), b in amount(), reserve_in in reserve(), reserve_out in reserve(), fee_bps in 0..=100u64) {
+            let (small, large) = (a.min(b), a.max(b));
+            prop_assert!(
+                constant_product_out(small, reserve_in, reserve_out, fee_bps)
+                    <= constant_product_out(large, reserve_in, reserve_out, fee_bps)
+            );
+        }
+
+        #[test]
+        fn output_shrinks_with_fee(amount_in in amount(), reserve_in in reserve(), reserve_out in reserve(), a in 0..=10_000u64, b in 0..=10_000u64) {
+            prop_assert!(
+                constant_product_out(amount_in, reserve_in, reserve_out, a.max(b))
+                    <= constant_product_out(amount_in, reserve_in, reserve_out, a.min(b))
+            );
+        }
+
+        #[test]
+        fn swap_never_decreases_k(amount_in in amount(), reserve_in in reserve(), reserve_out in reserve(), fee_bps in 0..=100u64) {
+            let amount_out = constant_product_out(amount_in, reserve_in, reserve_out, fee_bps);
+            let k_before = reserve_in as u128 * reserve_out as u128;
+            let k_after = (reserve_in as u128 + amount_in as u128) * (reserve_out - amount_out) as u128;
+            prop_assert!(k_after >= k_before);
+        }
+
+        #[test]
+        fn price_impact_is_nonnegative_and_grows_with_input(a in amount(), b in amount(), reserve_in in reserve(), reserve_out in reserve()) {
+            let (small, large) = (a.min(b), a.max(b));
+            let small = estimate_swap(small, reserve_in, reserve_out, RAYDIUM_FEE_BPS).price_impact;
+            let large = estimate_swap(large, reserve_in, reserve_out, RAYDIUM_FEE_BPS).price_impact;
+            // f64 rounding of the spot prices
+            prop_assert!(small >= -1e-9);
+            prop_assert!(large >= small - 1e-9, "{} < {}", large, small);
+        }
+
+        #[test]
+        fn quote_fee_is_rounded_up_and_deducted(pool in pool(), amount_in in amount(), direction in direction()) {
+            let quote = quote_swap(&pool, amount_in, direction);
+            let owed = amount_in as u128 * pool.swap_fee_numerator as u128;
+            let denominator = pool.swap_fee_denominator as u128;
+            prop_assert_eq!(quote.amount_in, amount_in);
+            prop_assert!(quote.fee_amount as u128 * denominator >= owed);
+            prop_assert!(quote.fee_amount == 0 || (quote.fee_amount as u128 - 1) * denominator < owed);
+            prop_assert!(quote.fee_amount <= amount_in);
+
+            let (reserve_in, reserve_out) = match direction {
+                SwapDirection::CoinToPc => (pool.coin_reserve, pool.pc_reserve),
+                SwapDirection::PcToCoin => (pool.pc_reserve, pool.coin_reserve),
+            };
+            prop_assert_eq!(quote.amount_out, constant_product_out(amount_in - quote.fee_amount, reserve_in, reserve_out, 0));
+            prop_assert!(quote.amount_out <= constant_product_out(amount_in, reserve_in, reserve_out, 0));
+        }
+
+        #[test]
+        fn quote_never_drains_the_pool(pool in pool(), amount_in in amount(), direction in direction()) {
+            let quote = quote_swap(&pool, amount_in, direction);
+            let reserve_out = match direction {
+                SwapDirection::CoinToPc => pool.pc_reserve,
+                SwapDirection::PcToCoin => pool.coin_reserve,
+            };
+            prop_assert!(quote.amount_out < reserve_out);
+            prop_assert!(quote.price_impact.is_finite());
+        }
+
+        #[test]
+        fn minimum_out_shrinks_with_slippage(pool in pool(), amount_in in amount(), a in 0..=20_000u64, b in 0..=20_000u64) {
+            let quote = quote_swap(&pool, amount_in, SwapDirection::PcToCoin);
+            prop_assert_eq!(quote.minimum_out(0), quote.amount_out);
+            prop_assert!(quote.minimum_out(a.max(b)) <= quote.minimum_out(a.min(b)));
+            prop_assert!(quote.minimum_out(a) <= quote.amount_out);
+            prop_assert_eq!(quote.minimum_out(10_000), 0);
+        }
+
+        #[test]
+        fn ui_amount_keeps_every_digit(amount in any::<u64>(), decimals in 0..=28u8) {
+            let mut ui = ui_amount(amount, decimals);
+            ui.rescale(decimals.into());
+            prop_assert_eq!(ui.mantissa(), amount as i128);
+        }
+    }
+}
@@ -0,0 +1,625 @@
+use crate::auth::{self, ApiTokens, Client, StreamSlot};
+use crate::config::{ApiConfig, ApiScope, SlowConsumers};
+use crate::events::{self, Event};
+use crate::filter::{EventFilter, FilterParams};
+use crate::history::{EventHistory, LiveEvent};
+use crate::sniper::unix_now;
+use crate::store::{PoolCursor, PoolRecord, Store, StoredEvent};
+use crate::volume::VOLUME_WINDOWS_SECS;
+use crate::webhooks::Webhooks;
+#[cfg(feature = "openapi")]
+use crate::{
+    store::{HolderSnapshot, PerformancePoint, PnlSummary, PoolVolume, Position},
+    webhooks::Webhook,
+};
+use axum::{
+    extract::{Path, Query, State},
+    Extension,
+    http::{HeaderMap, StatusCode},
+    middleware,
+    response::{
+        sse::{self, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::{delete, get},
+    Json, Router,
+};
+use futures::stream::{self, Stream};
+use tracing::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::time::Instant;
+
+// stored events read per store query while catching up
+const REPLAY_BATCH: usize = 500;
+// pools per page when the query sets no limit, and the most it may ask for
+const DEFAULT_PAGE: usize = 100;
+const MAX_PAGE: usize = 1000;
+const WEBHOOKS_DISABLED: &str = "Webhooks are disabled";
+
+#[derive(Clone)]
+struct ApiState {
+    store: Arc<Store>,
+    history: Option<EventHistory>,
+    webhooks: Option<Webhooks>,
+    slow_consumers: SlowConsumers,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams), into_params(parameter_in = Query))]
+struct PositionsQuery {
+    wallet: Option<String>,
+    #[serde(default)]
+    open: bool,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams), into_params(parameter_in = Query))]
+struct PnlQuery {
+    wallet: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams), into_params(parameter_in = Query))]
+struct HoldersQuery {
+    amm: String,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams), into_params(parameter_in = Query))]
+struct PerformanceQuery {
+    amm: String,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams), into_params(parameter_in = Query))]
+struct PoolsQuery {
+    creator: Option<String>,
+    quote_mint: Option<String>,
+    // unix seconds of detection
+    since: Option<u64>,
+    until: Option<u64>,
+    // next_cursor of the previous page
+    cursor: Option<String>,
+    limit: Option<usize>,
+}
+
+/// Pools newest first; `next_cursor` is absent on the last page.
+#[derive(Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+struct PoolPage {
+    pools: Vec<PoolRecord>,
+    next_cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams), into_params(parameter_in = Query))]
+struct VolumeQuery {
+    amm: Option<String>,
+}
+
+/// Where a consumer of `GET /events` starts (without any of them it only
+/// gets live events), and the events it wants.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams), into_params(parameter_in = Query))]
+struct EventsQuery {
+    // the id of the last event received, like the Last-Event-ID header
+    after: Option<i64>,
+    since_slot: Option<u64>,
+    // unix seconds
+    since: Option<u64>,
+    // comma-separated lists
+    types: Option<String>,
+    quote: Option<String>,
+    // sol, stable or token
+    pairs: Option<String>,
+    min_liquidity_sol: Option<f64>,
+    max_risks: Option<usize>,
+    watchlist: Option<String>,
+}
+
+/// The body of `POST /webhooks`; the filter takes the criteria of
+/// `GET /events`.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+struct NewWebhook {
+    url: String,
+    secret: Option<String>,
+    #[serde(default)]
+    filter: FilterParams,
+}
+
+/// `history` serves `GET /events` and `webhooks` the `/webhooks` routes,
+/// which answer 404 without them. With `[[api.tokens]]` every route needs
+/// a bearer token with its scope.
+pub async fn serve(config: ApiConfig, store: Arc<Store>, history: Option<EventHistory>, webhooks: Option<Webhooks>) {
+    let tokens = ApiTokens::new(config.tokens.clone());
+    if tokens.is_empty() {
+        warn!(listen = %config.listen, "REST API has no [[api.tokens]], every endpoint is open");
+    }
+    let state = ApiState { store: store.clone(), history, webhooks, slow_consumers: config.slow_consumers };
+    let stats = Router::new()
+        .route("/positions", get(positions))
+        .route("/pnl", get(pnl))
+        .route("/pools", get(pools))
+        .route("/volume", get(volume))
+        .route("/holders", get(holders))
+        .route("/performance", get(performance))
+        .with_state(state.clone());
+    #[cfg(feature = "graphql")]
+    let stats = stats.merge(crate::graphql::router(store));
+    let events = Router::new()
+        .route("/schema/events.json", get(event_schema))
+        .route("/events", get(events))
+        .with_state(state.clone());
+    let webhooks = Router::new()
+        .route("/webhooks", get(list_webhooks).post(register_webhook))
+        .route("/webhooks/:id", delete(remove_webhook))
+        .with_state(state);
+    let app = Router::new()
+        .merge(docs())
+        .merge(stats.route_layer(middleware::from_fn_with_state((tokens.clone(), ApiScope::ReadStats), auth::authorize)))
+        .merge(events.route_layer(middleware::from_fn_with_state((tokens.clone(), ApiScope::ReadEvents), auth::authorize)))
+        .merge(webhooks.route_layer(middleware::from_fn_with_state((tokens, ApiScope::ManageWebhooks), auth::authorize)));
+
+    let listen = config.listen;
+    let listener = match tokio::net::TcpListener::bind(&listen).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!(listen = %listen, error = %e, "Failed to bind REST API");
+            return;
+        }
+    };
+    info!(listen = %listen, "REST API listening");
+    if let Err(e) = axum::serve(listener, app).await {
+        error!(error = %e, "REST API stopped");
+    }
+}
+
+/// `GET /openapi.json`, the OpenAPI document of the routes, for generating
+/// clients. It describes the API without any of its data, so it is served
+/// without a token.
+#[cfg(feature = "openapi")]
+fn docs() -> Router {
+    use utoipa::OpenApi;
+    Router::new().route("/openapi.json", get(|| async { Json(ApiDoc::openapi()) }))
+}
+
+#[cfg(not(feature = "openapi"))]
+fn docs() -> Router {
+    Router::new()
+}
+
+#[cfg(feature = "openapi")]
+#[derive(utoipa::OpenApi)]
+#[openapi(
+    info(title = "Raydium V4 monitor"),
+    paths(
+        positions,
+        pnl,
+        pools,
+        volume,
+        holders,
+        performance,
+        event_schema,
+        events,
+        list_webhooks,
+        register_webhook,
+        remove_webhook
+    ),
+    components(schemas(
+        Position,
+        PnlSummary,
+        PoolPage,
+        crate::store::PoolRecord,
+        PoolVolume,
+        crate::store::WindowVolume,
+        HolderSnapshot,
+        crate::store::TopHolder,
+        PerformancePoint,
+        Webhook,
+        NewWebhook,
+        FilterParams
+    )),
+    modifiers(&BearerAuth),
+    security(("bearer" = []))
+)]
+struct ApiDoc;
+
+/// `[[api.tokens]]` as the document's security scheme.
+#[cfg(feature = "openapi")]
+struct BearerAuth;
+
+#[cfg(feature = "openapi")]
+impl utoipa::Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+        let components = openapi.components.get_or_insert_with(Default::default);
+        let bearer = HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build();
+        components.add_security_scheme("bearer", SecurityScheme::Http(bearer));
+    }
+}
+
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        get,
+        path = "/positions",
+        tag = "stats",
+        params(PositionsQuery),
+        responses((status = 200, body = Vec<Position>)),
+    )
+)]
+async fn positions(State(state): State<ApiState>, Query(query): Query<PositionsQuery>) -> Response {
+    match state.store.positions(query.wallet.as_deref(), query.open) {
+        Ok(positions) => Json(positions).into_response(),
+        Err(e) => internal_error(e),
+    }
+}
+
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        get,
+        path = "/pnl",
+        tag = "stats",
+        params(PnlQuery),
+        responses((status = 200, body = Vec<PnlSummary>)),
+    )
+)]
+async fn pnl(State(state): State<ApiState>, Query(query): Query<PnlQuery>) -> Response {
+    match state.store.pnl_summary(query.wallet.as_deref()) {
+        Ok(summary) => Json(summary).into_response(),
+        Err(e) => internal_error(e),
+    }
+}
+
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        get,
+        path = "/pools",
+        tag = "stats",
+        params(PoolsQuery),
+        responses((status = 200, body = PoolPage), (status = 400, description = "Invalid cursor")),
+    )
+)]
+async fn pools(State(state): State<ApiState>, Query(query): Query<PoolsQuery>) -> Response {
+    let cursor = match query.cursor.as_deref().map(str::parse::<PoolCursor>).transpose() {
+        Ok(cursor) => cursor,
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE).clamp(1, MAX_PAGE);
+    let result = state.store.pools(
+        query.creator.as_deref(),
+        query.quote_mint.as_deref(),
+        query.since.unwrap_or(0),
+        query.until.unwrap_or(u64::MAX),
+        cursor.as_ref(),
+        limit,
+    );
+    match result {
+        Ok(pools) => {
+            let next_cursor = pools.last().filter(|_| pools.len() == limit).map(|pool| pool.cursor().to_string());
+            Json(PoolPage { pools, next_cursor }).into_response()
+        }
+        Err(e) => internal_error(e),
+    }
+}
+
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        get,
+        path = "/volume",
+        tag = "stats",
+        params(VolumeQuery),
+        responses((status = 200, body = Vec<PoolVolume>)),
+    )
+)]
+async fn volume(State(state): State<ApiState>, Query(query): Query<VolumeQuery>) -> Response {
+    match state.store.pool_volumes(unix_now(), query.amm.as_deref(), VOLUME_WINDOWS_SECS) {
+        Ok(volumes) => Json(volumes).into_response(),
+        Err(e) => internal_error(e),
+    }
+}
+
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        get,
+        path = "/holders",
+        tag = "stats",
+        params(HoldersQuery),
+        responses((status = 200, body = Vec<HolderSnapshot>)),
+    )
+)]
+async fn holders(State(state): State<ApiState>, Query(query): Query<HoldersQuery>) -> Response {
+    match state.store.holder_snapshots(&query.amm) {
+        Ok(snapshots) => Json(snapshots).into_response(),
+        Err(e) => internal_error(e),
+    }
+}
+
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        get,
+        path = "/performance",
+        tag = "stats",
+        params(PerformanceQuery),
+        responses((status = 200, body = Vec<PerformancePoint>)),
+    )
+)]
+async fn performance(State(state): State<ApiState>, Query(query): Query<PerformanceQuery>) -> Response {
+    match state.store.performance(&query.amm) {
+        Ok(points) => Json(points).into_response(),
+        Err(e) => internal_error(e),
+    }
+}
+
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        get,
+        path = "/schema/events.json",
+        tag = "events",
+        responses(
+            (status = 200, description = "JSON Schema of the events", content_type = "application/json"),
+        ),
+    )
+)]
+async fn event_schema() -> Response {
+    Json(events::schema()).into_response()
+}
+
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        get,
+        path = "/events",
+        tag = "events",
+        params(
+            EventsQuery,
+            ("Last-Event-ID" = Option<i64>, Header, description = "The id of the last event received"),
+        ),
+        responses(
+            (status = 200, description = "Server-sent events, each with its id", content_type = "text/event-stream"),
+            (status = 400, description = "Invalid filter"),
+            (status = 404, description = "Event history is disabled"),
+            (status = 429, description = "Over the token's max_streams"),
+        ),
+    )
+)]
+/// Server-sent events: the stored events from the requested point, then
+/// live ones as they are emitted. Each carries its id, so a reconnecting
+/// client resumes exactly where it left off.
+async fn events(
+    State(state): State<ApiState>,
+    client: Option<Extension<Arc<Client>>>,
+    Query(query): Query<EventsQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(history) = state.history else {
+        return (StatusCode::NOT_FOUND, "Event history is disabled ([api] history_days = 0)").into_response();
+    };
+    let client = client.map(|Extension(client)| client);
+    let slot = match &client {
+        Some(client) => match client.open_stream() {
+            Some(slot) => Some(slot),
+            None => {
+                warn!(token = client.name(), "Event stream over the token's max_streams");
+                return (StatusCode::TOO_MANY_REQUESTS, "Too many open event streams for this token").into_response();
+            }
+        },
+        None => None,
+    };
+    let filter = match EventFilter::parse(
+        query.types.as_deref(),
+        query.quote.as_deref(),
+        query.pairs.as_deref(),
+        query.min_liquidity_sol,
+        query.max_risks,
+        query.watchlist.as_deref(),
+    ) {
+        Ok(filter) => filter,
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
+    // before reading the store, so nothing emitted meanwhile is missed
+    let live = history.subscribe();
+    let last_event_id = headers.get("last-event-id").and_then(|id| id.to_str().ok()).and_then(|id| id.parse().ok());
+    let after = match (query.after.or(last_event_id), query.since_slot, query.since) {
+        (Some(after), _, _) => Some(after),
+        (None, None, None) => None,
+        (None, slot, since) => match history.store().first_event_id(slot, since) {
+            Ok(first) => first.map(|id| id - 1),
+            Err(e) => return internal_error(e),
+        },
+    };
+    let replay = EventReplay {
+        history,
+        live,
+        filter,
+        caught_up: after.is_none(),
+        after,
+        backlog: VecDeque::new(),
+        slow_consumers: state.slow_consumers,
+        interval: client.as_ref().and_then(|client| client.event_interval()),
+        next_send: Instant::now(),
+        client,
+        _slot: slot,
+    };
+    Sse::new(replay.into_stream()).keep_alive(KeepAlive::default()).into_response()
+}
+
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        get,
+        path = "/webhooks",
+        tag = "webhooks",
+        responses((status = 200, body = Vec<Webhook>)),
+    )
+)]
+async fn list_webhooks(State(state): State<ApiState>) -> Response {
+    let Some(webhooks) = state.webhooks else {
+        return (StatusCode::NOT_FOUND, WEBHOOKS_DISABLED).into_response();
+    };
+    Json(webhooks.list()).into_response()
+}
+
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        post,
+        path = "/webhooks",
+        tag = "webhooks",
+        request_body = NewWebhook,
+        responses(
+            (status = 201, body = Webhook),
+            (status = 400, description = "Invalid url or filter"),
+        ),
+    )
+)]
+/// Registers a destination for events from the next one dispatched; answers
+/// 201 with it, without the secret.
+async fn register_webhook(State(state): State<ApiState>, Json(request): Json<NewWebhook>) -> Response {
+    let Some(webhooks) = state.webhooks else {
+        return (StatusCode::NOT_FOUND, WEBHOOKS_DISABLED).into_response();
+    };
+    if let Err(e) = Webhooks::validate(&request.url, &request.filter) {
+        return (StatusCode::BAD_REQUEST, e.to_string()).into_response();
+    }
+    match webhooks.register(&request.url, request.secret.as_deref().filter(|secret| !secret.is_empty()), request.filter) {
+        Ok(webhook) => (StatusCode::CREATED, Json(webhook)).into_response(),
+        Err(e) => internal_error(e),
+    }
+}
+
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        delete,
+        path = "/webhooks/{id}",
+        tag = "webhooks",
+        params(("id" = i64, Path, description = "The id returned when registering it")),
+        responses(
+            (status = 204, description = "Removed"),
+            (status = 404, description = "No such webhook"),
+        ),
+    )
+)]
+async fn remove_webhook(State(state): State<ApiState>, Path(id): Path<i64>) -> Response {
+    let Some(webhooks) = state.webhooks else {
+        return (StatusCode::NOT_FOUND, WEBHOOKS_DISABLED).into_response();
+    };
+    match webhooks.remove(id) {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => internal_error(e),
+    }
+}
+
+struct EventReplay {
+    history: EventHistory,
+    live: broadcast::Receiver<Arc<LiveEvent>>,
+    filter: EventFilter,
+    // the id of the last event sent or filtered out
+    after: Option<i64>,
+    backlog: VecDeque<StoredEvent>,
+    caught_up: bool,
+    slow_consumers: SlowConsumers,
+    // spacing under the token's max_events_per_sec
+    interval: Option<Duration>,
+    next_send: Instant,
+    client: Option<Arc<Client>>,
+    _slot: Option<StreamSlot>,
+}
+
+impl EventReplay {
+    fn into_stream(self) -> impl Stream<Item = Result<sse::Event, Infallible>> {
+        stream::unfold(self, |mut replay| async move {
+            let event = replay.next().await?;
+            if let Some(interval) = replay.interval {
+                tokio::time::sleep_until(replay.next_send).await;
+                replay.next_send = Instant::now().max(replay.next_send) + interval;
+            }
+            Some((Ok(sse::Event::default().id(event.id.to_string()).data(&event.event)), replay))
+        })
+    }
+
+    /// Stored events until there are no more, then live ones, skipping those
+    /// already read from the store and those the filter rejects. A consumer
+    /// falling too far behind the live events catches up from the store
+    /// again, or is dropped with `slow_consumers = "drop"`.
+    async fn next(&mut self) -> Option<StoredEvent> {
+        loop {
+            if let Some(stored) = self.backlog.pop_front() {
+                self.after = Some(stored.id);
+                match serde_json::from_str::<Event>(&stored.event) {
+                    Ok(event) if !self.filter.matches(&event) => continue,
+                    Ok(_) => return Some(stored),
+                    Err(e) => warn!(id = stored.id, error = %e, "Skipping unreadable stored event"),
+                }
+                continue;
+            }
+            if !self.caught_up {
+                match self.history.store().events_after(self.after.unwrap_or(0), REPLAY_BATCH) {
+                    Ok(events) if events.is_empty() => self.caught_up = true,
+                    Ok(events) => self.backlog.extend(events),
+                    Err(e) => {
+                        error!(error = %e, "Failed to read the event history");
+                        return None;
+                    }
+                }
+                continue;
+            }
+            match self.live.recv().await {
+                Ok(live) if self.after.is_none_or(|after| live.stored.id > after) => {
+                    self.after = Some(live.stored.id);
+                    if self.filter.matches(&live.event) {
+                        return Some(live.stored.clone());
+                    }
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(skipped)) => {
+                    let token = self.client.as_ref().map(|client| client.name());
+                    if self.slow_consumers == SlowConsumers::Drop {
+                        warn!(token, skipped, "Event stream consumer fell behind, dropping it");
+                        return None;
+                    }
+                    warn!(token, skipped, "Event stream consumer fell behind, catching up from the store");
+                    self.caught_up = self.after.is_none();
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+fn internal_error(e: anyhow::Error) -> Response {
+    error!(error = %e, "REST API request failed");
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+}
+
+#[cfg(all(test, feature = "openapi"))]
+mod tests {
+    use super::*;
+    use utoipa::OpenApi;
+
+    #[test]
+    fn openapi_documents_every_route() {
+        let doc = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let paths = doc["paths"].as_object().unwrap();
+        for path in ["/positions", "/pnl", "/pools", "/volume", "/holders", "/performance", "/events", "/webhooks"] {
+            assert!(paths.contains_key(path), "{} is missing", path);
+        }
+        assert!(paths["/webhooks/{id}"]["delete"].is_object());
+        assert_eq!(doc["components"]["securitySchemes"]["bearer"]["scheme"], "bearer");
+        assert!(doc["components"]["schemas"]["PoolPage"]["properties"]["next_cursor"].is_object());
+    }
+}
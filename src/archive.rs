@@ -0,0 +1,103 @@
+use crate::cloud::{aws::Credentials, google::ServiceAccountAuth};
+use crate::config::{ArchiveBackend, ArchiveConfig};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use solana_sdk::signature::Signature;
+use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
+use tracing::{info, warn};
+use url::Url;
+
+const GCS_SCOPE: &str = "https://www.googleapis.com/auth/devstorage.read_write";
+
+/// A transaction serialized for upload, taken before decoding consumes it.
+pub struct ArchivedTransaction {
+    signature: Signature,
+    key: String,
+    body: Vec<u8>,
+}
+
+enum Backend {
+    S3 { credentials: Credentials },
+    Gcs { auth: Box<ServiceAccountAuth> },
+}
+
+/// Stores the fetched transaction (base64 with meta, as returned by
+/// getTransaction) of every detected launch as JSON in S3 or GCS under
+/// `<prefix>/YYYY/MM/DD/<signature>.json`, keyed by block time.
+pub struct Archive {
+    http: reqwest::Client,
+    config: ArchiveConfig,
+    backend: Backend,
+}
+
+impl Archive {
+    pub fn new(config: ArchiveConfig) -> Result<Self> {
+        if config.bucket.is_empty() {
+            return Err(anyhow!("[archive] requires bucket"));
+        }
+        let backend = match config.backend {
+            ArchiveBackend::S3 => Backend::S3 { credentials: Credentials::from_env("[archive]")? },
+            ArchiveBackend::Gcs => {
+                let credentials_path =
+                    config.credentials_path.as_deref().ok_or_else(|| anyhow!("[archive] with backend = \"gcs\" requires credentials_path"))?;
+                Backend::Gcs { auth: Box::new(ServiceAccountAuth::from_file(credentials_path, GCS_SCOPE)?) }
+            }
+        };
+        Ok(Self { http: reqwest::Client::new(), config, backend })
+    }
+
+    pub fn prepare(&self, signature: Signature, tx: &EncodedConfirmedTransactionWithStatusMeta) -> Result<ArchivedTransaction> {
+        Ok(ArchivedTransaction { signature, key: self.key(&signature, tx.block_time), body: serde_json::to_vec(tx)? })
+    }
+
+    /// Uploads the transaction, logging failures; launches are reported
+    /// whether or not archiving succeeds.
+    pub async fn store(&self, tx: ArchivedTransaction) {
+        match self.upload(&tx.key, tx.body).await {
+            Ok(()) => info!(signature = %tx.signature, key = %tx.key, "Archived transaction"),
+            Err(e) => warn!(signature = %tx.signature, key = %tx.key, error = %e, "Failed to archive transaction"),
+        }
+    }
+
+    fn key(&self, signature: &Signature, block_time: Option<i64>) -> String {
+        let time = block_time.and_then(|time| DateTime::from_timestamp(time, 0)).unwrap_or_else(Utc::now);
+        let prefix = self.config.prefix.trim_matches('/');
+        let path = format!("{}/{}.json", time.format("%Y/%m/%d"), signature);
+        if prefix.is_empty() { path } else { format!("{}/{}", prefix, path) }
+    }
+
+    async fn upload(&self, key: &str, body: Vec<u8>) -> Result<()> {
+        let request = match &self.backend {
+            Backend::S3 { credentials } => {
+                let url = match &self.config.endpoint {
+                    // path-style for S3-compatible stores such as MinIO or R2
+                    Some(endpoint) => Url::parse(&format!("{}/{}/{}", endpoint.trim_end_matches('/'), self.config.bucket, key))?,
+                    None => Url::parse(&format!("https://{}.s3.{}.amazonaws.com/{}", self.config.bucket, self.config.region, key))?,
+                };
+                let headers = credentials.sign(
+                    "PUT",
+                    &url,
+                    &self.config.region,
+                    "s3",
+                    &[("content-type", "application/json".to_string())],
+                    &body,
+                )?;
+                headers.into_iter().fold(self.http.put(url), |request, (name, value)| request.header(name, value))
+            }
+            Backend::Gcs { auth } => {
+                let url = format!("https://storage.googleapis.com/upload/storage/v1/b/{}/o", self.config.bucket);
+                self.http
+                    .post(url)
+                    .query(&[("uploadType", "media"), ("name", key)])
+                    .bearer_auth(auth.access_token().await?)
+                    .header("content-type", "application/json")
+            }
+        };
+        let response = request.body(body).send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            return Err(anyhow!("upload returned {}: {}", status, response.text().await.unwrap_or_default()));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,145 @@
+use crate::config::AuthorityWatchConfig;
+use crate::events::{AuthorityChangedEvent, Event};
+use crate::raydium::{AmmInfo, NewPool};
+use crate::rpc::KeyRing;
+use crate::sinks::Sinks;
+use anyhow::{anyhow, Result};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{pubsub_client::PubsubClient, rpc_config::RpcAccountInfoConfig};
+use solana_sdk::{commitment_config::CommitmentConfig, program_pack::Pack, pubkey::Pubkey};
+use spl_token::state::Mint;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// The authorities an account's data holds, by name.
+type Authorities = Vec<(&'static str, Option<Pubkey>)>;
+
+/// Reads the authorities from an account's data.
+type Reader = fn(&[u8]) -> Result<Authorities>;
+
+/// Subscribes to the base mint and the pool account of each new pool for a
+/// while and alerts when an authority changes: the mint or freeze authority
+/// of the base token (set_authority, from either token program), or the
+/// pool's owner.
+pub struct AuthorityWatcher {
+    keys: Arc<KeyRing>,
+    sinks: Sinks,
+    config: AuthorityWatchConfig,
+}
+
+impl AuthorityWatcher {
+    pub fn new(keys: Arc<KeyRing>, sinks: Sinks, config: AuthorityWatchConfig) -> Self {
+        Self { keys, sinks, config }
+    }
+
+    /// `name` is the pool's display name used in alerts.
+    pub async fn watch(self: Arc<Self>, pool: NewPool, name: String) {
+        let amm = pool.accounts.amm;
+        let quote = pool.accounts.quote_mint();
+        let base = if quote == pool.accounts.coin_mint { pool.accounts.pc_mint } else { pool.accounts.coin_mint };
+        let accounts: [(Pubkey, Reader); 2] = [(base, mint_authorities), (amm, pool_owner)];
+        let watches = accounts.map(|(account, authorities)| {
+            // the pubsub client blocks
+            let (watcher, name) = (self.clone(), name.clone());
+            tokio::task::spawn_blocking(move || watcher.run(&amm, &name, &account, authorities))
+        });
+        for (watch, (account, _)) in futures::future::join_all(watches).await.into_iter().zip(accounts) {
+            match watch {
+                Ok(Ok(changes)) => info!(pool = %amm, account = %account, changes, "Finished watching authorities"),
+                Ok(Err(e)) => warn!(pool = %amm, account = %account, error = %e, "Stopped watching authorities"),
+                Err(e) => warn!(pool = %amm, account = %account, error = %e, "Authority watcher panicked"),
+            }
+        }
+    }
+
+    fn run(&self, amm: &Pubkey, name: &str, account: &Pubkey, authorities: Reader) -> Result<u64> {
+        let (mut subscription, receiver) = PubsubClient::account_subscribe(
+            &self.keys.ws_url("accountSubscribe"),
+            account,
+            Some(RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(CommitmentConfig::confirmed()),
+                ..Default::default()
+            }),
+        )?;
+        info!(pool = %amm, account = %account, window_mins = self.config.window_mins, "Watching authorities");
+
+        let end = Instant::now() + Duration::from_secs(self.config.window_mins * 60);
+        // from the first notification; an account that never changes sends none
+        let mut known: Option<Authorities> = None;
+        let mut changes = 0;
+        let result = loop {
+            let Some(remaining) = end.checked_duration_since(Instant::now()) else {
+                break Ok(changes);
+            };
+            let update = match receiver.recv_timeout(remaining) {
+                Ok(update) => update,
+                Err(e) if e.is_timeout() => break Ok(changes),
+                Err(_) => break Err(anyhow!("account subscription closed")),
+            };
+            let Some(data) = update.value.data.decode() else {
+                continue;
+            };
+            let current = match authorities(&data) {
+                Ok(current) => current,
+                Err(e) => break Err(e),
+            };
+            let previous = known.replace(current.clone()).unwrap_or_else(|| current.clone());
+            for ((authority, from), (_, to)) in previous.into_iter().zip(current).filter(|((_, from), (_, to))| from != to) {
+                let changed = AuthorityChangedEvent {
+                    amm: *amm,
+                    name: name.to_string(),
+                    account: *account,
+                    authority: authority.to_string(),
+                    from: from.map(|from| from.to_string()),
+                    to: to.map(|to| to.to_string()),
+                    slot: update.context.slot,
+                };
+                warn!(pool = %amm, account = %account, authority, from = ?changed.from, to = ?changed.to, "Authority changed");
+                changes += 1;
+                let sinks = self.sinks.clone();
+                tokio::spawn(async move { sinks.dispatch(&Event::AuthorityChanged(changed)).await });
+            }
+        };
+        let _ = subscription.shutdown();
+        result
+    }
+}
+
+/// The base layout of SPL Token and Token-2022 mints; Token-2022 extensions
+/// follow it.
+fn mint_authorities(data: &[u8]) -> Result<Authorities> {
+    let mint = Mint::unpack_from_slice(data.get(..Mint::LEN).ok_or_else(|| anyhow!("mint account too short"))?)?;
+    Ok(vec![("mint_authority", mint.mint_authority.into()), ("freeze_authority", mint.freeze_authority.into())])
+}
+
+fn pool_owner(data: &[u8]) -> Result<Authorities> {
+    Ok(vec![("pool_owner", Some(AmmInfo::unpack(data)?.amm_owner))])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::program_option::COption;
+
+    #[test]
+    fn reads_both_mint_authorities() {
+        let authority = Pubkey::new_unique();
+        let mint = Mint {
+            mint_authority: COption::None,
+            supply: 1_000_000_000,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::Some(authority),
+        };
+        // with room for Token-2022 extensions
+        let mut data = vec![0; Mint::LEN + 90];
+        mint.pack_into_slice(&mut data[..Mint::LEN]);
+        assert_eq!(
+            mint_authorities(&data).unwrap(),
+            vec![("mint_authority", None), ("freeze_authority", Some(authority))]
+        );
+        assert!(mint_authorities(&data[..40]).is_err());
+    }
+}
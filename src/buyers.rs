@@ -0,0 +1,254 @@
+use crate::config::BuyersConfig;
+use crate::jito::DEFAULT_TIP_ACCOUNTS;
+use crate::raydium::{NewPool, SwapLog};
+use crate::sniper::token_balance;
+use crate::store::{FirstBuyer, SnipeReport, Store};
+use anyhow::{anyhow, Result};
+use solana_client::{
+    rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+    rpc_config::RpcTransactionConfig,
+};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+// signatures fetched per page of a pool's history
+const SIGNATURE_PAGE: usize = 1000;
+
+/// Looks at the first swaps on a new pool and classifies the buyer wallets.
+pub struct BuyerAnalyzer {
+    rpc_client: Arc<RpcClient>,
+    store: Arc<Store>,
+    config: BuyersConfig,
+    known_bots: Vec<Pubkey>,
+    bot_programs: Vec<Pubkey>,
+    tip_accounts: Vec<Pubkey>,
+}
+
+/// Sniping signals of one first buy.
+struct BuySignals {
+    // quote spent and base received, from the pool's ray_log
+    amount_in: u64,
+    amount_out: u64,
+    jito_tipped: bool,
+    bot_program: bool,
+}
+
+impl BuyerAnalyzer {
+    pub fn new(rpc_client: Arc<RpcClient>, store: Arc<Store>, config: BuyersConfig) -> Result<Self> {
+        let known_bots = config
+            .known_bots
+            .iter()
+            .map(|bot| Pubkey::from_str(bot).map_err(|e| anyhow!("Invalid known bot {}: {}", bot, e)))
+            .collect::<Result<Vec<_>>>()?;
+        let bot_programs = config
+            .bot_programs
+            .iter()
+            .map(|program| Pubkey::from_str(program).map_err(|e| anyhow!("Invalid bot program {}: {}", program, e)))
+            .collect::<Result<Vec<_>>>()?;
+        let tip_accounts = DEFAULT_TIP_ACCOUNTS.iter().map(|account| Pubkey::from_str(account)).collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { rpc_client, store, config, known_bots, bot_programs, tip_accounts })
+    }
+
+    /// Waits `delay_secs` for the first swaps to land, then records and
+    /// classifies the first `count` buyers and looks for sniping.
+    pub async fn analyze(self: Arc<Self>, pool: NewPool, creation: Signature) {
+        tokio::time::sleep(Duration::from_secs(self.config.delay_secs)).await;
+        let amm = pool.accounts.amm;
+        // many sequential RPC calls, kept off the async workers
+        let analyzer = self.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let buys = analyzer.first_buyers(&pool, &creation)?;
+            let report = analyzer.snipe_report(&pool, &buys)?;
+            Ok((buys.into_iter().map(|(buyer, _)| buyer).collect::<Vec<_>>(), report))
+        })
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|result| result);
+        match result {
+            Ok((buyers, report)) => {
+                for buyer in &buyers {
+                    if let Err(e) = self.store.insert_first_buyer(buyer) {
+                        warn!(pool = %amm, error = %e, "Failed to record first buyer");
+                    }
+                }
+                info!(
+                    pool = %amm,
+                    buyers = buyers.len(),
+                    fresh = buyers.iter().filter(|b| b.fresh).count(),
+                    known_bots = buyers.iter().filter(|b| b.known_bot).count(),
+                    deployer_funded = buyers.iter().filter(|b| b.deployer_funded).count(),
+                    "First buyers analysed"
+                );
+                if let Err(e) = self.store.insert_snipe_report(&report) {
+                    warn!(pool = %amm, error = %e, "Failed to record snipe report");
+                }
+                if report.sniped {
+                    warn!(
+                        pool = %amm,
+                        sniper_buys = report.sniper_buys,
+                        early_buys = report.early_buys,
+                        sniped_percent = format_args!("{:.2}", report.sniped_percent),
+                        "Pool was sniped"
+                    );
+                }
+            }
+            Err(e) => warn!(pool = %amm, error = %e, "Failed to analyse first buyers"),
+        }
+    }
+
+    fn first_buyers(&self, pool: &NewPool, creation: &Signature) -> Result<Vec<(FirstBuyer, BuySignals)>> {
+        let amm = pool.accounts.amm;
+        let quote = pool.accounts.quote_mint();
+        let quote_is_coin = quote == pool.accounts.coin_mint;
+        let base = if quote == pool.accounts.coin_mint { pool.accounts.pc_mint } else { pool.accounts.coin_mint };
+
+        // newest first, so reverse to get the swaps right after creation
+        let mut signatures = self.rpc_client.get_signatures_for_address_with_config(
+            &amm,
+            GetConfirmedSignaturesForAddress2Config {
+                until: Some(*creation),
+                limit: Some(SIGNATURE_PAGE),
+                commitment: Some(CommitmentConfig::confirmed()),
+                ..Default::default()
+            },
+        )?;
+        signatures.reverse();
+
+        let mut buyers = Vec::new();
+        for status in signatures.iter().filter(|status| status.err.is_none()) {
+            if buyers.len() >= self.config.count {
+                break;
+            }
+            let signature = Signature::from_str(&status.signature)?;
+            let tx = self.fetch(&signature)?;
+            let Some(transaction) = tx.transaction.transaction.decode() else {
+                continue;
+            };
+            let keys = transaction.message.static_account_keys();
+            let Some(payer) = keys.first().copied() else {
+                continue;
+            };
+            let Some(meta) = &tx.transaction.meta else {
+                continue;
+            };
+            // a buy increases the payer's balance of the base token
+            if token_balance(&meta.post_token_balances, &payer, &base) <= token_balance(&meta.pre_token_balances, &payer, &base) {
+                continue;
+            }
+            // routed swaps log one ray_log per hop; the buy on this pool
+            // spends the quote token
+            let logs = Option::<Vec<String>>::from(meta.log_messages.clone()).unwrap_or_default();
+            let swap = logs.iter().filter_map(|line| SwapLog::parse(line)).find(|swap| swap.coin_to_pc == quote_is_coin);
+            let signals = BuySignals {
+                amount_in: swap.as_ref().map_or(0, |swap| swap.amount_in),
+                amount_out: swap.as_ref().map_or(0, |swap| swap.amount_out),
+                jito_tipped: keys.iter().any(|key| self.tip_accounts.contains(key)),
+                bot_program: keys.iter().any(|key| self.bot_programs.contains(key)),
+            };
+            let (fresh, deployer_funded) = self.classify(&payer, &pool.accounts.creator)?;
+            let buyer = FirstBuyer {
+                amm: amm.to_string(),
+                signature: signature.to_string(),
+                buyer: payer.to_string(),
+                slot: tx.slot,
+                fresh,
+                known_bot: self.known_bots.contains(&payer),
+                deployer_funded,
+            };
+            buyers.push((buyer, signals));
+        }
+        Ok(buyers)
+    }
+
+    /// Looks at the buys within `snipe_slots` of the first one: buys that
+    /// repeat another's size, tip Jito, call a bot program or come from a
+    /// known bot are counted as snipes.
+    fn snipe_report(&self, pool: &NewPool, buys: &[(FirstBuyer, BuySignals)]) -> Result<SnipeReport> {
+        let first_slot = buys.iter().map(|(buyer, _)| buyer.slot).min().unwrap_or(0);
+        let early = buys
+            .iter()
+            .filter(|(buyer, _)| buyer.slot <= first_slot + self.config.snipe_slots)
+            .collect::<Vec<_>>();
+        let mut sizes: HashMap<u64, usize> = HashMap::new();
+        for (_, signals) in early.iter().filter(|(_, signals)| signals.amount_in > 0) {
+            *sizes.entry(signals.amount_in).or_default() += 1;
+        }
+        let identical = |signals: &BuySignals| sizes.get(&signals.amount_in).is_some_and(|&count| count > 1);
+
+        let mut report = SnipeReport {
+            amm: pool.accounts.amm.to_string(),
+            early_buys: early.len() as u64,
+            sniper_buys: 0,
+            identical_buys: 0,
+            jito_buys: 0,
+            bot_program_buys: 0,
+            sniped_amount: 0,
+            sniped_percent: 0.0,
+            sniped: false,
+        };
+        for (buyer, signals) in &early {
+            report.identical_buys += identical(signals) as u64;
+            report.jito_buys += signals.jito_tipped as u64;
+            report.bot_program_buys += signals.bot_program as u64;
+            if buyer.known_bot || identical(signals) || signals.jito_tipped || signals.bot_program {
+                report.sniper_buys += 1;
+                report.sniped_amount += signals.amount_out;
+            }
+        }
+        report.sniped = report.sniper_buys as usize >= self.config.sniped_min_buys;
+
+        if report.sniped_amount > 0 {
+            let quote = pool.accounts.quote_mint();
+            let base = if quote == pool.accounts.coin_mint { pool.accounts.pc_mint } else { pool.accounts.coin_mint };
+            let supply = self.rpc_client.get_token_supply(&base)?.amount.parse::<u64>()?;
+            if supply > 0 {
+                report.sniped_percent = report.sniped_amount as f64 / supply as f64 * 100.0;
+            }
+        }
+        Ok(report)
+    }
+
+    /// (fresh, funded by the deployer). Only wallets whose whole history fits
+    /// in `history_limit` signatures are checked for deployer funding, by the
+    /// fee payer of their first transaction.
+    fn classify(&self, wallet: &Pubkey, creator: &Pubkey) -> Result<(bool, bool)> {
+        let history = self.rpc_client.get_signatures_for_address_with_config(
+            wallet,
+            GetConfirmedSignaturesForAddress2Config {
+                limit: Some(self.config.history_limit),
+                commitment: Some(CommitmentConfig::confirmed()),
+                ..Default::default()
+            },
+        )?;
+        let fresh = history.len() <= self.config.fresh_max_txs;
+        let deployer_funded = match history.last() {
+            Some(oldest) if history.len() < self.config.history_limit => {
+                let tx = self.fetch(&Signature::from_str(&oldest.signature)?)?;
+                fee_payer(&tx).is_some_and(|payer| payer == *creator)
+            }
+            _ => false,
+        };
+        Ok((fresh, deployer_funded))
+    }
+
+    fn fetch(&self, signature: &Signature) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+        Ok(self.rpc_client.get_transaction_with_config(
+            signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Base64),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )?)
+    }
+}
+
+fn fee_payer(tx: &EncodedConfirmedTransactionWithStatusMeta) -> Option<Pubkey> {
+    let transaction = tx.transaction.transaction.decode()?;
+    transaction.message.static_account_keys().first().copied()
+}
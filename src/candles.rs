@@ -0,0 +1,278 @@
+use crate::config::{MilestoneConfig, TrackingConfig};
+use crate::events::{Event, GraduatedEvent, LiquidityPulledEvent};
+use crate::raydium::{self, NewPool, KNOWN_QUOTES};
+use crate::sinks::Sinks;
+use crate::sniper::unix_now;
+use crate::store::{Candle, Store};
+use anyhow::Result;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{info, warn};
+
+// the SOL reference price is refreshed at most this often
+const SOL_PRICE_TTL_SECS: u64 = 60;
+
+/// Builds the candle of one resolution from price samples.
+struct CandleBuilder {
+    resolution_secs: u64,
+    current: Option<Candle>,
+}
+
+impl CandleBuilder {
+    fn new(resolution_secs: u64) -> Self {
+        Self { resolution_secs, current: None }
+    }
+
+    /// Adds a sample and returns the previous candle once a new one starts.
+    fn push(&mut self, amm: &str, time: u64, price: f64, volume: f64) -> Option<Candle> {
+        let start_time = time - time % self.resolution_secs;
+        match &mut self.current {
+            Some(candle) if candle.start_time == start_time => {
+                candle.high = candle.high.max(price);
+                candle.low = candle.low.min(price);
+                candle.close = price;
+                candle.volume += volume;
+                None
+            }
+            _ => self.current.replace(Candle {
+                amm: amm.to_string(),
+                resolution_secs: self.resolution_secs,
+                start_time,
+                open: price,
+                high: price,
+                low: price,
+                close: price,
+                volume,
+            }),
+        }
+    }
+}
+
+/// Detects a sharp drop of the quote reserve within a sliding window.
+struct RugDetector {
+    drop_percent: f64,
+    window_secs: u64,
+    // (time, quote reserve) samples within the window
+    samples: VecDeque<(u64, u64)>,
+}
+
+impl RugDetector {
+    /// Returns the highest reserve in the window when the new sample is more
+    /// than `drop_percent` below it.
+    fn push(&mut self, time: u64, reserve: u64) -> Option<u64> {
+        while self.samples.front().is_some_and(|&(at, _)| at + self.window_secs < time) {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((time, reserve));
+        let peak = self.samples.iter().map(|&(_, reserve)| reserve).max()?;
+        ((reserve as f64) < peak as f64 * (1.0 - self.drop_percent / 100.0)).then_some(peak)
+    }
+}
+
+/// Tracks how long a pool has met one milestone.
+struct MilestoneProgress {
+    config: MilestoneConfig,
+    met_since: Option<u64>,
+    reached: bool,
+}
+
+impl MilestoneProgress {
+    /// Returns how long the thresholds have held, the first time that is at
+    /// least `sustain_secs`.
+    fn push(&mut self, time: u64, liquidity_usd: f64, volume_usd: f64) -> Option<u64> {
+        if self.reached {
+            return None;
+        }
+        let met = self.config.liquidity_usd.is_none_or(|min| liquidity_usd >= min)
+            && self.config.volume_usd.is_none_or(|min| volume_usd >= min);
+        if !met {
+            self.met_since = None;
+            return None;
+        }
+        let since = *self.met_since.get_or_insert(time);
+        if time - since < self.config.sustain_secs {
+            return None;
+        }
+        self.reached = true;
+        Some(time - since)
+    }
+}
+
+/// Polls the vault balances of new pools for a while after launch, writes
+/// price candles to the store, alerts when the liquidity is pulled and sends
+/// follow-ups when a pool reaches a milestone.
+pub struct PriceTracker {
+    rpc_client: Arc<RpcClient>,
+    store: Arc<Store>,
+    sinks: Sinks,
+    config: TrackingConfig,
+    // (fetched at, USD per SOL)
+    sol_price: Mutex<Option<(u64, f64)>>,
+}
+
+impl PriceTracker {
+    pub fn new(rpc_client: Arc<RpcClient>, store: Arc<Store>, sinks: Sinks, config: TrackingConfig) -> Self {
+        Self { rpc_client, store, sinks, config, sol_price: Mutex::new(None) }
+    }
+
+    /// USD price of a quote token; only stablecoins and WSOL are priced.
+    fn quote_usd_price(&self, quote: &Pubkey) -> Option<f64> {
+        let quote = quote.to_string();
+        match KNOWN_QUOTES.iter().find(|(mint, _)| *mint == quote).map(|(_, name)| *name) {
+            Some("USDC") | Some("USDT") => Some(1.0),
+            Some("WSOL") => self.sol_usd_price(),
+            _ => None,
+        }
+    }
+
+    fn sol_usd_price(&self) -> Option<f64> {
+        let now = unix_now();
+        let mut cached = self.sol_price.lock().unwrap();
+        if let Some((fetched_at, price)) = *cached {
+            if now < fetched_at + SOL_PRICE_TTL_SECS {
+                return Some(price);
+            }
+        }
+        match raydium::fetch_sol_usd_price(&self.rpc_client) {
+            Ok(price) => {
+                *cached = Some((now, price));
+                Some(price)
+            }
+            Err(e) => {
+                warn!(error = %e, "Failed to fetch SOL price");
+                // fall back to the stale price
+                cached.map(|(_, price)| price)
+            }
+        }
+    }
+
+    /// `name` is the pool's display name used in alerts.
+    pub async fn track(self: Arc<Self>, pool: NewPool, name: String) {
+        let amm = pool.accounts.amm;
+        info!(pool = %amm, window_mins = self.config.window_mins, "Tracking pool price");
+        if let Err(e) = self.run(&pool, &name).await {
+            warn!(pool = %amm, error = %e, "Stopped tracking pool");
+        }
+    }
+
+    async fn run(&self, pool: &NewPool, name: &str) -> Result<()> {
+        let amm = pool.accounts.amm.to_string();
+        let (info, _) = raydium::fetch_pool(&self.rpc_client, &pool.accounts.amm)?;
+        // price in quote per base, base being the side that isn't the quote token
+        let quote_mint = pool.accounts.quote_mint();
+        let quote_is_coin = quote_mint == pool.accounts.coin_mint;
+        let (base_decimals, quote_decimals) = if quote_is_coin {
+            (info.pc_decimals, info.coin_decimals)
+        } else {
+            (info.coin_decimals, info.pc_decimals)
+        };
+        let scale = 10f64.powi(base_decimals as i32 - quote_decimals as i32);
+
+        let mut builders = self
+            .config
+            .resolutions_secs
+            .iter()
+            .filter(|&&resolution| resolution > 0)
+            .map(|&resolution| CandleBuilder::new(resolution))
+            .collect::<Vec<_>>();
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.poll_interval_secs.max(1)));
+        let end = unix_now() + self.config.window_mins * 60;
+        let mut last_quote_reserve = None;
+        let mut rug = self.config.rug_drop_percent.map(|drop_percent| RugDetector {
+            drop_percent,
+            window_secs: self.config.rug_window_secs,
+            samples: VecDeque::new(),
+        });
+        let quote_unit = 10f64.powi(quote_decimals as i32);
+        let mut milestones = self
+            .config
+            .milestones
+            .iter()
+            .map(|milestone| MilestoneProgress { config: milestone.clone(), met_since: None, reached: false })
+            .collect::<Vec<_>>();
+        let mut total_volume = 0.0;
+
+        while unix_now() < end {
+            interval.tick().await;
+            let (coin, pc) = match raydium::fetch_vault_reserves(&self.rpc_client, &pool.accounts) {
+                Ok(reserves) => reserves,
+                Err(e) => {
+                    warn!(pool = %amm, error = %e, "Failed to poll pool reserves");
+                    continue;
+                }
+            };
+            let state = info.pool_state(coin, pc);
+            let (base_reserve, quote_reserve) = if quote_is_coin {
+                (state.pc_reserve, state.coin_reserve)
+            } else {
+                (state.coin_reserve, state.pc_reserve)
+            };
+            if base_reserve == 0 {
+                continue;
+            }
+            let price = quote_reserve as f64 / base_reserve as f64 * scale;
+            let volume = last_quote_reserve.map_or(0.0, |last: u64| last.abs_diff(quote_reserve) as f64 / quote_unit);
+            last_quote_reserve = Some(quote_reserve);
+            total_volume += volume;
+
+            let now = unix_now();
+            // alert once per pool
+            if let Some(peak) = rug.as_mut().and_then(|detector| detector.push(now, quote_reserve)) {
+                rug = None;
+                let pulled = LiquidityPulledEvent {
+                    amm: pool.accounts.amm,
+                    name: name.to_string(),
+                    from_reserve: peak as f64 / quote_unit,
+                    to_reserve: quote_reserve as f64 / quote_unit,
+                    drop_percent: (1.0 - quote_reserve as f64 / peak as f64) * 100.0,
+                    window_secs: self.config.rug_window_secs,
+                };
+                warn!(pool = %amm, drop_percent = format_args!("{:.1}", pulled.drop_percent), "Liquidity pulled");
+                if let Err(e) = self.store.insert_rug(&amm, now, pulled.drop_percent) {
+                    warn!(pool = %amm, error = %e, "Failed to record pulled liquidity");
+                }
+                let sinks = self.sinks.clone();
+                tokio::spawn(async move { sinks.dispatch(&Event::LiquidityPulled(pulled)).await });
+            }
+            if milestones.iter().any(|progress| !progress.reached) {
+                if let Some(usd) = self.quote_usd_price(&quote_mint) {
+                    let liquidity_usd = 2.0 * quote_reserve as f64 / quote_unit * usd;
+                    let volume_usd = total_volume * usd;
+                    for progress in &mut milestones {
+                        let Some(sustained_secs) = progress.push(now, liquidity_usd, volume_usd) else {
+                            continue;
+                        };
+                        let graduated = GraduatedEvent {
+                            amm: pool.accounts.amm,
+                            name: name.to_string(),
+                            milestone: progress.config.name.clone(),
+                            liquidity_usd,
+                            volume_usd,
+                            sustained_secs,
+                        };
+                        info!(pool = %amm, milestone = %graduated.milestone, liquidity_usd, volume_usd, "Pool graduated");
+                        let sinks = self.sinks.clone();
+                        tokio::spawn(async move { sinks.dispatch(&Event::Graduated(graduated)).await });
+                    }
+                }
+            }
+            for builder in &mut builders {
+                if let Some(candle) = builder.push(&amm, now, price, volume) {
+                    self.store.insert_candle(&candle)?;
+                }
+            }
+        }
+
+        // flush the candles still open at the end of the window
+        for builder in builders {
+            if let Some(candle) = builder.current {
+                self.store.insert_candle(&candle)?;
+            }
+        }
+        info!(pool = %amm, "Finished tracking pool price");
+        Ok(())
+    }
+}
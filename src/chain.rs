@@ -0,0 +1,98 @@
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::RpcTransactionConfig,
+    rpc_request::RpcRequest,
+};
+use solana_sdk::{account::Account, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
+use std::collections::HashMap;
+
+/// The RPC calls behind transaction decoding and token lookups, so both can
+/// run against canned data.
+pub trait ChainRpc: Send + Sync {
+    fn get_transaction(
+        &self,
+        signature: &Signature,
+        config: RpcTransactionConfig,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta>;
+    /// None when the account doesn't exist.
+    fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>>;
+    /// Helius DAS `getAsset`.
+    fn get_asset(&self, mint: &Pubkey) -> Result<Value>;
+}
+
+impl ChainRpc for RpcClient {
+    fn get_transaction(
+        &self,
+        signature: &Signature,
+        config: RpcTransactionConfig,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+        Ok(self.get_transaction_with_config(signature, config)?)
+    }
+
+    fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>> {
+        Ok(self.get_account_with_commitment(pubkey, self.commitment())?.value)
+    }
+
+    fn get_asset(&self, mint: &Pubkey) -> Result<Value> {
+        Ok(self.send(RpcRequest::Custom { method: "getAsset" }, json!({ "id": mint.to_string() }))?)
+    }
+}
+
+/// Canned transactions, accounts and assets. Unknown transactions and
+/// assets fail, unknown accounts don't exist.
+#[derive(Default)]
+pub struct MockRpc {
+    // as JSON, the transaction type isn't Clone
+    transactions: HashMap<Signature, Value>,
+    accounts: HashMap<Pubkey, Account>,
+    assets: HashMap<Pubkey, Value>,
+}
+
+impl MockRpc {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_transaction(mut self, signature: Signature, tx: &EncodedConfirmedTransactionWithStatusMeta) -> Self {
+        self.transactions.insert(signature, serde_json::to_value(tx).expect("transaction serializes to JSON"));
+        self
+    }
+
+    pub fn with_account(mut self, pubkey: Pubkey, account: Account) -> Self {
+        self.accounts.insert(pubkey, account);
+        self
+    }
+
+    pub fn with_asset(mut self, mint: Pubkey, asset: Value) -> Self {
+        self.assets.insert(mint, asset);
+        self
+    }
+}
+
+impl ChainRpc for MockRpc {
+    fn get_transaction(
+        &self,
+        signature: &Signature,
+        _config: RpcTransactionConfig,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+        let tx = self
+            .transactions
+            .get(signature)
+            .ok_or_else(|| anyhow!("transaction {} not found", signature))?;
+        Ok(serde_json::from_value(tx.clone())?)
+    }
+
+    fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>> {
+        Ok(self.accounts.get(pubkey).cloned())
+    }
+
+    fn get_asset(&self, mint: &Pubkey) -> Result<Value> {
+        self.assets
+            .get(mint)
+            .cloned()
+            .ok_or_else(|| anyhow!("asset {} not found", mint))
+    }
+}
@@ -0,0 +1,125 @@
+use crate::config::ClockConfig;
+use anyhow::{anyhow, Result};
+use solana_client::rpc_client::RpcClient;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::net::UdpSocket;
+use tracing::{info, warn};
+
+// seconds between the NTP (1900) and UNIX (1970) epochs
+const NTP_UNIX_OFFSET_SECS: f64 = 2_208_988_800.0;
+const NTP_TIMEOUT: Duration = Duration::from_secs(5);
+// block time samples per check; the smallest difference has the least
+// network and confirmation lag in it
+const BLOCK_TIME_SAMPLES: usize = 3;
+
+// local clock minus network time, in milliseconds
+static DRIFT_MS: AtomicI64 = AtomicI64::new(0);
+static MEASURED: AtomicBool = AtomicBool::new(false);
+// subtracted from local time for block delays; only set beyond
+// `max_drift_ms`, below it the measurement is mostly noise
+static CORRECTION_MS: AtomicI64 = AtomicI64::new(0);
+
+/// The last measured drift of the local clock (ahead is positive), None
+/// before the first check.
+pub fn drift_ms() -> Option<i64> {
+    MEASURED.load(Ordering::Relaxed).then(|| DRIFT_MS.load(Ordering::Relaxed))
+}
+
+/// Seconds between `block_time` and now, with the local clock corrected when
+/// it drifted beyond `max_drift_ms`, so the delay is network latency only.
+pub fn delay_since(block_time: i64) -> u64 {
+    let now_ms = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as i64);
+    let corrected_ms = now_ms - CORRECTION_MS.load(Ordering::Relaxed);
+    (corrected_ms / 1000).saturating_sub(block_time).max(0) as u64
+}
+
+/// Measures the drift every `interval_secs` and warns when it exceeds
+/// `max_drift_ms`.
+pub async fn monitor(rpc_client: Arc<RpcClient>, config: ClockConfig) {
+    if config.interval_secs == 0 {
+        return;
+    }
+    let mut interval = tokio::time::interval(Duration::from_secs(config.interval_secs));
+    loop {
+        interval.tick().await;
+        let drift = match &config.ntp_server {
+            Some(server) => ntp_drift(server).await,
+            None => block_time_drift(&rpc_client).await,
+        };
+        let drift = match drift {
+            Ok(drift) => drift,
+            Err(e) => {
+                warn!(error = %e, "Failed to measure clock drift");
+                continue;
+            }
+        };
+        let first = !MEASURED.swap(true, Ordering::Relaxed);
+        DRIFT_MS.store(drift, Ordering::Relaxed);
+        let source = if config.ntp_server.is_some() { "ntp" } else { "block_time" };
+        let drifted = drift.unsigned_abs() > config.max_drift_ms;
+        CORRECTION_MS.store(if drifted { drift } else { 0 }, Ordering::Relaxed);
+        if drifted {
+            warn!(drift_ms = drift, max_drift_ms = config.max_drift_ms, source, "Local clock is off, correcting block delays");
+        } else if first {
+            info!(drift_ms = drift, source, "Measured clock drift");
+        }
+    }
+}
+
+/// Local time minus the block time of the latest confirmed slots. Block
+/// times are whole seconds, so this is only accurate to about a second.
+async fn block_time_drift(rpc_client: &RpcClient) -> Result<i64> {
+    let mut drift: Option<i64> = None;
+    for _ in 0..BLOCK_TIME_SAMPLES {
+        let slot = rpc_client.get_slot()?;
+        let block_time = rpc_client.get_block_time(slot)?;
+        let now_ms = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as i64;
+        let sample = now_ms - block_time * 1000;
+        drift = Some(drift.map_or(sample, |drift| drift.min(sample)));
+        tokio::time::sleep(Duration::from_millis(400)).await;
+    }
+    drift.ok_or_else(|| anyhow!("no block time samples"))
+}
+
+/// Local time minus the server's time from one SNTP exchange, corrected for
+/// the round trip.
+async fn ntp_drift(server: &str) -> Result<i64> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket.connect(server).await?;
+    let mut request = [0u8; 48];
+    // leap indicator 0, version 3, mode 3 (client)
+    request[0] = 0x1b;
+    let sent = unix_secs_f64();
+    socket.send(&request).await?;
+    let mut response = [0u8; 48];
+    let len = tokio::time::timeout(NTP_TIMEOUT, socket.recv(&mut response))
+        .await
+        .map_err(|_| anyhow!("no response from {}", server))??;
+    let received = unix_secs_f64();
+    if len < 48 {
+        return Err(anyhow!("short NTP response from {}", server));
+    }
+    let server_received = ntp_timestamp(&response[32..40]);
+    let server_sent = ntp_timestamp(&response[40..48]);
+    if server_sent == 0.0 {
+        return Err(anyhow!("invalid NTP response from {}", server));
+    }
+    // server minus local, the standard NTP offset
+    let offset = ((server_received - sent) + (server_sent - received)) / 2.0;
+    Ok((-offset * 1000.0).round() as i64)
+}
+
+fn ntp_timestamp(bytes: &[u8]) -> f64 {
+    let secs = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64;
+    let fraction = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as f64 / 4_294_967_296.0;
+    if secs == 0.0 {
+        return 0.0;
+    }
+    secs - NTP_UNIX_OFFSET_SECS + fraction
+}
+
+fn unix_secs_f64() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0.0, |d| d.as_secs_f64())
+}
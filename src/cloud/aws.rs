@@ -0,0 +1,222 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use ring::{digest, hmac};
+use url::Url;
+
+/// Credentials from the standard AWS environment variables.
+pub struct Credentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+impl Credentials {
+    /// `section` names the config section in errors, e.g. "[archive]".
+    pub fn from_env(section: &str) -> Result<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        Ok(Self {
+            access_key_id: var("AWS_ACCESS_KEY_ID").ok_or_else(|| anyhow!("{} requires AWS_ACCESS_KEY_ID", section))?,
+            secret_access_key: var("AWS_SECRET_ACCESS_KEY").ok_or_else(|| anyhow!("{} requires AWS_SECRET_ACCESS_KEY", section))?,
+            session_token: var("AWS_SESSION_TOKEN"),
+        })
+    }
+
+    /// Headers signing a request with Signature Version 4, including
+    /// x-amz-date, x-amz-content-sha256 and the session token. `headers`
+    /// are the other headers to sign, such as content-type.
+    pub fn sign(
+        &self,
+        method: &str,
+        url: &Url,
+        region: &str,
+        service: &str,
+        headers: &[(&'static str, String)],
+        body: &[u8],
+    ) -> Result<Vec<(&'static str, String)>> {
+        let host = url.host_str().ok_or_else(|| anyhow!("no host in {}", url))?;
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        };
+        let payload_hash = sha256_hex(body);
+
+        let mut signed = headers.to_vec();
+        signed.push(("host", host));
+        signed.push(("x-amz-content-sha256", payload_hash.clone()));
+        signed.push(("x-amz-date", Utc::now().format("%Y%m%dT%H%M%SZ").to_string()));
+        if let Some(token) = &self.session_token {
+            signed.push(("x-amz-security-token", token.clone()));
+        }
+        let authorization = self.authorization(method, url, region, service, &mut signed, &payload_hash);
+        signed.push(("authorization", authorization));
+        // reqwest sets host itself
+        signed.retain(|(name, _)| *name != "host");
+        Ok(signed)
+    }
+
+    /// The Authorization header for a request with the `signed` headers,
+    /// host and x-amz-date among them, which are sorted by name.
+    fn authorization(
+        &self,
+        method: &str,
+        url: &Url,
+        region: &str,
+        service: &str,
+        signed: &mut [(&'static str, String)],
+        payload_hash: &str,
+    ) -> String {
+        signed.sort_by_key(|(name, _)| *name);
+        let amz_date = signed.iter().find(|(name, _)| *name == "x-amz-date").map(|(_, value)| value.as_str()).unwrap_or_default();
+        let date = amz_date.get(..8).unwrap_or_default();
+        let signed_headers = signed.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";");
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            method,
+            canonical_uri(url, service),
+            canonical_query(url),
+            signed.iter().map(|(name, value)| format!("{}:{}\n", name, value.trim())).collect::<String>(),
+            signed_headers,
+            payload_hash,
+        );
+        let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+        let string_to_sign = format!("AWS4-HMAC-SHA256\n{}\n{}\n{}", amz_date, scope, sha256_hex(canonical_request.as_bytes()));
+        let key = [region, service, "aws4_request"].iter().fold(
+            hmac_sha256(format!("AWS4{}", self.secret_access_key).as_bytes(), date.as_bytes()),
+            |key, part| hmac_sha256(&key, part.as_bytes()),
+        );
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key_id,
+            scope,
+            signed_headers,
+            hex(&hmac_sha256(&key, string_to_sign.as_bytes()))
+        )
+    }
+}
+
+/// The path with each segment URI-encoded. S3 encodes the decoded segments
+/// once, every other service encodes the path as sent once more.
+fn canonical_uri(url: &Url, service: &str) -> String {
+    let path = if url.path().is_empty() { "/" } else { url.path() };
+    path.split('/')
+        .map(|segment| if service == "s3" { uri_encode(&percent_decode(segment)) } else { uri_encode(segment.as_bytes()) })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The decoded query parameters, URI-encoded and sorted by name, then value.
+fn canonical_query(url: &Url) -> String {
+    let mut params = url
+        .query_pairs()
+        .map(|(name, value)| (uri_encode(name.as_bytes()), uri_encode(value.as_bytes())))
+        .collect::<Vec<_>>();
+    params.sort();
+    params.iter().map(|(name, value)| format!("{}={}", name, value)).collect::<Vec<_>>().join("&")
+}
+
+/// Percent-encodes everything but the unreserved characters, in upper case.
+fn uri_encode(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+fn percent_decode(segment: &str) -> Vec<u8> {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes.get(i + 1..i + 3).and_then(|hex| std::str::from_utf8(hex).ok()).and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], escaped) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    decoded
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex(digest::digest(&digest::SHA256, data).as_ref())
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, key), data).as_ref().to_vec()
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // from the AWS Signature Version 4 test suite
+    fn example() -> Credentials {
+        Credentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+        }
+    }
+
+    fn signature(method: &str, url: &str, content_type: Option<&str>, body: &[u8]) -> String {
+        let url = Url::parse(url).unwrap();
+        let mut signed = vec![("host", url.host_str().unwrap().to_string()), ("x-amz-date", "20150830T123600Z".to_string())];
+        if let Some(content_type) = content_type {
+            signed.push(("content-type", content_type.to_string()));
+        }
+        let authorization = example().authorization(method, &url, "us-east-1", "service", &mut signed, &sha256_hex(body));
+        authorization.rsplit("Signature=").next().unwrap().to_string()
+    }
+
+    #[test]
+    fn matches_the_sigv4_test_suite() {
+        // get-vanilla
+        let url = Url::parse("https://example.amazonaws.com/").unwrap();
+        let mut signed = vec![("x-amz-date", "20150830T123600Z".to_string()), ("host", "example.amazonaws.com".to_string())];
+        assert_eq!(
+            example().authorization("GET", &url, "us-east-1", "service", &mut signed, &sha256_hex(b"")),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, SignedHeaders=host;x-amz-date, \
+             Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+
+        let unreserved = "-._~0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+        let cases = [
+            // get-vanilla-query-order-key-case
+            ("https://example.amazonaws.com/?Param2=value2&Param1=value1".to_string(), "b97d918cfa904a5beff61c982a1b6f458b799221646efd99d3219ec94cdf2500"),
+            // get-unreserved
+            (format!("https://example.amazonaws.com/{}", unreserved), "07ef7494c76fa4850883e2b006601f940f8a34d404d0cfa977f52a65bbf5f24f"),
+            // get-vanilla-query-unreserved
+            (format!("https://example.amazonaws.com/?{0}={0}", unreserved), "9c3e54bfcdf0b19771a7f523ee5669cdf59bc7cc0884027167c21bb143a40197"),
+            // get-vanilla-utf8-query
+            ("https://example.amazonaws.com/?\u{1234}=bar".to_string(), "2cdec8eed098649ff3a119c94853b13c643bcf08f8b0a1d91e12c9027818dd04"),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(signature("GET", &url, None, b""), expected, "{}", url);
+        }
+        // post-x-www-form-urlencoded
+        assert_eq!(
+            signature("POST", "https://example.amazonaws.com/", Some("application/x-www-form-urlencoded"), b"Param1=value1"),
+            "ff11897932ad3f4e8b18135d722051e5ac45fc38421b1da7b9d196a0fe09473a"
+        );
+    }
+
+    #[test]
+    fn encodes_the_path_twice_except_for_s3() {
+        let url = Url::parse("https://bucket.s3.amazonaws.com/pools/a b+c\u{1234}.json").unwrap();
+        assert_eq!(canonical_uri(&url, "s3"), "/pools/a%20b%2Bc%E1%88%B4.json");
+        assert_eq!(canonical_uri(&url, "sqs"), "/pools/a%2520b%2Bc%25E1%2588%25B4.json");
+        assert_eq!(canonical_uri(&Url::parse("https://sns.us-east-1.amazonaws.com").unwrap(), "sns"), "/");
+    }
+}
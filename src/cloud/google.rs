@@ -0,0 +1,108 @@
+use crate::sniper::unix_now;
+use anyhow::{anyhow, Context, Result};
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use ring::rand::SystemRandom;
+use ring::signature::{RsaKeyPair, RSA_PKCS1_SHA256};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+
+// access tokens are refreshed this long before they expire
+const TOKEN_MARGIN_SECS: u64 = 60;
+
+/// The fields of a Google service account key file that are needed.
+#[derive(Deserialize)]
+struct ServiceAccount {
+    client_email: String,
+    private_key: String,
+    token_uri: String,
+}
+
+struct AccessToken {
+    token: String,
+    expires_at: u64,
+}
+
+/// OAuth access tokens for a Google service account.
+pub struct ServiceAccountAuth {
+    http: reqwest::Client,
+    scope: &'static str,
+    client_email: String,
+    token_uri: String,
+    key: RsaKeyPair,
+    token: Mutex<Option<AccessToken>>,
+}
+
+impl ServiceAccountAuth {
+    /// Reads the JSON key file at `path`.
+    pub fn from_file(path: &str, scope: &'static str) -> Result<Self> {
+        let credentials = std::fs::read_to_string(path).with_context(|| format!("failed to read {}", path))?;
+        let account: ServiceAccount = serde_json::from_str(&credentials)?;
+        Ok(Self {
+            http: reqwest::Client::new(),
+            scope,
+            client_email: account.client_email,
+            token_uri: account.token_uri,
+            key: parse_private_key(&account.private_key)?,
+            token: Mutex::new(None),
+        })
+    }
+
+    /// A cached access token, exchanging a freshly signed JWT once it
+    /// expires.
+    pub async fn access_token(&self) -> Result<String> {
+        let mut token = self.token.lock().await;
+        let now = unix_now();
+        if let Some(token) = token.as_ref().filter(|token| token.expires_at > now + TOKEN_MARGIN_SECS) {
+            return Ok(token.token.clone());
+        }
+        let response: Value = self
+            .http
+            .post(&self.token_uri)
+            .form(&[("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"), ("assertion", &self.jwt(now)?)])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let access_token = response["access_token"]
+            .as_str()
+            .ok_or_else(|| anyhow!("unexpected token response: {}", response))?
+            .to_string();
+        let expires_in = response["expires_in"].as_u64().unwrap_or(3600);
+        *token = Some(AccessToken { token: access_token.clone(), expires_at: now + expires_in });
+        Ok(access_token)
+    }
+
+    fn jwt(&self, now: u64) -> Result<String> {
+        let header = URL_SAFE_NO_PAD.encode(json!({ "alg": "RS256", "typ": "JWT" }).to_string());
+        let claims = URL_SAFE_NO_PAD.encode(
+            json!({
+                "iss": self.client_email,
+                "scope": self.scope,
+                "aud": self.token_uri,
+                "iat": now,
+                "exp": now + 3600,
+            })
+            .to_string(),
+        );
+        let message = format!("{}.{}", header, claims);
+        let mut signature = vec![0; self.key.public().modulus_len()];
+        self.key
+            .sign(&RSA_PKCS1_SHA256, &SystemRandom::new(), message.as_bytes(), &mut signature)
+            .map_err(|_| anyhow!("failed to sign the service account JWT"))?;
+        Ok(format!("{}.{}", message, URL_SAFE_NO_PAD.encode(signature)))
+    }
+
+}
+
+/// The PKCS#8 RSA key from the key file's PEM `private_key`.
+fn parse_private_key(pem: &str) -> Result<RsaKeyPair> {
+    let body = pem
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .collect::<String>();
+    let der = STANDARD.decode(body.trim())?;
+    RsaKeyPair::from_pkcs8(&der).map_err(|e| anyhow!("invalid service account private key: {}", e))
+}
@@ -0,0 +1,5 @@
+//! Authentication for the AWS and Google Cloud APIs used by the sinks and
+//! the transaction archive.
+
+pub mod aws;
+pub mod google;
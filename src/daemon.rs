@@ -0,0 +1,143 @@
+use anyhow::{anyhow, Context, Result};
+use std::path::PathBuf;
+
+// set for the re-executed process after a reload, which is already detached
+const DAEMONIZED_ENV: &str = "RAYDIUM_MONITOR_DAEMONIZED";
+
+/// Written by `daemonize` and removed again when dropped.
+pub struct PidFile {
+    path: PathBuf,
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Detaches from the terminal (fork, setsid, standard streams to /dev/null)
+/// and writes the daemon's PID to `pid_file`. Must run before the async
+/// runtime starts. Fails when `pid_file` names a running process.
+#[cfg(unix)]
+pub fn daemonize(pid_file: &str) -> Result<PidFile> {
+    let path = PathBuf::from(pid_file);
+    if std::env::var_os(DAEMONIZED_ENV).is_none() {
+        if let Some(pid) = running_pid(&path) {
+            return Err(anyhow!("Already running with PID {} ({})", pid, pid_file));
+        }
+        detach()?;
+        std::env::set_var(DAEMONIZED_ENV, "1");
+    }
+    std::fs::write(&path, format!("{}\n", std::process::id()))
+        .with_context(|| format!("Failed to write PID file {}", pid_file))?;
+    Ok(PidFile { path })
+}
+
+#[cfg(not(unix))]
+pub fn daemonize(_pid_file: &str) -> Result<PidFile> {
+    Err(anyhow!("--daemon is only supported on Unix"))
+}
+
+#[cfg(unix)]
+fn running_pid(path: &std::path::Path) -> Option<i32> {
+    let pid = std::fs::read_to_string(path).ok()?.trim().parse::<i32>().ok()?;
+    // signal 0 only checks that the process exists
+    (unsafe { libc::kill(pid, 0) } == 0).then_some(pid)
+}
+
+#[cfg(unix)]
+fn detach() -> Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    // the first child leaves the parent's process group, the second can
+    // never reacquire a controlling terminal
+    for setsid in [true, false] {
+        match unsafe { libc::fork() } {
+            -1 => return Err(std::io::Error::last_os_error()).context("fork failed"),
+            0 => {}
+            _ => std::process::exit(0),
+        }
+        if setsid && unsafe { libc::setsid() } == -1 {
+            return Err(std::io::Error::last_os_error()).context("setsid failed");
+        }
+    }
+    let null = std::fs::OpenOptions::new().read(true).write(true).open("/dev/null")?;
+    for fd in [libc::STDIN_FILENO, libc::STDOUT_FILENO, libc::STDERR_FILENO] {
+        if unsafe { libc::dup2(null.as_raw_fd(), fd) } == -1 {
+            return Err(std::io::Error::last_os_error()).context("dup2 failed");
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the process with a fresh copy of itself and the same arguments,
+/// which reads the config again. The PID, and with it the PID file, stays
+/// the same. Only returns on failure.
+#[cfg(unix)]
+pub fn reexec() -> anyhow::Error {
+    use std::os::unix::process::CommandExt;
+
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => return e.into(),
+    };
+    std::process::Command::new(exe).args(std::env::args_os().skip(1)).exec().into()
+}
+
+#[cfg(not(unix))]
+pub fn reexec() -> anyhow::Error {
+    anyhow!("reloading is only supported on Unix")
+}
+
+/// A control request sent to the process as a signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    // SIGHUP
+    Reload,
+    // SIGUSR1
+    DumpState,
+    // SIGTERM, so the PID file is removed
+    Shutdown,
+}
+
+/// SIGHUP, SIGUSR1 and SIGTERM as `Control` requests. Never yields outside Unix.
+pub struct Signals {
+    #[cfg(unix)]
+    hangup: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    user1: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    terminate: tokio::signal::unix::Signal,
+}
+
+impl Signals {
+    #[cfg(unix)]
+    pub fn new() -> Result<Self> {
+        use tokio::signal::unix::{signal, SignalKind};
+        Ok(Self {
+            hangup: signal(SignalKind::hangup())?,
+            user1: signal(SignalKind::user_defined1())?,
+            terminate: signal(SignalKind::terminate())?,
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub fn new() -> Result<Self> {
+        Ok(Self {})
+    }
+
+    #[cfg(unix)]
+    pub async fn recv(&mut self) -> Control {
+        tokio::select! {
+            Some(()) = self.hangup.recv() => Control::Reload,
+            Some(()) = self.user1.recv() => Control::DumpState,
+            Some(()) = self.terminate.recv() => Control::Shutdown,
+            else => std::future::pending().await,
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub async fn recv(&mut self) -> Control {
+        std::future::pending().await
+    }
+}
@@ -0,0 +1,295 @@
+use crate::raydium::{
+    Initialize2Accounts, ADMIN_CANCEL_ORDERS_DISCRIMINATOR, INITIALIZE2_DISCRIMINATOR, SET_PARAMS_DISCRIMINATOR,
+    WITHDRAW_PNL_DISCRIMINATOR,
+};
+use crate::config::ProgramDecoder;
+use crate::launchpad::{self, LaunchAccounts, MintParams};
+use crate::layout::DecodedLayout;
+use crate::programs::{self, Program};
+use anyhow::{anyhow, Result};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_sdk::pubkey::Pubkey;
+use solana_transaction_status::{
+    parse_accounts::ParsedAccount, EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction, UiInnerInstructions,
+    UiInstruction, UiLoadedAddresses, UiMessage, UiParsedInstruction,
+};
+use std::str::FromStr;
+
+/// The data of a Raydium V4 initialize2 instruction.
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+pub struct Initialize2Data {
+    pub discriminator: u8,
+    pub nonce: u8,
+    pub open_time: u64,
+    pub init_pc_amount: u64,
+    pub init_coin_amount: u64,
+}
+
+/// A top-level or inner instruction with its accounts resolved to pubkeys.
+pub struct ResolvedInstruction {
+    pub program_id: Pubkey,
+    pub accounts: Vec<Pubkey>,
+    pub data: Vec<u8>,
+}
+
+/// Instructions of a binary-encoded transaction, or None when it cannot be
+/// decoded or uses lookup tables the node didn't resolve. Each top-level
+/// instruction is followed by the instructions it invoked, so pools created
+/// through CPI (launchpads, bundlers) are found as well.
+pub fn compiled_instructions(tx: &EncodedConfirmedTransactionWithStatusMeta) -> Result<Option<Vec<ResolvedInstruction>>> {
+    let Some(transaction) = tx.transaction.transaction.decode() else {
+        return Ok(None);
+    };
+    let message = transaction.message;
+    let meta = tx.transaction.meta.as_ref();
+
+    // Account indexes refer to the static keys followed by the writable and
+    // readonly addresses loaded from lookup tables
+    let mut account_keys = message.static_account_keys().to_vec();
+    let loaded = meta.and_then(|meta| Option::<&UiLoadedAddresses>::from(meta.loaded_addresses.as_ref()));
+    match loaded {
+        Some(loaded) => {
+            for address in loaded.writable.iter().chain(&loaded.readonly) {
+                account_keys.push(Pubkey::from_str(address)?);
+            }
+        }
+        None if message.address_table_lookups().is_some_and(|lookups| !lookups.is_empty()) => return Ok(None),
+        None => {}
+    }
+    let key = |index: u8| {
+        account_keys
+            .get(index as usize)
+            .copied()
+            .ok_or_else(|| anyhow!("Account index {} out of range ({} keys)", index, account_keys.len()))
+    };
+
+    let inner = meta.and_then(|meta| Option::<&Vec<UiInnerInstructions>>::from(meta.inner_instructions.as_ref()));
+    let mut instructions = Vec::new();
+    for (index, ix) in message.instructions().iter().enumerate() {
+        instructions.push(ResolvedInstruction {
+            program_id: key(ix.program_id_index)?,
+            accounts: ix.accounts.iter().map(|&index| key(index)).collect::<Result<_>>()?,
+            data: ix.data.clone(),
+        });
+        for inner_ix in invoked_by(inner, index) {
+            // binary-encoded transactions come with compiled inner instructions
+            if let UiInstruction::Compiled(inner_ix) = inner_ix {
+                instructions.push(ResolvedInstruction {
+                    program_id: key(inner_ix.program_id_index)?,
+                    accounts: inner_ix.accounts.iter().map(|&index| key(index)).collect::<Result<_>>()?,
+                    data: bs58::decode(&inner_ix.data).into_vec()?,
+                });
+            }
+        }
+    }
+    Ok(Some(instructions))
+}
+
+/// Instructions of a jsonParsed transaction, inner instructions following
+/// the instruction that invoked them. Programs the node can't parse, such as
+/// Raydium, come back partially decoded with base58 data.
+pub fn parsed_instructions(tx: &EncodedConfirmedTransactionWithStatusMeta) -> Result<Vec<ResolvedInstruction>> {
+    let EncodedTransaction::Json(transaction) = &tx.transaction.transaction else {
+        return Err(anyhow!("Expected a jsonParsed transaction"));
+    };
+    let UiMessage::Parsed(message) = &transaction.message else {
+        return Err(anyhow!("Expected a parsed message"));
+    };
+    let inner = tx
+        .transaction
+        .meta
+        .as_ref()
+        .and_then(|meta| Option::<&Vec<UiInnerInstructions>>::from(meta.inner_instructions.as_ref()));
+    let mut instructions = Vec::new();
+    for (index, ix) in message.instructions.iter().enumerate() {
+        for ix in std::iter::once(ix).chain(invoked_by(inner, index)) {
+            if let Some(ix) = resolve_parsed(ix, &message.account_keys)? {
+                instructions.push(ix);
+            }
+        }
+    }
+    Ok(instructions)
+}
+
+/// The inner instructions invoked by the top-level instruction at `index`.
+fn invoked_by(inner: Option<&Vec<UiInnerInstructions>>, index: usize) -> impl Iterator<Item = &UiInstruction> {
+    inner
+        .into_iter()
+        .flatten()
+        .filter(move |inner| inner.index as usize == index)
+        .flat_map(|inner| &inner.instructions)
+}
+
+fn resolve_parsed(ix: &UiInstruction, account_keys: &[ParsedAccount]) -> Result<Option<ResolvedInstruction>> {
+    match ix {
+        UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(ix)) => Ok(Some(ResolvedInstruction {
+            program_id: Pubkey::from_str(&ix.program_id)?,
+            accounts: ix.accounts.iter().map(|account| Pubkey::from_str(account)).collect::<Result<_, _>>()?,
+            data: bs58::decode(&ix.data).into_vec()?,
+        })),
+        UiInstruction::Compiled(ix) => {
+            let key = |index: u8| -> Result<Pubkey> {
+                let account = account_keys
+                    .get(index as usize)
+                    .ok_or_else(|| anyhow!("Account index {} out of range ({} keys)", index, account_keys.len()))?;
+                Ok(Pubkey::from_str(&account.pubkey)?)
+            };
+            Ok(Some(ResolvedInstruction {
+                program_id: key(ix.program_id_index)?,
+                accounts: ix.accounts.iter().map(|&index| key(index)).collect::<Result<_>>()?,
+                data: bs58::decode(&ix.data).into_vec()?,
+            }))
+        }
+        // fully parsed instructions belong to programs such as System and Token
+        UiInstruction::Parsed(UiParsedInstruction::Parsed(_)) => Ok(None),
+    }
+}
+
+/// The initialize2 instructions of the Raydium V4 programs monitored among
+/// `instructions`, skipping other Raydium instructions such as swaps in the
+/// same transaction.
+pub fn initialize2_instructions(instructions: &[ResolvedInstruction]) -> impl Iterator<Item = &ResolvedInstruction> {
+    instructions.iter().filter(|ix| {
+        programs::decodes_with(&ix.program_id, ProgramDecoder::RaydiumV4) && ix.data.first() == Some(&INITIALIZE2_DISCRIMINATOR)
+    })
+}
+
+/// The data and accounts (by their position in the initialize2 layout) of
+/// an initialize2 instruction.
+pub fn initialize2(ix: &ResolvedInstruction) -> Result<(Initialize2Data, Initialize2Accounts)> {
+    let data = Initialize2Data::try_from_slice(&ix.data)?;
+    let accounts = Initialize2Accounts { program: ix.program_id, ..Initialize2Accounts::from_keys(&ix.accounts)? };
+    Ok((data, accounts))
+}
+
+/// The initialize instructions of the launchpads monitored among
+/// `instructions`, each opening a token sale.
+pub fn launch_instructions(instructions: &[ResolvedInstruction]) -> impl Iterator<Item = &ResolvedInstruction> {
+    instructions
+        .iter()
+        .filter(|ix| programs::decodes_with(&ix.program_id, ProgramDecoder::RaydiumLaunchpad) && launchpad::is_initialize(&ix.data))
+}
+
+/// The minted token and accounts of a launchpad initialize instruction.
+pub fn launch(ix: &ResolvedInstruction) -> Result<(MintParams, LaunchAccounts)> {
+    Ok((MintParams::parse(&ix.data)?, LaunchAccounts::from_keys(ix.program_id, &ix.accounts)?))
+}
+
+/// The pool creation instructions of the monitored programs with a custom
+/// decoder among `instructions`, each with its program.
+pub fn layout_instructions(instructions: &[ResolvedInstruction]) -> impl Iterator<Item = (&'static Program, &ResolvedInstruction)> {
+    instructions.iter().filter_map(|ix| {
+        let program = programs::get(&ix.program_id)?;
+        program.layout.as_ref().is_some_and(|layout| layout.matches(&ix.data)).then_some((program, ix))
+    })
+}
+
+/// The named accounts and arguments of an instruction from
+/// [`layout_instructions`].
+pub fn layout(program: &Program, ix: &ResolvedInstruction) -> Result<DecodedLayout> {
+    let layout = program.layout.as_ref().ok_or_else(|| anyhow!("program {} has no layout", program.id))?;
+    layout.decode(&ix.accounts, &ix.data)
+}
+
+/// Whether an instruction creates a pool or opens a sale on a monitored
+/// program, for sources that see instructions before the transaction is
+/// fetched.
+pub fn is_creation(program_id: &Pubkey, data: &[u8]) -> bool {
+    let Some(program) = programs::get(program_id) else {
+        return false;
+    };
+    match program.decoder {
+        ProgramDecoder::RaydiumV4 => data.first() == Some(&INITIALIZE2_DISCRIMINATOR),
+        ProgramDecoder::RaydiumLaunchpad => launchpad::is_initialize(data),
+        ProgramDecoder::Custom => program.layout.as_ref().is_some_and(|layout| layout.matches(data)),
+    }
+}
+
+/// The pool parameters of Raydium V4 setParams, by their `AmmParams` number.
+const AMM_PARAMS: &[&str] = &[
+    "status",
+    "state",
+    "order_num",
+    "depth",
+    "amount_wave",
+    "min_price_multiplier",
+    "max_price_multiplier",
+    "min_size",
+    "vol_max_cut_ratio",
+    "fees",
+    "amm_owner",
+    "open_time",
+    "last_order_distance",
+    "init_order_depth",
+    "switch_time",
+    "clear_open_time",
+    "separate",
+    "update_open_order",
+];
+const PARAM_FEES: u8 = 9;
+const PARAM_AMM_OWNER: u8 = 10;
+
+/// A Raydium V4 instruction only the program's admin can send, changing a
+/// pool after launch.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AdminInstruction {
+    SetParams(SetParams),
+    // collects the protocol fees from the vaults
+    WithdrawPnl,
+    AdminCancelOrders,
+}
+
+impl AdminInstruction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AdminInstruction::SetParams(_) => "set_params",
+            AdminInstruction::WithdrawPnl => "withdraw_pnl",
+            AdminInstruction::AdminCancelOrders => "admin_cancel_orders",
+        }
+    }
+}
+
+/// The parameter a setParams instruction sets. Most take a number; fees
+/// take the whole fee schedule, of which only the swap fee is kept, and the
+/// owner a pubkey.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetParams {
+    pub param: u8,
+    pub value: Option<u64>,
+    // (numerator, denominator)
+    pub swap_fee: Option<(u64, u64)>,
+    pub new_owner: Option<Pubkey>,
+}
+
+impl SetParams {
+    pub fn param_name(&self) -> &'static str {
+        AMM_PARAMS.get(self.param as usize).copied().unwrap_or("unknown")
+    }
+}
+
+/// The admin instruction `ix` sends to the pool `amm`, if any. The pool is
+/// the second account of each of them.
+pub fn admin_instruction(ix: &ResolvedInstruction, amm: &Pubkey) -> Option<AdminInstruction> {
+    if !programs::decodes_with(&ix.program_id, ProgramDecoder::RaydiumV4) || ix.accounts.get(1) != Some(amm) {
+        return None;
+    }
+    let u64_at = |offset: usize| ix.data.get(offset..offset + 8).map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()));
+    match *ix.data.first()? {
+        SET_PARAMS_DISCRIMINATOR => {
+            let param = *ix.data.get(1)?;
+            let set = match param {
+                // eight u64s, the swap fee last
+                PARAM_FEES => SetParams { param, value: None, swap_fee: Some((u64_at(50)?, u64_at(58)?)), new_owner: None },
+                PARAM_AMM_OWNER => {
+                    let owner = Pubkey::try_from(ix.data.get(2..34)?).ok()?;
+                    SetParams { param, value: None, swap_fee: None, new_owner: Some(owner) }
+                }
+                _ => SetParams { param, value: u64_at(2), swap_fee: None, new_owner: None },
+            };
+            Some(AdminInstruction::SetParams(set))
+        }
+        WITHDRAW_PNL_DISCRIMINATOR => Some(AdminInstruction::WithdrawPnl),
+        ADMIN_CANCEL_ORDERS_DISCRIMINATOR => Some(AdminInstruction::AdminCancelOrders),
+        _ => None,
+    }
+}
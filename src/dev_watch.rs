@@ -0,0 +1,90 @@
+use crate::config::DevWatchConfig;
+use crate::events::{DeveloperSoldEvent, Event};
+use crate::raydium::NewPool;
+use crate::sinks::Sinks;
+use crate::sniper::{associated_token_address, unix_now};
+use crate::store::Store;
+use anyhow::Result;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Holdings of one watched wallet.
+struct Holding {
+    deployer: bool,
+    peak: u64,
+    alerted: bool,
+}
+
+/// Watches the base-token holdings of a pool's deployer, and of wallets the
+/// deployer funded, and alerts when they sell or move a large share.
+pub struct DevWatcher {
+    rpc_client: Arc<RpcClient>,
+    store: Arc<Store>,
+    sinks: Sinks,
+    config: DevWatchConfig,
+}
+
+impl DevWatcher {
+    pub fn new(rpc_client: Arc<RpcClient>, store: Arc<Store>, sinks: Sinks, config: DevWatchConfig) -> Self {
+        Self { rpc_client, store, sinks, config }
+    }
+
+    /// `name` is the pool's display name used in alerts.
+    pub async fn watch(self: Arc<Self>, pool: NewPool, name: String) {
+        let amm = pool.accounts.amm;
+        if let Err(e) = self.run(&pool, &name).await {
+            warn!(pool = %amm, error = %e, "Stopped watching deployer");
+        }
+    }
+
+    async fn run(&self, pool: &NewPool, name: &str) -> Result<()> {
+        let amm = pool.accounts.amm;
+        let quote = pool.accounts.quote_mint();
+        let base = if quote == pool.accounts.coin_mint { pool.accounts.pc_mint } else { pool.accounts.coin_mint };
+        info!(pool = %amm, deployer = %pool.accounts.creator, window_mins = self.config.window_mins, "Watching deployer holdings");
+
+        let mut holdings = HashMap::from([(pool.accounts.creator, Holding { deployer: true, peak: 0, alerted: false })]);
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.poll_interval_secs.max(1)));
+        let end = unix_now() + self.config.window_mins * 60;
+
+        while unix_now() < end {
+            interval.tick().await;
+            // wallets funded by the deployer show up once the first buyers
+            // are analysed
+            for wallet in self.store.deployer_funded_buyers(&amm.to_string())? {
+                holdings.entry(Pubkey::from_str(&wallet)?).or_insert(Holding { deployer: false, peak: 0, alerted: false });
+            }
+
+            for (wallet, holding) in holdings.iter_mut().filter(|(_, holding)| !holding.alerted) {
+                // holdings outside the associated token account aren't seen
+                let balance = match self.rpc_client.get_token_account_balance(&associated_token_address(wallet, &base)) {
+                    Ok(balance) => balance.amount.parse::<u64>()?,
+                    Err(_) => 0,
+                };
+                holding.peak = holding.peak.max(balance);
+                if holding.peak == 0 || (balance as f64) >= holding.peak as f64 * (1.0 - self.config.sell_percent / 100.0) {
+                    continue;
+                }
+                holding.alerted = true;
+                let sold = DeveloperSoldEvent {
+                    amm,
+                    name: name.to_string(),
+                    wallet: *wallet,
+                    deployer: holding.deployer,
+                    from_amount: holding.peak,
+                    to_amount: balance,
+                    drop_percent: (1.0 - balance as f64 / holding.peak as f64) * 100.0,
+                };
+                warn!(pool = %amm, wallet = %wallet, deployer = holding.deployer, drop_percent = format_args!("{:.1}", sold.drop_percent), "Developer sold");
+                let sinks = self.sinks.clone();
+                tokio::spawn(async move { sinks.dispatch(&Event::DeveloperSold(sold)).await });
+            }
+        }
+        Ok(())
+    }
+}
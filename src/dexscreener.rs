@@ -0,0 +1,129 @@
+use crate::config::DexScreenerConfig;
+use crate::events::{DexScreenerEvent, Event};
+use crate::raydium::NewPool;
+use crate::sinks::Sinks;
+use crate::sniper::unix_now;
+use anyhow::Result;
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+#[derive(Deserialize)]
+struct PairsResponse {
+    #[serde(default)]
+    pairs: Option<Vec<Pair>>,
+    #[serde(default)]
+    pair: Option<Pair>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Pair {
+    url: String,
+    price_usd: Option<String>,
+    liquidity: Option<Liquidity>,
+    // only present once the token has a paid profile
+    info: Option<PairInfo>,
+}
+
+#[derive(Deserialize)]
+struct Liquidity {
+    usd: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct PairInfo {
+    #[serde(default)]
+    websites: Vec<Website>,
+    #[serde(default)]
+    socials: Vec<Social>,
+}
+
+#[derive(Deserialize)]
+struct Website {
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct Social {
+    #[serde(rename = "type")]
+    kind: String,
+    url: String,
+}
+
+/// Polls DexScreener for new pools: sends a follow-up once the pair is
+/// indexed and another when it gets a paid profile.
+pub struct DexScreener {
+    http: reqwest::Client,
+    sinks: Sinks,
+    config: DexScreenerConfig,
+}
+
+impl DexScreener {
+    pub fn new(sinks: Sinks, config: DexScreenerConfig) -> Self {
+        Self { http: reqwest::Client::new(), sinks, config }
+    }
+
+    /// `name` is the pool's display name used in the follow-ups.
+    pub async fn watch(self: Arc<Self>, pool: NewPool, name: String) {
+        let amm = pool.accounts.amm;
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.poll_interval_secs.max(1)));
+        let index_deadline = unix_now() + self.config.index_timeout_mins * 60;
+        let event = loop {
+            interval.tick().await;
+            match self.fetch(&amm, &name).await {
+                Ok(Some(event)) => break event,
+                Ok(None) => {}
+                Err(e) => warn!(pool = %amm, error = %e, "DexScreener request failed"),
+            }
+            if unix_now() >= index_deadline {
+                info!(pool = %amm, "Pair not indexed by DexScreener in time");
+                return;
+            }
+        };
+        info!(pool = %amm, url = %event.url, paid_profile = event.paid_profile, "Pair indexed by DexScreener");
+        let paid_profile = event.paid_profile;
+        self.sinks.dispatch(&Event::DexScreenerListed(event)).await;
+        if paid_profile {
+            return;
+        }
+
+        let profile_deadline = unix_now() + self.config.profile_window_mins * 60;
+        while unix_now() < profile_deadline {
+            interval.tick().await;
+            match self.fetch(&amm, &name).await {
+                Ok(Some(event)) if event.paid_profile => {
+                    info!(pool = %amm, "Pair got a DexScreener profile");
+                    self.sinks.dispatch(&Event::DexScreenerProfile(event)).await;
+                    return;
+                }
+                Ok(_) => {}
+                Err(e) => warn!(pool = %amm, error = %e, "DexScreener request failed"),
+            }
+        }
+    }
+
+    /// None until the pair is indexed.
+    async fn fetch(&self, amm: &Pubkey, name: &str) -> Result<Option<DexScreenerEvent>> {
+        let url = format!("{}/latest/dex/pairs/solana/{}", self.config.api_url.trim_end_matches('/'), amm);
+        let response: PairsResponse = self.http.get(&url).send().await?.error_for_status()?.json().await?;
+        let Some(pair) = response.pairs.and_then(|pairs| pairs.into_iter().next()).or(response.pair) else {
+            return Ok(None);
+        };
+        Ok(Some(DexScreenerEvent {
+            amm: *amm,
+            name: name.to_string(),
+            url: pair.url,
+            price_usd: pair.price_usd.and_then(|price| price.parse().ok()),
+            liquidity_usd: pair.liquidity.and_then(|liquidity| liquidity.usd),
+            paid_profile: pair.info.is_some(),
+            websites: pair.info.as_ref().map_or_else(Vec::new, |info| info.websites.iter().map(|site| site.url.clone()).collect()),
+            socials: pair
+                .info
+                .as_ref()
+                .map_or_else(Vec::new, |info| info.socials.iter().map(|social| format!("{}: {}", social.kind, social.url)).collect()),
+        }))
+    }
+}
@@ -0,0 +1,176 @@
+use crate::config::DigestConfig;
+use crate::events::{Event, Priority};
+use crate::sinks::Sinks;
+use crate::sniper::unix_now;
+use crate::store::{Launch, Store};
+use anyhow::Result;
+use chrono::{DateTime, Days, TimeZone};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use solana_sdk::native_token::lamports_to_sol;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+/// Aggregates over the pools detected in one day.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Digest {
+    // local date the day ends on
+    pub date: String,
+    pub since: u64,
+    pub until: u64,
+    pub pools: u64,
+    // initial liquidity of the SOL pools
+    pub sol_pools: u64,
+    pub sol_liquidity_lamports: u64,
+    pub rugs: u64,
+    pub biggest: Vec<Launch>,
+    pub top_creators: Vec<(String, u64)>,
+    #[serde(skip)]
+    pub priority: Priority,
+}
+
+impl Digest {
+    pub fn build(store: &Store, until: u64, tz: Tz, top: usize, priority: Priority) -> Result<Self> {
+        let since = until.saturating_sub(DAY_SECS);
+        let liquidities = store.sol_liquidities(since, until)?;
+        Ok(Self {
+            date: DateTime::from_timestamp(until as i64, 0)
+                .map_or_else(|| until.to_string(), |time| time.with_timezone(&tz).format("%Y-%m-%d").to_string()),
+            since,
+            until,
+            pools: store.pools_per_day(since, until)?.iter().map(|(_, count)| count).sum(),
+            sol_pools: liquidities.len() as u64,
+            sol_liquidity_lamports: liquidities.iter().sum(),
+            rugs: store.rug_count(since, until)?,
+            biggest: store.biggest_launches(since, until, top)?,
+            top_creators: store.top_creators(since, until, top)?,
+            priority,
+        })
+    }
+
+    pub fn markdown(&self) -> String {
+        let mut out = format!("# Raydium V4 launches, {}\n\n", self.date);
+        out.push_str(&format!("- Pools: {}\n", self.pools));
+        out.push_str(&format!(
+            "- Initial SOL liquidity: {:.2} SOL over {} SOL pools\n",
+            lamports_to_sol(self.sol_liquidity_lamports),
+            self.sol_pools
+        ));
+        out.push_str(&format!("- Liquidity pulled: {}\n", self.rugs));
+
+        if !self.biggest.is_empty() {
+            out.push_str("\n## Biggest launches\n\n| Token | Pool | Liquidity |\n|---|---|---|\n");
+            for launch in &self.biggest {
+                out.push_str(&format!(
+                    "| {} | {} | {:.2} SOL |\n",
+                    launch.base_mint,
+                    launch.amm,
+                    lamports_to_sol(launch.sol_liquidity_lamports)
+                ));
+            }
+        }
+        if !self.top_creators.is_empty() {
+            out.push_str("\n## Top creators\n\n| Creator | Pools |\n|---|---|\n");
+            for (creator, count) in &self.top_creators {
+                out.push_str(&format!("| {} | {} |\n", creator, count));
+            }
+        }
+        out
+    }
+
+    pub fn html(&self) -> String {
+        let mut out = format!(
+            "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Raydium V4 launches, {date}</title></head>\n<body>\n<h1>Raydium V4 launches, {date}</h1>\n<ul>\n",
+            date = escape(&self.date)
+        );
+        out.push_str(&format!("<li>Pools: {}</li>\n", self.pools));
+        out.push_str(&format!(
+            "<li>Initial SOL liquidity: {:.2} SOL over {} SOL pools</li>\n",
+            lamports_to_sol(self.sol_liquidity_lamports),
+            self.sol_pools
+        ));
+        out.push_str(&format!("<li>Liquidity pulled: {}</li>\n</ul>\n", self.rugs));
+
+        if !self.biggest.is_empty() {
+            out.push_str("<h2>Biggest launches</h2>\n<table>\n<tr><th>Token</th><th>Pool</th><th>Liquidity</th></tr>\n");
+            for launch in &self.biggest {
+                out.push_str(&format!(
+                    "<tr><td>{}</td><td>{}</td><td>{:.2} SOL</td></tr>\n",
+                    escape(&launch.base_mint),
+                    escape(&launch.amm),
+                    lamports_to_sol(launch.sol_liquidity_lamports)
+                ));
+            }
+            out.push_str("</table>\n");
+        }
+        if !self.top_creators.is_empty() {
+            out.push_str("<h2>Top creators</h2>\n<table>\n<tr><th>Creator</th><th>Pools</th></tr>\n");
+            for (creator, count) in &self.top_creators {
+                out.push_str(&format!("<tr><td>{}</td><td>{}</td></tr>\n", escape(creator), count));
+            }
+            out.push_str("</table>\n");
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    /// Writes `digest-<date>.md` and `digest-<date>.html` to `dir`.
+    fn write(&self, dir: &str) -> Result<()> {
+        std::fs::create_dir_all(dir)?;
+        let dir = Path::new(dir);
+        std::fs::write(dir.join(format!("digest-{}.md", self.date)), self.markdown())?;
+        std::fs::write(dir.join(format!("digest-{}.html", self.date)), self.html())?;
+        Ok(())
+    }
+}
+
+/// Sends the digest of the previous 24 hours every day at `hour` in `tz`.
+pub async fn run(store: Arc<Store>, sinks: Sinks, config: DigestConfig, tz: Tz) {
+    loop {
+        let until = next_run(unix_now(), config.hour, tz);
+        tokio::time::sleep(Duration::from_secs(until.saturating_sub(unix_now()))).await;
+        let digest = match Digest::build(&store, until, tz, config.top, config.priority) {
+            Ok(digest) => digest,
+            Err(e) => {
+                warn!(error = %e, "Failed to build daily digest");
+                continue;
+            }
+        };
+        info!(date = %digest.date, pools = digest.pools, rugs = digest.rugs, "Daily digest");
+        if let Some(dir) = &config.dir {
+            if let Err(e) = digest.write(dir) {
+                warn!(dir = %dir, error = %e, "Failed to write daily digest");
+            }
+        }
+        sinks.dispatch(&Event::Digest(Box::new(digest))).await;
+    }
+}
+
+/// The next time after `now` that the local clock in `tz` reads `hour`:00.
+fn next_run(now: u64, hour: u32, tz: Tz) -> u64 {
+    let Some(now_local) = DateTime::from_timestamp(now as i64, 0).map(|time| time.with_timezone(&tz)) else {
+        return now + DAY_SECS;
+    };
+    let mut date = now_local.date_naive();
+    // a day later when the hour is already past, or skipped by a DST change
+    for _ in 0..3 {
+        let run = date
+            .and_hms_opt(hour.min(23), 0, 0)
+            .and_then(|time| tz.from_local_datetime(&time).earliest())
+            .map(|time| time.timestamp() as u64);
+        if let Some(run) = run.filter(|&run| run > now) {
+            return run;
+        }
+        date = date.checked_add_days(Days::new(1)).unwrap_or(date);
+    }
+    now + DAY_SECS
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
@@ -0,0 +1,94 @@
+use super::{Enrichment, EnrichmentProvider, RateLimiter};
+use crate::config::BirdeyeConfig;
+use anyhow::{anyhow, Result};
+use futures::future::BoxFuture;
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+
+// shares of supply, as fractions, above which a holder group is a risk
+const TOP10_HOLDER_RISK: f64 = 0.5;
+const CREATOR_RISK: f64 = 0.1;
+
+/// Token price and security data from Birdeye.
+pub struct Birdeye {
+    http: reqwest::Client,
+    api_url: String,
+    api_key: String,
+    limiter: RateLimiter,
+}
+
+impl Birdeye {
+    pub fn new(config: &BirdeyeConfig) -> Result<Self> {
+        if config.api_key.is_empty() {
+            return Err(anyhow!("[enrichment.birdeye] requires api_key"));
+        }
+        Ok(Self {
+            http: reqwest::Client::new(),
+            api_url: config.api_url.trim_end_matches('/').to_string(),
+            api_key: config.api_key.clone(),
+            limiter: RateLimiter::new(config.requests_per_sec),
+        })
+    }
+
+    /// The `data` field of a Birdeye response.
+    async fn get(&self, path: &str, mint: &Pubkey) -> Result<Value> {
+        self.limiter.acquire().await;
+        let response: Value = self
+            .http
+            .get(format!("{}{}", self.api_url, path))
+            .query(&[("address", mint.to_string())])
+            .header("X-API-KEY", &self.api_key)
+            .header("x-chain", "solana")
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if response.get("success").and_then(Value::as_bool) != Some(true) {
+            return Err(anyhow!("Birdeye {} failed: {}", path, response));
+        }
+        Ok(response.get("data").cloned().unwrap_or(Value::Null))
+    }
+}
+
+impl EnrichmentProvider for Birdeye {
+    fn name(&self) -> &str {
+        "birdeye"
+    }
+
+    fn enrich<'a>(&'a self, mint: &'a Pubkey) -> BoxFuture<'a, Result<Enrichment>> {
+        Box::pin(async move {
+            let price = self.get("/defi/price", mint).await?;
+            let security = self.get("/defi/token_security", mint).await?;
+
+            let share = |field: &str| security.get(field).and_then(Value::as_f64);
+            let flag = |field: &str| security.get(field).and_then(Value::as_bool).unwrap_or(false);
+            let mut risks = Vec::new();
+            if let Some(top10) = share("top10HolderPercent").filter(|&top10| top10 > TOP10_HOLDER_RISK) {
+                risks.push(format!("top 10 holders own {:.0}%", top10 * 100.0));
+            }
+            if let Some(creator) = share("creatorPercentage").filter(|&creator| creator > CREATOR_RISK) {
+                risks.push(format!("creator holds {:.0}%", creator * 100.0));
+            }
+            if flag("mutableMetadata") {
+                risks.push("mutable metadata".to_string());
+            }
+            if flag("transferFeeEnable") {
+                risks.push("transfer fee".to_string());
+            }
+            if flag("nonTransferable") {
+                risks.push("non-transferable".to_string());
+            }
+
+            let mut enrichment = Enrichment {
+                price_usd: price.get("value").and_then(Value::as_f64),
+                risks,
+                ..Default::default()
+            };
+            if !security.is_null() {
+                enrichment.details.insert("security".to_string(), security);
+            }
+            Ok(enrichment)
+        })
+    }
+}
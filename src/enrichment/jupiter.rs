@@ -0,0 +1,78 @@
+use super::{Enrichment, EnrichmentProvider};
+use crate::config::JupiterConfig;
+use anyhow::{anyhow, Result};
+use futures::future::BoxFuture;
+use serde::Deserialize;
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+#[derive(Deserialize)]
+struct Token {
+    address: String,
+}
+
+/// Tags tokens on Jupiter's verified token list. The list is fetched in the
+/// background and refreshed every `refresh_mins`.
+pub struct Jupiter {
+    // None until the first fetch succeeds
+    verified: Arc<RwLock<Option<HashSet<String>>>>,
+}
+
+impl Jupiter {
+    /// Starts the refresh task; must be called within the runtime.
+    pub fn new(config: &JupiterConfig) -> Self {
+        let verified = Arc::new(RwLock::new(None));
+        tokio::spawn(refresh(reqwest::Client::new(), config.clone(), verified.clone()));
+        Self { verified }
+    }
+}
+
+async fn refresh(http: reqwest::Client, config: JupiterConfig, verified: Arc<RwLock<Option<HashSet<String>>>>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(config.refresh_mins.max(1) * 60));
+    loop {
+        interval.tick().await;
+        match fetch(&http, &config.list_urls).await {
+            Ok(tokens) => {
+                info!(tokens = tokens.len(), "Refreshed Jupiter verified token list");
+                *verified.write().unwrap() = Some(tokens);
+            }
+            // keep the previous list
+            Err(e) => warn!(error = %e, "Failed to refresh Jupiter token list"),
+        }
+    }
+}
+
+/// Union of the lists at `urls`.
+async fn fetch(http: &reqwest::Client, urls: &[String]) -> Result<HashSet<String>> {
+    let mut tokens = HashSet::new();
+    for url in urls {
+        let list: Vec<Token> = http.get(url).send().await?.error_for_status()?.json().await?;
+        tokens.extend(list.into_iter().map(|token| token.address));
+    }
+    Ok(tokens)
+}
+
+impl EnrichmentProvider for Jupiter {
+    fn name(&self) -> &str {
+        "jupiter"
+    }
+
+    fn enrich<'a>(&'a self, mint: &'a Pubkey) -> BoxFuture<'a, Result<Enrichment>> {
+        Box::pin(async move {
+            let verified = match &*self.verified.read().unwrap() {
+                Some(tokens) => tokens.contains(&mint.to_string()),
+                None => return Err(anyhow!("Jupiter token list not loaded yet")),
+            };
+            let mut enrichment = Enrichment::default();
+            if verified {
+                enrichment.tags.push("verified".to_string());
+            }
+            enrichment.details.insert("verified".to_string(), json!(verified));
+            Ok(enrichment)
+        })
+    }
+}
@@ -0,0 +1,116 @@
+mod birdeye;
+mod jupiter;
+mod rugcheck;
+
+use crate::config::EnrichmentConfig;
+use crate::events::PoolEvent;
+use anyhow::Result;
+use futures::future::{join_all, BoxFuture};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// An external source of data about a new pool's base token.
+pub trait EnrichmentProvider: Send + Sync {
+    fn name(&self) -> &str;
+    /// When true, the built-in token risk flags are dropped from events this
+    /// provider has a report for.
+    fn replaces_builtin(&self) -> bool {
+        false
+    }
+    fn enrich<'a>(&'a self, mint: &'a Pubkey) -> BoxFuture<'a, Result<Enrichment>>;
+}
+
+/// What a provider found about a token.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Enrichment {
+    pub price_usd: Option<f64>,
+    // the provider's risk findings, e.g. "mutable metadata"
+    pub risks: Vec<String>,
+    // notable non-risk labels, e.g. "verified"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    // provider-specific fields kept with the event
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub details: BTreeMap<String, Value>,
+}
+
+/// Runs every configured provider for new pools.
+#[derive(Clone, Default)]
+pub struct Enrichers {
+    providers: Arc<Vec<Box<dyn EnrichmentProvider>>>,
+    timeout: Duration,
+}
+
+impl Enrichers {
+    pub fn from_config(config: &EnrichmentConfig) -> Result<Self> {
+        let mut providers: Vec<Box<dyn EnrichmentProvider>> = Vec::new();
+        if let Some(birdeye) = &config.birdeye {
+            providers.push(Box::new(birdeye::Birdeye::new(birdeye)?));
+        }
+        if let Some(jupiter) = &config.jupiter {
+            providers.push(Box::new(jupiter::Jupiter::new(jupiter)));
+        }
+        if let Some(rugcheck) = &config.rugcheck {
+            providers.push(Box::new(rugcheck::RugCheck::new(rugcheck)));
+        }
+        Ok(Self { providers: Arc::new(providers), timeout: Duration::from_millis(config.timeout_ms) })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+
+    /// Queries the providers concurrently for the base token; a provider
+    /// that fails or misses the timeout is left out with a warning.
+    pub async fn enrich(&self, event: &mut PoolEvent) {
+        let mint = *event.base().mint;
+        let results = join_all(
+            self.providers
+                .iter()
+                .map(|provider| async move { (provider, tokio::time::timeout(self.timeout, provider.enrich(&mint)).await) }),
+        )
+        .await;
+        for (provider, result) in results {
+            let name = provider.name();
+            match result {
+                Ok(Ok(enrichment)) => {
+                    if provider.replaces_builtin() {
+                        // providers report on the token, not on earlier pools
+                        event.flags.retain(|flag| *flag == "relaunch");
+                    }
+                    event.enrichment.insert(name.to_string(), enrichment);
+                }
+                Ok(Err(e)) => warn!(provider = name, mint = %mint, error = %e, "Enrichment failed"),
+                Err(_) => warn!(provider = name, mint = %mint, "Enrichment timed out"),
+            }
+        }
+    }
+}
+
+/// Spaces requests to an API at least `1 / requests_per_sec` apart.
+struct RateLimiter {
+    interval: Duration,
+    next: tokio::sync::Mutex<Instant>,
+}
+
+impl RateLimiter {
+    fn new(requests_per_sec: f64) -> Self {
+        let interval = if requests_per_sec > 0.0 { Duration::from_secs_f64(1.0 / requests_per_sec) } else { Duration::ZERO };
+        Self { interval, next: tokio::sync::Mutex::new(Instant::now()) }
+    }
+
+    async fn acquire(&self) {
+        let mut next = self.next.lock().await;
+        let now = Instant::now();
+        if *next > now {
+            tokio::time::sleep(*next - now).await;
+        }
+        *next = Instant::now().max(*next) + self.interval;
+    }
+}
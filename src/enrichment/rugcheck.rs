@@ -0,0 +1,79 @@
+use super::{Enrichment, EnrichmentProvider, RateLimiter};
+use crate::config::RugCheckConfig;
+use anyhow::Result;
+use futures::future::BoxFuture;
+use serde::Deserialize;
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+
+#[derive(Deserialize)]
+struct Summary {
+    score: Option<i64>,
+    #[serde(default)]
+    risks: Vec<Risk>,
+}
+
+#[derive(Deserialize)]
+struct Risk {
+    name: String,
+    // "danger", "warn" or "info"
+    #[serde(default)]
+    level: String,
+}
+
+/// Risk report summary from rugcheck.xyz.
+pub struct RugCheck {
+    http: reqwest::Client,
+    api_url: String,
+    replace_builtin: bool,
+    limiter: RateLimiter,
+}
+
+impl RugCheck {
+    pub fn new(config: &RugCheckConfig) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            api_url: config.api_url.trim_end_matches('/').to_string(),
+            replace_builtin: config.replace_builtin,
+            limiter: RateLimiter::new(config.requests_per_sec),
+        }
+    }
+}
+
+impl EnrichmentProvider for RugCheck {
+    fn name(&self) -> &str {
+        "rugcheck"
+    }
+
+    fn replaces_builtin(&self) -> bool {
+        self.replace_builtin
+    }
+
+    fn enrich<'a>(&'a self, mint: &'a Pubkey) -> BoxFuture<'a, Result<Enrichment>> {
+        Box::pin(async move {
+            self.limiter.acquire().await;
+            let summary: Summary = self
+                .http
+                .get(format!("{}/v1/tokens/{}/report/summary", self.api_url, mint))
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            let mut enrichment = Enrichment {
+                // informational entries aren't risks
+                risks: summary
+                    .risks
+                    .into_iter()
+                    .filter(|risk| risk.level == "danger" || risk.level == "warn")
+                    .map(|risk| risk.name)
+                    .collect(),
+                ..Default::default()
+            };
+            if let Some(score) = summary.score {
+                enrichment.details.insert("score".to_string(), json!(score));
+            }
+            Ok(enrichment)
+        })
+    }
+}
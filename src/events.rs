@@ -0,0 +1,835 @@
+use crate::digest::Digest;
+use crate::enrichment::Enrichment;
+use crate::metrics::RuntimeSummary;
+use crate::store::PerformancePoint;
+use rust_decimal::Decimal;
+use schemars::schema::RootSchema;
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Event priority; sinks can be limited to events at or above a priority.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+/// Which side of the pool (in initialize2's coin/pc terms) is the quote
+/// currency; the other side is the new base token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum QuoteSide {
+    Coin,
+    Pc,
+}
+
+/// What a pool is paired against, from its quote mint: SOL, a stablecoin
+/// (USDC or USDT) or another token. Token-token pools are rarely worth
+/// sniping.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PairType {
+    #[default]
+    Sol,
+    Stable,
+    Token,
+}
+
+impl PairType {
+    pub const ALL: [PairType; 3] = [PairType::Sol, PairType::Stable, PairType::Token];
+
+    pub fn of(quote_mint: &Pubkey) -> Self {
+        match crate::raydium::quote_label(quote_mint) {
+            "sol" => PairType::Sol,
+            "usdc" | "usdt" => PairType::Stable,
+            _ => PairType::Token,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PairType::Sol => "sol",
+            PairType::Stable => "stable",
+            PairType::Token => "token",
+        }
+    }
+}
+
+impl FromStr for PairType {
+    type Err = anyhow::Error;
+
+    fn from_str(name: &str) -> anyhow::Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|pair_type| pair_type.name() == name)
+            .ok_or_else(|| anyhow::anyhow!("unknown pair type {}, expected sol, stable or token", name))
+    }
+}
+
+/// One side of a pool.
+#[derive(Debug, Clone, Copy)]
+pub struct PoolToken<'a> {
+    pub mint: &'a Pubkey,
+    pub name: Option<&'a str>,
+    pub init_amount: Decimal,
+}
+
+impl PoolToken<'_> {
+    /// The metadata name, or the shortened mint without metadata.
+    pub fn label(&self) -> String {
+        token_label(self.name, self.mint)
+    }
+}
+
+/// `name`, or the shortened mint (`AbCd…WxYz`) when there is no name.
+pub fn token_label(name: Option<&str>, mint: &Pubkey) -> String {
+    match name {
+        Some(name) => name.to_string(),
+        None => {
+            let mint = mint.to_string();
+            format!("{}…{}", &mint[..4], &mint[mint.len() - 4..])
+        }
+    }
+}
+
+/// A newly detected pool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PoolEvent {
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub signature: Signature,
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub amm: Pubkey,
+    // wallet that created the pool
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub creator: Pubkey,
+    pub slot: u64,
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub coin_mint: Pubkey,
+    // None when the token has no metadata
+    pub coin_name: Option<String>,
+    pub coin_symbol: Option<String>,
+    // image URL, known with token_info_source = "helius" or [metadata]
+    pub coin_image: Option<String>,
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub pc_mint: Pubkey,
+    pub pc_name: Option<String>,
+    pub pc_symbol: Option<String>,
+    pub pc_image: Option<String>,
+    // initial amounts in UI units, serialized as exact decimal strings
+    pub init_coin_amount: Decimal,
+    pub init_pc_amount: Decimal,
+    // determined from the known quote tokens, pc when ambiguous
+    pub quote_side: QuoteSide,
+    // from the quote mint, so events read back recompute it
+    #[serde(skip_deserializing)]
+    pub pair_type: PairType,
+    // initial SOL liquidity for SOL pairs, and the price impact of buying
+    // `price_impact_probe_sol`
+    pub liquidity_sol: Option<f64>,
+    pub price_impact: Option<f64>,
+    // risk flags of the non-SOL token, e.g. mint or freeze authority still
+    // set, or relaunch when the pair had an earlier pool
+    #[serde(deserialize_with = "risk_flags")]
+    pub flags: Vec<&'static str>,
+    pub open_time: u64,
+    // seconds between the block time and detection
+    pub block_delay_secs: Option<u64>,
+    // the launchpad sale the base token came from, as in its token_launched
+    // event
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub launch_pool: Option<String>,
+    // seconds since the base token was minted and the wallet that paid for
+    // it, when [mint_watch] saw the mint
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_age_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deployer: Option<String>,
+    // base-token data by provider name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub enrichment: BTreeMap<String, Enrichment>,
+}
+
+impl PoolEvent {
+    /// `base/quote`.
+    pub fn name(&self) -> String {
+        format!("{}/{}", self.base().label(), self.quote().label())
+    }
+
+    fn coin(&self) -> PoolToken<'_> {
+        PoolToken { mint: &self.coin_mint, name: self.coin_name.as_deref(), init_amount: self.init_coin_amount }
+    }
+
+    fn pc(&self) -> PoolToken<'_> {
+        PoolToken { mint: &self.pc_mint, name: self.pc_name.as_deref(), init_amount: self.init_pc_amount }
+    }
+
+    /// The new token.
+    pub fn base(&self) -> PoolToken<'_> {
+        match self.quote_side {
+            QuoteSide::Coin => self.pc(),
+            QuoteSide::Pc => self.coin(),
+        }
+    }
+
+    /// The quote currency, e.g. WSOL or USDC.
+    pub fn quote(&self) -> PoolToken<'_> {
+        match self.quote_side {
+            QuoteSide::Coin => self.coin(),
+            QuoteSide::Pc => self.pc(),
+        }
+    }
+
+    /// The built-in risk flags followed by the enrichment providers' risks.
+    pub fn risks(&self) -> Vec<String> {
+        self.flags
+            .iter()
+            .map(|flag| flag.to_string())
+            .chain(self.enrichment.values().flat_map(|enrichment| enrichment.risks.iter().cloned()))
+            .collect()
+    }
+
+    /// Labels from the enrichment providers, e.g. "verified".
+    pub fn tags(&self) -> Vec<String> {
+        self.enrichment.values().flat_map(|enrichment| enrichment.tags.iter().cloned()).collect()
+    }
+
+    /// High for SOL pairs without risks, normal for SOL pairs with risks, low
+    /// otherwise.
+    pub fn priority(&self) -> Priority {
+        match (self.liquidity_sol.is_some(), self.risks().is_empty()) {
+            (true, true) => Priority::High,
+            (true, false) => Priority::Normal,
+            (false, _) => Priority::Low,
+        }
+    }
+}
+
+/// The quote reserve of a tracked pool dropped sharply, usually because the
+/// creator removed the liquidity.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LiquidityPulledEvent {
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub amm: Pubkey,
+    pub name: String,
+    // quote reserve in UI units: highest within the window, and now
+    pub from_reserve: f64,
+    pub to_reserve: f64,
+    pub drop_percent: f64,
+    pub window_secs: u64,
+}
+
+/// The deployer of a pool, or a wallet it funded, sold or moved a large
+/// share of its base tokens.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DeveloperSoldEvent {
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub amm: Pubkey,
+    pub name: String,
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub wallet: Pubkey,
+    // false for wallets funded by the deployer
+    pub deployer: bool,
+    // raw base-token balance: highest seen, and now
+    pub from_amount: u64,
+    pub to_amount: u64,
+    pub drop_percent: f64,
+}
+
+/// Raydium's admin changed a tracked pool after launch: its status, fees or
+/// another parameter, or took its protocol fees. Status and fee changes
+/// often precede a rug or a relaunch.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PoolAdminEvent {
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub amm: Pubkey,
+    pub name: String,
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub signature: Signature,
+    // set_params, withdraw_pnl or admin_cancel_orders
+    pub instruction: String,
+    // the parameter set_params changed, e.g. status, fees or open_time
+    pub param: Option<String>,
+    // the parameter before, when known, and after
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// An authority of a tracked pool or its base token changed after launch: the
+/// base mint's mint or freeze authority, or the pool's owner. A mint
+/// authority handed back or a freeze authority appearing is a major red
+/// flag.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AuthorityChangedEvent {
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub amm: Pubkey,
+    pub name: String,
+    // the mint or the pool
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub account: Pubkey,
+    // mint_authority, freeze_authority or pool_owner
+    pub authority: String,
+    // None for no authority
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub slot: u64,
+}
+
+/// Liquidity moved from an earlier pool of the same pair to a new one: the
+/// earlier pool's quote reserve is mostly gone when the new pool appears.
+/// Relaunching into a fresh pool is a common way to reset the chart or trap
+/// buyers of the first pool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LiquidityMigratedEvent {
+    // the new pool
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub amm: Pubkey,
+    pub name: String,
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub from_amm: Pubkey,
+    // both pools were created by the same wallet
+    pub same_creator: bool,
+    // quote reserve of the earlier pool in UI units: at launch, and now
+    pub from_reserve: f64,
+    pub to_reserve: f64,
+    pub drop_percent: f64,
+    // initial quote amount of the new pool in UI units
+    pub new_reserve: f64,
+}
+
+/// A token sale opened on a monitored launchpad. When the sale completes and
+/// the token moves to an AMM pool, that pool's `new_pool` event carries the
+/// same `launch_pool`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TokenLaunchedEvent {
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub signature: Signature,
+    // the sale's pool state
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub launch_pool: Pubkey,
+    // the launchpad program and its name in [[programs]]
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub program: Pubkey,
+    pub launchpad: String,
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub creator: Pubkey,
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub base_mint: Pubkey,
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub quote_mint: Pubkey,
+    // from the instruction, as the token's metadata is created with it
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub decimals: u8,
+    pub slot: u64,
+}
+
+/// A pool created on a program decoded from its `[programs.layout]`, such as
+/// a minor DEX or launch venue, with the accounts and arguments the layout
+/// names.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DexPoolEvent {
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub signature: Signature,
+    // the program and its name in [[programs]]
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub program: Pubkey,
+    pub dex: String,
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub pool: Pubkey,
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub base_mint: Pubkey,
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub quote_mint: Pubkey,
+    // every account the layout names, pool and mints included
+    pub accounts: BTreeMap<String, String>,
+    // the arguments, as strings so 64- and 128-bit integers stay exact
+    pub fields: BTreeMap<String, String>,
+    // the launchpad sale the base token came from, as in its token_launched
+    // event
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub launch_pool: Option<String>,
+    pub slot: u64,
+}
+
+/// DexScreener's view of a new pair.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DexScreenerEvent {
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub amm: Pubkey,
+    pub name: String,
+    pub url: String,
+    pub price_usd: Option<f64>,
+    pub liquidity_usd: Option<f64>,
+    // the token has a paid DexScreener profile
+    pub paid_profile: bool,
+    pub websites: Vec<String>,
+    // "type: url"
+    pub socials: Vec<String>,
+}
+
+/// A tracked pool reached one of the configured milestones.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GraduatedEvent {
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub amm: Pubkey,
+    pub name: String,
+    pub milestone: String,
+    pub liquidity_usd: f64,
+    // quote volume since detection
+    pub volume_usd: f64,
+    pub sustained_secs: u64,
+}
+
+/// Price and liquidity of a pool at the configured offsets after launch.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PerformanceReportEvent {
+    #[serde(serialize_with = "display", deserialize_with = "parse")]
+    #[schemars(with = "String")]
+    pub amm: Pubkey,
+    pub name: String,
+    pub points: Vec<PerformancePoint>,
+}
+
+/// No source delivered a pool or sale creation for the deadman timeout.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FeedStalledEvent {
+    pub silent_secs: u64,
+    // the sources merged into the feed
+    pub sources: Vec<String>,
+}
+
+/// Version of the event JSON, sent as `schema_version` with every event.
+/// Bumped when a field is removed, renamed or changes meaning; added fields
+/// and event types keep the version.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Something worth telling the sinks about. Serialized as an object tagged
+/// with `type`, next to `schema_version`.
+// `remote = "Self"` turns the derived impls into `Event::serialize` and
+// `Event::deserialize`, wrapped below to add the version
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(remote = "Self", tag = "type", rename_all = "snake_case")]
+#[schemars(rename = "Event")]
+pub enum Event {
+    NewPool(Box<PoolEvent>),
+    TokenLaunched(TokenLaunchedEvent),
+    DexPool(DexPoolEvent),
+    LiquidityPulled(LiquidityPulledEvent),
+    DeveloperSold(DeveloperSoldEvent),
+    PoolAdmin(PoolAdminEvent),
+    AuthorityChanged(AuthorityChangedEvent),
+    LiquidityMigrated(LiquidityMigratedEvent),
+    Graduated(GraduatedEvent),
+    DexScreenerListed(DexScreenerEvent),
+    DexScreenerProfile(DexScreenerEvent),
+    PerformanceReport(PerformanceReportEvent),
+    Digest(Box<Digest>),
+    Summary(RuntimeSummary),
+    FeedStalled(FeedStalledEvent),
+}
+
+impl Serialize for Event {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        struct Tagged<'a>(&'a Event);
+
+        impl Serialize for Tagged<'_> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                Event::serialize(self.0, serializer)
+            }
+        }
+
+        #[derive(Serialize)]
+        struct Versioned<'a> {
+            schema_version: u32,
+            #[serde(flatten)]
+            event: Tagged<'a>,
+        }
+
+        Versioned { schema_version: SCHEMA_VERSION, event: Tagged(self) }.serialize(serializer)
+    }
+}
+
+// events from before the version, such as old delivery queue entries, read
+// the same; the version is ignored
+impl<'de> Deserialize<'de> for Event {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut event = Event::deserialize(deserializer)?;
+        if let Event::NewPool(pool) = &mut event {
+            pool.pair_type = PairType::of(pool.quote().mint);
+        }
+        Ok(event)
+    }
+}
+
+/// The JSON Schema of the events as the sinks receive them, `schema_version`
+/// included.
+pub fn schema() -> RootSchema {
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    struct VersionedEvent {
+        /// Version of the event JSON, bumped when a field is removed, renamed
+        /// or changes meaning
+        #[schemars(range(min = 1))]
+        schema_version: u32,
+        #[serde(flatten)]
+        event: Event,
+    }
+
+    let mut schema = schemars::schema_for!(VersionedEvent);
+    let metadata = schema.schema.metadata();
+    metadata.title = Some(format!("Raydium V4 monitor event, schema version {}", SCHEMA_VERSION));
+    schema
+}
+
+impl Event {
+    /// The `type` of each event.
+    pub const TYPES: &'static [&'static str] = &[
+        "new_pool",
+        "token_launched",
+        "dex_pool",
+        "liquidity_pulled",
+        "developer_sold",
+        "pool_admin",
+        "authority_changed",
+        "liquidity_migrated",
+        "graduated",
+        "dex_screener_listed",
+        "dex_screener_profile",
+        "performance_report",
+        "digest",
+        "summary",
+        "feed_stalled",
+    ];
+
+    /// The `type` the event is serialized with.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Event::NewPool(_) => "new_pool",
+            Event::TokenLaunched(_) => "token_launched",
+            Event::DexPool(_) => "dex_pool",
+            Event::LiquidityPulled(_) => "liquidity_pulled",
+            Event::DeveloperSold(_) => "developer_sold",
+            Event::PoolAdmin(_) => "pool_admin",
+            Event::AuthorityChanged(_) => "authority_changed",
+            Event::LiquidityMigrated(_) => "liquidity_migrated",
+            Event::Graduated(_) => "graduated",
+            Event::DexScreenerListed(_) => "dex_screener_listed",
+            Event::DexScreenerProfile(_) => "dex_screener_profile",
+            Event::PerformanceReport(_) => "performance_report",
+            Event::Digest(_) => "digest",
+            Event::Summary(_) => "summary",
+            Event::FeedStalled(_) => "feed_stalled",
+        }
+    }
+
+    /// The pool, mints and wallets the event is about.
+    pub fn addresses(&self) -> Vec<&Pubkey> {
+        match self {
+            Event::NewPool(pool) => vec![&pool.amm, &pool.coin_mint, &pool.pc_mint, &pool.creator],
+            Event::TokenLaunched(launched) => vec![&launched.launch_pool, &launched.base_mint, &launched.creator],
+            Event::DexPool(pool) => vec![&pool.pool, &pool.base_mint, &pool.quote_mint],
+            Event::LiquidityPulled(pulled) => vec![&pulled.amm],
+            Event::DeveloperSold(sold) => vec![&sold.amm, &sold.wallet],
+            Event::PoolAdmin(admin) => vec![&admin.amm],
+            Event::AuthorityChanged(changed) => vec![&changed.amm, &changed.account],
+            Event::LiquidityMigrated(migrated) => vec![&migrated.amm, &migrated.from_amm],
+            Event::Graduated(graduated) => vec![&graduated.amm],
+            Event::DexScreenerListed(dexscreener) | Event::DexScreenerProfile(dexscreener) => vec![&dexscreener.amm],
+            Event::PerformanceReport(report) => vec![&report.amm],
+            Event::Digest(_) | Event::Summary(_) | Event::FeedStalled(_) => Vec::new(),
+        }
+    }
+
+    pub fn priority(&self) -> Priority {
+        match self {
+            Event::NewPool(pool) => pool.priority(),
+            Event::LiquidityPulled(_)
+            | Event::DeveloperSold(_)
+            | Event::AuthorityChanged(_)
+            | Event::LiquidityMigrated(_)
+            | Event::FeedStalled(_) => Priority::High,
+            // protocol fee collection and order cancels are routine
+            Event::PoolAdmin(admin) if admin.param.is_some() => Priority::High,
+            Event::PoolAdmin(_) => Priority::Normal,
+            Event::TokenLaunched(_) | Event::DexPool(_) => Priority::Normal,
+            Event::Graduated(_) | Event::PerformanceReport(_) => Priority::Normal,
+            Event::DexScreenerListed(_) => Priority::Low,
+            Event::DexScreenerProfile(_) => Priority::Normal,
+            Event::Digest(digest) => digest.priority,
+            Event::Summary(summary) => summary.priority,
+        }
+    }
+
+    pub fn title(&self) -> String {
+        match self {
+            Event::NewPool(pool) => format!("New pool: {}", pool.name()),
+            Event::TokenLaunched(launched) => format!("Token launched: {}", launched.symbol),
+            Event::DexPool(pool) => format!("New {} pool: {}", pool.dex, token_label(None, &pool.base_mint)),
+            Event::LiquidityPulled(pulled) => format!("Liquidity pulled: {}", pulled.name),
+            Event::DeveloperSold(sold) => format!("Developer sold: {}", sold.name),
+            Event::PoolAdmin(admin) => format!("Pool changed by admin: {}", admin.name),
+            Event::AuthorityChanged(changed) => format!("Authority changed: {}", changed.name),
+            Event::LiquidityMigrated(migrated) => format!("Liquidity migrated: {}", migrated.name),
+            Event::Graduated(graduated) => format!("Graduated ({}): {}", graduated.milestone, graduated.name),
+            Event::DexScreenerListed(listed) => format!("Listed on DexScreener: {}", listed.name),
+            Event::DexScreenerProfile(profile) => format!("DexScreener profile: {}", profile.name),
+            Event::PerformanceReport(report) => format!("Performance: {}", report.name),
+            Event::Digest(digest) => format!("Daily digest: {}", digest.date),
+            Event::Summary(summary) => format!("Runtime summary ({} min)", summary.interval_secs / 60),
+            Event::FeedStalled(stalled) => format!("No pools for {} min", stalled.silent_secs / 60),
+        }
+    }
+
+    pub fn body(&self) -> String {
+        match self {
+            Event::NewPool(pool) => {
+                let mut body = match pool.liquidity_sol {
+                    Some(sol) => format!("Liquidity: {:.2} SOL", sol),
+                    None => format!("{} {} / {} {}", pool.base().init_amount, pool.base().label(), pool.quote().init_amount, pool.quote().label()),
+                };
+                if let Some(countdown) = crate::time::opens_in(pool.open_time, crate::sniper::unix_now()) {
+                    body.push_str(&format!("\n{}", countdown));
+                }
+                let risks = pool.risks();
+                if !risks.is_empty() {
+                    body.push_str(&format!("\nRisk: {}", risks.join(", ")));
+                }
+                let tags = pool.tags();
+                if !tags.is_empty() {
+                    body.push_str(&format!("\nTags: {}", tags.join(", ")));
+                }
+                for (provider, enrichment) in &pool.enrichment {
+                    if let Some(price) = enrichment.price_usd {
+                        body.push_str(&format!("\n{} price: ${}", provider, price));
+                    }
+                }
+                if let Some(launch_pool) = &pool.launch_pool {
+                    body.push_str(&format!("\nLaunched on: {}", launch_pool));
+                }
+                if let (Some(age_secs), Some(deployer)) = (pool.token_age_secs, &pool.deployer) {
+                    let same = if *deployer == pool.creator.to_string() { " (pool creator)" } else { "" };
+                    body.push_str(&format!("\nMinted {}m {}s ago by {}{}", age_secs / 60, age_secs % 60, deployer, same));
+                }
+                body.push_str(&format!("\nPool: {}", pool.amm));
+                body
+            }
+            Event::TokenLaunched(launched) => format!(
+                "{} ({}) on {}\nCreator: {}\nMint: {}\nLaunch pool: {}",
+                launched.name, launched.symbol, launched.launchpad, launched.creator, launched.base_mint, launched.launch_pool
+            ),
+            Event::DexPool(pool) => {
+                let mut lines = pool.fields.iter().map(|(name, value)| format!("{}: {}", name, value)).collect::<Vec<_>>();
+                lines.push(format!("Mints: {} / {}", pool.base_mint, pool.quote_mint));
+                if let Some(launch_pool) = &pool.launch_pool {
+                    lines.push(format!("Launched on: {}", launch_pool));
+                }
+                lines.push(format!("Pool: {}", pool.pool));
+                lines.join("\n")
+            }
+            Event::LiquidityPulled(pulled) => format!(
+                "Quote reserve down {:.1}% in {}s ({:.4} -> {:.4})\nPool: {}",
+                pulled.drop_percent, pulled.window_secs, pulled.from_reserve, pulled.to_reserve, pulled.amm
+            ),
+            Event::DeveloperSold(sold) => format!(
+                "{} {} sold or moved {:.1}% of its tokens\nPool: {}",
+                if sold.deployer { "Deployer" } else { "Deployer-funded wallet" },
+                sold.wallet,
+                sold.drop_percent,
+                sold.amm
+            ),
+            Event::PoolAdmin(admin) => {
+                let change = match (&admin.param, &admin.from, &admin.to) {
+                    (Some(param), Some(from), Some(to)) => format!("{}: {} -> {}", param, from, to),
+                    (Some(param), None, Some(to)) => format!("{} set to {}", param, to),
+                    (Some(param), _, None) => format!("{} changed", param),
+                    (None, _, _) => admin.instruction.clone(),
+                };
+                format!("{}\nTx: {}\nPool: {}", change, admin.signature, admin.amm)
+            }
+            Event::AuthorityChanged(changed) => format!(
+                "{} of {}: {} -> {}\nPool: {}",
+                changed.authority,
+                changed.account,
+                changed.from.as_deref().unwrap_or("none"),
+                changed.to.as_deref().unwrap_or("none"),
+                changed.amm
+            ),
+            Event::LiquidityMigrated(migrated) => format!(
+                "{}earlier pool {} down {:.1}% ({:.4} -> {:.4}), new pool opened with {:.4}\nPool: {}",
+                if migrated.same_creator { "Same creator, " } else { "" },
+                migrated.from_amm,
+                migrated.drop_percent,
+                migrated.from_reserve,
+                migrated.to_reserve,
+                migrated.new_reserve,
+                migrated.amm
+            ),
+            Event::Graduated(graduated) => format!(
+                "Liquidity ${:.0}, volume ${:.0}, held for {}s\nPool: {}",
+                graduated.liquidity_usd, graduated.volume_usd, graduated.sustained_secs, graduated.amm
+            ),
+            Event::DexScreenerListed(dex) | Event::DexScreenerProfile(dex) => {
+                let mut body = dex.url.clone();
+                if let Some(price) = dex.price_usd {
+                    body.push_str(&format!("\nPrice: ${}", price));
+                }
+                if let Some(liquidity) = dex.liquidity_usd {
+                    body.push_str(&format!("\nLiquidity: ${:.0}", liquidity));
+                }
+                for link in dex.websites.iter().chain(&dex.socials) {
+                    body.push_str(&format!("\n{}", link));
+                }
+                body.push_str(&format!("\nPool: {}", dex.amm));
+                body
+            }
+            Event::PerformanceReport(report) => {
+                let mut lines = report
+                    .points
+                    .iter()
+                    .map(|point| {
+                        format!(
+                            "+{}: price {:+.1}%, liquidity {:+.1}% ({:.4})",
+                            crate::time::format_offset(point.offset_secs),
+                            point.price_change_percent,
+                            point.liquidity_change_percent,
+                            point.quote_liquidity
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                lines.push(format!("Pool: {}", report.amm));
+                lines.join("\n")
+            }
+            Event::Digest(digest) => digest.markdown(),
+            Event::Summary(summary) => {
+                let mut body = format!(
+                    "Pools: {} detected, {} filtered, {} emitted\nRPC errors: {}",
+                    summary.pools_detected, summary.pools_filtered, summary.events_emitted, summary.rpc_errors
+                );
+                if let Some(latency) = summary.avg_latency_ms {
+                    body.push_str(&format!("\nAvg latency: {} ms", latency));
+                }
+                if let Some(rate) = summary.cache_hit_rate {
+                    body.push_str(&format!("\nCache hit rate: {:.1}%", rate * 100.0));
+                }
+                if let Some(drift) = summary.clock_drift_ms {
+                    body.push_str(&format!("\nClock drift: {} ms", drift));
+                }
+                if !summary.rpc_calls.is_empty() {
+                    let total: u64 = summary.rpc_calls.iter().map(|usage| usage.calls).sum();
+                    let top = summary.rpc_calls.iter().take(3).map(|usage| format!("{} {}", usage.method, usage.calls));
+                    body.push_str(&format!("\nRPC calls: {} ({})", total, top.collect::<Vec<_>>().join(", ")));
+                }
+                body
+            }
+            Event::FeedStalled(stalled) => format!(
+                "No source delivered a pool or sale creation for {}s, check the subscriptions\nSources: {}",
+                stalled.silent_secs,
+                stalled.sources.join(", ")
+            ),
+        }
+    }
+}
+
+// pubkeys and signatures are written as base58 strings
+fn display<T: Display, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+fn parse<'de, T: FromStr, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error>
+where
+    T::Err: Display,
+{
+    String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+}
+
+// the flags set by the monitor, so read-back events keep static strings
+const RISK_FLAGS: [&str; 3] = ["mint", "freeze", "relaunch"];
+
+fn risk_flags<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<&'static str>, D::Error> {
+    let flags = Vec::<String>::deserialize(deserializer)?;
+    Ok(flags.iter().filter_map(|flag| RISK_FLAGS.iter().find(|known| *known == flag).copied()).collect())
+}
+
+/// A rug alert, for tests across the crate.
+#[cfg(test)]
+pub(crate) fn liquidity_pulled(amm: Pubkey, name: &str) -> Event {
+    Event::LiquidityPulled(LiquidityPulledEvent {
+        amm,
+        name: name.to_string(),
+        from_reserve: 120.0,
+        to_reserve: 0.5,
+        drop_percent: 99.6,
+        window_secs: 60,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn events_carry_the_schema_version() {
+        let json = serde_json::to_value(liquidity_pulled(Pubkey::new_unique(), "DOG/WSOL")).unwrap();
+        assert_eq!(json["schema_version"], SCHEMA_VERSION);
+        assert_eq!(json["type"], "liquidity_pulled");
+        assert_eq!(json["type"], liquidity_pulled(Pubkey::new_unique(), "DOG/WSOL").kind());
+        assert_eq!(json["window_secs"], 60);
+    }
+
+    #[test]
+    fn events_read_back_with_or_without_a_version() {
+        let mut json = serde_json::to_value(liquidity_pulled(Pubkey::new_unique(), "DOG/WSOL")).unwrap();
+        assert!(matches!(serde_json::from_value(json.clone()).unwrap(), Event::LiquidityPulled(_)));
+        json.as_object_mut().unwrap().remove("schema_version");
+        assert!(matches!(serde_json::from_value(json).unwrap(), Event::LiquidityPulled(pulled) if pulled.window_secs == 60));
+    }
+
+    #[test]
+    fn schema_requires_the_version_and_lists_every_type() {
+        let schema = serde_json::to_value(schema()).unwrap();
+        assert_eq!(schema["required"], json!(["schema_version"]));
+        let types = schema["oneOf"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|variant| variant["properties"]["type"]["enum"][0].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(Value::from(types), json!(Event::TYPES));
+    }
+}
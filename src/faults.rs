@@ -0,0 +1,116 @@
+use crate::config::FaultsConfig;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tracing::warn;
+
+static FAULTS: OnceLock<Faults> = OnceLock::new();
+
+/// A kind of injected failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    // the log subscription drops after a notification
+    WsDisconnect,
+    // a notification is delivered twice
+    WsDuplicate,
+    // an RPC request hangs for `rpc_timeout_ms` and fails
+    RpcTimeout,
+    // an RPC response body is not valid JSON
+    RpcMalformed,
+}
+
+impl Fault {
+    const ALL: [Fault; 4] = [Fault::WsDisconnect, Fault::WsDuplicate, Fault::RpcTimeout, Fault::RpcMalformed];
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|fault| *fault == self).unwrap_or(0)
+    }
+}
+
+/// Randomly injected failures for resilience testing. Each fault kind draws
+/// from its own generator seeded from `seed`, so a run is reproducible as
+/// long as the calls of that kind happen in the same order.
+struct Faults {
+    config: FaultsConfig,
+    // splitmix64 state per fault kind
+    states: [Mutex<u64>; 4],
+}
+
+/// Enables fault injection for the rest of the process.
+pub fn init(config: &FaultsConfig) {
+    warn!(
+        seed = config.seed,
+        ws_disconnect_rate = config.ws_disconnect_rate,
+        ws_duplicate_rate = config.ws_duplicate_rate,
+        rpc_timeout_rate = config.rpc_timeout_rate,
+        rpc_malformed_rate = config.rpc_malformed_rate,
+        "Fault injection enabled"
+    );
+    let _ = FAULTS.set(Faults::new(config));
+}
+
+/// Whether to inject `fault` now; always false unless enabled.
+pub fn inject(fault: Fault) -> bool {
+    FAULTS.get().is_some_and(|faults| faults.inject(fault))
+}
+
+impl Faults {
+    fn new(config: &FaultsConfig) -> Self {
+        let states = Fault::ALL.map(|fault| Mutex::new(config.seed.wrapping_add(fault.index() as u64)));
+        Self { config: config.clone(), states }
+    }
+
+    fn inject(&self, fault: Fault) -> bool {
+        let rate = match fault {
+            Fault::WsDisconnect => self.config.ws_disconnect_rate,
+            Fault::WsDuplicate => self.config.ws_duplicate_rate,
+            Fault::RpcTimeout => self.config.rpc_timeout_rate,
+            Fault::RpcMalformed => self.config.rpc_malformed_rate,
+        };
+        if rate <= 0.0 {
+            return false;
+        }
+        let injected = next_f64(&mut self.states[fault.index()].lock().unwrap()) < rate;
+        if injected {
+            warn!(fault = ?fault, "Injecting fault");
+        }
+        injected
+    }
+}
+
+/// How long an injected RPC timeout hangs.
+pub fn rpc_timeout() -> Duration {
+    Duration::from_millis(FAULTS.get().map_or(0, |faults| faults.config.rpc_timeout_ms))
+}
+
+// uniform in [0, 1)
+fn next_f64(state: &mut u64) -> f64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draws(faults: &Faults, fault: Fault) -> Vec<bool> {
+        (0..64).map(|_| faults.inject(fault)).collect()
+    }
+
+    #[test]
+    fn rates_bound_the_faults_and_seeds_repeat_them() {
+        let config = FaultsConfig { seed: 7, ws_disconnect_rate: 1.0, ws_duplicate_rate: 0.5, ..FaultsConfig::default() };
+        let faults = Faults::new(&config);
+        assert!(draws(&faults, Fault::RpcTimeout).iter().all(|injected| !injected));
+        assert!(draws(&faults, Fault::WsDisconnect).iter().all(|injected| *injected));
+
+        let duplicates = draws(&faults, Fault::WsDuplicate);
+        assert!(duplicates.contains(&true) && duplicates.contains(&false));
+        // drawing other kinds in between does not shift a kind's sequence
+        assert_eq!(draws(&Faults::new(&config), Fault::WsDuplicate), duplicates);
+        assert_ne!(draws(&Faults::new(&FaultsConfig { seed: 8, ..config }), Fault::WsDuplicate), duplicates);
+    }
+}
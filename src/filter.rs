@@ -0,0 +1,224 @@
+use crate::events::{Event, PairType, QuoteSide};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+/// Criteria a subscriber of `GET /events` passes at subscribe time, so bots
+/// with different criteria can share one monitor and only receive what they
+/// act on. The pool criteria apply to new pools; the other events pass them
+/// and are only limited by type and watchlist.
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    // event types; empty for all
+    types: HashSet<String>,
+    // quote mints and symbols (lowercase); both empty for any quote
+    quote_mints: HashSet<Pubkey>,
+    quote_symbols: HashSet<String>,
+    // sol, stable or token; empty for any
+    pair_types: HashSet<PairType>,
+    min_liquidity_sol: Option<f64>,
+    // risk flags of the base token, built-in and from enrichment
+    max_risks: Option<usize>,
+    // pools, mints and wallets; empty for any
+    watchlist: HashSet<Pubkey>,
+}
+
+impl EventFilter {
+    /// `types`, `quotes`, `pairs` and `watchlist` are comma-separated, as in
+    /// the query string.
+    pub fn parse(
+        types: Option<&str>,
+        quotes: Option<&str>,
+        pairs: Option<&str>,
+        min_liquidity_sol: Option<f64>,
+        max_risks: Option<usize>,
+        watchlist: Option<&str>,
+    ) -> Result<Self> {
+        let types = list(types).map(str::to_string).collect::<HashSet<_>>();
+        if let Some(unknown) = types.iter().find(|kind| !Event::TYPES.contains(&kind.as_str())) {
+            return Err(anyhow!("unknown event type {}, expected one of {}", unknown, Event::TYPES.join(", ")));
+        }
+        let pair_types = list(pairs).map(PairType::from_str).collect::<Result<_>>()?;
+        let watchlist = list(watchlist)
+            .map(|address| Pubkey::from_str(address).map_err(|_| anyhow!("invalid watchlist address {}", address)))
+            .collect::<Result<_>>()?;
+        let (quote_mints, quote_symbols) = list(quotes).fold(
+            (HashSet::new(), HashSet::new()),
+            |(mut mints, mut symbols), quote| {
+                match Pubkey::from_str(quote) {
+                    Ok(mint) => mints.insert(mint),
+                    Err(_) => symbols.insert(quote.to_lowercase()),
+                };
+                (mints, symbols)
+            },
+        );
+        Ok(Self {
+            types,
+            quote_mints,
+            quote_symbols,
+            pair_types,
+            min_liquidity_sol,
+            max_risks,
+            watchlist,
+        })
+    }
+
+    pub fn matches(&self, event: &Event) -> bool {
+        if !self.types.is_empty() && !self.types.contains(event.kind()) {
+            return false;
+        }
+        if !self.watchlist.is_empty() && !event.addresses().into_iter().any(|address| self.watchlist.contains(address)) {
+            return false;
+        }
+        let Event::NewPool(pool) = event else {
+            return true;
+        };
+        // before the quote lookups, so token-token pools drop out cheaply
+        if !self.pair_types.is_empty() && !self.pair_types.contains(&pool.pair_type) {
+            return false;
+        }
+        let quote_symbol = match pool.quote_side {
+            QuoteSide::Coin => pool.coin_symbol.as_deref(),
+            QuoteSide::Pc => pool.pc_symbol.as_deref(),
+        };
+        let any_quote = self.quote_mints.is_empty() && self.quote_symbols.is_empty();
+        if !any_quote
+            && !self.quote_mints.contains(pool.quote().mint)
+            && !quote_symbol.is_some_and(|symbol| self.quote_symbols.contains(&symbol.to_lowercase()))
+        {
+            return false;
+        }
+        if let Some(min) = self.min_liquidity_sol {
+            if !pool.liquidity_sol.is_some_and(|liquidity| liquidity >= min) {
+                return false;
+            }
+        }
+        self.max_risks.is_none_or(|max| pool.risks().len() <= max)
+    }
+}
+
+/// The criteria of an `EventFilter` as given, kept with a registered webhook.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(default)]
+pub struct FilterParams {
+    pub types: Option<String>,
+    pub quote: Option<String>,
+    pub pairs: Option<String>,
+    pub min_liquidity_sol: Option<f64>,
+    pub max_risks: Option<usize>,
+    pub watchlist: Option<String>,
+}
+
+impl FilterParams {
+    pub fn parse(&self) -> Result<EventFilter> {
+        EventFilter::parse(
+            self.types.as_deref(),
+            self.quote.as_deref(),
+            self.pairs.as_deref(),
+            self.min_liquidity_sol,
+            self.max_risks,
+            self.watchlist.as_deref(),
+        )
+    }
+}
+
+fn list(value: Option<&str>) -> impl Iterator<Item = &str> {
+    value.unwrap_or_default().split(',').map(str::trim).filter(|item| !item.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{liquidity_pulled, PoolEvent};
+    use rust_decimal::Decimal;
+    use solana_sdk::signature::Signature;
+    use std::collections::BTreeMap;
+
+    fn pool(liquidity_sol: Option<f64>, flags: Vec<&'static str>) -> PoolEvent {
+        PoolEvent {
+            signature: Signature::new_unique(),
+            amm: Pubkey::new_unique(),
+            creator: Pubkey::new_unique(),
+            slot: 250_000_000,
+            coin_mint: Pubkey::new_unique(),
+            coin_name: Some("Dog Coin".to_string()),
+            coin_symbol: Some("DOG".to_string()),
+            coin_image: None,
+            pc_mint: spl_token::native_mint::id(),
+            pc_name: Some("Wrapped SOL".to_string()),
+            pc_symbol: Some("WSOL".to_string()),
+            pc_image: None,
+            init_coin_amount: Decimal::new(200_000_000_000, 6),
+            init_pc_amount: Decimal::new(5_000_000_000, 9),
+            quote_side: QuoteSide::Pc,
+            pair_type: PairType::Sol,
+            liquidity_sol,
+            price_impact: None,
+            flags,
+            open_time: 1_700_000_000,
+            block_delay_secs: Some(1),
+            launch_pool: None,
+            token_age_secs: None,
+            deployer: None,
+            enrichment: BTreeMap::new(),
+        }
+    }
+
+    fn filter(types: &str, quotes: &str, min_liquidity_sol: Option<f64>, max_risks: Option<usize>, watchlist: &str) -> EventFilter {
+        EventFilter::parse(Some(types), Some(quotes), None, min_liquidity_sol, max_risks, Some(watchlist)).unwrap()
+    }
+
+    #[test]
+    fn an_empty_filter_passes_everything() {
+        let any = filter("", "", None, None, "");
+        assert!(any.matches(&Event::NewPool(Box::new(pool(None, vec!["mint", "freeze"])))));
+        assert!(any.matches(&liquidity_pulled(Pubkey::new_unique(), "DOG/WSOL")));
+    }
+
+    #[test]
+    fn pool_criteria_apply_to_new_pools_only() {
+        let strict = filter("", "wsol", Some(5.0), Some(0), "");
+        assert!(strict.matches(&Event::NewPool(Box::new(pool(Some(5.0), vec![])))));
+        assert!(!strict.matches(&Event::NewPool(Box::new(pool(Some(4.9), vec![])))));
+        assert!(!strict.matches(&Event::NewPool(Box::new(pool(None, vec![])))));
+        assert!(!strict.matches(&Event::NewPool(Box::new(pool(Some(5.0), vec!["mint"])))));
+        assert!(strict.matches(&liquidity_pulled(Pubkey::new_unique(), "DOG/WSOL")));
+
+        let usdc = filter("", "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v", None, None, "");
+        assert!(!usdc.matches(&Event::NewPool(Box::new(pool(Some(5.0), vec![])))));
+        let sol = filter("", "USDC,So11111111111111111111111111111111111111112", None, None, "");
+        assert!(sol.matches(&Event::NewPool(Box::new(pool(Some(5.0), vec![])))));
+    }
+
+    #[test]
+    fn pair_types_select_new_pools() {
+        let no_tokens = EventFilter::parse(None, None, Some("sol,stable"), None, None, None).unwrap();
+        assert!(no_tokens.matches(&Event::NewPool(Box::new(pool(Some(5.0), vec![])))));
+        let token_pair = PoolEvent { pair_type: PairType::Token, ..pool(None, vec![]) };
+        assert!(!no_tokens.matches(&Event::NewPool(Box::new(token_pair))));
+        assert!(no_tokens.matches(&liquidity_pulled(Pubkey::new_unique(), "DOG/WSOL")));
+        assert!(EventFilter::parse(None, None, Some("sol,meme"), None, None, None).is_err());
+    }
+
+    #[test]
+    fn types_and_watchlist_apply_to_every_event() {
+        let watched = pool(Some(5.0), vec![]);
+        let by_mint = filter("new_pool, liquidity_pulled", "", None, None, &watched.coin_mint.to_string());
+        assert!(by_mint.matches(&Event::NewPool(Box::new(watched.clone()))));
+        assert!(!by_mint.matches(&Event::NewPool(Box::new(pool(Some(5.0), vec![])))));
+        assert!(!by_mint.matches(&liquidity_pulled(Pubkey::new_unique(), "DOG/WSOL")));
+
+        let by_amm = filter("liquidity_pulled", "", None, None, &watched.amm.to_string());
+        assert!(by_amm.matches(&liquidity_pulled(watched.amm, "DOG/WSOL")));
+        assert!(!by_amm.matches(&Event::NewPool(Box::new(watched))));
+    }
+
+    #[test]
+    fn rejects_unknown_types_and_addresses() {
+        assert!(EventFilter::parse(Some("new_pools"), None, None, None, None, None).is_err());
+        assert!(EventFilter::parse(None, None, None, None, None, Some("not-an-address")).is_err());
+    }
+}
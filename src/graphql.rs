@@ -0,0 +1,326 @@
+use crate::store::{
+    CreatorSummary, FirstBuyer, HolderSnapshot, PerformancePoint, PoolCursor, PoolRecord, Rug, SnipeReport, Store,
+    SwapCursor, SwapRecord,
+};
+use async_graphql::{
+    http::GraphiQLSource, ComplexObject, Context, EmptyMutation, EmptySubscription, Object, Result, Schema,
+};
+use axum::{
+    extract::State,
+    response::{Html, IntoResponse},
+    routing::get,
+    Json, Router,
+};
+use std::sync::Arc;
+
+// rows of a list field when the query sets no limit, and the most it may ask for
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 1000;
+// deep enough for pool -> creator -> pools -> swaps
+const MAX_DEPTH: usize = 8;
+
+pub type StoreSchema = Schema<Query, EmptyMutation, EmptySubscription>;
+
+pub fn schema(store: Arc<Store>) -> StoreSchema {
+    Schema::build(Query, EmptyMutation, EmptySubscription).data(store).limit_depth(MAX_DEPTH).finish()
+}
+
+/// `POST /graphql` for queries, `GET /graphql` for the GraphiQL editor.
+pub fn router(store: Arc<Store>) -> Router {
+    Router::new().route("/graphql", get(graphiql).post(execute)).with_state(schema(store))
+}
+
+async fn execute(State(schema): State<StoreSchema>, Json(request): Json<async_graphql::Request>) -> impl IntoResponse {
+    Json(schema.execute(request).await)
+}
+
+async fn graphiql() -> impl IntoResponse {
+    Html(GraphiQLSource::build().endpoint("/graphql").finish())
+}
+
+fn store<'a>(ctx: &Context<'a>) -> &'a Store {
+    ctx.data_unchecked::<Arc<Store>>()
+}
+
+fn limit(limit: Option<usize>) -> usize {
+    limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT)
+}
+
+fn pools(
+    store: &Store,
+    creator: Option<&str>,
+    quote_mint: Option<&str>,
+    since: Option<u64>,
+    until: Option<u64>,
+    after: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<Pool>> {
+    let after = after.map(|after| after.parse::<PoolCursor>()).transpose()?;
+    let pools = store.pools(
+        creator,
+        quote_mint,
+        since.unwrap_or(0),
+        until.unwrap_or(u64::MAX),
+        after.as_ref(),
+        self::limit(limit),
+    )?;
+    Ok(pools.into_iter().map(Pool).collect())
+}
+
+pub struct Query;
+
+#[Object]
+impl Query {
+    /// Detected pools, newest first. `since` and `until` are unix seconds
+    /// of detection; `after` is the `cursor` of the last pool of the previous
+    /// page.
+    #[allow(clippy::too_many_arguments)]
+    async fn pools(
+        &self,
+        ctx: &Context<'_>,
+        creator: Option<String>,
+        quote_mint: Option<String>,
+        since: Option<u64>,
+        until: Option<u64>,
+        after: Option<String>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Pool>> {
+        pools(store(ctx), creator.as_deref(), quote_mint.as_deref(), since, until, after, limit)
+    }
+
+    async fn pool(&self, ctx: &Context<'_>, amm: String) -> Result<Option<Pool>> {
+        Ok(store(ctx).pool(&amm)?.map(Pool))
+    }
+
+    async fn creator(&self, ctx: &Context<'_>, address: String) -> Result<Option<Creator>> {
+        Ok(store(ctx).creator_summary(&address)?.map(Creator))
+    }
+}
+
+/// A detected pool with what the monitor recorded about it since. Amounts
+/// are raw, in the mints' smallest units.
+pub struct Pool(PoolRecord);
+
+#[Object]
+impl Pool {
+    async fn signature(&self) -> &str {
+        &self.0.signature
+    }
+
+    async fn amm(&self) -> &str {
+        &self.0.amm
+    }
+
+    async fn slot(&self) -> u64 {
+        self.0.slot
+    }
+
+    /// Pass as `after` to continue with the pools after this one.
+    async fn cursor(&self) -> String {
+        self.0.cursor().to_string()
+    }
+
+    async fn coin_mint(&self) -> &str {
+        &self.0.coin_mint
+    }
+
+    async fn pc_mint(&self) -> &str {
+        &self.0.pc_mint
+    }
+
+    async fn quote_mint(&self) -> &str {
+        &self.0.quote_mint
+    }
+
+    async fn init_coin_amount(&self) -> u64 {
+        self.0.init_coin_amount
+    }
+
+    async fn init_pc_amount(&self) -> u64 {
+        self.0.init_pc_amount
+    }
+
+    async fn sol_liquidity_lamports(&self) -> Option<u64> {
+        self.0.sol_liquidity_lamports
+    }
+
+    async fn open_time(&self) -> u64 {
+        self.0.open_time
+    }
+
+    async fn detected_at(&self) -> u64 {
+        self.0.detected_at
+    }
+
+    async fn creator(&self, ctx: &Context<'_>) -> Result<Option<Creator>> {
+        Ok(store(ctx).creator_summary(&self.0.creator)?.map(Creator))
+    }
+
+    /// Newest first, after the swap whose `cursor` is `after`.
+    async fn swaps(&self, ctx: &Context<'_>, after: Option<String>, limit: Option<usize>) -> Result<Vec<SwapRecord>> {
+        let after = after.map(|after| after.parse::<SwapCursor>()).transpose()?;
+        Ok(store(ctx).swaps(&self.0.amm, after.as_ref(), self::limit(limit))?)
+    }
+
+    async fn first_buyers(&self, ctx: &Context<'_>) -> Result<Vec<FirstBuyer>> {
+        Ok(store(ctx).first_buyers(&self.0.amm)?)
+    }
+
+    async fn snipes(&self, ctx: &Context<'_>) -> Result<Option<SnipeReport>> {
+        Ok(store(ctx).snipe_report(&self.0.amm)?)
+    }
+
+    /// Set once the pool's liquidity was pulled.
+    async fn rug(&self, ctx: &Context<'_>) -> Result<Option<Rug>> {
+        Ok(store(ctx).rug(&self.0.amm)?)
+    }
+
+    async fn holders(&self, ctx: &Context<'_>) -> Result<Vec<HolderSnapshot>> {
+        Ok(store(ctx).holder_snapshots(&self.0.amm)?)
+    }
+
+    async fn performance(&self, ctx: &Context<'_>) -> Result<Vec<PerformancePoint>> {
+        Ok(store(ctx).performance(&self.0.amm)?)
+    }
+}
+
+/// A wallet that created pools.
+pub struct Creator(CreatorSummary);
+
+#[Object]
+impl Creator {
+    async fn address(&self) -> &str {
+        &self.0.creator
+    }
+
+    async fn pool_count(&self) -> u64 {
+        self.0.pools
+    }
+
+    async fn rug_count(&self) -> u64 {
+        self.0.rugs
+    }
+
+    async fn first_detected_at(&self) -> u64 {
+        self.0.first_detected_at
+    }
+
+    async fn last_detected_at(&self) -> u64 {
+        self.0.last_detected_at
+    }
+
+    /// Newest first, after the pool whose `cursor` is `after`.
+    async fn pools(&self, ctx: &Context<'_>, after: Option<String>, limit: Option<usize>) -> Result<Vec<Pool>> {
+        pools(store(ctx), Some(&self.0.creator), None, None, None, after, limit)
+    }
+}
+
+#[ComplexObject]
+impl SwapRecord {
+    /// Pass as `after` to continue with the swaps after this one.
+    async fn cursor(&self) -> String {
+        SwapCursor { time: self.time, signature: self.signature.clone(), seq: self.seq }.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn pool(amm: &str, creator: &str, detected_at: u64) -> PoolRecord {
+        PoolRecord {
+            signature: format!("sig-{}", amm),
+            amm: amm.to_string(),
+            coin_mint: format!("mint-{}", amm),
+            pc_mint: "So11111111111111111111111111111111111111112".to_string(),
+            quote_mint: "So11111111111111111111111111111111111111112".to_string(),
+            creator: creator.to_string(),
+            slot: detected_at * 2,
+            init_coin_amount: 200_000_000_000,
+            init_pc_amount: 5_000_000_000,
+            sol_liquidity_lamports: Some(5_000_000_000),
+            open_time: detected_at,
+            detected_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn joins_pools_creators_swaps_and_rugs() {
+        let store = Arc::new(Store::open(":memory:").unwrap());
+        store.insert_pool(&pool("amm-1", "creator", 100)).unwrap();
+        store.insert_pool(&pool("amm-2", "creator", 200)).unwrap();
+        store.insert_rug("amm-1", 150, 98.5).unwrap();
+        for (seq, time) in [(0, 110), (1, 120)] {
+            store
+                .insert_swap(&SwapRecord {
+                    amm: "amm-1".to_string(),
+                    signature: "swap".to_string(),
+                    seq,
+                    time,
+                    buy: true,
+                    base_amount: 1000.0,
+                    quote_amount: 0.5,
+                })
+                .unwrap();
+        }
+
+        let response = schema(store)
+            .execute(
+                r#"{
+                    pool(amm: "amm-1") {
+                        detectedAt
+                        rug { dropPercent }
+                        swaps(limit: 1) { time }
+                        creator { poolCount rugCount pools { amm rug { detectedAt } } }
+                    }
+                    pools(since: 150) { amm }
+                }"#,
+            )
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.data.into_json().unwrap(),
+            json!({
+                "pool": {
+                    "detectedAt": 100,
+                    "rug": { "dropPercent": 98.5 },
+                    "swaps": [{ "time": 120 }],
+                    "creator": {
+                        "poolCount": 2,
+                        "rugCount": 1,
+                        "pools": [{ "amm": "amm-2", "rug": null }, { "amm": "amm-1", "rug": { "detectedAt": 150 } }]
+                    }
+                },
+                "pools": [{ "amm": "amm-2" }]
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn pages_through_pools_by_cursor() {
+        let store = Arc::new(Store::open(":memory:").unwrap());
+        for (amm, detected_at) in [("amm-1", 100), ("amm-2", 200), ("amm-3", 300)] {
+            store.insert_pool(&pool(amm, "creator", detected_at)).unwrap();
+        }
+        let schema = schema(store);
+        let page = |after: Option<String>| {
+            let after = after.map_or(String::new(), |after| format!(r#", after: "{}""#, after));
+            let schema = schema.clone();
+            async move {
+                let response = schema.execute(format!("{{ pools(limit: 2{}) {{ amm cursor }} }}", after)).await;
+                assert!(response.errors.is_empty(), "{:?}", response.errors);
+                response.data.into_json().unwrap()["pools"].as_array().unwrap().clone()
+            }
+        };
+
+        let first = page(None).await;
+        assert_eq!(first.iter().map(|pool| pool["amm"].as_str().unwrap()).collect::<Vec<_>>(), ["amm-3", "amm-2"]);
+        assert_eq!(first[1]["cursor"], "400:sig-amm-2");
+        let second = page(first[1]["cursor"].as_str().map(str::to_string)).await;
+        assert_eq!(second.iter().map(|pool| pool["amm"].as_str().unwrap()).collect::<Vec<_>>(), ["amm-1"]);
+
+        let response = schema.execute(r#"{ pools(after: "not-a-cursor") { amm } }"#).await;
+        assert_eq!(response.errors.len(), 1);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::events::Event;
+use crate::sniper::unix_now;
+use crate::store::{Store, StoredEvent};
+use crate::systemd;
+use anyhow::Result;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{error, info};
+
+// live events buffered per consumer before it has to catch up from the store
+const LIVE_CAPACITY: usize = 1024;
+
+/// A just recorded event, parsed once for every subscriber's filter.
+#[derive(Debug)]
+pub struct LiveEvent {
+    pub stored: StoredEvent,
+    pub event: Event,
+}
+
+/// Every dispatched event, kept in the store for consumers catching up after
+/// their own downtime and broadcast to the ones following live. Ids are
+/// assigned and broadcast in the same order, so a consumer switching from
+/// the store to the broadcast neither misses nor repeats an event.
+#[derive(Clone)]
+pub struct EventHistory {
+    store: Arc<Store>,
+    live: broadcast::Sender<Arc<LiveEvent>>,
+    // held from insert to broadcast
+    order: Arc<Mutex<()>>,
+}
+
+impl EventHistory {
+    pub fn new(store: Arc<Store>) -> Self {
+        let (live, _) = broadcast::channel(LIVE_CAPACITY);
+        Self { store, live, order: Arc::new(Mutex::new(())) }
+    }
+
+    pub fn record(&self, event: &Event) {
+        if let Err(e) = self.insert(event) {
+            error!(event = %event.title(), error = %e, "Failed to record event");
+        }
+    }
+
+    fn insert(&self, event: &Event) -> Result<()> {
+        let json = serde_json::to_string(event)?;
+        let slot = match event {
+            Event::NewPool(pool) => pool.slot,
+            _ => systemd::last_slot(),
+        };
+        let _order = self.order.lock().unwrap();
+        let emitted_at = unix_now();
+        let id = self.store.insert_event(emitted_at, slot, &json)?;
+        // no receivers is fine
+        let stored = StoredEvent { id, emitted_at, slot, event: json };
+        let _ = self.live.send(Arc::new(LiveEvent { stored, event: event.clone() }));
+        Ok(())
+    }
+
+    /// Subscribe before reading the store, so events recorded in between
+    /// arrive on the receiver.
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<LiveEvent>> {
+        self.live.subscribe()
+    }
+
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+
+    /// Hourly deletes events older than `retention_days`.
+    pub async fn prune(self, retention_days: u64) {
+        let mut interval = tokio::time::interval(Duration::from_secs(3600));
+        loop {
+            interval.tick().await;
+            let before = unix_now().saturating_sub(retention_days * 86_400);
+            match self.store.prune_events(before) {
+                Ok(0) => {}
+                Ok(pruned) => info!(pruned, "Pruned event history"),
+                Err(e) => error!(error = %e, "Failed to prune event history"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::liquidity_pulled;
+    use solana_sdk::pubkey::Pubkey;
+
+    #[test]
+    fn recorded_events_are_read_back_and_broadcast_in_order() {
+        let history = EventHistory::new(Arc::new(Store::open(":memory:").unwrap()));
+        let mut live = history.subscribe();
+        for name in ["A/WSOL", "B/WSOL", "C/WSOL"] {
+            history.record(&liquidity_pulled(Pubkey::new_unique(), name));
+        }
+
+        let first = history.store().first_event_id(None, Some(0)).unwrap().unwrap();
+        let stored = history.store().events_after(first - 1, 10).unwrap();
+        let broadcast = (0..3).map(|_| live.try_recv().unwrap().stored.id).collect::<Vec<_>>();
+        assert_eq!(stored.iter().map(|event| event.id).collect::<Vec<_>>(), broadcast);
+        assert!(stored[1].event.contains("B/WSOL"));
+        assert_eq!(history.store().events_after(stored[1].id, 10).unwrap().len(), 1);
+        assert_eq!(history.store().first_event_id(None, Some(unix_now() + 60)).unwrap(), None);
+        assert_eq!(history.store().prune_events(unix_now() + 1).unwrap(), 3);
+    }
+}
@@ -0,0 +1,90 @@
+use crate::config::HoldersConfig;
+use crate::raydium::NewPool;
+use crate::sniper::unix_now;
+use crate::store::{HolderSnapshot, Store, TopHolder};
+use anyhow::Result;
+use solana_client::rpc_client::RpcClient;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+// token accounts counted in the top-holder share, out of the 20 returned by
+// getTokenLargestAccounts
+const TOP_HOLDERS: usize = 10;
+
+/// Takes snapshots of a new pool's base-token holder distribution at fixed
+/// offsets after detection.
+pub struct HolderSnapshotter {
+    rpc_client: Arc<RpcClient>,
+    store: Arc<Store>,
+    config: HoldersConfig,
+}
+
+impl HolderSnapshotter {
+    pub fn new(rpc_client: Arc<RpcClient>, store: Arc<Store>, config: HoldersConfig) -> Self {
+        Self { rpc_client, store, config }
+    }
+
+    pub async fn schedule(self: Arc<Self>, pool: NewPool) {
+        let amm = pool.accounts.amm;
+        let detected_at = unix_now();
+        let mut offsets = self.config.offsets_secs.clone();
+        offsets.sort_unstable();
+        offsets.dedup();
+        for offset_secs in offsets {
+            let due = detected_at + offset_secs;
+            tokio::time::sleep(Duration::from_secs(due.saturating_sub(unix_now()))).await;
+            match self.snapshot(&pool, offset_secs) {
+                Ok(snapshot) => info!(
+                    pool = %amm,
+                    offset_secs,
+                    top1_percent = format_args!("{:.2}", snapshot.top1_percent),
+                    top10_percent = format_args!("{:.2}", snapshot.top10_percent),
+                    pool_percent = format_args!("{:.2}", snapshot.pool_percent),
+                    "Took holder snapshot"
+                ),
+                Err(e) => warn!(pool = %amm, offset_secs, error = %e, "Failed to take holder snapshot"),
+            }
+        }
+    }
+
+    fn snapshot(&self, pool: &NewPool, offset_secs: u64) -> Result<HolderSnapshot> {
+        let quote_is_coin = pool.accounts.quote_mint() == pool.accounts.coin_mint;
+        let (base, vault) = if quote_is_coin {
+            (pool.accounts.pc_mint, pool.accounts.pc_vault)
+        } else {
+            (pool.accounts.coin_mint, pool.accounts.coin_vault)
+        };
+        let supply = self.rpc_client.get_token_supply(&base)?;
+        let supply = supply.ui_amount.unwrap_or(0.0);
+        let largest = self.rpc_client.get_token_largest_accounts(&base)?;
+
+        let share = |amount: f64| if supply > 0.0 { amount / supply * 100.0 } else { 0.0 };
+        let vault = vault.to_string();
+        let mut pool_percent = 0.0;
+        let mut top_holders = Vec::new();
+        for account in largest {
+            let amount = account.amount.ui_amount.unwrap_or(0.0);
+            // tokens in the pool vault are liquidity, not a holder
+            if account.address == vault {
+                pool_percent = share(amount);
+            } else if amount > 0.0 {
+                top_holders.push(TopHolder { token_account: account.address, amount, percent: share(amount) });
+            }
+        }
+        top_holders.truncate(TOP_HOLDERS);
+
+        let snapshot = HolderSnapshot {
+            amm: pool.accounts.amm.to_string(),
+            offset_secs,
+            taken_at: unix_now(),
+            supply,
+            pool_percent,
+            top1_percent: top_holders.first().map_or(0.0, |holder| holder.percent),
+            top10_percent: top_holders.iter().map(|holder| holder.percent).sum(),
+            top_holders,
+        };
+        self.store.insert_holder_snapshot(&snapshot)?;
+        Ok(snapshot)
+    }
+}
@@ -0,0 +1,127 @@
+use crate::config::JitoConfig;
+use anyhow::{anyhow, Result};
+use tracing::{info, warn};
+use serde_json::{json, Value};
+use solana_sdk::{pubkey::Pubkey, transaction::Transaction};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// mainnet tip accounts used when getTipAccounts fails
+pub const DEFAULT_TIP_ACCOUNTS: [&str; 8] = [
+    "96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5",
+    "HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe",
+    "Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY",
+    "ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49",
+    "DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh",
+    "ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt",
+    "DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL",
+    "3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT",
+];
+const BUNDLE_STATUS_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// JSON-RPC client for the Jito block engine.
+pub struct JitoClient {
+    http: reqwest::Client,
+    bundles_url: String,
+    tip_accounts: Mutex<Vec<Pubkey>>,
+    next_tip_account: AtomicUsize,
+}
+
+impl JitoClient {
+    pub fn new(config: &JitoConfig) -> Self {
+        let tip_accounts = DEFAULT_TIP_ACCOUNTS
+            .iter()
+            .map(|account| Pubkey::from_str(account).unwrap())
+            .collect();
+        Self {
+            http: reqwest::Client::new(),
+            bundles_url: format!("{}/api/v1/bundles", config.block_engine_url.trim_end_matches('/')),
+            tip_accounts: Mutex::new(tip_accounts),
+            next_tip_account: AtomicUsize::new(0),
+        }
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let response: Value = self
+            .http
+            .post(&self.bundles_url)
+            .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+            .send()
+            .await?
+            .json()
+            .await?;
+        if let Some(error) = response.get("error") {
+            return Err(anyhow!("Jito {} failed: {}", method, error));
+        }
+        response
+            .get("result")
+            .cloned()
+            .ok_or_else(|| anyhow!("Jito {} returned no result", method))
+    }
+
+    /// Refreshes the tip accounts from the block engine.
+    pub async fn refresh_tip_accounts(&self) -> Result<()> {
+        let result = self.call("getTipAccounts", json!([])).await?;
+        let accounts = result
+            .as_array()
+            .ok_or_else(|| anyhow!("Unexpected getTipAccounts result: {}", result))?
+            .iter()
+            .filter_map(|account| account.as_str().and_then(|s| Pubkey::from_str(s).ok()))
+            .collect::<Vec<_>>();
+        if accounts.is_empty() {
+            return Err(anyhow!("getTipAccounts returned no accounts"));
+        }
+        *self.tip_accounts.lock().unwrap() = accounts;
+        Ok(())
+    }
+
+    /// Rotates through the tip accounts to spread write-lock contention.
+    pub fn next_tip_account(&self) -> Pubkey {
+        let accounts = self.tip_accounts.lock().unwrap();
+        let index = self.next_tip_account.fetch_add(1, Ordering::Relaxed) % accounts.len();
+        accounts[index]
+    }
+
+    pub async fn send_bundle(&self, transactions: &[Transaction]) -> Result<String> {
+        let encoded = transactions
+            .iter()
+            .map(|tx| Ok(bs58::encode(bincode::serialize(tx)?).into_string()))
+            .collect::<Result<Vec<_>>>()?;
+        let result = self.call("sendBundle", json!([encoded])).await?;
+        result
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("Unexpected sendBundle result: {}", result))
+    }
+
+    /// The bundle's in-flight status: Invalid, Pending, Failed or Landed.
+    pub async fn bundle_status(&self, bundle_id: &str) -> Result<String> {
+        let result = self.call("getInflightBundleStatuses", json!([[bundle_id]])).await?;
+        Ok(result["value"][0]["status"].as_str().unwrap_or("Unknown").to_string())
+    }
+
+    /// Polls the bundle until it lands, fails or times out, returning the final
+    /// status.
+    pub async fn wait_for_bundle(&self, bundle_id: &str, timeout: Duration) -> Result<String> {
+        let started = Instant::now();
+        let mut last_status = String::from("Unknown");
+        while started.elapsed() < timeout {
+            match self.bundle_status(bundle_id).await {
+                Ok(status) => {
+                    if status != last_status {
+                        info!(bundle_id, status = %status, "Bundle status changed");
+                    }
+                    match status.as_str() {
+                        "Landed" | "Failed" => return Ok(status),
+                        _ => last_status = status,
+                    }
+                }
+                Err(e) => warn!(bundle_id, error = %e, "Failed to get bundle status"),
+            }
+            tokio::time::sleep(BUNDLE_STATUS_POLL_INTERVAL).await;
+        }
+        Err(anyhow!("Bundle {} not resolved after {}s (last status {})", bundle_id, timeout.as_secs(), last_status))
+    }
+}
@@ -0,0 +1,107 @@
+use anyhow::{anyhow, Result};
+use borsh::BorshDeserialize;
+use solana_sdk::pubkey::Pubkey;
+
+/// Raydium LaunchLab on mainnet.
+pub const RAYDIUM_LAUNCHLAB_PROGRAM_ID: &str = "LanMV9sAd7wArD4vJFi2qDdfnVhFxYSUg6eADduJ3uj";
+
+// Anchor discriminators, the first 8 bytes of sha256("global:<name>")
+pub const INITIALIZE_DISCRIMINATOR: [u8; 8] = [175, 175, 109, 31, 13, 152, 155, 237];
+pub const INITIALIZE_V2_DISCRIMINATOR: [u8; 8] = [67, 153, 175, 39, 218, 16, 38, 32];
+
+/// Whether `data` is an instruction opening a sale, with either version of
+/// initialize.
+pub fn is_initialize(data: &[u8]) -> bool {
+    data.get(..8).is_some_and(|discriminator| {
+        discriminator == INITIALIZE_DISCRIMINATOR || discriminator == INITIALIZE_V2_DISCRIMINATOR
+    })
+}
+
+/// The token a sale mints, the first argument of initialize. The curve and
+/// vesting parameters after it are not read.
+#[derive(BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct MintParams {
+    pub decimals: u8,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+}
+
+impl MintParams {
+    /// From the instruction data, discriminator included.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let mut args = data.get(8..).ok_or_else(|| anyhow!("initialize instruction has no arguments"))?;
+        Ok(Self::deserialize(&mut args)?)
+    }
+}
+
+/// Account layout of initialize and initialize_v2, up to the vaults.
+#[derive(Debug, Clone)]
+pub struct LaunchAccounts {
+    pub program: Pubkey,
+    pub payer: Pubkey,
+    pub creator: Pubkey,
+    // the sale's pool state, which holds the bonding curve
+    pub launch_pool: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub base_vault: Pubkey,
+    pub quote_vault: Pubkey,
+}
+
+impl LaunchAccounts {
+    /// From the instruction's accounts in order.
+    pub fn from_keys(program: Pubkey, accounts: &[Pubkey]) -> Result<Self> {
+        let key = |position: usize| -> Result<Pubkey> {
+            accounts
+                .get(position)
+                .copied()
+                .ok_or_else(|| anyhow!("launchpad initialize instruction has only {} accounts", accounts.len()))
+        };
+        Ok(Self {
+            program,
+            payer: key(0)?,
+            creator: key(1)?,
+            launch_pool: key(5)?,
+            base_mint: key(6)?,
+            quote_mint: key(7)?,
+            base_vault: key(8)?,
+            quote_vault: key(9)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+
+    #[derive(BorshSerialize)]
+    struct Args {
+        decimals: u8,
+        name: String,
+        symbol: String,
+        uri: String,
+        // the start of the curve parameters
+        curve: u8,
+    }
+
+    #[test]
+    fn reads_the_mint_params_of_either_version() {
+        let args = Args {
+            decimals: 6,
+            name: "Bonk Dog".to_string(),
+            symbol: "BDOG".to_string(),
+            uri: "https://example.com/bdog.json".to_string(),
+            curve: 0,
+        };
+        for discriminator in [INITIALIZE_DISCRIMINATOR, INITIALIZE_V2_DISCRIMINATOR] {
+            let data = [discriminator.to_vec(), borsh::to_vec(&args).unwrap()].concat();
+            assert!(is_initialize(&data));
+            let params = MintParams::parse(&data).unwrap();
+            assert_eq!((params.decimals, params.symbol.as_str()), (6, "BDOG"));
+        }
+        assert!(!is_initialize(&[1, 0, 0]));
+        assert!(MintParams::parse(&INITIALIZE_DISCRIMINATOR).is_err());
+    }
+}
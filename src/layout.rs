@@ -0,0 +1,154 @@
+use crate::config::{FieldType, LayoutConfig};
+use anyhow::{anyhow, Result};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{BTreeMap, HashSet};
+
+// accounts every layout names, as the event is built around them
+const REQUIRED_ACCOUNTS: [&str; 3] = ["pool", "base_mint", "quote_mint"];
+
+/// A validated `[programs.layout]`, decoding the pool creation instruction
+/// of a program without a built-in decoder.
+#[derive(Debug, Clone)]
+pub struct Layout {
+    discriminator: Vec<u8>,
+    pub log: String,
+    accounts: BTreeMap<String, usize>,
+    fields: Vec<(String, FieldType)>,
+}
+
+/// The named accounts and arguments of an instruction matching a layout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedLayout {
+    pub accounts: BTreeMap<String, Pubkey>,
+    // as strings, so 64- and 128-bit integers stay exact
+    pub fields: BTreeMap<String, String>,
+}
+
+impl DecodedLayout {
+    /// One of the required accounts.
+    pub fn account(&self, name: &str) -> Pubkey {
+        self.accounts[name]
+    }
+}
+
+impl Layout {
+    pub fn new(config: &LayoutConfig) -> Result<Self> {
+        if config.discriminator.is_empty() {
+            return Err(anyhow!("layout discriminator is empty"));
+        }
+        if config.log.is_empty() {
+            return Err(anyhow!("layout log is empty"));
+        }
+        if let Some(missing) = REQUIRED_ACCOUNTS.iter().find(|name| !config.accounts.contains_key(**name)) {
+            return Err(anyhow!("layout accounts lack {}", missing));
+        }
+        let mut names = HashSet::new();
+        if let Some(field) = config.data.iter().find(|field| !names.insert(field.name.as_str())) {
+            return Err(anyhow!("layout data lists {} twice", field.name));
+        }
+        Ok(Self {
+            discriminator: config.discriminator.clone(),
+            log: config.log.clone(),
+            accounts: config.accounts.clone(),
+            fields: config.data.iter().map(|field| (field.name.clone(), field.kind)).collect(),
+        })
+    }
+
+    pub fn matches(&self, data: &[u8]) -> bool {
+        data.starts_with(&self.discriminator)
+    }
+
+    /// The named accounts and arguments of an instruction, given its
+    /// accounts in order and its data.
+    pub fn decode(&self, accounts: &[Pubkey], data: &[u8]) -> Result<DecodedLayout> {
+        let accounts = self
+            .accounts
+            .iter()
+            .map(|(name, &position)| {
+                let key = accounts
+                    .get(position)
+                    .copied()
+                    .ok_or_else(|| anyhow!("instruction has only {} accounts, {} is at {}", accounts.len(), name, position))?;
+                Ok((name.clone(), key))
+            })
+            .collect::<Result<_>>()?;
+        let mut rest = data.get(self.discriminator.len()..).unwrap_or_default();
+        let fields = self
+            .fields
+            .iter()
+            .map(|(name, kind)| Ok((name.clone(), read(&mut rest, *kind).map_err(|e| anyhow!("{}: {}", name, e))?)))
+            .collect::<Result<_>>()?;
+        Ok(DecodedLayout { accounts, fields })
+    }
+}
+
+fn take<'a>(data: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    if data.len() < len {
+        return Err(anyhow!("data ends {} bytes short", len - data.len()));
+    }
+    let (value, rest) = data.split_at(len);
+    *data = rest;
+    Ok(value)
+}
+
+/// Reads one little-endian Borsh value off the front of `data`.
+fn read(data: &mut &[u8], kind: FieldType) -> Result<String> {
+    Ok(match kind {
+        FieldType::U8 => take(data, 1)?[0].to_string(),
+        FieldType::U16 => u16::from_le_bytes(take(data, 2)?.try_into()?).to_string(),
+        FieldType::U32 => u32::from_le_bytes(take(data, 4)?.try_into()?).to_string(),
+        FieldType::U64 => u64::from_le_bytes(take(data, 8)?.try_into()?).to_string(),
+        FieldType::U128 => u128::from_le_bytes(take(data, 16)?.try_into()?).to_string(),
+        FieldType::I64 => i64::from_le_bytes(take(data, 8)?.try_into()?).to_string(),
+        FieldType::Bool => (take(data, 1)?[0] != 0).to_string(),
+        FieldType::Pubkey => Pubkey::try_from(take(data, 32)?)?.to_string(),
+        FieldType::String => {
+            let len = u32::from_le_bytes(take(data, 4)?.try_into()?) as usize;
+            String::from_utf8(take(data, len)?.to_vec())?
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FieldConfig;
+
+    fn config() -> LayoutConfig {
+        let field = |name: &str, kind| FieldConfig { name: name.to_string(), kind };
+        LayoutConfig {
+            discriminator: vec![9, 9],
+            log: "Instruction: CreatePool".to_string(),
+            accounts: [("pool", 2), ("base_mint", 0), ("quote_mint", 1), ("creator", 3)]
+                .into_iter()
+                .map(|(name, position)| (name.to_string(), position))
+                .collect(),
+            data: vec![field("symbol", FieldType::String), field("base_amount", FieldType::U64), field("locked", FieldType::Bool)],
+        }
+    }
+
+    #[test]
+    fn decodes_named_accounts_and_arguments() {
+        let layout = Layout::new(&config()).unwrap();
+        let keys = (0..4).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        let mut data = vec![9, 9, 3, 0, 0, 0];
+        data.extend(b"DOG");
+        data.extend(u64::MAX.to_le_bytes());
+        data.extend([1, 0xff]);
+        assert!(layout.matches(&data));
+        assert!(!layout.matches(&[9, 8]));
+
+        let decoded = layout.decode(&keys, &data).unwrap();
+        assert_eq!(decoded.account("pool"), keys[2]);
+        assert_eq!(decoded.accounts["creator"], keys[3]);
+        assert_eq!(decoded.fields["symbol"], "DOG");
+        assert_eq!(decoded.fields["base_amount"], u64::MAX.to_string());
+        assert_eq!(decoded.fields["locked"], "true");
+        assert!(layout.decode(&keys[..3], &data).is_err());
+        assert!(layout.decode(&keys, &data[..10]).is_err());
+
+        let mut config = config();
+        config.accounts.remove("quote_mint");
+        assert!(Layout::new(&config).is_err());
+    }
+}
@@ -0,0 +1,199 @@
+use crate::config::LeaderConfig;
+use anyhow::{anyhow, Context, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tracing::{info, warn};
+use url::Url;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+// a leader whose renewal has not answered this long before its lock could
+// expire steps down, so it never overlaps the next leader
+const STEP_DOWN_MARGIN: Duration = Duration::from_secs(1);
+// extends the lock only while this instance still holds it
+const RENEW_SCRIPT: &str =
+    "if redis.call('get', KEYS[1]) == ARGV[1] then return redis.call('pexpire', KEYS[1], ARGV[2]) else return 0 end";
+
+// true unless leader election is configured and another instance leads
+static LEADER: AtomicBool = AtomicBool::new(true);
+
+/// Whether this instance may emit alerts and trade. Always true without
+/// `[leader]`.
+pub fn is_leader() -> bool {
+    LEADER.load(Ordering::Relaxed)
+}
+
+/// Starts as a follower and contends for a Redis lock (`SET NX PX`) that the
+/// leader renews every `renew_interval_secs`. A leader that cannot renew
+/// steps down before the lock expires, also when Redis stops answering, so
+/// two instances never lead at once;
+/// a follower takes over once the lock has expired.
+pub fn spawn(config: LeaderConfig) -> Result<()> {
+    let url = Url::parse(&config.redis_url).with_context(|| format!("Invalid redis_url {}", config.redis_url))?;
+    if url.scheme() != "redis" {
+        return Err(anyhow!("[leader] redis_url must be a redis:// URL"));
+    }
+    LEADER.store(false, Ordering::Relaxed);
+    tokio::spawn(run(config, url));
+    Ok(())
+}
+
+async fn run(config: LeaderConfig, url: Url) {
+    let instance = config.instance_id.clone().unwrap_or_else(default_instance_id);
+    let ttl = Duration::from_secs(config.ttl_secs.max(1));
+    let mut interval = tokio::time::interval(Duration::from_secs(config.renew_interval_secs.max(1)));
+    let mut connection: Option<Redis> = None;
+    // when the lock was last acquired or renewed
+    let mut held_since: Option<Instant> = None;
+    info!(instance = %instance, key = %config.key, "Following until elected leader");
+    loop {
+        interval.tick().await;
+        // a Redis that stops answering mid-command must not keep a leader
+        // leading past its lock
+        let deadline = contend_deadline(held_since.filter(|_| is_leader()).map(|since| since.elapsed()), ttl);
+        let attempt = async {
+            match connection.as_mut() {
+                Some(redis) => contend(redis, &config.key, &instance, ttl, is_leader()).await,
+                None => match Redis::connect(&url).await {
+                    Ok(redis) => contend(connection.insert(redis), &config.key, &instance, ttl, is_leader()).await,
+                    Err(e) => Err(e),
+                },
+            }
+        };
+        let Ok(result) = tokio::time::timeout(deadline, attempt).await else {
+            connection = None;
+            held_since = None;
+            warn!(timeout_ms = deadline.as_millis() as u64, "Leader election timed out");
+            if LEADER.swap(false, Ordering::Relaxed) {
+                warn!(instance = %instance, "Cannot renew the leader lock in time, following");
+            }
+            continue;
+        };
+        match result {
+            Ok(true) => {
+                held_since = Some(Instant::now());
+                if !LEADER.swap(true, Ordering::Relaxed) {
+                    info!(instance = %instance, "Elected leader, emitting alerts and trading");
+                }
+            }
+            Ok(false) => {
+                held_since = None;
+                if LEADER.swap(false, Ordering::Relaxed) {
+                    warn!(instance = %instance, "Lost leadership, following");
+                }
+            }
+            Err(e) => {
+                connection = None;
+                warn!(error = %e, "Leader election failed");
+                // the lock may expire before the next renewal gets through
+                let expiring = held_since.is_none_or(|since| since.elapsed() + interval.period() >= ttl);
+                if expiring && LEADER.swap(false, Ordering::Relaxed) {
+                    warn!(instance = %instance, "Cannot renew the leader lock, following");
+                }
+            }
+        }
+    }
+}
+
+/// Renews the lock when leading, otherwise tries to take it. True when this
+/// instance holds it afterwards.
+async fn contend(redis: &mut Redis, key: &str, instance: &str, ttl: Duration, leading: bool) -> Result<bool> {
+    let ttl_ms = ttl.as_millis().to_string();
+    if leading {
+        let renewed = redis.command(&["EVAL", RENEW_SCRIPT, "1", key, instance, &ttl_ms]).await?;
+        return Ok(matches!(renewed, Reply::Integer(1)));
+    }
+    let acquired = redis.command(&["SET", key, instance, "NX", "PX", &ttl_ms]).await?;
+    Ok(matches!(acquired, Reply::Simple(ref status) if status == "OK"))
+}
+
+/// How long a round of the election may take: until shortly before the lock
+/// held for `held_for` expires, or a whole TTL while following.
+fn contend_deadline(held_for: Option<Duration>, ttl: Duration) -> Duration {
+    match held_for {
+        Some(held_for) => ttl.saturating_sub(held_for).saturating_sub(STEP_DOWN_MARGIN),
+        None => ttl,
+    }
+}
+
+fn default_instance_id() -> String {
+    let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "localhost".to_string());
+    format!("{}:{}", host, std::process::id())
+}
+
+enum Reply {
+    Simple(String),
+    Integer(i64),
+    // contents are not needed by the election commands
+    Bulk,
+}
+
+/// Just enough of RESP2 for the election commands.
+struct Redis {
+    stream: BufReader<TcpStream>,
+}
+
+impl Redis {
+    /// Connects to `redis://[user:password@]host[:port][/db]`.
+    async fn connect(url: &Url) -> Result<Self> {
+        let host = url.host_str().ok_or_else(|| anyhow!("redis_url has no host"))?;
+        let port = url.port().unwrap_or(6379);
+        let stream = tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect((host, port)))
+            .await
+            .map_err(|_| anyhow!("timed out connecting to Redis at {}:{}", host, port))??;
+        let mut redis = Self { stream: BufReader::new(stream) };
+        if let Some(password) = url.password() {
+            match url.username() {
+                "" => redis.command(&["AUTH", password]).await?,
+                user => redis.command(&["AUTH", user, password]).await?,
+            };
+        }
+        if let Some(db) = url.path().strip_prefix('/').filter(|db| !db.is_empty()) {
+            redis.command(&["SELECT", db]).await?;
+        }
+        Ok(redis)
+    }
+
+    async fn command(&mut self, args: &[&str]) -> Result<Reply> {
+        let mut request = format!("*{}\r\n", args.len());
+        for arg in args {
+            request.push_str(&format!("${}\r\n{}\r\n", arg.len(), arg));
+        }
+        self.stream.get_mut().write_all(request.as_bytes()).await?;
+
+        let mut line = String::new();
+        if self.stream.read_line(&mut line).await? == 0 {
+            return Err(anyhow!("Redis closed the connection"));
+        }
+        let line = line.trim_end();
+        let (kind, rest) = line.split_at(line.len().min(1));
+        match kind {
+            "+" => Ok(Reply::Simple(rest.to_string())),
+            "-" => Err(anyhow!("Redis error: {}", rest)),
+            ":" => Ok(Reply::Integer(rest.parse()?)),
+            "$" => {
+                let len: i64 = rest.parse()?;
+                if len >= 0 {
+                    let mut data = vec![0; len as usize + 2];
+                    self.stream.read_exact(&mut data).await?;
+                }
+                Ok(Reply::Bulk)
+            }
+            _ => Err(anyhow!("unexpected Redis reply {}", line)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leaders_give_up_on_redis_before_their_lock_expires() {
+        let ttl = Duration::from_secs(15);
+        assert_eq!(contend_deadline(None, ttl), ttl);
+        assert_eq!(contend_deadline(Some(Duration::from_secs(5)), ttl), Duration::from_secs(9));
+        assert_eq!(contend_deadline(Some(Duration::from_millis(14_500)), ttl), Duration::ZERO);
+    }
+}
@@ -0,0 +1,64 @@
+//! Raydium V4 liquidity pool monitor. Besides the `raydium_v4_monitor`
+//! binary the modules can be used as a library, e.g. [`amm::quote_swap`]
+//! to quote swaps against a pool.
+
+pub mod admin_watch;
+pub mod amm;
+pub mod api;
+pub mod archive;
+pub mod auth;
+pub mod authority_watch;
+pub mod buyers;
+pub mod candles;
+pub mod chain;
+pub mod clock;
+pub mod cloud;
+pub mod config;
+pub mod daemon;
+pub mod decode;
+pub mod dev_watch;
+pub mod dexscreener;
+pub mod enrichment;
+pub mod digest;
+pub mod events;
+pub mod faults;
+pub mod filter;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+pub mod history;
+pub mod holders;
+pub mod jito;
+pub mod launchpad;
+pub mod layout;
+pub mod leader;
+pub mod links;
+pub mod loadgen;
+pub mod logging;
+pub mod loki;
+pub mod markets;
+pub mod metrics;
+pub mod migration;
+pub mod mints;
+pub mod performance;
+pub mod positions;
+pub mod priority_fee;
+pub mod programs;
+pub mod raydium;
+pub mod recording;
+pub mod redact;
+pub mod rpc;
+pub mod sinks;
+pub mod sniper;
+pub mod sources;
+pub mod stats;
+pub mod store;
+pub mod syslog;
+pub mod systemd;
+pub mod time;
+pub mod tokens;
+pub mod tui;
+pub mod volume;
+pub mod wallet;
+pub mod webhooks;
+
+pub const RAYDIUM_V4_PROGRAM_ID: &str = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8";
@@ -0,0 +1,61 @@
+use crate::config::LinkConfig;
+use crate::events::PoolEvent;
+use anyhow::{anyhow, Result};
+
+/// Built-in templates by name. Placeholders: `{pool}`, `{signature}`,
+/// `{mint}` (the base token) and `{quote_mint}`.
+const PRESETS: &[(&str, &str)] = &[
+    ("solscan", "https://solscan.io/tx/{signature}"),
+    ("solanafm", "https://solana.fm/tx/{signature}"),
+    ("xray", "https://xray.helius.xyz/tx/{signature}"),
+    ("dexscreener", "https://dexscreener.com/solana/{pool}"),
+    ("birdeye", "https://birdeye.so/token/{mint}?chain=solana"),
+    ("photon", "https://photon-sol.tinyastro.io/en/lp/{pool}"),
+    // swap pages with the pair pre-filled, buying the base token
+    ("jupiter", "https://jup.ag/swap/{quote_mint}-{mint}"),
+    ("raydium", "https://raydium.io/swap/?inputMint={quote_mint}&outputMint={mint}"),
+];
+
+/// The links a sink shows for each new pool, in configured order.
+#[derive(Debug, Clone, Default)]
+pub struct Links {
+    templates: Vec<(String, String)>,
+}
+
+impl Links {
+    pub fn from_config(links: &[LinkConfig]) -> Result<Self> {
+        let templates = links
+            .iter()
+            .map(|link| match link {
+                LinkConfig::Preset(name) => PRESETS
+                    .iter()
+                    .find(|(preset, _)| preset == name)
+                    .map(|(name, url)| (name.to_string(), url.to_string()))
+                    .ok_or_else(|| anyhow!("unknown link preset {:?}", name)),
+                LinkConfig::Custom { name, url } => Ok((name.clone(), url.clone())),
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { templates })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+
+    /// (name, url) pairs for the pool.
+    pub fn render<'a>(&'a self, pool: &PoolEvent) -> Vec<(&'a str, String)> {
+        let base = pool.base().mint.to_string();
+        let quote = pool.quote().mint.to_string();
+        self.templates
+            .iter()
+            .map(|(name, url)| {
+                let url = url
+                    .replace("{pool}", &pool.amm.to_string())
+                    .replace("{signature}", &pool.signature.to_string())
+                    .replace("{mint}", &base)
+                    .replace("{quote_mint}", &quote);
+                (name.as_str(), url)
+            })
+            .collect()
+    }
+}
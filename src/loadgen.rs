@@ -0,0 +1,100 @@
+use crate::recording::Replay;
+use crate::sources::CHANNEL_CAPACITY;
+use std::time::{Duration, Instant};
+
+/// How the pipeline kept up with a replay: the figures for sizing the
+/// signature channel, `[rpc_limits]` and sink batches before deploying.
+pub struct LoadReport {
+    started: Instant,
+    // signatures waiting in the channel when each one was taken
+    peak_pending: usize,
+    // time the main loop spent on each signature
+    handled: Vec<Duration>,
+}
+
+impl Default for LoadReport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LoadReport {
+    pub fn new() -> Self {
+        Self { started: Instant::now(), peak_pending: 0, handled: Vec::new() }
+    }
+
+    /// A signature was taken from the channel with `pending` more behind it.
+    pub fn received(&mut self, pending: usize) {
+        self.peak_pending = self.peak_pending.max(pending);
+    }
+
+    pub fn handled(&mut self, elapsed: Duration) {
+        self.handled.push(elapsed);
+    }
+
+    /// The `percent` percentile of the handling times, nearest rank.
+    pub fn percentile(&self, percent: f64) -> Option<Duration> {
+        let mut sorted = self.handled.clone();
+        sorted.sort();
+        let rank = (percent / 100.0 * sorted.len() as f64).ceil() as usize;
+        sorted.get(rank.clamp(1, sorted.len().max(1)) - 1).copied()
+    }
+
+    /// Share of the run the main loop was busy; near 1.0 it is the
+    /// bottleneck and the channel fills up.
+    pub fn utilization(&self, elapsed: Duration) -> f64 {
+        let busy = self.handled.iter().sum::<Duration>();
+        if elapsed.is_zero() {
+            return 0.0;
+        }
+        (busy.as_secs_f64() / elapsed.as_secs_f64()).min(1.0)
+    }
+
+    pub fn print(&self, replay: &Replay) {
+        let elapsed = self.started.elapsed();
+        let ms = |duration: Option<Duration>| duration.map_or("-".to_string(), |d| format!("{} ms", d.as_millis()));
+        let speed = replay.speed.map_or("unpaced".to_string(), |speed| format!("{}x", speed));
+        println!("{:<24} {}", "Speed", speed);
+        println!("{:<24} {}", "Signatures", self.handled.len());
+        println!("{:<24} {:.1} s", "Elapsed", elapsed.as_secs_f64());
+        println!("{:<24} {:.2}/s", "Throughput", self.handled.len() as f64 / elapsed.as_secs_f64().max(f64::EPSILON));
+        println!("{:<24} {} / {}", "Peak pending signatures", self.peak_pending, CHANNEL_CAPACITY);
+        println!("{:<24} {}", "Max source lag", ms(replay.speed.map(|_| replay.max_lag())));
+        println!("{:<24} {}", "Handling p50", ms(self.percentile(50.0)));
+        println!("{:<24} {}", "Handling p95", ms(self.percentile(95.0)));
+        println!("{:<24} {}", "Handling p99", ms(self.percentile(99.0)));
+        println!("{:<24} {}", "Handling max", ms(self.percentile(100.0)));
+        println!("{:<24} {:.0}%", "Main loop utilization", self.utilization(elapsed) * 100.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(millis: &[u64]) -> LoadReport {
+        let mut report = LoadReport::new();
+        for &ms in millis {
+            report.handled(Duration::from_millis(ms));
+        }
+        report
+    }
+
+    #[test]
+    fn percentiles_use_the_nearest_rank() {
+        let report = report(&[40, 10, 30, 20, 50, 60, 70, 80, 90, 100]);
+        assert_eq!(report.percentile(50.0), Some(Duration::from_millis(50)));
+        assert_eq!(report.percentile(95.0), Some(Duration::from_millis(100)));
+        assert_eq!(report.percentile(0.0), Some(Duration::from_millis(10)));
+        assert_eq!(report.percentile(100.0), Some(Duration::from_millis(100)));
+        assert_eq!(LoadReport::new().percentile(50.0), None);
+    }
+
+    #[test]
+    fn utilization_is_busy_time_over_elapsed() {
+        let report = report(&[250, 250]);
+        assert_eq!(report.utilization(Duration::from_secs(2)), 0.25);
+        assert_eq!(report.utilization(Duration::from_millis(100)), 1.0);
+        assert_eq!(report.utilization(Duration::ZERO), 0.0);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::config::{LogFormat, LogRotation, LoggingConfig};
+use crate::loki::{Loki, LokiWriter};
+use crate::redact::Redactor;
+use crate::syslog::SyslogWriter;
+use crate::tui::{Dashboard, DashboardWriter};
+use anyhow::{anyhow, Context, Result};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing_appender::rolling::{self, RollingFileAppender};
+use tracing_subscriber::{
+    fmt::{self, MakeWriter},
+    layer::SubscriberExt,
+    util::SubscriberInitExt,
+    EnvFilter, Layer, Registry,
+};
+
+type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+/// Installs the global subscriber. Logs go to stderr, or to the dashboard's
+/// log pane when one is given, and additionally to rotating files when
+/// `dir` is configured, to Loki as JSON when `loki` is and to a syslog
+/// collector when `syslog` is. Every output is passed through `redactor`.
+/// `RUST_LOG` overrides the configured level.
+pub fn init(config: &LoggingConfig, redactor: Redactor, dashboard: Option<Arc<Dashboard>>) -> Result<()> {
+    let filter = match EnvFilter::try_from_default_env() {
+        Ok(filter) => filter,
+        Err(_) => EnvFilter::try_new(&config.level).with_context(|| format!("Invalid log level {}", config.level))?,
+    };
+
+    let mut layers: Vec<BoxedLayer> = Vec::new();
+    match dashboard {
+        Some(dashboard) => layers.push(
+            fmt::layer()
+                .with_writer(redactor.writer(DashboardWriter::new(dashboard)))
+                .with_ansi(false)
+                .with_target(false)
+                .without_time()
+                .boxed(),
+        ),
+        None => layers.push(format_layer(config.format, redactor.writer(std::io::stderr), true)),
+    }
+    if let Some(dir) = &config.dir {
+        layers.push(format_layer(config.format, redactor.writer(appender(config, dir, "monitor", "log")?), false));
+    }
+    if let Some(loki) = &config.loki {
+        layers.push(format_layer(LogFormat::Json, redactor.writer(LokiWriter::new(Loki::new(loki)?)), false));
+    }
+    if let Some(syslog) = &config.syslog {
+        // the timestamp and severity are in the syslog header
+        layers.push(
+            fmt::layer()
+                .with_writer(redactor.writer(SyslogWriter::new(syslog)?))
+                .with_ansi(false)
+                .with_level(false)
+                .without_time()
+                .boxed(),
+        );
+    }
+
+    tracing_subscriber::registry()
+        .with(layers)
+        .with(filter)
+        .try_init()
+        .map_err(|e| anyhow!("Failed to install log subscriber: {}", e))
+}
+
+fn format_layer<W>(format: LogFormat, writer: W, ansi: bool) -> BoxedLayer
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = fmt::layer().with_writer(writer).with_ansi(ansi);
+    match format {
+        LogFormat::Text => layer.boxed(),
+        LogFormat::Json => layer.json().flatten_event(true).boxed(),
+    }
+}
+
+/// A writer for `dir/prefix.<period>.suffix` that rotates as configured and
+/// keeps only the latest `max_files` files.
+pub fn appender(config: &LoggingConfig, dir: &str, prefix: &str, suffix: &str) -> Result<RollingFileAppender> {
+    let rotation = match config.rotation {
+        LogRotation::Minutely => rolling::Rotation::MINUTELY,
+        LogRotation::Hourly => rolling::Rotation::HOURLY,
+        LogRotation::Daily => rolling::Rotation::DAILY,
+        LogRotation::Never => rolling::Rotation::NEVER,
+    };
+    let mut builder = rolling::Builder::new()
+        .rotation(rotation)
+        .filename_prefix(prefix)
+        .filename_suffix(suffix);
+    if config.max_files > 0 {
+        builder = builder.max_log_files(config.max_files);
+    }
+    // the appender prunes old files before it creates the directory
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create log directory {}", dir))?;
+    builder.build(dir).with_context(|| format!("Failed to open log directory {}", dir))
+}
+
+pub fn unix_now_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
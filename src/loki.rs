@@ -0,0 +1,168 @@
+use crate::config::LokiConfig;
+use anyhow::{anyhow, Result};
+use serde_json::json;
+use tokio::sync::mpsc;
+use tracing::error;
+use tracing_subscriber::fmt::MakeWriter;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// entries queued for the push task before new ones are dropped
+const QUEUE_LEN: usize = 10_000;
+
+/// One log line and the labels of its stream, on top of the configured ones.
+struct Entry {
+    labels: Vec<(&'static str, String)>,
+    timestamp_ns: u128,
+    line: String,
+}
+
+/// Pushes lines to Loki's HTTP API from a background task, grouped into
+/// streams by their labels.
+#[derive(Clone)]
+pub struct Loki {
+    tx: mpsc::Sender<Entry>,
+}
+
+impl Loki {
+    /// Starts the push task; must be called within the runtime.
+    pub fn new(config: &LokiConfig) -> Result<Self> {
+        if config.url.is_empty() {
+            return Err(anyhow!("[logging.loki] requires url"));
+        }
+        let (tx, rx) = mpsc::channel(QUEUE_LEN);
+        tokio::spawn(Pusher { http: reqwest::Client::new(), config: config.clone() }.run(rx));
+        Ok(Self { tx })
+    }
+
+    /// Queues a line, failing when the queue is full.
+    pub fn push(&self, labels: Vec<(&'static str, String)>, line: String) -> Result<()> {
+        let timestamp_ns = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+        self.tx
+            .try_send(Entry { labels, timestamp_ns, line })
+            .map_err(|e| anyhow!("Loki queue unavailable: {}", e))
+    }
+}
+
+struct Pusher {
+    http: reqwest::Client,
+    config: LokiConfig,
+}
+
+impl Pusher {
+    async fn run(self, mut rx: mpsc::Receiver<Entry>) {
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.flush_interval_secs.max(1)));
+        let mut batch = Vec::new();
+        loop {
+            tokio::select! {
+                entry = rx.recv() => match entry {
+                    Some(entry) => {
+                        batch.push(entry);
+                        if batch.len() < self.config.batch_size {
+                            continue;
+                        }
+                    }
+                    None => break,
+                },
+                _ = interval.tick() => {}
+            }
+            if batch.is_empty() {
+                continue;
+            }
+            let entries = std::mem::take(&mut batch);
+            if let Err(e) = self.push(&entries).await {
+                error!(entries = entries.len(), error = %e, "Failed to push to Loki");
+            }
+        }
+    }
+
+    async fn push(&self, entries: &[Entry]) -> Result<()> {
+        let mut streams = BTreeMap::<BTreeMap<&str, &str>, Vec<[String; 2]>>::new();
+        for entry in entries {
+            let mut labels = self.config.labels.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect::<BTreeMap<_, _>>();
+            labels.extend(entry.labels.iter().map(|(k, v)| (*k, v.as_str())));
+            streams.entry(labels).or_default().push([entry.timestamp_ns.to_string(), entry.line.clone()]);
+        }
+        let body = json!({
+            "streams": streams
+                .into_iter()
+                .map(|(labels, values)| json!({ "stream": labels, "values": values }))
+                .collect::<Vec<_>>(),
+        });
+
+        let url = format!("{}/loki/api/v1/push", self.config.url.trim_end_matches('/'));
+        let mut request = self.http.post(url).json(&body);
+        if let Some(tenant) = &self.config.tenant {
+            request = request.header("x-scope-orgid", tenant);
+        }
+        if let Some(username) = &self.config.username {
+            request = request.basic_auth(username, self.config.password.as_deref());
+        }
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            return Err(anyhow!("Loki returned {}: {}", status, response.text().await.unwrap_or_default()));
+        }
+        Ok(())
+    }
+}
+
+/// Sends formatted log lines to Loki, labelled with their level.
+pub struct LokiWriter {
+    loki: Loki,
+}
+
+impl LokiWriter {
+    pub fn new(loki: Loki) -> Self {
+        Self { loki }
+    }
+}
+
+impl<'a> MakeWriter<'a> for LokiWriter {
+    type Writer = EntryWriter;
+
+    fn make_writer(&'a self) -> EntryWriter {
+        EntryWriter { loki: self.loki.clone(), severity: "info", buf: Vec::new() }
+    }
+
+    fn make_writer_for(&'a self, meta: &tracing::Metadata<'_>) -> EntryWriter {
+        let severity = match *meta.level() {
+            tracing::Level::ERROR => "error",
+            tracing::Level::WARN => "warn",
+            tracing::Level::INFO => "info",
+            tracing::Level::DEBUG => "debug",
+            tracing::Level::TRACE => "trace",
+        };
+        EntryWriter { loki: self.loki.clone(), severity, buf: Vec::new() }
+    }
+}
+
+/// Buffers one formatted event and queues it on drop. Lines that don't fit
+/// in the queue are dropped, as logging about them would only add more.
+pub struct EntryWriter {
+    loki: Loki,
+    severity: &'static str,
+    buf: Vec<u8>,
+}
+
+impl Write for EntryWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for EntryWriter {
+    fn drop(&mut self) {
+        let line = String::from_utf8_lossy(&self.buf).trim_end().to_string();
+        if !line.is_empty() {
+            let labels = vec![("kind", "log".to_string()), ("severity", self.severity.to_string())];
+            let _ = self.loki.push(labels, line);
+        }
+    }
+}
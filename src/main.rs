@@ -1,274 +1,1261 @@
 use solana_client::{
-    pubsub_client::PubsubClient,
     rpc_client::RpcClient,
-    rpc_config::{RpcTransactionConfig, RpcTransactionLogsFilter, RpcTransactionLogsConfig},
-    rpc_response::Response as RpcResponse,
+    rpc_config::RpcTransactionConfig,
 };
 use solana_sdk::{
     commitment_config::CommitmentConfig,
+    native_token::{lamports_to_sol, sol_to_lamports},
     pubkey::Pubkey,
     signature::Signature,
 };
-use solana_transaction_status::UiTransactionEncoding;
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
 use spl_token::state::Mint;
 use solana_program::program_pack::Pack;
 use anyhow::{Result, anyhow};
+use chrono_tz::Tz;
+use clap::{Parser, Subcommand};
 use std::str::FromStr;
-use tokio::sync::mpsc;
-use log::{info, error, warn};
-use borsh::{BorshDeserialize, BorshSerialize};
-use std::time::Duration;
-
-const RAYDIUM_V4_PROGRAM_ID: &str = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8";
-const RPC_URL: &str = "https://mainnet.helius-rpc.com/?your_api";
-const WS_URL: &str = "wss://mainnet.helius-rpc.com/?ypur_api";
-const TOKEN_METADATA_PROGRAM_ID: &str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s";
+use tracing::{info, error, warn};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use raydium_v4_monitor::{
+    amm, api, faults, leader, logging, programs, systemd,
+    admin_watch::AdminWatcher,
+    authority_watch::AuthorityWatcher,
+    archive::{Archive, ArchivedTransaction},
+    buyers::BuyerAnalyzer,
+    candles::PriceTracker,
+    chain::ChainRpc,
+    clock,
+    dev_watch::DevWatcher,
+    dexscreener::DexScreener,
+    enrichment::Enrichers,
+    digest::{self, Digest},
+    history::EventHistory,
+    holders::HolderSnapshotter,
+    loadgen::LoadReport,
+    markets::MarketWatcher,
+    config::{Config, RetryConfig},
+    events::{token_label, DexPoolEvent, Event, FeedStalledEvent, PairType, PoolEvent, QuoteSide, TokenLaunchedEvent},
+    metrics::{self, Metrics},
+    migration::MigrationDetector,
+    mints::MintWatcher,
+    performance::PerformanceReporter,
+    positions::{ExitRules, PositionTracker},
+    raydium::{self, NewPool},
+    recording::{self, Replay},
+    redact::Redactor,
+    rpc::{self, KeyRing},
+    daemon::{self, Control, Signals},
+    decode::{self, compiled_instructions, parsed_instructions},
+    sinks::Sinks,
+    sources,
+    sniper::{unix_now, Sniper},
+    stats,
+    store::{LaunchRecord, PoolRecord, RetryEntry, Store},
+    time,
+    tokens::{self, TokenCache, TokenInfo},
+    tui::{self, Dashboard, PoolRow},
+    volume::VolumeTracker,
+    wallet::{self, Wallets},
+    webhooks::Webhooks,
+};
 const MAX_RETRIES: u32 = 3;
 const RETRY_DELAY: Duration = Duration::from_secs(2);
+// how often the persistent retry queue is checked
+const RETRY_QUEUE_POLL_SECS: u64 = 30;
 
-#[derive(BorshDeserialize, BorshSerialize, Debug)]
-struct Initialize2Data {
-    discriminator: u8,
-    nonce: u8,
-    open_time: u64,
-    init_pc_amount: u64,
-    init_coin_amount: u64,
+
+#[derive(Parser)]
+#[command(version, about = "Raydium V4 liquidity pool monitor")]
+struct Cli {
+    /// Run the monitor with a terminal dashboard instead of scrolling logs
+    #[arg(long)]
+    tui: bool,
+    /// Detach from the terminal and run in the background; SIGHUP reloads the
+    /// config, SIGUSR1 logs the internal state
+    #[arg(long, conflicts_with = "tui")]
+    daemon: bool,
+    /// PID file written in daemon mode
+    #[arg(long, default_value = "monitor.pid")]
+    pid_file: String,
+    /// Write every WebSocket log notification and RPC response to this
+    /// directory, for `replay`
+    #[arg(long)]
+    record: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-struct TokenInfo {
-    name: String,
-    decimals: u8,
+#[derive(Subcommand)]
+enum Command {
+    /// Manage wallets
+    Wallet {
+        #[command(subcommand)]
+        command: WalletCommand,
+    },
+    /// Print statistics from the store (positions and PnL by default)
+    Stats {
+        #[command(subcommand)]
+        view: Option<StatsView>,
+        /// Only this wallet ("paper" for paper trading)
+        #[arg(long, global = true)]
+        wallet: Option<String>,
+    },
+    /// Fetch a pool state account and print its reserves, price and risk flags
+    Pool {
+        /// Address of the AMM (pool state) account
+        address: Pubkey,
+    },
+    /// Fetch one transaction, decode it like the live monitor and print the event JSON
+    Decode {
+        /// Signature of the transaction creating the pool or opening the sale
+        signature: Signature,
+    },
+    /// Run a session recorded with --record through the pipeline again, with
+    /// an in-memory store and without trading, and report how it kept up
+    Replay {
+        /// Directory given to --record
+        dir: PathBuf,
+        /// Keep the recorded pace instead of replaying as fast as possible
+        #[arg(long, conflicts_with = "speed")]
+        realtime: bool,
+        /// Replay at this multiple of the recorded pace, e.g. 10 to load test
+        /// with the recorded launch bursts ten times as dense
+        #[arg(long, value_parser = parse_speed)]
+        speed: Option<f64>,
+    },
 }
 
-async fn fetch_token_info(rpc_client: &RpcClient, token_pubkey: &Pubkey) -> Result<TokenInfo> {
-    // 获取代币信息
-    let mint_account = rpc_client.get_account(token_pubkey)?;
-    let mint = Mint::unpack_from_slice(&mint_account.data)?;
-    
-    // 获取元数据 PDA
-    let metadata_program_id = Pubkey::from_str(TOKEN_METADATA_PROGRAM_ID)?;
-    let seeds = &[
-        b"metadata",
-        metadata_program_id.as_ref(),
-        token_pubkey.as_ref(),
-    ];
-    let (metadata_address, _) = Pubkey::find_program_address(seeds, &metadata_program_id);
-
-    // 获取元数据
-    match rpc_client.get_account(&metadata_address) {
-        Ok(metadata_account) => {
-            info!("Metadata account data length: {}", metadata_account.data.len());
-            
-            // 跳过前缀数据，直接解析名称
-            if metadata_account.data.len() < 65 {
-                warn!("Metadata account data too short");
-                return Ok(TokenInfo {
-                    name: format!("Unknown Token {}", token_pubkey),
-                    decimals: mint.decimals,
-                });
+#[derive(Subcommand)]
+enum StatsView {
+    /// Positions and realized/unrealized PnL per wallet
+    Positions,
+    /// Rolling 1m/5m/1h trades and quote volume of recently tracked pools
+    Volume {
+        /// Only this pool
+        #[arg(long)]
+        amm: Option<String>,
+    },
+    /// The daily digest of the 24 hours up to now, as markdown
+    Digest,
+    /// Pools per day, median initial liquidity, quote tokens and top creators
+    Pools {
+        /// Start of the range, as an age such as 30m, 24h, 7d or 4w
+        #[arg(long, default_value = "7d", value_parser = parse_age)]
+        since: Duration,
+        /// End of the range as an age, defaults to now
+        #[arg(long, value_parser = parse_age)]
+        until: Option<Duration>,
+    },
+    /// RPC and WebSocket calls per endpoint and method, with the projected monthly cost
+    Rpc {
+        /// Start of the range, as an age such as 24h, 7d or 4w
+        #[arg(long, default_value = "30d", value_parser = parse_age)]
+        since: Duration,
+    },
+    /// Requests per RPC provider API key in recent quota periods
+    Keys {
+        /// Number of periods to show
+        #[arg(long, default_value_t = 3)]
+        periods: usize,
+    },
+}
+
+/// Parses an age such as `90s`, `30m`, `24h`, `7d` or `4w`.
+fn parse_age(value: &str) -> Result<Duration> {
+    let (number, unit) = value.split_at(value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len()));
+    let number: u64 = number.parse().map_err(|_| anyhow!("invalid age {}", value))?;
+    let secs = match unit {
+        "s" | "" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86_400,
+        "w" => 7 * 86_400,
+        _ => return Err(anyhow!("invalid age unit in {}, expected s, m, h, d or w", value)),
+    };
+    Ok(Duration::from_secs(number * secs))
+}
+
+/// Parses a replay speed, a positive multiple of the recorded pace.
+fn parse_speed(value: &str) -> Result<f64> {
+    match value.parse::<f64>() {
+        Ok(speed) if speed.is_finite() && speed > 0.0 => Ok(speed),
+        _ => Err(anyhow!("invalid speed {}, expected a positive number such as 0.5 or 10", value)),
+    }
+}
+
+#[derive(Subcommand)]
+enum WalletCommand {
+    /// Encrypt a Solana keypair file with a password
+    Encrypt { input: String, output: String },
+    /// List configured wallets and their public keys
+    List,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let config = Config::load()?;
+
+    // Forking has to happen before the runtime starts its threads
+    let _pid_file = match cli.daemon {
+        true => {
+            if cli.command.is_some() {
+                return Err(anyhow!("--daemon only applies to the monitor itself, not to subcommands"));
             }
+            let logging = &config.logging;
+            if logging.dir.is_none() && logging.loki.is_none() && logging.syslog.is_none() {
+                return Err(anyhow!("--daemon closes stderr; configure [logging] dir, loki or syslog"));
+            }
+            Some(daemon::daemonize(&cli.pid_file)?)
+        }
+        false => None,
+    };
+    tokio::runtime::Runtime::new()?.block_on(start(cli, config))
+}
+
+async fn start(cli: Cli, config: Config) -> Result<()> {
 
-            let name_start = 65; // 跳过前缀数据
-            let name_length = metadata_account.data[name_start] as usize;
-            
-            if metadata_account.data.len() < name_start + 1 + name_length {
-                warn!("Metadata account data too short for name");
-                return Ok(TokenInfo {
-                    name: format!("Unknown Token {}", token_pubkey),
-                    decimals: mint.decimals,
+    // In dashboard mode logs go to the log pane
+    let dashboard = match (cli.tui, &cli.command) {
+        (true, None) => Some(Dashboard::new()),
+        _ => None,
+    };
+    logging::init(&config.logging, Redactor::from_config(&config), dashboard.clone())?;
+    // before anything decodes or subscribes, subcommands included
+    programs::init(&config.programs)?;
+
+    if let Some(dir) = &cli.record {
+        if cli.command.is_some() {
+            return Err(anyhow!("--record only applies to the monitor itself, not to subcommands"));
+        }
+        recording::start(dir)?;
+    }
+
+    match cli.command {
+        None => match dashboard {
+            Some(dashboard) => {
+                let ui = tokio::task::spawn_blocking({
+                    let dashboard = dashboard.clone();
+                    move || tui::run(dashboard)
                 });
+                tokio::select! {
+                    result = run(config, Some(dashboard), None) => result,
+                    result = ui => result?,
+                }
             }
-
-            let name_data = &metadata_account.data[name_start + 1..name_start + 1 + name_length];
-            
-            match String::from_utf8(name_data.to_vec()) {
-                Ok(name) => {
-                    info!("Successfully parsed token name: {}", name);
-                    Ok(TokenInfo {
-                        name: name.trim_matches(char::from(0)).to_string(),
-                        decimals: mint.decimals,
-                    })
+            None => run(config, None, None).await,
+        },
+        Some(Command::Wallet { command: WalletCommand::Encrypt { input, output } }) => wallet::encrypt_file(&input, &output),
+        Some(Command::Wallet { command: WalletCommand::List }) => {
+            let wallets = Wallets::load(&config.wallets)?;
+            for wallet_config in &config.wallets {
+                let wallet = wallets.get(&wallet_config.label)?;
+                println!("{}\t{}", wallet.label, wallet.pubkey());
+            }
+            Ok(())
+        }
+        Some(Command::Stats { view, wallet }) => {
+            let store = Store::open(&config.store.path)?;
+            match view {
+                None | Some(StatsView::Positions) => stats::print_positions(&store, wallet.as_deref()),
+                Some(StatsView::Volume { amm }) => stats::print_volume(&store, unix_now(), amm.as_deref()),
+                Some(StatsView::Digest) => {
+                    let digest = config.digest.clone().unwrap_or_default();
+                    let digest = Digest::build(&store, unix_now(), config.timezone, digest.top, digest.priority)?;
+                    println!("{}", digest.markdown());
+                    Ok(())
                 }
-                Err(e) => {
-                    warn!("Failed to parse name data: {}", e);
-                    warn!("Name data bytes: {:?}", name_data);
-                    Ok(TokenInfo {
-                        name: format!("Unknown Token {}", token_pubkey),
-                        decimals: mint.decimals,
-                    })
+                Some(StatsView::Pools { since, until }) => {
+                    let now = unix_now();
+                    let until = until.map_or(now, |age| now.saturating_sub(age.as_secs()));
+                    stats::print_pools(&store, now.saturating_sub(since.as_secs()), until)
                 }
+                Some(StatsView::Rpc { since }) => stats::print_rpc_usage(&store, unix_now(), since, config.rpc_costs.as_ref()),
+                Some(StatsView::Keys { periods }) => stats::print_key_usage(&store, config.api_keys.as_ref(), periods),
+            }
+        }
+        Some(Command::Pool { address }) => {
+            let rpc_client = rpc::client(KeyRing::from_config(&config, Metrics::new())?, &config.rpc_limits);
+            let tokens = TokenCache::new(tokens::provider(&config, rpc_client.clone()));
+            inspect_pool(&rpc_client, &tokens, &address, config.timezone).await
+        }
+        Some(Command::Decode { signature }) => {
+            let metrics = Metrics::new();
+            let rpc_client = rpc::client(KeyRing::from_config(&config, metrics.clone())?, &config.rpc_limits);
+            let tokens = TokenCache::new(tokens::provider(&config, rpc_client.clone()));
+            let probe_lamports = sol_to_lamports(config.price_impact_probe_sol);
+            let decoded = process_transaction(rpc_client.as_ref(), &tokens, &metrics, signature, probe_lamports).await?;
+            if decoded.is_empty() {
+                return Err(anyhow!("Transaction {} creates no pool or sale on the monitored programs", signature));
             }
+            for launched in decoded.launches {
+                println!("{}", serde_json::to_string_pretty(&Event::TokenLaunched(launched))?);
+            }
+            for pool in decoded.dex_pools {
+                println!("{}", serde_json::to_string_pretty(&Event::DexPool(pool))?);
+            }
+            for (_, event) in decoded.pools {
+                println!("{}", serde_json::to_string_pretty(&Event::NewPool(Box::new(event)))?);
+            }
+            Ok(())
         }
-        Err(e) => {
-            warn!("Failed to get metadata account: {}", e);
-            Ok(TokenInfo {
-                name: format!("Unknown Token {}", token_pubkey),
-                decimals: mint.decimals,
-            })
+        Some(Command::Replay { dir, realtime, speed }) => {
+            let speed = speed.or(realtime.then_some(1.0));
+            run(config.for_replay(), None, Some(Replay::load(&dir, speed)?)).await
         }
     }
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    // 设置日志级别为 INFO
-    std::env::set_var("RUST_LOG", "info");
-    env_logger::init();
-    
-    info!("Starting Raydium V4 liquidity pool monitor...");
-    info!("Connecting to RPC endpoint: {}", RPC_URL);
-    info!("Connecting to WebSocket endpoint: {}", WS_URL);
-
-    let rpc_client = RpcClient::new_with_commitment(RPC_URL.to_string(), CommitmentConfig::confirmed());
-    let _raydium_pubkey = Pubkey::from_str(RAYDIUM_V4_PROGRAM_ID)?;
-
-    // 创建一个 mpsc 通道来接收日志
-    let (tx, mut rx) = mpsc::channel::<RpcResponse<solana_client::rpc_response::RpcLogsResponse>>(100);
-
-    // 启动 WebSocket 订阅的任务
-    tokio::spawn(async move {
-        info!("Starting WebSocket subscription...");
-        match PubsubClient::logs_subscribe(
-            WS_URL,
-            RpcTransactionLogsFilter::Mentions(vec![RAYDIUM_V4_PROGRAM_ID.to_string()]),
-            RpcTransactionLogsConfig {
-                commitment: Some(CommitmentConfig::confirmed()),
-            },
-        ) {
-            Ok((_, receiver)) => {
-                info!("Successfully subscribed to program logs");
-                // 从订阅中接收日志并发送到通道
-                while let Ok(log) = receiver.recv() {
-                    if tx.send(log).await.is_err() {
-                        error!("Failed to send log through channel, exiting...");
-                        break;
-                    }
-                }
-            }
-            Err(e) => {
-                error!("Failed to subscribe to program logs: {}", e);
+/// Runs the monitor, or with `replay` feeds a recorded session through the
+/// same pipeline and returns once it is done.
+async fn run(config: Config, dashboard: Option<Arc<Dashboard>>, replay: Option<Arc<Replay>>) -> Result<()> {
+    info!(rpc_url = %config.rpc_url, ws_url = %config.ws_url, "Starting Raydium V4 liquidity pool monitor");
+
+    if let Some(faults) = &config.faults {
+        faults::init(faults);
+    }
+    if let Some(leader) = &config.leader {
+        leader::spawn(leader.clone())?;
+    }
+    let metrics = Metrics::new();
+    let keys = KeyRing::from_config(&config, metrics.clone())?;
+    let rpc_client = match &replay {
+        Some(replay) => replay.rpc_client(),
+        None => rpc::client(keys.clone(), &config.rpc_limits),
+    };
+    let store = Arc::new(Store::open(&config.store.path)?);
+    tokio::spawn(keys.clone().track_usage(store.clone()));
+    // The dashboard shows new pools itself
+    let mut sinks = Sinks::from_config(&config, dashboard.is_none())?;
+    // registered through the REST API
+    let webhooks = config.api.as_ref().map(|_| Webhooks::load(store.clone())).transpose()?;
+    if let Some(webhooks) = &webhooks {
+        sinks = sinks.with_webhooks(webhooks.clone());
+    }
+    sinks = sinks.with_delivery_queue(store.clone(), &config.delivery)?;
+    let history = config.api.as_ref().filter(|api| api.history_days > 0).map(|api| {
+        let history = EventHistory::new(store.clone());
+        tokio::spawn(history.clone().prune(api.history_days));
+        history
+    });
+    if let Some(history) = &history {
+        sinks = sinks.with_history(history.clone());
+    }
+    tokio::spawn(sinks.clone().redeliver());
+    let tokens = TokenCache::new(tokens::provider(&config, rpc_client.clone()));
+    if config.summary.interval_mins > 0 {
+        tokio::spawn(metrics::report(
+            metrics.clone(),
+            sinks.clone(),
+            Duration::from_secs(config.summary.interval_mins * 60),
+            config.summary.priority,
+        ));
+    }
+
+    if let Some(digest) = &config.digest {
+        tokio::spawn(digest::run(store.clone(), sinks.clone(), digest.clone(), config.timezone));
+    }
+
+    if let Some(api_config) = &config.api {
+        tokio::spawn(api::serve(api_config.clone(), store.clone(), history, webhooks));
+    }
+
+    // replays have no live markets to watch
+    let markets = match config.market_watch.as_ref().filter(|_| replay.is_none()) {
+        Some(market_watch) => {
+            let markets = Arc::new(MarketWatcher::new(keys.clone(), market_watch)?);
+            tokio::spawn(markets.clone().run());
+            Some(markets)
+        }
+        None => None,
+    };
+    let mints = match config.mint_watch.as_ref().filter(|_| replay.is_none()) {
+        Some(mint_watch) => {
+            let mints = Arc::new(MintWatcher::new(keys.clone(), rpc_client.clone(), mint_watch));
+            tokio::spawn(mints.clone().run());
+            Some(mints)
+        }
+        None => None,
+    };
+
+    // Optional auto-buy module
+    let sniper = match &config.sniper {
+        Some(sniper_config) if sniper_config.enabled => {
+            let wallets = Wallets::load(&config.wallets)?;
+            let mut sniper = Sniper::new(sniper_config.clone(), rpc_client.clone(), &wallets, store.clone())?;
+            if let Some(markets) = &markets {
+                sniper = sniper.with_markets(markets.clone());
             }
+            sniper.prepare().await;
+            let sniper = Arc::new(sniper);
+            let tracker = Arc::new(PositionTracker::new(
+                store.clone(),
+                rpc_client.clone(),
+                sniper.clone(),
+                ExitRules::from_config(sniper_config),
+                Duration::from_secs(sniper_config.mark_interval_secs.max(1)),
+                Duration::from_secs(sniper_config.paper_track_duration_secs),
+            ));
+            tokio::spawn(tracker.run());
+            Some(sniper)
         }
-        warn!("WebSocket subscription task ended");
+        _ => None,
+    };
+
+    let pipeline = Arc::new(Pipeline {
+        rpc_client: rpc_client.clone(),
+        tokens,
+        metrics: metrics.clone(),
+        store: store.clone(),
+        sinks: sinks.clone(),
+        enrichers: Enrichers::from_config(&config.enrichment)?,
+        dashboard: dashboard.clone(),
+        sniper,
+        probe_lamports: sol_to_lamports(config.price_impact_probe_sol),
+        pair_types: config.pair_types.clone(),
+        retry: config.retry.clone(),
+        tracker: config
+            .tracking
+            .clone()
+            .map(|tracking| Arc::new(PriceTracker::new(rpc_client.clone(), store.clone(), sinks.clone(), tracking))),
+        buyers: match config.buyers.clone() {
+            Some(buyers) => Some(Arc::new(BuyerAnalyzer::new(rpc_client.clone(), store.clone(), buyers)?)),
+            None => None,
+        },
+        dev_watch: config
+            .dev_watch
+            .clone()
+            .map(|dev_watch| Arc::new(DevWatcher::new(rpc_client.clone(), store.clone(), sinks.clone(), dev_watch))),
+        admin_watch: config
+            .admin_watch
+            .clone()
+            .map(|admin_watch| Arc::new(AdminWatcher::new(keys.clone(), rpc_client.clone(), sinks.clone(), admin_watch))),
+        authority_watch: config
+            .authority_watch
+            .clone()
+            .map(|authority_watch| Arc::new(AuthorityWatcher::new(keys.clone(), sinks.clone(), authority_watch))),
+        markets,
+        mints,
+        migration: config
+            .migration
+            .clone()
+            .map(|migration| Arc::new(MigrationDetector::new(rpc_client.clone(), store.clone(), sinks.clone(), migration))),
+        volume: config
+            .volume
+            .clone()
+            .map(|volume| Arc::new(VolumeTracker::new(keys.clone(), rpc_client.clone(), store.clone(), volume))),
+        holders: config
+            .holders
+            .clone()
+            .map(|holders| Arc::new(HolderSnapshotter::new(rpc_client.clone(), store.clone(), holders))),
+        performance: config
+            .performance
+            .clone()
+            .map(|performance| Arc::new(PerformanceReporter::new(rpc_client.clone(), store.clone(), sinks.clone(), performance))),
+        dexscreener: config.dexscreener.clone().map(|dexscreener| Arc::new(DexScreener::new(sinks.clone(), dexscreener))),
+        archive: match config.archive.clone() {
+            Some(archive) => Some(Arc::new(Archive::new(archive)?)),
+            None => None,
+        },
     });
+    tokio::spawn(pipeline.clone().run_retries());
+    tokio::spawn(systemd::watchdog());
+    tokio::spawn(clock::monitor(rpc_client.clone(), config.clock.clone()));
 
-    info!("Monitoring logs for program: {}", RAYDIUM_V4_PROGRAM_ID);
-    info!("Waiting for transactions...");
+    let sources = match &replay {
+        Some(replay) => sources::replay(replay.clone()),
+        None => sources::from_config(&config, keys.clone(), rpc_client.clone(), dashboard.clone())?,
+    };
+    let source_names = sources.iter().map(|source| source.name().to_string()).collect::<Vec<_>>();
+    let deadman = config.deadman.as_ref().filter(|deadman| deadman.timeout_mins > 0).map(|deadman| {
+        let (sinks, sources) = (sinks.clone(), source_names.clone());
+        sources::Deadman {
+            timeout: Duration::from_secs(deadman.timeout_mins * 60),
+            alert: Box::new(move |silent| {
+                let sinks = sinks.clone();
+                let stalled = Event::FeedStalled(FeedStalledEvent { silent_secs: silent.as_secs(), sources: sources.clone() });
+                tokio::spawn(async move { sinks.dispatch(&stalled).await });
+            }),
+        }
+    });
+    let mut rx = sources::spawn(sources, deadman);
+    let mut signals = Signals::new()?;
+    // only replays report how the pipeline kept up
+    let mut load = replay.is_some().then(LoadReport::new);
+    info!(programs = programs::all().len(), "Waiting for transactions");
 
-    // 主循环从通道接收日志
-    while let Some(log) = rx.recv().await {
-        if log.value.logs.iter().any(|l| l.contains("initialize2")) {
-            info!("Found initialize2 instruction in transaction: {}", log.value.signature);
-            match Signature::from_str(&log.value.signature) {
-                Ok(signature) => {
-                    // 等待交易完成，减少等待时间
+    // Main loop over creation signatures from the sources and control
+    // signals
+    loop {
+        tokio::select! {
+            signature = rx.recv() => {
+                let Some(signature) = signature else { break };
+                let received_at = Instant::now();
+                if let Some(load) = &mut load {
+                    load.received(rx.len());
+                }
+                metrics.pool_detected();
+                info!(signature = %signature, "Found pool or launch creation");
+                // Give the transaction a moment to become fetchable
+                if replay.is_none() {
                     tokio::time::sleep(Duration::from_millis(500)).await;
-                    if let Err(e) = process_transaction(&rpc_client, signature).await {
-                        error!("Failed to process transaction {}: {}", signature, e);
+                }
+                pipeline.handle(signature, received_at).await;
+                if let Some(load) = &mut load {
+                    load.handled(received_at.elapsed());
+                }
+            }
+            control = signals.recv() => match control {
+                Control::Reload => match Config::load() {
+                    Ok(_) => {
+                        info!("Reloading the config");
+                        error!(error = %daemon::reexec(), "Failed to restart with the new config");
                     }
+                    Err(e) => error!(error = %e, "Invalid config, keeping the running one"),
+                },
+                Control::DumpState => pipeline.dump_state(&source_names, rx.len(), &keys),
+                Control::Shutdown => {
+                    info!("Shutting down");
+                    return Ok(());
                 }
+            },
+        }
+    }
+
+    if let (Some(replay), Some(load)) = (&replay, &load) {
+        load.print(replay);
+        // Enrichment and sinks finish in the background
+        info!("Replay finished, press Ctrl-C to exit");
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result?,
+            _ = async { while signals.recv().await != Control::Shutdown {} } => {}
+        }
+        return Ok(());
+    }
+    warn!("Main loop ended unexpectedly");
+    Ok(())
+}
+
+/// Turns initialize2 transactions into pool events and hands them to the
+/// store, dashboard, sinks and sniper.
+struct Pipeline {
+    rpc_client: Arc<RpcClient>,
+    tokens: TokenCache,
+    metrics: Arc<Metrics>,
+    store: Arc<Store>,
+    sinks: Sinks,
+    enrichers: Enrichers,
+    dashboard: Option<Arc<Dashboard>>,
+    sniper: Option<Arc<Sniper>>,
+    probe_lamports: u64,
+    pair_types: Vec<PairType>,
+    retry: RetryConfig,
+    tracker: Option<Arc<PriceTracker>>,
+    buyers: Option<Arc<BuyerAnalyzer>>,
+    dev_watch: Option<Arc<DevWatcher>>,
+    admin_watch: Option<Arc<AdminWatcher>>,
+    authority_watch: Option<Arc<AuthorityWatcher>>,
+    migration: Option<Arc<MigrationDetector>>,
+    markets: Option<Arc<MarketWatcher>>,
+    mints: Option<Arc<MintWatcher>>,
+    volume: Option<Arc<VolumeTracker>>,
+    holders: Option<Arc<HolderSnapshotter>>,
+    performance: Option<Arc<PerformanceReporter>>,
+    dexscreener: Option<Arc<DexScreener>>,
+    archive: Option<Arc<Archive>>,
+}
+
+impl Pipeline {
+    /// Processes a signature from the subscription. Transactions that cannot
+    /// be fetched yet go to the retry queue.
+    async fn handle(&self, signature: Signature, received_at: Instant) {
+        if self.already_recorded(&signature) {
+            return;
+        }
+        let tx = match fetch_transaction(self.rpc_client.as_ref(), &self.metrics, &signature, UiTransactionEncoding::Base64).await {
+            Ok(tx) => tx,
+            Err(e) => {
+                let now = unix_now();
+                match self.store.enqueue_retry(&signature.to_string(), now, now + self.retry.delay_secs) {
+                    Ok(()) => warn!(signature = %signature, error = %e, retry_in_secs = self.retry.delay_secs, "Transaction not available, queued for retry"),
+                    Err(store_error) => error!(signature = %signature, error = %e, store_error = %store_error, "Transaction not available and could not be queued"),
+                }
+                return;
+            }
+        };
+        let archived = self.archive.as_ref().map(|archive| archive.prepare(signature, &tx));
+        match decode_transaction(self.rpc_client.as_ref(), &self.tokens, &self.metrics, signature, tx, self.probe_lamports).await {
+            Ok(decoded) if decoded.is_empty() => self.metrics.pool_filtered(),
+            Ok(decoded) => {
+                self.archive(signature, archived);
+                self.launch(decoded.launches);
+                self.dex_pools(decoded.dex_pools);
+                self.emit(decoded.pools, Some(received_at));
+            }
+            Err(e) => error!(signature = %signature, error = %e, "Failed to process transaction"),
+        }
+    }
+
+    /// Logs queue lengths, cache sizes and subscriptions, for debugging a
+    /// long-running process (SIGUSR1).
+    fn dump_state(&self, sources: &[String], pending_signatures: usize, keys: &KeyRing) {
+        let retry_queue = self.store.retry_queue_len().map_err(|e| warn!(error = %e, "Failed to count the retry queue")).ok();
+        info!(
+            sources = ?sources,
+            slot = systemd::last_slot(),
+            pending_signatures,
+            retry_queue,
+            cached_mints = self.tokens.cached_mints(),
+            volume_subscriptions = self.volume.as_ref().map(|volume| volume.subscriptions()),
+            predicted_pools = self.markets.as_ref().map(|markets| markets.predicted()),
+            new_mints = self.mints.as_ref().map(|mints| mints.indexed()),
+            clock_drift_ms = clock::drift_ms(),
+            "State dump"
+        );
+        keys.dump_state();
+        self.sinks.dump_state();
+    }
+
+    /// Periodically retries queued signatures, doubling the delay after every
+    /// failed attempt and giving up after `max_attempts`.
+    async fn run_retries(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(Duration::from_secs(RETRY_QUEUE_POLL_SECS));
+        loop {
+            interval.tick().await;
+            let entries = match self.store.due_retries(unix_now()) {
+                Ok(entries) => entries,
                 Err(e) => {
-                    error!("Failed to parse signature {}: {}", log.value.signature, e);
+                    error!(error = %e, "Failed to load the retry queue");
+                    continue;
+                }
+            };
+            for entry in entries {
+                if let Err(e) = self.retry(&entry).await {
+                    error!(signature = %entry.signature, error = %e, "Failed to update the retry queue");
                 }
             }
         }
     }
 
-    warn!("Main loop ended unexpectedly");
+    async fn retry(&self, entry: &RetryEntry) -> Result<()> {
+        let signature = Signature::from_str(&entry.signature)?;
+        if self.already_recorded(&signature) {
+            return self.store.remove_retry(&entry.signature);
+        }
+        let attempts = entry.attempts + 1;
+        let tx = match fetch_transaction(self.rpc_client.as_ref(), &self.metrics, &signature, UiTransactionEncoding::Base64).await {
+            Ok(tx) => tx,
+            Err(e) if attempts >= self.retry.max_attempts => {
+                error!(signature = %signature, attempts, error = %e, "Giving up on transaction");
+                return self.store.remove_retry(&entry.signature);
+            }
+            Err(e) => {
+                let delay = self.retry.delay_secs.saturating_mul(1 << attempts.min(16));
+                warn!(signature = %signature, attempts, retry_in_secs = delay, error = %e, "Transaction still not available");
+                return self.store.reschedule_retry(&entry.signature, attempts, unix_now() + delay);
+            }
+        };
+        self.store.remove_retry(&entry.signature)?;
+        info!(signature = %signature, attempts, delay_secs = unix_now().saturating_sub(entry.first_seen_at), "Fetched queued transaction");
+        let archived = self.archive.as_ref().map(|archive| archive.prepare(signature, &tx));
+        match decode_transaction(self.rpc_client.as_ref(), &self.tokens, &self.metrics, signature, tx, self.probe_lamports).await {
+            Ok(decoded) if decoded.is_empty() => self.metrics.pool_filtered(),
+            Ok(decoded) => {
+                self.archive(signature, archived);
+                self.launch(decoded.launches);
+                self.dex_pools(decoded.dex_pools);
+                self.emit(decoded.pools, None);
+            }
+            Err(e) => error!(signature = %signature, error = %e, "Failed to process transaction"),
+        }
+        Ok(())
+    }
+
+    /// Uploads a launch's transaction in the background when `[archive]` is
+    /// configured.
+    fn archive(&self, signature: Signature, tx: Option<Result<ArchivedTransaction>>) {
+        match (&self.archive, tx) {
+            (Some(archive), Some(Ok(tx))) => {
+                let archive = archive.clone();
+                tokio::spawn(async move { archive.store(tx).await });
+            }
+            (_, Some(Err(e))) => warn!(signature = %signature, error = %e, "Failed to serialize transaction for the archive"),
+            _ => {}
+        }
+    }
+
+    /// Whether the signature was processed before, e.g. before a restart.
+    fn already_recorded(&self, signature: &Signature) -> bool {
+        match self.store.has_pool_signature(&signature.to_string()) {
+            Ok(true) => {
+                info!(signature = %signature, "Transaction already recorded, skipping");
+                true
+            }
+            Ok(false) => false,
+            Err(e) => {
+                warn!(signature = %signature, error = %e, "Failed to check the store for the transaction");
+                false
+            }
+        }
+    }
+
+    /// Records and dispatches launchpad sales, so the pool the token later
+    /// moves to can be linked to its sale.
+    fn launch(&self, launches: Vec<TokenLaunchedEvent>) {
+        for launched in launches {
+            let record = LaunchRecord {
+                base_mint: launched.base_mint.to_string(),
+                launch_pool: launched.launch_pool.to_string(),
+                program: launched.program.to_string(),
+                signature: launched.signature.to_string(),
+                creator: launched.creator.to_string(),
+                quote_mint: launched.quote_mint.to_string(),
+                symbol: launched.symbol.clone(),
+                slot: launched.slot,
+                detected_at: unix_now(),
+            };
+            match self.store.insert_launch(&record) {
+                Ok(true) => {}
+                Ok(false) => {
+                    info!(mint = %launched.base_mint, "Launch already recorded, skipping");
+                    continue;
+                }
+                Err(e) => warn!(mint = %launched.base_mint, error = %e, "Failed to record launch"),
+            }
+            info!(launchpad = %launched.launchpad, mint = %launched.base_mint, symbol = %launched.symbol, launch_pool = %launched.launch_pool, "Token launched");
+            let sinks = self.sinks.clone();
+            tokio::spawn(async move { sinks.dispatch(&Event::TokenLaunched(launched)).await });
+        }
+    }
+
+    /// Dispatches the pools of programs with a custom decoder, linked to the
+    /// base token's launch when one was recorded.
+    fn dex_pools(&self, pools: Vec<DexPoolEvent>) {
+        for mut pool in pools {
+            match self.store.launch(&pool.base_mint.to_string()) {
+                Ok(launch) => pool.launch_pool = launch.map(|launch| launch.launch_pool),
+                Err(e) => warn!(pool = %pool.pool, error = %e, "Failed to look up the token's launch"),
+            }
+            info!(dex = %pool.dex, pool = %pool.pool, base_mint = %pool.base_mint, quote_mint = %pool.quote_mint, "New DEX pool");
+            let sinks = self.sinks.clone();
+            tokio::spawn(async move { sinks.dispatch(&Event::DexPool(pool)).await });
+        }
+    }
+
+    /// Records and dispatches decoded pools. `received_at` is None for pools
+    /// from the retry queue.
+    fn emit(&self, pools: Vec<(NewPool, PoolEvent)>, received_at: Option<Instant>) {
+        for (pool, mut event) in pools {
+            if !self.pair_types.contains(&event.pair_type) {
+                info!(pool = %event.amm, pair_type = event.pair_type.name(), "Pair type not in pair_types, skipping");
+                self.metrics.pool_filtered();
+                continue;
+            }
+            if let Some(predicted) = self.markets.as_ref().and_then(|markets| markets.get(&event.amm)) {
+                info!(pool = %event.amm, market = %predicted.market, ahead_secs = predicted.seen_at.elapsed().as_secs(), "Pool was predicted from its OpenBook market");
+            }
+            match self.store.launch(&event.base().mint.to_string()) {
+                Ok(Some(launch)) => {
+                    info!(pool = %event.amm, launch_pool = %launch.launch_pool, "Token moved to the pool from its launchpad sale");
+                    event.launch_pool = Some(launch.launch_pool);
+                }
+                Ok(None) => {}
+                Err(e) => warn!(pool = %event.amm, error = %e, "Failed to look up the token's launch"),
+            }
+            if let Some(minted) = self.mints.as_ref().and_then(|mints| mints.get(event.base().mint)) {
+                let age_secs = unix_now().saturating_sub(minted.created_at);
+                info!(pool = %event.amm, mint = %minted.mint, deployer = %minted.deployer, age_secs, "Token was minted shortly before its pool");
+                event.token_age_secs = Some(age_secs);
+                event.deployer = Some(minted.deployer.to_string());
+            }
+            // looked up before the pool itself is recorded
+            let earlier = self.migration.as_ref().map(|migration| migration.earlier_pools(&pool)).unwrap_or_default();
+            if !earlier.is_empty() {
+                info!(pool = %event.amm, earlier = earlier.len(), "Pair already has a pool, flagging relaunch");
+                event.flags.push("relaunch");
+            }
+            match self.store.insert_pool(&pool_record(&pool, &event)) {
+                Ok(true) => {}
+                Ok(false) => {
+                    info!(pool = %event.amm, "Pool already recorded, skipping");
+                    continue;
+                }
+                Err(e) => warn!(pool = %event.amm, error = %e, "Failed to record pool"),
+            }
+            self.metrics.event_emitted(received_at.map(|at| at.elapsed()));
+            if let Some(dashboard) = &self.dashboard {
+                if let Some(received_at) = received_at {
+                    dashboard.record_latency(received_at.elapsed());
+                }
+                dashboard.record_pool(PoolRow::from_event(&event));
+            }
+            let name = event.name();
+            let creation = event.signature;
+            let sinks = self.sinks.clone();
+            let enrichers = self.enrichers.clone();
+            tokio::spawn(async move {
+                // external lookups only delay the sinks
+                let mut event = event;
+                enrichers.enrich(&mut event).await;
+                sinks.dispatch(&Event::NewPool(Box::new(event))).await
+            });
+            let validation = (self.rpc_client.clone(), pool.clone());
+            tokio::spawn(async move {
+                let (rpc_client, pool) = validation;
+                match raydium::validate_new_pool(&rpc_client, &pool) {
+                    Ok(issues) if issues.is_empty() => info!(pool = %pool.accounts.amm, "Pool state matches initialize2"),
+                    Ok(issues) => warn!(pool = %pool.accounts.amm, issues = %issues.join("; "), "Pool state differs from initialize2"),
+                    Err(e) => warn!(pool = %pool.accounts.amm, error = %e, "Failed to validate pool state"),
+                }
+            });
+            if let Some(tracker) = &self.tracker {
+                tokio::spawn(tracker.clone().track(pool.clone(), name.clone()));
+            }
+            if let Some(volume) = &self.volume {
+                tokio::spawn(volume.clone().watch(pool.clone()));
+            }
+            if let Some(holders) = &self.holders {
+                tokio::spawn(holders.clone().schedule(pool.clone()));
+            }
+            if let Some(dexscreener) = &self.dexscreener {
+                tokio::spawn(dexscreener.clone().watch(pool.clone(), name.clone()));
+            }
+            if let Some(performance) = &self.performance {
+                tokio::spawn(performance.clone().schedule(pool.clone(), name.clone()));
+            }
+            if let Some(admin_watch) = &self.admin_watch {
+                tokio::spawn(admin_watch.clone().watch(pool.clone(), name.clone()));
+            }
+            if let Some(migration) = self.migration.as_ref().filter(|_| !earlier.is_empty()) {
+                tokio::spawn(migration.clone().check(pool.clone(), name.clone(), earlier));
+            }
+            if let Some(authority_watch) = &self.authority_watch {
+                tokio::spawn(authority_watch.clone().watch(pool.clone(), name.clone()));
+            }
+            if let Some(dev_watch) = &self.dev_watch {
+                tokio::spawn(dev_watch.clone().watch(pool.clone(), name));
+            }
+            if let Some(buyers) = &self.buyers {
+                tokio::spawn(buyers.clone().analyze(pool.clone(), creation));
+            }
+            // followers only alert and buy once elected
+            if let Some(sniper) = self.sniper.as_ref().filter(|_| leader::is_leader()) {
+                let sniper = sniper.clone();
+                tokio::spawn(async move { sniper.on_new_pool(pool).await });
+            }
+        }
+    }
+}
+
+fn pool_record(pool: &NewPool, event: &PoolEvent) -> PoolRecord {
+    let wsol = spl_token::native_mint::id();
+    let sol_liquidity_lamports = if pool.accounts.pc_mint == wsol {
+        Some(pool.init_pc_amount)
+    } else if pool.accounts.coin_mint == wsol {
+        Some(pool.init_coin_amount)
+    } else {
+        None
+    };
+    PoolRecord {
+        signature: event.signature.to_string(),
+        amm: pool.accounts.amm.to_string(),
+        coin_mint: pool.accounts.coin_mint.to_string(),
+        pc_mint: pool.accounts.pc_mint.to_string(),
+        quote_mint: pool.accounts.quote_mint().to_string(),
+        creator: pool.accounts.creator.to_string(),
+        slot: event.slot,
+        init_coin_amount: pool.init_coin_amount,
+        init_pc_amount: pool.init_pc_amount,
+        sol_liquidity_lamports,
+        open_time: pool.open_time,
+        detected_at: unix_now(),
+    }
+}
+
+/// Risk flags of the non-SOL tokens of a pool.
+fn risk_flags(tokens: &[(&Pubkey, &TokenInfo)]) -> Vec<&'static str> {
+    let wsol = spl_token::native_mint::id();
+    let mut flags = Vec::new();
+    for (mint, info) in tokens {
+        if **mint == wsol {
+            continue;
+        }
+        if info.mint_authority {
+            flags.push("mint");
+        }
+        if info.freeze_authority {
+            flags.push("freeze");
+        }
+    }
+    flags
+}
+
+/// Prints the decoded state, reserves, price and risk flags of a pool.
+async fn inspect_pool(rpc_client: &RpcClient, tokens: &TokenCache, amm: &Pubkey, timezone: Tz) -> Result<()> {
+    let (info, state) = raydium::fetch_pool(rpc_client, amm)?;
+    let metrics = Metrics::default();
+    let coin = tokens.get(&metrics, &info.coin_mint).await?;
+    let pc = tokens.get(&metrics, &info.pc_mint).await?;
+    let lp_mint = Mint::unpack_from_slice(&rpc_client.get_account_data(&info.lp_mint)?)?;
+
+    let coin_reserve = amm::ui_amount(state.coin_reserve, info.coin_decimals as u8);
+    let pc_reserve = amm::ui_amount(state.pc_reserve, info.pc_decimals as u8);
+    let flags = risk_flags(&[(&info.coin_mint, &coin), (&info.pc_mint, &pc)]);
+    let coin_label = token_label(coin.name.as_deref(), &info.coin_mint);
+    let pc_label = token_label(pc.name.as_deref(), &info.pc_mint);
+
+    println!("Pool        {}", amm);
+    println!("Status      {} ({})", info.status_name(), info.status);
+    println!("Open time   {}", time::format_open_time(info.pool_open_time, unix_now(), timezone));
+    let (coin_side, pc_side) =
+        if raydium::quote_mint(&info.coin_mint, &info.pc_mint) == info.coin_mint { ("quote", "base") } else { ("base", "quote") };
+    println!("Coin        {} {} ({} decimals, {})", coin_label, info.coin_mint, info.coin_decimals, coin_side);
+    println!("Pc          {} {} ({} decimals, {})", pc_label, info.pc_mint, info.pc_decimals, pc_side);
+    println!("Reserves    {} {} / {} {}", coin_reserve, coin_label, pc_reserve, pc_label);
+    if let Some(price) = pc_reserve.checked_div(coin_reserve) {
+        println!("Price       {:.10} {} per {}", price, pc_label, coin_label);
+    }
+    println!("Swap fee    {}/{}", info.swap_fee_numerator, info.swap_fee_denominator);
+    println!(
+        "LP mint     {} (supply {}, pool lp_amount {})",
+        info.lp_mint,
+        amm::ui_amount(lp_mint.supply, lp_mint.decimals),
+        amm::ui_amount(info.lp_amount, lp_mint.decimals)
+    );
+    println!("Market      {} ({})", info.market, info.market_program);
+    println!("Flags       {}", if flags.is_empty() { "-".to_string() } else { flags.join(",") });
     Ok(())
 }
 
-async fn process_transaction(rpc_client: &RpcClient, signature: Signature) -> Result<()> {
+/// The pools and launchpad sales of one transaction.
+#[derive(Default)]
+struct Decoded {
+    pools: Vec<(NewPool, PoolEvent)>,
+    launches: Vec<TokenLaunchedEvent>,
+    // from programs with a custom decoder
+    dex_pools: Vec<DexPoolEvent>,
+}
+
+impl Decoded {
+    fn is_empty(&self) -> bool {
+        self.pools.is_empty() && self.launches.is_empty() && self.dex_pools.is_empty()
+    }
+}
+
+async fn process_transaction(
+    rpc: &dyn ChainRpc,
+    tokens: &TokenCache,
+    metrics: &Metrics,
+    signature: Signature,
+    probe_lamports: u64,
+) -> Result<Decoded> {
+    let tx = fetch_transaction(rpc, metrics, &signature, UiTransactionEncoding::Base64).await?;
+    decode_transaction(rpc, tokens, metrics, signature, tx, probe_lamports).await
+}
+
+/// Fetches a transaction, retrying a few times on failure.
+async fn fetch_transaction(
+    rpc: &dyn ChainRpc,
+    metrics: &Metrics,
+    signature: &Signature,
+    encoding: UiTransactionEncoding,
+) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
     let tx_config = RpcTransactionConfig {
         max_supported_transaction_version: Some(0),
-        encoding: Some(UiTransactionEncoding::Base64),
-        commitment: Some(CommitmentConfig::confirmed()),  // 使用 confirmed 而不是 finalized
+        encoding: Some(encoding),
+        commitment: Some(CommitmentConfig::confirmed()),  // confirmed rather than finalized
     };
 
-    // 使用重试机制获取交易
     let mut retries = 0;
-    let tx = loop {
-        match rpc_client.get_transaction_with_config(&signature, tx_config.clone()) {
-            Ok(tx) => break tx,
+    loop {
+        match rpc.get_transaction(signature, tx_config) {
+            Ok(tx) => return Ok(tx),
             Err(e) => {
+                metrics.rpc_error();
                 if retries >= MAX_RETRIES {
                     return Err(anyhow!("Failed to get transaction after {} retries: {}", MAX_RETRIES, e));
                 }
-                warn!("Failed to get transaction, retrying ({}/{}): {}", retries + 1, MAX_RETRIES, e);
+                warn!(signature = %signature, attempt = retries + 1, max_retries = MAX_RETRIES, error = %e, "Failed to get transaction, retrying");
                 tokio::time::sleep(RETRY_DELAY).await;
                 retries += 1;
-                continue;
             }
         }
-    };
+    }
+}
 
-    // 解析交易数据
-    let transaction = tx.transaction.transaction.decode().ok_or_else(|| anyhow!("Failed to decode transaction"))?;
-    let message = transaction.message;
+async fn decode_transaction(
+    rpc: &dyn ChainRpc,
+    tokens: &TokenCache,
+    metrics: &Metrics,
+    signature: Signature,
+    tx: EncodedConfirmedTransactionWithStatusMeta,
+    probe_lamports: u64,
+) -> Result<Decoded> {
 
-    // 获取账户和指令
-    let static_keys = message.static_account_keys();
-    let instructions = message.instructions();
+    // Base64 is decoded locally; fall back to the node's jsonParsed form for
+    // transactions that fail to decode or whose lookup tables weren't resolved
+    let block_time = tx.block_time;
+    let slot = tx.slot;
+    let instructions = match compiled_instructions(&tx)? {
+        Some(instructions) => instructions,
+        None => {
+            warn!(signature = %signature, "Cannot decode transaction locally, refetching as jsonParsed");
+            let tx = fetch_transaction(rpc, metrics, &signature, UiTransactionEncoding::JsonParsed).await?;
+            parsed_instructions(&tx)?
+        }
+    };
 
-    // 查找 Raydium 指令
-    let raydium_ix = instructions.iter()
-        .find(|ix| {
-            static_keys[ix.program_id_index as usize] == Pubkey::from_str(RAYDIUM_V4_PROGRAM_ID).unwrap()
-        });
+    // Decode every Raydium initialize2 instruction
+    let mut pools = Vec::new();
+    for ix in decode::initialize2_instructions(&instructions) {
+        let (data, accounts) = decode::initialize2(ix)?;
+        let token_a_account = &accounts.coin_mint;
+        let token_b_account = &accounts.pc_mint;
 
-    if let Some(ix) = raydium_ix {
-        // 直接使用指令数据的原始字节
-        let data = Initialize2Data::try_from_slice(&ix.data)?;
-        
-        // 获取相关账户
-        let lp_account = &static_keys[4];
-        let token_a_account = &static_keys[8];
-        let token_b_account = &static_keys[9];
-
-        // 获取代币信息
-        let token_a_info = match fetch_token_info(rpc_client, token_a_account).await {
+        // Fetch token info
+        let token_a_info = match tokens.get(metrics, token_a_account).await {
             Ok(info) => info,
             Err(e) => {
-                warn!("Failed to fetch token A info: {}", e);
+                warn!(mint = %token_a_account, error = %e, "Failed to fetch token A info");
                 TokenInfo {
-                    name: format!("Unknown Token {}", token_a_account),
-                    decimals: 9, // 默认使用 9 位小数
+                    name: None,
+                    symbol: None,
+                    image: None,
+                    decimals: 9, // default to 9 decimals
+                    mint_authority: false,
+                    freeze_authority: false,
                 }
             }
         };
 
-        let token_b_info = match fetch_token_info(rpc_client, token_b_account).await {
+        let token_b_info = match tokens.get(metrics, token_b_account).await {
             Ok(info) => info,
             Err(e) => {
-                warn!("Failed to fetch token B info: {}", e);
+                warn!(mint = %token_b_account, error = %e, "Failed to fetch token B info");
                 TokenInfo {
-                    name: format!("Unknown Token {}", token_b_account),
-                    decimals: 9, // 默认使用 9 位小数
+                    name: None,
+                    symbol: None,
+                    image: None,
+                    decimals: 9, // default to 9 decimals
+                    mint_authority: false,
+                    freeze_authority: false,
                 }
             }
         };
 
-        // 输出信息
-        info!("Found new liquidity pool!");
-        info!("----------------------------");
-        info!("Transaction: https://solscan.io/tx/{}", signature);
-        info!("New LP Account: {}", lp_account);
-        info!("Token A: {} ({})", token_a_info.name, token_a_account);
-        info!("Token A Amount: {}", data.init_coin_amount as f64 / 10f64.powi(token_a_info.decimals as i32));
-        info!("Token B: {} ({})", token_b_info.name, token_b_account);
-        info!("Token B Amount: {}", data.init_pc_amount as f64 / 10f64.powi(token_b_info.decimals as i32));
-        info!("Open Time: {}", data.open_time);
-
-        // 计算延迟
-        if let Some(block_time) = tx.block_time {
-            let current_time = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)?
-                .as_secs();
-            let delay = current_time.saturating_sub(block_time as u64);
-            info!("Transaction delay: {} seconds", delay);
+        // Price impact of buying with SOL
+        let wsol = spl_token::native_mint::id();
+        let sol_side = if *token_b_account == wsol {
+            Some((data.init_pc_amount, data.init_coin_amount))
+        } else if *token_a_account == wsol {
+            Some((data.init_coin_amount, data.init_pc_amount))
+        } else {
+            None
+        };
+        let price_impact = sol_side.map(|(sol_reserve, token_reserve)| {
+            amm::estimate_swap(probe_lamports, sol_reserve, token_reserve, amm::RAYDIUM_FEE_BPS).price_impact
+        });
+
+        // Detection delay, corrected for local clock drift
+        let block_delay_secs = block_time.map(clock::delay_since);
+
+        let flags = risk_flags(&[(token_a_account, &token_a_info), (token_b_account, &token_b_info)]);
+
+        let event = PoolEvent {
+            signature,
+            amm: accounts.amm,
+            creator: accounts.creator,
+            slot,
+            coin_mint: *token_a_account,
+            coin_name: token_a_info.name,
+            coin_symbol: token_a_info.symbol,
+            coin_image: token_a_info.image,
+            pc_mint: *token_b_account,
+            pc_name: token_b_info.name,
+            pc_symbol: token_b_info.symbol,
+            pc_image: token_b_info.image,
+            init_coin_amount: amm::ui_amount(data.init_coin_amount, token_a_info.decimals),
+            init_pc_amount: amm::ui_amount(data.init_pc_amount, token_b_info.decimals),
+            quote_side: if accounts.quote_mint() == accounts.coin_mint { QuoteSide::Coin } else { QuoteSide::Pc },
+            pair_type: PairType::of(&accounts.quote_mint()),
+            liquidity_sol: sol_side.map(|(sol_reserve, _)| lamports_to_sol(sol_reserve)),
+            price_impact,
+            flags,
+            open_time: data.open_time,
+            block_delay_secs,
+            launch_pool: None,
+            token_age_secs: None,
+            deployer: None,
+            enrichment: BTreeMap::new(),
+        };
+        let pool = NewPool {
+            accounts,
+            open_time: data.open_time,
+            init_coin_amount: data.init_coin_amount,
+            init_pc_amount: data.init_pc_amount,
+        };
+        pools.push((pool, event));
+    }
+
+    // Launchpad sales, named after the token they mint
+    let mut launches = Vec::new();
+    for ix in decode::launch_instructions(&instructions) {
+        let (mint, accounts) = decode::launch(ix)?;
+        launches.push(TokenLaunchedEvent {
+            signature,
+            launch_pool: accounts.launch_pool,
+            program: accounts.program,
+            launchpad: programs::get(&accounts.program).map_or_else(|| accounts.program.to_string(), |program| program.name.clone()),
+            creator: accounts.creator,
+            base_mint: accounts.base_mint,
+            quote_mint: accounts.quote_mint,
+            name: mint.name,
+            symbol: mint.symbol,
+            uri: mint.uri,
+            decimals: mint.decimals,
+            slot,
+        });
+    }
+
+    // Pools of the programs decoded from their layout
+    let mut dex_pools = Vec::new();
+    for (program, ix) in decode::layout_instructions(&instructions) {
+        let decoded = decode::layout(program, ix)?;
+        dex_pools.push(DexPoolEvent {
+            signature,
+            program: program.id,
+            dex: program.name.clone(),
+            pool: decoded.account("pool"),
+            base_mint: decoded.account("base_mint"),
+            quote_mint: decoded.account("quote_mint"),
+            accounts: decoded.accounts.iter().map(|(name, key)| (name.clone(), key.to_string())).collect(),
+            fields: decoded.fields,
+            launch_pool: None,
+            slot,
+        });
+    }
+
+    Ok(Decoded { pools, launches, dex_pools })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+    use raydium_v4_monitor::chain::MockRpc;
+    use raydium_v4_monitor::decode::Initialize2Data;
+    use raydium_v4_monitor::RAYDIUM_V4_PROGRAM_ID;
+    use solana_sdk::{
+        account::Account,
+        instruction::{AccountMeta, Instruction},
+        message::Message,
+        transaction::Transaction,
+    };
+    use solana_transaction_status::{EncodedTransaction, EncodedTransactionWithStatusMeta, TransactionBinaryEncoding};
+
+    fn mint_account(decimals: u8, mint_authority: Option<Pubkey>) -> Account {
+        let mint = Mint {
+            mint_authority: mint_authority.into(),
+            supply: 1_000_000,
+            decimals,
+            is_initialized: true,
+            freeze_authority: None.into(),
+        };
+        let mut data = vec![0; Mint::LEN];
+        Mint::pack(mint, &mut data).unwrap();
+        Account { lamports: 1_461_600, data, owner: spl_token::id(), executable: false, rent_epoch: 0 }
+    }
+
+    /// A base64 transaction with one Raydium instruction creating a
+    /// `coin_mint`/WSOL pool.
+    fn raydium_transaction(coin_mint: Pubkey, data: &Initialize2Data) -> EncodedConfirmedTransactionWithStatusMeta {
+        let mut accounts = (0..21).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        accounts[8] = coin_mint;
+        accounts[9] = spl_token::native_mint::id();
+        let ix = Instruction::new_with_borsh(
+            Pubkey::from_str(RAYDIUM_V4_PROGRAM_ID).unwrap(),
+            data,
+            accounts.iter().map(|key| AccountMeta::new(*key, false)).collect(),
+        );
+        let tx = Transaction::new_unsigned(Message::new(&[ix], Some(&accounts[17])));
+        let blob = base64::engine::general_purpose::STANDARD.encode(bincode::serialize(&tx).unwrap());
+        EncodedConfirmedTransactionWithStatusMeta {
+            slot: 250_000_000,
+            block_time: None,
+            transaction: EncodedTransactionWithStatusMeta {
+                transaction: EncodedTransaction::Binary(blob, TransactionBinaryEncoding::Base64),
+                meta: None,
+                version: None,
+            },
         }
-        info!("----------------------------");
     }
 
-    Ok(())
+    fn initialize2_data() -> Initialize2Data {
+        Initialize2Data {
+            discriminator: raydium::INITIALIZE2_DISCRIMINATOR,
+            nonce: 254,
+            open_time: 1_700_000_000,
+            init_pc_amount: 5_000_000_000,
+            init_coin_amount: 200_000_000_000,
+        }
+    }
+
+    async fn decode(rpc: MockRpc, signature: Signature) -> Result<Vec<(NewPool, PoolEvent)>> {
+        let rpc = Arc::new(rpc);
+        let tokens = TokenCache::new(tokens::provider(&Config::default(), rpc.clone()));
+        Ok(process_transaction(rpc.as_ref(), &tokens, &Metrics::default(), signature, sol_to_lamports(1.0)).await?.pools)
+    }
+
+    #[tokio::test]
+    async fn decodes_initialize2() {
+        let coin_mint = Pubkey::new_unique();
+        let signature = Signature::new_unique();
+        let rpc = MockRpc::new()
+            .with_transaction(signature, &raydium_transaction(coin_mint, &initialize2_data()))
+            .with_account(coin_mint, mint_account(6, Some(Pubkey::new_unique())))
+            .with_account(spl_token::native_mint::id(), mint_account(9, None));
+
+        let pools = decode(rpc, signature).await.unwrap();
+        assert_eq!(pools.len(), 1);
+        let (pool, event) = &pools[0];
+        assert_eq!(pool.accounts.coin_mint, coin_mint);
+        assert_eq!(pool.init_pc_amount, 5_000_000_000);
+        assert_eq!(event.signature, signature);
+        assert_eq!(event.slot, 250_000_000);
+        assert_eq!(event.open_time, 1_700_000_000);
+        assert_eq!(event.quote_side, QuoteSide::Pc);
+        assert_eq!(event.init_coin_amount, amm::ui_amount(200_000_000_000, 6));
+        assert_eq!(event.init_pc_amount, amm::ui_amount(5_000_000_000, 9));
+        assert_eq!(event.liquidity_sol, Some(5.0));
+        assert!(event.price_impact.is_some_and(|impact| impact > 0.0));
+        assert_eq!(event.flags, vec!["mint"]);
+        assert_eq!(event.block_delay_secs, None);
+    }
+
+    #[tokio::test]
+    async fn missing_mint_defaults_to_nine_decimals() {
+        let coin_mint = Pubkey::new_unique();
+        let signature = Signature::new_unique();
+        let rpc = MockRpc::new().with_transaction(signature, &raydium_transaction(coin_mint, &initialize2_data()));
+
+        let pools = decode(rpc, signature).await.unwrap();
+        let (_, event) = &pools[0];
+        assert_eq!(event.init_coin_amount, amm::ui_amount(200_000_000_000, 9));
+        assert_eq!(event.coin_name, None);
+        assert!(event.flags.is_empty());
+    }
+
+    #[tokio::test]
+    async fn skips_other_raydium_instructions() {
+        let signature = Signature::new_unique();
+        let swap = Initialize2Data { discriminator: 9, ..initialize2_data() };
+        let rpc = MockRpc::new().with_transaction(signature, &raydium_transaction(Pubkey::new_unique(), &swap));
+
+        assert!(decode(rpc, signature).await.unwrap().is_empty());
+    }
 }
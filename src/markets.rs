@@ -0,0 +1,194 @@
+use crate::config::{MarketWatchConfig, ProgramDecoder};
+use crate::programs;
+use crate::raydium::{self, MarketAccounts};
+use crate::rpc::KeyRing;
+use anyhow::{anyhow, Result};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    pubsub_client::PubsubClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::RpcFilterType,
+};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+/// Size of an OpenBook MarketState account.
+const MARKET_LEN: u64 = 388;
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// The Raydium pool expected for an OpenBook market.
+#[derive(Debug, Clone)]
+pub struct PredictedPool {
+    pub amm: Pubkey,
+    pub market: Pubkey,
+    pub coin_mint: Pubkey,
+    pub pc_mint: Pubkey,
+    // for the swap instruction, so a buy skips fetching the market
+    pub market_accounts: MarketAccounts,
+    pub seen_at: Instant,
+}
+
+/// Subscribes to the market accounts of the OpenBook program and predicts
+/// the Raydium pool of each market it sees, one per monitored Raydium V4
+/// program, since pools are created on a market that exists first. When the
+/// pool appears its market is already decoded, and a buy takes the swap
+/// accounts from here instead of fetching them at the critical moment.
+pub struct MarketWatcher {
+    keys: Arc<KeyRing>,
+    program: Pubkey,
+    ttl: Duration,
+    // by predicted pool address
+    predictions: Mutex<HashMap<Pubkey, PredictedPool>>,
+}
+
+impl MarketWatcher {
+    pub fn new(keys: Arc<KeyRing>, config: &MarketWatchConfig) -> Result<Self> {
+        let program = Pubkey::from_str(&config.program_id)
+            .map_err(|_| anyhow!("invalid [market_watch] program_id {}", config.program_id))?;
+        Ok(Self {
+            keys,
+            program,
+            ttl: Duration::from_secs(config.ttl_mins * 60),
+            predictions: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn predicted(&self) -> usize {
+        self.predictions.lock().unwrap().len()
+    }
+
+    /// The prediction of a pool whose market was seen within `ttl_mins`.
+    pub fn get(&self, amm: &Pubkey) -> Option<PredictedPool> {
+        self.predictions.lock().unwrap().get(amm).filter(|predicted| predicted.seen_at.elapsed() < self.ttl).cloned()
+    }
+
+    /// The swap accounts of a market seen within `ttl_mins`.
+    pub fn market_accounts(&self, market: &Pubkey) -> Option<MarketAccounts> {
+        amm_addresses(market)
+            .into_iter()
+            .find_map(|amm| self.get(&amm).filter(|predicted| predicted.market == *market))
+            .map(|predicted| predicted.market_accounts)
+    }
+
+    /// Keeps the subscription up, reconnecting when it drops.
+    pub async fn run(self: Arc<Self>) {
+        loop {
+            // the pubsub client blocks
+            let watcher = self.clone();
+            match tokio::task::spawn_blocking(move || watcher.subscribe()).await {
+                Ok(Err(e)) => warn!(program = %self.program, error = %e, "OpenBook market subscription ended"),
+                Ok(Ok(())) => {}
+                Err(e) => warn!(program = %self.program, error = %e, "OpenBook market watcher panicked"),
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    }
+
+    fn subscribe(&self) -> Result<()> {
+        let (_subscription, receiver) = PubsubClient::program_subscribe(
+            &self.keys.ws_url("programSubscribe"),
+            &self.program,
+            Some(RpcProgramAccountsConfig {
+                filters: Some(vec![RpcFilterType::DataSize(MARKET_LEN)]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        )?;
+        info!(program = %self.program, "Subscribed to OpenBook markets");
+        while let Ok(update) = receiver.recv() {
+            let (Ok(market), Some(data)) = (Pubkey::from_str(&update.value.pubkey), update.value.account.data.decode()) else {
+                continue;
+            };
+            match self.observe(market, &data) {
+                Ok(Some(predicted)) => {
+                    info!(market = %market, amm = %predicted.amm, coin_mint = %predicted.coin_mint, pc_mint = %predicted.pc_mint, "Predicted pool for OpenBook market")
+                }
+                Ok(None) => {}
+                Err(e) => debug!(market = %market, error = %e, "Skipping unreadable market"),
+            }
+        }
+        Err(anyhow!("market subscription closed"))
+    }
+
+    /// Registers the pools of a market not seen within `ttl_mins`, returning
+    /// the first new prediction. Expired predictions are dropped meanwhile.
+    fn observe(&self, market: Pubkey, data: &[u8]) -> Result<Option<PredictedPool>> {
+        let amms = amm_addresses(&market);
+        let Some(&amm) = amms.first() else {
+            return Ok(None);
+        };
+        let mut predictions = self.predictions.lock().unwrap();
+        if predictions.get(&amm).is_some_and(|predicted| predicted.seen_at.elapsed() < self.ttl) {
+            return Ok(None);
+        }
+        let market_accounts = raydium::market_accounts(&market, &self.program, data)?;
+        let pubkey_at = |offset: usize| Pubkey::try_from(&data[offset..offset + 32]).unwrap();
+        let predicted = PredictedPool {
+            amm,
+            market,
+            coin_mint: pubkey_at(53),
+            pc_mint: pubkey_at(85),
+            market_accounts,
+            seen_at: Instant::now(),
+        };
+        predictions.retain(|_, predicted| predicted.seen_at.elapsed() < self.ttl);
+        for amm in amms {
+            predictions.insert(amm, PredictedPool { amm, ..predicted.clone() });
+        }
+        Ok(Some(predicted))
+    }
+}
+
+/// The pool each monitored Raydium V4 program would create for `market`.
+fn amm_addresses(market: &Pubkey) -> Vec<Pubkey> {
+    programs::ids(ProgramDecoder::RaydiumV4).iter().map(|program| raydium::amm_address(program, market)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::metrics::Metrics;
+
+    fn watcher() -> MarketWatcher {
+        let keys = KeyRing::from_config(&Config::default(), Metrics::new()).unwrap();
+        MarketWatcher::new(keys, &MarketWatchConfig::default()).unwrap()
+    }
+
+    /// MarketState data with a vault signer nonce valid for `market`.
+    fn market_data(market: &Pubkey, program: &Pubkey, coin_mint: &Pubkey, pc_mint: &Pubkey) -> Vec<u8> {
+        let nonce = (0u64..)
+            .find(|nonce| Pubkey::create_program_address(&[market.as_ref(), &nonce.to_le_bytes()], program).is_ok())
+            .unwrap();
+        let mut data = vec![0; MARKET_LEN as usize];
+        data[45..53].copy_from_slice(&nonce.to_le_bytes());
+        data[53..85].copy_from_slice(coin_mint.as_ref());
+        data[85..117].copy_from_slice(pc_mint.as_ref());
+        data
+    }
+
+    #[test]
+    fn predicts_each_market_once() {
+        let watcher = watcher();
+        let (market, coin_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let data = market_data(&market, &watcher.program, &coin_mint, &spl_token::native_mint::id());
+
+        let predicted = watcher.observe(market, &data).unwrap().unwrap();
+        assert_eq!(predicted.amm, amm_addresses(&market)[0]);
+        assert_eq!(predicted.coin_mint, coin_mint);
+        assert!(watcher.observe(market, &data).unwrap().is_none());
+        assert_eq!(watcher.get(&predicted.amm).unwrap().market, market);
+        assert!(watcher.market_accounts(&market).is_some());
+        assert!(watcher.market_accounts(&Pubkey::new_unique()).is_none());
+        assert!(watcher.observe(Pubkey::new_unique(), &data[..100]).is_err());
+        assert_eq!(watcher.predicted(), 1);
+    }
+}
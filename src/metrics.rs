@@ -0,0 +1,142 @@
+use crate::clock;
+use crate::events::{Event, Priority};
+use crate::sinks::Sinks;
+use crate::store::RpcUsage;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::info;
+
+/// Runtime counters shared by the monitor tasks. Each summary report takes
+/// and resets them, so a report covers only its own interval.
+#[derive(Default)]
+pub struct Metrics {
+    pools_detected: AtomicU64,
+    pools_filtered: AtomicU64,
+    events_emitted: AtomicU64,
+    latency_total_ms: AtomicU64,
+    latency_count: AtomicU64,
+    rpc_errors: AtomicU64,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+    // (endpoint, method) -> calls
+    rpc_calls: Mutex<BTreeMap<(String, String), u64>>,
+}
+
+impl Metrics {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    pub fn pool_detected(&self) {
+        self.pools_detected.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn pool_filtered(&self) {
+        self.pools_filtered.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// `latency` is None for events from the retry queue, which would skew the
+    /// average.
+    pub fn event_emitted(&self, latency: Option<Duration>) {
+        self.events_emitted.fetch_add(1, Ordering::Relaxed);
+        if let Some(latency) = latency {
+            self.latency_total_ms.fetch_add(latency.as_millis() as u64, Ordering::Relaxed);
+            self.latency_count.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn rpc_error(&self) {
+        self.rpc_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn rpc_call(&self, endpoint: &str, method: &str) {
+        *self.rpc_calls.lock().unwrap().entry((endpoint.to_string(), method.to_string())).or_default() += 1;
+    }
+
+    pub fn cache_hit(&self) {
+        self.cache_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn cache_miss(&self) {
+        self.cache_misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn take(&self, interval: Duration, priority: Priority) -> RuntimeSummary {
+        let take = |counter: &AtomicU64| counter.swap(0, Ordering::Relaxed);
+        let latency_total_ms = take(&self.latency_total_ms);
+        let latency_count = take(&self.latency_count);
+        let cache_hits = take(&self.cache_hits);
+        let cache_misses = take(&self.cache_misses);
+        let mut rpc_calls = std::mem::take(&mut *self.rpc_calls.lock().unwrap())
+            .into_iter()
+            .map(|((endpoint, method), calls)| RpcUsage { endpoint, method, calls })
+            .collect::<Vec<_>>();
+        rpc_calls.sort_by_key(|usage| std::cmp::Reverse(usage.calls));
+        RuntimeSummary {
+            interval_secs: interval.as_secs(),
+            pools_detected: take(&self.pools_detected),
+            pools_filtered: take(&self.pools_filtered),
+            events_emitted: take(&self.events_emitted),
+            avg_latency_ms: (latency_count > 0).then(|| latency_total_ms / latency_count),
+            rpc_errors: take(&self.rpc_errors),
+            cache_hits,
+            cache_misses,
+            cache_hit_rate: (cache_hits + cache_misses > 0).then(|| cache_hits as f64 / (cache_hits + cache_misses) as f64),
+            rpc_calls,
+            clock_drift_ms: clock::drift_ms(),
+            priority,
+        }
+    }
+}
+
+/// Counters for one summary interval.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RuntimeSummary {
+    pub interval_secs: u64,
+    pub pools_detected: u64,
+    pub pools_filtered: u64,
+    pub events_emitted: u64,
+    // from receiving the log to emitting the event
+    pub avg_latency_ms: Option<u64>,
+    pub rpc_errors: u64,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    pub cache_hit_rate: Option<f64>,
+    // RPC and WebSocket calls per endpoint and method, most called first
+    pub rpc_calls: Vec<RpcUsage>,
+    // local clock minus network time at the last check
+    pub clock_drift_ms: Option<i64>,
+    #[serde(skip)]
+    pub priority: Priority,
+}
+
+/// Logs a summary every `interval` and dispatches it to the sinks as an
+/// event with `priority`, so only sinks accepting that priority receive it.
+pub async fn report(metrics: Arc<Metrics>, sinks: Sinks, interval: Duration, priority: Priority) {
+    let mut ticker = tokio::time::interval(interval);
+    // the first tick completes immediately
+    ticker.tick().await;
+    loop {
+        ticker.tick().await;
+        let summary = metrics.take(interval, priority);
+        info!(
+            interval_secs = summary.interval_secs,
+            pools_detected = summary.pools_detected,
+            pools_filtered = summary.pools_filtered,
+            events_emitted = summary.events_emitted,
+            avg_latency_ms = summary.avg_latency_ms,
+            rpc_errors = summary.rpc_errors,
+            cache_hits = summary.cache_hits,
+            cache_misses = summary.cache_misses,
+            cache_hit_rate = summary.cache_hit_rate,
+            clock_drift_ms = summary.clock_drift_ms,
+            rpc_calls = summary.rpc_calls.iter().map(|usage| usage.calls).sum::<u64>(),
+            "Runtime summary"
+        );
+        sinks.dispatch(&Event::Summary(summary)).await;
+    }
+}
@@ -0,0 +1,137 @@
+use crate::amm::PoolState;
+use crate::config::MigrationConfig;
+use crate::events::{Event, LiquidityMigratedEvent};
+use crate::raydium::{self, AmmInfo, NewPool};
+use crate::sinks::Sinks;
+use crate::sniper::unix_now;
+use crate::store::{PoolRecord, Store};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Matches new pools against the earlier pools of their pair in the store. A
+/// new pool with an earlier one is flagged as a relaunch; when the earlier
+/// pool's quote reserve has mostly drained by then, the liquidity is taken
+/// to have moved to the new pool and a `liquidity_migrated` alert follows.
+pub struct MigrationDetector {
+    rpc_client: Arc<RpcClient>,
+    store: Arc<Store>,
+    sinks: Sinks,
+    config: MigrationConfig,
+}
+
+impl MigrationDetector {
+    pub fn new(rpc_client: Arc<RpcClient>, store: Arc<Store>, sinks: Sinks, config: MigrationConfig) -> Self {
+        Self { rpc_client, store, sinks, config }
+    }
+
+    /// The pair's earlier pools within `lookback_hours`, newest first.
+    pub fn earlier_pools(&self, pool: &NewPool) -> Vec<PoolRecord> {
+        let accounts = &pool.accounts;
+        let since = unix_now().saturating_sub(self.config.lookback_hours * 3600);
+        match self.store.pair_pools(
+            &accounts.amm.to_string(),
+            &accounts.coin_mint.to_string(),
+            &accounts.pc_mint.to_string(),
+            since,
+        ) {
+            Ok(pools) => pools,
+            Err(e) => {
+                warn!(pool = %accounts.amm, error = %e, "Failed to look up earlier pools of the pair");
+                Vec::new()
+            }
+        }
+    }
+
+    /// Checks how much of each earlier pool's liquidity is left. `name` is
+    /// the new pool's display name used in alerts.
+    pub async fn check(self: Arc<Self>, pool: NewPool, name: String, earlier: Vec<PoolRecord>) {
+        let amm = pool.accounts.amm;
+        let quote = pool.accounts.quote_mint();
+        for record in earlier {
+            let Ok(from_amm) = Pubkey::from_str(&record.amm) else {
+                continue;
+            };
+            let (info, state) = match raydium::fetch_pool(&self.rpc_client, &from_amm) {
+                Ok(pool) => pool,
+                Err(e) => {
+                    warn!(pool = %amm, earlier = %from_amm, error = %e, "Failed to read earlier pool of the pair");
+                    continue;
+                }
+            };
+            let (from_reserve, to_reserve) = quote_reserves(&record, &info, &state, &quote);
+            let drop_percent = if from_reserve > 0.0 { (from_reserve - to_reserve) / from_reserve * 100.0 } else { 0.0 };
+            if drop_percent < self.config.min_drop_percent {
+                info!(pool = %amm, earlier = %from_amm, drop_percent = format_args!("{:.1}", drop_percent), "Earlier pool of the pair still holds its liquidity");
+                continue;
+            }
+            let quote_decimals = if info.coin_mint == quote { info.coin_decimals } else { info.pc_decimals };
+            let new_quote = if pool.accounts.coin_mint == quote { pool.init_coin_amount } else { pool.init_pc_amount };
+            let migrated = LiquidityMigratedEvent {
+                amm,
+                name: name.clone(),
+                from_amm,
+                same_creator: record.creator == pool.accounts.creator.to_string(),
+                from_reserve,
+                to_reserve,
+                drop_percent,
+                new_reserve: new_quote as f64 / 10f64.powi(quote_decimals as i32),
+            };
+            warn!(pool = %amm, earlier = %from_amm, same_creator = migrated.same_creator, drop_percent = format_args!("{:.1}", drop_percent), "Liquidity migrated from an earlier pool");
+            self.sinks.dispatch(&Event::LiquidityMigrated(migrated)).await;
+        }
+    }
+}
+
+/// The earlier pool's reserve of `quote` in UI units, at its launch and now.
+fn quote_reserves(record: &PoolRecord, info: &AmmInfo, state: &PoolState, quote: &Pubkey) -> (f64, f64) {
+    let (launch, now, decimals) = if info.coin_mint == *quote {
+        (record.init_coin_amount, state.coin_reserve, info.coin_decimals)
+    } else {
+        (record.init_pc_amount, state.pc_reserve, info.pc_decimals)
+    };
+    let scale = 10f64.powi(decimals as i32);
+    (launch as f64 / scale, now as f64 / scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserves_follow_the_new_pools_quote_side() {
+        let (token, wsol) = (Pubkey::new_unique(), spl_token::native_mint::id());
+        // the earlier pool had the pair the other way round
+        let info = AmmInfo {
+            coin_mint: wsol,
+            pc_mint: token,
+            coin_decimals: 9,
+            pc_decimals: 6,
+            ..AmmInfo::unpack(&[0; raydium::AMM_INFO_LEN]).unwrap()
+        };
+        let record = PoolRecord {
+            signature: "sig".to_string(),
+            amm: Pubkey::new_unique().to_string(),
+            coin_mint: wsol.to_string(),
+            pc_mint: token.to_string(),
+            quote_mint: token.to_string(),
+            creator: Pubkey::new_unique().to_string(),
+            slot: 1,
+            init_coin_amount: 80_000_000_000,
+            init_pc_amount: 1_000_000_000_000,
+            sol_liquidity_lamports: Some(80_000_000_000),
+            open_time: 0,
+            detected_at: 0,
+        };
+        let state = PoolState {
+            coin_reserve: 2_000_000_000,
+            pc_reserve: 900_000_000_000,
+            swap_fee_numerator: 25,
+            swap_fee_denominator: 10_000,
+        };
+        assert_eq!(quote_reserves(&record, &info, &state, &wsol), (80.0, 2.0));
+        assert_eq!(quote_reserves(&record, &info, &state, &token), (1_000_000.0, 900_000.0));
+    }
+}
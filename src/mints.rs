@@ -0,0 +1,161 @@
+use crate::config::MintWatchConfig;
+use crate::decode::{self, ResolvedInstruction};
+use crate::rpc::KeyRing;
+use crate::sniper::unix_now;
+use crate::tokens::TOKEN_METADATA_PROGRAM_ID;
+use anyhow::{anyhow, Result};
+use solana_client::{
+    pubsub_client::PubsubClient,
+    rpc_client::RpcClient,
+    rpc_config::{RpcTransactionConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter},
+};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+// InitializeMint and InitializeMint2, the same in both token programs
+const INITIALIZE_MINT: u8 = 0;
+const INITIALIZE_MINT2: u8 = 20;
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// A mint created with metadata shortly before its pool.
+#[derive(Debug, Clone)]
+pub struct NewMint {
+    pub mint: Pubkey,
+    // fee payer of the creating transaction
+    pub deployer: Pubkey,
+    pub signature: Signature,
+    // block time, or when the transaction was fetched without one
+    pub created_at: u64,
+    pub seen_at: Instant,
+}
+
+/// Subscribes to the logs of the token metadata program and indexes the
+/// mints initialized in the same transaction, with the wallet that paid for
+/// them. A pool usually follows its base token within minutes, so the
+/// token's age and deployer are known when it appears without a lookup on
+/// the critical path. Each matching transaction is fetched once.
+pub struct MintWatcher {
+    keys: Arc<KeyRing>,
+    rpc_client: Arc<RpcClient>,
+    ttl: Duration,
+    mints: Mutex<HashMap<Pubkey, NewMint>>,
+}
+
+impl MintWatcher {
+    pub fn new(keys: Arc<KeyRing>, rpc_client: Arc<RpcClient>, config: &MintWatchConfig) -> Self {
+        Self { keys, rpc_client, ttl: Duration::from_secs(config.ttl_mins * 60), mints: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn indexed(&self) -> usize {
+        self.mints.lock().unwrap().len()
+    }
+
+    /// A mint created within `ttl_mins`.
+    pub fn get(&self, mint: &Pubkey) -> Option<NewMint> {
+        self.mints.lock().unwrap().get(mint).filter(|minted| minted.seen_at.elapsed() < self.ttl).cloned()
+    }
+
+    /// Keeps the subscription up, reconnecting when it drops.
+    pub async fn run(self: Arc<Self>) {
+        loop {
+            // the pubsub client blocks
+            let watcher = self.clone();
+            match tokio::task::spawn_blocking(move || watcher.subscribe()).await {
+                Ok(Err(e)) => warn!(error = %e, "Mint subscription ended"),
+                Ok(Ok(())) => {}
+                Err(e) => warn!(error = %e, "Mint watcher panicked"),
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    }
+
+    fn subscribe(&self) -> Result<()> {
+        let (_subscription, receiver) = PubsubClient::logs_subscribe(
+            &self.keys.ws_url("logsSubscribe"),
+            RpcTransactionLogsFilter::Mentions(vec![TOKEN_METADATA_PROGRAM_ID.to_string()]),
+            RpcTransactionLogsConfig { commitment: Some(CommitmentConfig::confirmed()) },
+        )?;
+        info!(program = TOKEN_METADATA_PROGRAM_ID, "Subscribed to mints created with metadata");
+        while let Ok(log) = receiver.recv() {
+            // metadata for an existing mint, or a failed creation
+            if log.value.err.is_some() || !log.value.logs.iter().any(|line| line.contains("Instruction: InitializeMint")) {
+                continue;
+            }
+            let Ok(signature) = Signature::from_str(&log.value.signature) else {
+                continue;
+            };
+            match self.fetch(&signature) {
+                Ok(tx) => self.observe(signature, &tx),
+                Err(e) => debug!(signature = %signature, error = %e, "Failed to fetch mint transaction"),
+            }
+        }
+        Err(anyhow!("mint subscription closed"))
+    }
+
+    fn fetch(&self, signature: &Signature) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+        Ok(self.rpc_client.get_transaction_with_config(
+            signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Base64),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )?)
+    }
+
+    /// Indexes the mints a transaction initializes. Expired mints are dropped
+    /// meanwhile.
+    fn observe(&self, signature: Signature, tx: &EncodedConfirmedTransactionWithStatusMeta) {
+        let (Ok(Some(instructions)), Some(transaction)) = (decode::compiled_instructions(tx), tx.transaction.transaction.decode())
+        else {
+            debug!(signature = %signature, "Skipping mint transaction that cannot be decoded locally");
+            return;
+        };
+        let Some(&deployer) = transaction.message.static_account_keys().first() else {
+            return;
+        };
+        let created_at = tx.block_time.map_or_else(unix_now, |time| time as u64);
+        let mut mints = self.mints.lock().unwrap();
+        mints.retain(|_, minted| minted.seen_at.elapsed() < self.ttl);
+        for mint in initialized_mints(&instructions) {
+            debug!(mint = %mint, deployer = %deployer, "Indexed new mint");
+            mints.insert(mint, NewMint { mint, deployer, signature, created_at, seen_at: Instant::now() });
+        }
+    }
+}
+
+/// The mints initialized by either token program among `instructions`.
+fn initialized_mints(instructions: &[ResolvedInstruction]) -> Vec<Pubkey> {
+    let token_2022 = Pubkey::from_str(TOKEN_2022_PROGRAM_ID).unwrap();
+    instructions
+        .iter()
+        .filter(|ix| ix.program_id == spl_token::id() || ix.program_id == token_2022)
+        .filter(|ix| matches!(ix.data.first(), Some(&INITIALIZE_MINT) | Some(&INITIALIZE_MINT2)))
+        .filter_map(|ix| ix.accounts.first().copied())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_mints_initialized_by_either_token_program() {
+        let (legacy, extended) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let ix = |program_id, data: &[u8], mint| ResolvedInstruction { program_id, accounts: vec![mint], data: data.to_vec() };
+        let instructions = [
+            ix(solana_sdk::system_program::id(), &[0], Pubkey::new_unique()),
+            ix(spl_token::id(), &[INITIALIZE_MINT2, 6], legacy),
+            // MintTo
+            ix(spl_token::id(), &[7], legacy),
+            ix(Pubkey::from_str(TOKEN_2022_PROGRAM_ID).unwrap(), &[INITIALIZE_MINT, 9], extended),
+        ];
+        assert_eq!(initialized_mints(&instructions), [legacy, extended]);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::config::PerformanceConfig;
+use crate::events::{Event, PerformanceReportEvent};
+use crate::raydium::{self, NewPool};
+use crate::sinks::Sinks;
+use crate::sniper::unix_now;
+use crate::store::{PerformancePoint, Store};
+use solana_client::rpc_client::RpcClient;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Records a new pool's price and quote liquidity at fixed offsets after
+/// launch, relative to its initial reserves, and optionally posts the
+/// finished report.
+pub struct PerformanceReporter {
+    rpc_client: Arc<RpcClient>,
+    store: Arc<Store>,
+    sinks: Sinks,
+    config: PerformanceConfig,
+}
+
+impl PerformanceReporter {
+    pub fn new(rpc_client: Arc<RpcClient>, store: Arc<Store>, sinks: Sinks, config: PerformanceConfig) -> Self {
+        Self { rpc_client, store, sinks, config }
+    }
+
+    /// `name` is the pool's display name used in the posted report.
+    pub async fn schedule(self: Arc<Self>, pool: NewPool, name: String) {
+        let amm = pool.accounts.amm;
+        let detected_at = unix_now();
+        let mut offsets = self.config.offsets_secs.clone();
+        offsets.sort_unstable();
+        offsets.dedup();
+
+        let quote_is_coin = pool.accounts.quote_mint() == pool.accounts.coin_mint;
+        let (launch_base, launch_quote) = if quote_is_coin {
+            (pool.init_pc_amount, pool.init_coin_amount)
+        } else {
+            (pool.init_coin_amount, pool.init_pc_amount)
+        };
+        let mut points = Vec::new();
+        for offset_secs in offsets {
+            let due = detected_at + offset_secs;
+            tokio::time::sleep(Duration::from_secs(due.saturating_sub(unix_now()))).await;
+            let (info, state) = match raydium::fetch_pool(&self.rpc_client, &amm) {
+                Ok(pool) => pool,
+                Err(e) => {
+                    warn!(pool = %amm, offset_secs, error = %e, "Failed to read pool for performance report");
+                    continue;
+                }
+            };
+            let (base_decimals, quote_decimals, base, quote) = if quote_is_coin {
+                (info.pc_decimals, info.coin_decimals, state.pc_reserve, state.coin_reserve)
+            } else {
+                (info.coin_decimals, info.pc_decimals, state.coin_reserve, state.pc_reserve)
+            };
+            let point = PerformancePoint {
+                amm: amm.to_string(),
+                offset_secs,
+                taken_at: unix_now(),
+                price: price(base, quote, base_decimals, quote_decimals),
+                quote_liquidity: quote as f64 / 10f64.powi(quote_decimals as i32),
+                price_change_percent: change_percent(
+                    price(launch_base, launch_quote, base_decimals, quote_decimals),
+                    price(base, quote, base_decimals, quote_decimals),
+                ),
+                liquidity_change_percent: change_percent(launch_quote as f64, quote as f64),
+            };
+            info!(
+                pool = %amm,
+                offset_secs,
+                price_change_percent = format_args!("{:+.2}", point.price_change_percent),
+                liquidity_change_percent = format_args!("{:+.2}", point.liquidity_change_percent),
+                "Recorded pool performance"
+            );
+            if let Err(e) = self.store.insert_performance_point(&point) {
+                warn!(pool = %amm, error = %e, "Failed to record pool performance");
+            }
+            points.push(point);
+        }
+
+        if self.config.post && !points.is_empty() {
+            let report = PerformanceReportEvent { amm, name, points };
+            self.sinks.dispatch(&Event::PerformanceReport(report)).await;
+        }
+    }
+}
+
+/// Quote per base in UI units, zero for an empty base reserve.
+fn price(base: u64, quote: u64, base_decimals: u64, quote_decimals: u64) -> f64 {
+    if base == 0 {
+        return 0.0;
+    }
+    quote as f64 / base as f64 * 10f64.powi(base_decimals as i32 - quote_decimals as i32)
+}
+
+fn change_percent(from: f64, to: f64) -> f64 {
+    if from == 0.0 {
+        return 0.0;
+    }
+    (to - from) / from * 100.0
+}
@@ -0,0 +1,226 @@
+use crate::amm::{quote_swap, PoolState, SwapDirection};
+use crate::config::SniperConfig;
+use crate::raydium::{self, Initialize2Accounts};
+use crate::sniper::{unix_now, SellStatus, Sniper};
+use crate::store::{Position, Store};
+use anyhow::Result;
+use tracing::{error, info, warn};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{native_token::lamports_to_sol, signature::Signature};
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// a sell unseen this long after sending has outlived its blockhash
+const SELL_EXPIRY_SECS: u64 = 120;
+// wait after the first failed sell, doubling up to the max
+const SELL_BACKOFF_SECS: u64 = 15;
+const MAX_SELL_BACKOFF_SECS: u64 = 600;
+
+/// Take-profit / stop-loss / trailing-stop rules, in percent.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExitRules {
+    pub take_profit: Option<f64>,
+    pub stop_loss: Option<f64>,
+    pub trailing_stop: Option<f64>,
+}
+
+impl ExitRules {
+    pub fn from_config(config: &SniperConfig) -> Self {
+        Self {
+            take_profit: config.take_profit_percent,
+            stop_loss: config.stop_loss_percent,
+            trailing_stop: config.trailing_stop_percent,
+        }
+    }
+
+    /// Returns the name of the triggered rule.
+    fn triggered(&self, cost: u64, value: u64, peak: u64) -> Option<&'static str> {
+        let pnl = pnl_percent(cost, value);
+        if self.take_profit.is_some_and(|tp| pnl >= tp) {
+            return Some("take_profit");
+        }
+        if self.stop_loss.is_some_and(|sl| pnl <= -sl) {
+            return Some("stop_loss");
+        }
+        if let Some(trail) = self.trailing_stop {
+            if peak > cost && (value as f64) <= peak as f64 * (1.0 - trail / 100.0) {
+                return Some("trailing_stop");
+            }
+        }
+        None
+    }
+}
+
+/// Periodically marks every open position (live and paper) to market from
+/// pool reserves and closes positions whose exit rules trigger. Live sells
+/// are sent in the background and recorded before sending, then checked on
+/// the following marks, so a slow or failed sell holds up no other position
+/// and is never sent twice; failed sells back off.
+pub struct PositionTracker {
+    store: Arc<Store>,
+    rpc_client: Arc<RpcClient>,
+    sniper: Arc<Sniper>,
+    exits: ExitRules,
+    interval: Duration,
+    // paper positions are closed at their last value after this long
+    paper_duration: Duration,
+    // positions whose sell is being prepared or sent
+    selling: Mutex<HashSet<i64>>,
+}
+
+impl PositionTracker {
+    pub fn new(
+        store: Arc<Store>,
+        rpc_client: Arc<RpcClient>,
+        sniper: Arc<Sniper>,
+        exits: ExitRules,
+        interval: Duration,
+        paper_duration: Duration,
+    ) -> Self {
+        Self { store, rpc_client, sniper, exits, interval, paper_duration, selling: Mutex::new(HashSet::new()) }
+    }
+
+    pub async fn run(self: Arc<Self>) {
+        loop {
+            tokio::time::sleep(self.interval).await;
+            match self.store.positions(None, true) {
+                Ok(positions) => {
+                    for position in positions {
+                        if let Err(e) = self.mark(&position).await {
+                            warn!(position = position.id, pool = %position.amm, error = %e, "Failed to mark position");
+                        }
+                    }
+                }
+                Err(e) => error!(error = %e, "Failed to load open positions"),
+            }
+        }
+    }
+
+    async fn mark(self: &Arc<Self>, position: &Position) -> Result<()> {
+        if let Some(signature) = &position.sell_signature {
+            return self.check_sell(position, signature);
+        }
+        let pool: Initialize2Accounts = serde_json::from_str(&position.pool_accounts)?;
+        let (coin_reserve, pc_reserve) = raydium::fetch_vault_reserves(&self.rpc_client, &pool)?;
+        let value = position_value(position, coin_reserve, pc_reserve);
+        let peak = position.peak_value_lamports.unwrap_or(0).max(value);
+        let now = unix_now();
+        self.store.mark_position(position.id, value, now)?;
+        info!(
+            position = position.id,
+            wallet = %position.wallet,
+            paper = position.paper,
+            pool = %position.amm,
+            value_sol = lamports_to_sol(value),
+            pnl_percent = format_args!("{:+.2}", pnl_percent(position.cost_lamports, value)),
+            "Marked position"
+        );
+
+        let reason = match self.exits.triggered(position.cost_lamports, value, peak) {
+            Some(reason) => reason,
+            None if position.paper && now.saturating_sub(position.opened_at) >= self.paper_duration.as_secs() => "expired",
+            None => return Ok(()),
+        };
+
+        if position.paper {
+            return self.close(position, value, reason, None);
+        }
+        if let Some(retry_at) = position.sell_retry_at.filter(|retry_at| *retry_at > now) {
+            info!(position = position.id, reason, retry_in_secs = retry_at - now, "Exit rule hit, holding off after a failed sell");
+            return Ok(());
+        }
+        if !self.selling.lock().unwrap().insert(position.id) {
+            return Ok(());
+        }
+        info!(position = position.id, reason, attempt = position.sell_attempts + 1, "Exit rule hit, selling");
+        let tracker = self.clone();
+        let position = position.clone();
+        tokio::spawn(async move {
+            if let Err(e) = tracker.sell(&position, reason).await {
+                warn!(position = position.id, error = %e, "Sell failed");
+                let retry_at = unix_now() + sell_backoff_secs(position.sell_attempts);
+                if let Err(e) = tracker.store.sell_failed(position.id, retry_at) {
+                    error!(position = position.id, error = %e, "Failed to record failed sell");
+                }
+            }
+            tracker.selling.lock().unwrap().remove(&position.id);
+        });
+        Ok(())
+    }
+
+    /// Signs the sell and records it before sending, so a sell that lands
+    /// after an error is still found by `check_sell`.
+    async fn sell(&self, position: &Position, reason: &str) -> Result<()> {
+        let Some(transaction) = self.sniper.prepare_sell(position).await? else {
+            warn!(position = position.id, "Wallet holds none of the position's tokens, closing");
+            return self.close(position, 0, "no_tokens", None);
+        };
+        let signature = transaction.signatures[0].to_string();
+        self.store.sell_sent(position.id, &signature, unix_now(), reason)?;
+        self.sniper.send_sell(position, &transaction).await
+    }
+
+    /// Follows up a sent sell: closes the position once it landed, and clears
+    /// it for another attempt after a backoff when it failed or expired.
+    fn check_sell(&self, position: &Position, signature: &str) -> Result<()> {
+        let reason = position.exit_reason.as_deref().unwrap_or("sold");
+        let failure = match self.sniper.sell_status(&Signature::from_str(signature)?) {
+            SellStatus::Landed(proceeds) => return self.close(position, proceeds, reason, Some(signature)),
+            SellStatus::Failed(err) => err,
+            SellStatus::Pending if unix_now().saturating_sub(position.sell_sent_at.unwrap_or(0)) >= SELL_EXPIRY_SECS => {
+                "expired unconfirmed".to_string()
+            }
+            SellStatus::Pending => return Ok(()),
+        };
+        let backoff = sell_backoff_secs(position.sell_attempts);
+        warn!(position = position.id, signature, error = %failure, retry_in_secs = backoff, "Sell did not land");
+        self.store.sell_failed(position.id, unix_now() + backoff)
+    }
+
+    fn close(&self, position: &Position, proceeds: u64, reason: &str, signature: Option<&str>) -> Result<()> {
+        self.store.close_position(position.id, proceeds, unix_now(), reason, signature)?;
+        info!(
+            position = position.id,
+            paper = position.paper,
+            reason,
+            proceeds_sol = lamports_to_sol(proceeds),
+            pnl_percent = format_args!("{:+.2}", pnl_percent(position.cost_lamports, proceeds)),
+            "Closed position"
+        );
+        Ok(())
+    }
+}
+
+/// Wait before the next sell after `attempts` failed ones.
+fn sell_backoff_secs(attempts: u32) -> u64 {
+    SELL_BACKOFF_SECS.saturating_mul(1 << attempts.min(16)).min(MAX_SELL_BACKOFF_SECS)
+}
+
+/// SOL received for selling all of the position's tokens at the given
+/// reserves.
+pub fn position_value(position: &Position, coin_reserve: u64, pc_reserve: u64) -> u64 {
+    let direction = if position.sol_is_pc { SwapDirection::CoinToPc } else { SwapDirection::PcToCoin };
+    quote_swap(&PoolState::from_reserves(coin_reserve, pc_reserve), position.tokens, direction).amount_out
+}
+
+pub fn pnl_percent(cost: u64, value: u64) -> f64 {
+    if cost == 0 {
+        return 0.0;
+    }
+    (value as f64 - cost as f64) / cost as f64 * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sell_backoff_doubles_up_to_the_max() {
+        assert_eq!(sell_backoff_secs(0), 15);
+        assert_eq!(sell_backoff_secs(2), 60);
+        assert_eq!(sell_backoff_secs(10), MAX_SELL_BACKOFF_SECS);
+        assert_eq!(sell_backoff_secs(u32::MAX), MAX_SELL_BACKOFF_SECS);
+    }
+}
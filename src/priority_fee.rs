@@ -0,0 +1,67 @@
+use crate::config::{PriorityFeeConfig, PriorityFeeSource};
+use anyhow::{anyhow, Result};
+use tracing::{info, warn};
+use serde_json::{json, Value};
+use solana_client::{rpc_client::RpcClient, rpc_request::RpcRequest};
+use solana_sdk::pubkey::Pubkey;
+use std::sync::Arc;
+
+/// Estimates a compute unit price (micro-lamports) from recent priority fees.
+pub struct PriorityFeeEstimator {
+    config: PriorityFeeConfig,
+    rpc_client: Arc<RpcClient>,
+}
+
+impl PriorityFeeEstimator {
+    pub fn new(config: PriorityFeeConfig, rpc_client: Arc<RpcClient>) -> Self {
+        Self { config, rpc_client }
+    }
+
+    /// Returns `fallback` when estimation fails; the result is always clamped
+    /// to [min, max].
+    pub async fn estimate(&self, accounts: &[Pubkey], fallback: u64) -> u64 {
+        let estimate = match self.config.source {
+            PriorityFeeSource::Rpc => self.estimate_from_rpc(accounts),
+            PriorityFeeSource::Helius => self.estimate_from_helius(accounts).await,
+        };
+        let price = match estimate {
+            Ok(price) => price,
+            Err(e) => {
+                warn!(fallback, error = %e, "Failed to estimate priority fee, using fallback");
+                fallback
+            }
+        };
+        let price = price.clamp(self.config.min_micro_lamports, self.config.max_micro_lamports.max(self.config.min_micro_lamports));
+        info!(micro_lamports = price, "Using compute unit price");
+        price
+    }
+
+    fn estimate_from_rpc(&self, accounts: &[Pubkey]) -> Result<u64> {
+        let mut fees = self
+            .rpc_client
+            .get_recent_prioritization_fees(accounts)?
+            .into_iter()
+            .map(|fee| fee.prioritization_fee)
+            .collect::<Vec<_>>();
+        if fees.is_empty() {
+            return Err(anyhow!("no recent prioritization fees"));
+        }
+        fees.sort_unstable();
+        let index = (fees.len() - 1) * self.config.percentile.min(100) as usize / 100;
+        Ok(fees[index])
+    }
+
+    async fn estimate_from_helius(&self, accounts: &[Pubkey]) -> Result<u64> {
+        let response: Value = self.rpc_client.send(
+            RpcRequest::Custom { method: "getPriorityFeeEstimate" },
+            json!([{
+                "accountKeys": accounts.iter().map(|a| a.to_string()).collect::<Vec<_>>(),
+                "options": { "priorityLevel": self.config.helius_priority_level },
+            }]),
+        )?;
+        response["priorityFeeEstimate"]
+            .as_f64()
+            .map(|fee| fee as u64)
+            .ok_or_else(|| anyhow!("unexpected getPriorityFeeEstimate response: {}", response))
+    }
+}
@@ -0,0 +1,121 @@
+use crate::config::{ProgramConfig, ProgramDecoder};
+use crate::layout::Layout;
+use crate::RAYDIUM_V4_PROGRAM_ID;
+use anyhow::{anyhow, Result};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use tracing::{info, warn};
+
+static PROGRAMS: OnceLock<Vec<Program>> = OnceLock::new();
+
+/// A monitored program and the decoder of its instructions.
+#[derive(Debug, Clone)]
+pub struct Program {
+    pub id: Pubkey,
+    pub name: String,
+    pub decoder: ProgramDecoder,
+    // set for the custom decoder only
+    pub layout: Option<Layout>,
+}
+
+impl Program {
+    /// See [`ProgramDecoder::creation_log`].
+    pub fn creation_log(&self) -> &str {
+        match &self.layout {
+            Some(layout) => &layout.log,
+            None => self.decoder.creation_log().unwrap_or_default(),
+        }
+    }
+}
+
+/// Sets the monitored programs for the rest of the process. Without
+/// `[[programs]]` only the mainnet Raydium V4 program is monitored; with
+/// them, only the listed ones.
+pub fn init(config: &[ProgramConfig]) -> Result<()> {
+    let programs = parse(config)?;
+    for program in &programs {
+        info!(program = %program.id, name = %program.name, decoder = program.decoder.name(), "Monitoring program");
+    }
+    if PROGRAMS.set(programs).is_err() {
+        warn!("Monitored programs already set, ignoring [[programs]]");
+    }
+    Ok(())
+}
+
+fn parse(config: &[ProgramConfig]) -> Result<Vec<Program>> {
+    if config.is_empty() {
+        return Ok(mainnet());
+    }
+    let mut seen = HashSet::new();
+    config
+        .iter()
+        .map(|program| {
+            let id = Pubkey::from_str(&program.id).map_err(|_| anyhow!("invalid [[programs]] id {}", program.id))?;
+            if !seen.insert(id) {
+                return Err(anyhow!("[[programs]] lists {} twice", id));
+            }
+            let layout = match (program.decoder, &program.layout) {
+                (ProgramDecoder::Custom, Some(layout)) => {
+                    Some(Layout::new(layout).map_err(|e| anyhow!("[[programs]] {}: {}", id, e))?)
+                }
+                (ProgramDecoder::Custom, None) => return Err(anyhow!("[[programs]] {} has the custom decoder but no layout", id)),
+                (_, Some(_)) => return Err(anyhow!("[[programs]] {} has a layout but the {} decoder", id, program.decoder.name())),
+                (_, None) => None,
+            };
+            let name = program.name.clone().unwrap_or_else(|| program.decoder.name().to_string());
+            Ok(Program { id, name, decoder: program.decoder, layout })
+        })
+        .collect()
+}
+
+fn mainnet() -> Vec<Program> {
+    vec![Program {
+        id: Pubkey::from_str(RAYDIUM_V4_PROGRAM_ID).unwrap(),
+        name: ProgramDecoder::RaydiumV4.name().to_string(),
+        decoder: ProgramDecoder::RaydiumV4,
+        layout: None,
+    }]
+}
+
+/// Every monitored program.
+pub fn all() -> &'static [Program] {
+    PROGRAMS.get_or_init(mainnet)
+}
+
+pub fn get(id: &Pubkey) -> Option<&'static Program> {
+    all().iter().find(|program| program.id == *id)
+}
+
+/// Whether `id` is a monitored program decoded with `decoder`.
+pub fn decodes_with(id: &Pubkey, decoder: ProgramDecoder) -> bool {
+    get(id).is_some_and(|program| program.decoder == decoder)
+}
+
+/// The monitored programs decoded with `decoder`.
+pub fn ids(decoder: ProgramDecoder) -> Vec<Pubkey> {
+    all().iter().filter(|program| program.decoder == decoder).map(|program| program.id).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program(id: &str, name: Option<&str>) -> ProgramConfig {
+        ProgramConfig { id: id.to_string(), decoder: ProgramDecoder::RaydiumV4, name: name.map(str::to_string), layout: None }
+    }
+
+    #[test]
+    fn configured_programs_replace_mainnet() {
+        assert_eq!(parse(&[]).unwrap()[0].id.to_string(), RAYDIUM_V4_PROGRAM_ID);
+        let devnet = "HWy1jotHpo6UqeQxx49dpYYdQB8wj9Qk9MdxwjLvDHB8";
+        let programs = parse(&[program(devnet, Some("raydium_devnet")), program(RAYDIUM_V4_PROGRAM_ID, None)]).unwrap();
+        let names = programs.iter().map(|program| (program.id.to_string(), program.name.as_str())).collect::<Vec<_>>();
+        assert_eq!(names, [(devnet.to_string(), "raydium_devnet"), (RAYDIUM_V4_PROGRAM_ID.to_string(), "raydium_v4")]);
+        assert!(parse(&[program(devnet, None), program(devnet, None)]).is_err());
+        assert!(parse(&[program("not-a-program", None)]).is_err());
+        let custom = ProgramConfig { decoder: ProgramDecoder::Custom, ..program(devnet, None) };
+        assert!(parse(&[custom]).is_err());
+    }
+}
@@ -0,0 +1,422 @@
+use crate::amm::PoolState;
+use crate::config::ProgramDecoder;
+use crate::programs;
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::BorshDeserialize;
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use std::str::FromStr;
+
+pub const INITIALIZE2_DISCRIMINATOR: u8 = 1;
+pub const SWAP_BASE_IN_DISCRIMINATOR: u8 = 9;
+// instructions only the program's admin signs
+pub const SET_PARAMS_DISCRIMINATOR: u8 = 6;
+pub const WITHDRAW_PNL_DISCRIMINATOR: u8 = 7;
+pub const ADMIN_CANCEL_ORDERS_DISCRIMINATOR: u8 = 13;
+
+/// Well-known quote tokens and their names.
+pub const KNOWN_QUOTES: &[(&str, &str)] = &[
+    ("So11111111111111111111111111111111111111112", "WSOL"),
+    ("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v", "USDC"),
+    ("Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB", "USDT"),
+];
+
+/// Raydium V4 WSOL/USDC pool, used as the SOL price reference.
+pub const SOL_USDC_AMM: &str = "58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2";
+
+/// Account layout of the initialize2 instruction (positions in `ix.accounts`).
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Initialize2Accounts {
+    pub amm: Pubkey,
+    pub amm_authority: Pubkey,
+    pub amm_open_orders: Pubkey,
+    pub coin_mint: Pubkey,
+    pub pc_mint: Pubkey,
+    pub coin_vault: Pubkey,
+    pub pc_vault: Pubkey,
+    pub amm_target_orders: Pubkey,
+    pub serum_program: Pubkey,
+    pub serum_market: Pubkey,
+    // wallet that created the pool; missing from positions stored before it was added
+    #[serde(default)]
+    pub creator: Pubkey,
+    // the AMM program, the mainnet one for positions stored before it was
+    // added
+    #[serde(default = "mainnet_program")]
+    pub program: Pubkey,
+}
+
+fn mainnet_program() -> Pubkey {
+    Pubkey::from_str(crate::RAYDIUM_V4_PROGRAM_ID).unwrap()
+}
+
+impl Initialize2Accounts {
+    /// From the instruction's accounts in order, for the mainnet program.
+    pub fn from_keys(accounts: &[Pubkey]) -> Result<Self> {
+        let key = |position: usize| -> Result<Pubkey> {
+            accounts
+                .get(position)
+                .copied()
+                .ok_or_else(|| anyhow!("initialize2 instruction has only {} accounts", accounts.len()))
+        };
+
+        Ok(Self {
+            amm: key(4)?,
+            amm_authority: key(5)?,
+            amm_open_orders: key(6)?,
+            coin_mint: key(8)?,
+            pc_mint: key(9)?,
+            coin_vault: key(10)?,
+            pc_vault: key(11)?,
+            amm_target_orders: key(12)?,
+            serum_program: key(15)?,
+            serum_market: key(16)?,
+            creator: key(17)?,
+            program: mainnet_program(),
+        })
+    }
+
+    /// See [`quote_mint`].
+    pub fn quote_mint(&self) -> Pubkey {
+        quote_mint(&self.coin_mint, &self.pc_mint)
+    }
+}
+
+/// The pc mint, unless only the coin mint is a known quote token.
+pub fn quote_mint(coin_mint: &Pubkey, pc_mint: &Pubkey) -> Pubkey {
+    let known = |mint: &Pubkey| KNOWN_QUOTES.iter().any(|(quote, _)| *quote == mint.to_string());
+    if known(coin_mint) && !known(pc_mint) {
+        *coin_mint
+    } else {
+        *pc_mint
+    }
+}
+
+/// "sol", "usdc" or "usdt" for the well-known quotes, "other" otherwise;
+/// used to route and tag events by quote token.
+pub fn quote_label(mint: &Pubkey) -> &'static str {
+    match KNOWN_QUOTES.iter().find(|(quote, _)| *quote == mint.to_string()).map(|(_, name)| *name) {
+        Some("WSOL") => "sol",
+        Some("USDC") => "usdc",
+        Some("USDT") => "usdt",
+        _ => "other",
+    }
+}
+
+/// A newly created pool.
+#[derive(Clone)]
+pub struct NewPool {
+    pub accounts: Initialize2Accounts,
+    pub open_time: u64,
+    pub init_coin_amount: u64,
+    pub init_pc_amount: u64,
+}
+
+/// Current (coin, pc) reserves of a pool, read from its vault balances.
+pub fn fetch_vault_reserves(rpc_client: &RpcClient, pool: &Initialize2Accounts) -> Result<(u64, u64)> {
+    fetch_reserves(rpc_client, &pool.coin_vault, &pool.pc_vault)
+}
+
+fn fetch_reserves(rpc_client: &RpcClient, coin_vault: &Pubkey, pc_vault: &Pubkey) -> Result<(u64, u64)> {
+    let coin = rpc_client.get_token_account_balance(coin_vault)?.amount.parse::<u64>()?;
+    let pc = rpc_client.get_token_account_balance(pc_vault)?.amount.parse::<u64>()?;
+    Ok((coin, pc))
+}
+
+/// Size of the AMM v4 pool state account.
+pub const AMM_INFO_LEN: usize = 752;
+
+/// Fields of the AMM v4 pool state account (AmmInfo) used by the monitor.
+#[derive(Debug, Clone)]
+pub struct AmmInfo {
+    pub status: u64,
+    pub coin_decimals: u64,
+    pub pc_decimals: u64,
+    pub swap_fee_numerator: u64,
+    pub swap_fee_denominator: u64,
+    // protocol fees still held in the vaults
+    pub need_take_pnl_coin: u64,
+    pub need_take_pnl_pc: u64,
+    pub pool_open_time: u64,
+    pub coin_vault: Pubkey,
+    pub pc_vault: Pubkey,
+    pub coin_mint: Pubkey,
+    pub pc_mint: Pubkey,
+    pub lp_mint: Pubkey,
+    pub open_orders: Pubkey,
+    pub market: Pubkey,
+    pub market_program: Pubkey,
+    pub target_orders: Pubkey,
+    // the admin allowed to send setParams and the like
+    pub amm_owner: Pubkey,
+    pub lp_amount: u64,
+}
+
+impl AmmInfo {
+    pub fn unpack(data: &[u8]) -> Result<Self> {
+        if data.len() < AMM_INFO_LEN {
+            return Err(anyhow!("AmmInfo account too short: {} bytes", data.len()));
+        }
+        let u64_at = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+        let pubkey_at = |offset: usize| Pubkey::try_from(&data[offset..offset + 32]).unwrap();
+
+        Ok(Self {
+            status: u64_at(0),
+            coin_decimals: u64_at(32),
+            pc_decimals: u64_at(40),
+            // Fees start at 128: min_separate, trade, pnl, swap (numerator, denominator)
+            swap_fee_numerator: u64_at(176),
+            swap_fee_denominator: u64_at(184),
+            // StateData starts at 192
+            need_take_pnl_coin: u64_at(192),
+            need_take_pnl_pc: u64_at(200),
+            pool_open_time: u64_at(224),
+            coin_vault: pubkey_at(336),
+            pc_vault: pubkey_at(368),
+            coin_mint: pubkey_at(400),
+            pc_mint: pubkey_at(432),
+            lp_mint: pubkey_at(464),
+            open_orders: pubkey_at(496),
+            market: pubkey_at(528),
+            market_program: pubkey_at(560),
+            target_orders: pubkey_at(592),
+            amm_owner: pubkey_at(688),
+            lp_amount: u64_at(720),
+        })
+    }
+
+    pub fn status_name(&self) -> &'static str {
+        status_name(self.status)
+    }
+
+    /// The swap fee as a percentage of the input.
+    pub fn swap_fee_percent(&self) -> f64 {
+        fee_percent(self.swap_fee_numerator, self.swap_fee_denominator)
+    }
+
+    /// Tradable reserves from the vault balances, minus protocol fees not yet
+    /// taken.
+    pub fn pool_state(&self, coin_vault_balance: u64, pc_vault_balance: u64) -> PoolState {
+        PoolState {
+            coin_reserve: coin_vault_balance.saturating_sub(self.need_take_pnl_coin),
+            pc_reserve: pc_vault_balance.saturating_sub(self.need_take_pnl_pc),
+            swap_fee_numerator: self.swap_fee_numerator,
+            swap_fee_denominator: self.swap_fee_denominator,
+        }
+    }
+}
+
+/// The name of a pool `status`.
+pub fn status_name(status: u64) -> &'static str {
+    match status {
+        0 => "uninitialized",
+        1 => "initialized",
+        2 => "disabled",
+        3 => "withdraw_only",
+        4 => "liquidity_only",
+        5 => "orderbook_only",
+        6 => "swap_only",
+        7 => "waiting_trade",
+        _ => "unknown",
+    }
+}
+
+pub fn fee_percent(numerator: u64, denominator: u64) -> f64 {
+    if denominator == 0 {
+        return 0.0;
+    }
+    numerator as f64 / denominator as f64 * 100.0
+}
+
+/// The pool Raydium's UI and SDK create for an OpenBook market: the address
+/// of the AMM `program` derived from the market. Pools created with other
+/// keys don't match it.
+pub fn amm_address(program: &Pubkey, market: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program.as_ref(), market.as_ref(), b"amm_associated_seed"], program).0
+}
+
+/// Fetches and decodes a pool state account along with its current reserves.
+pub fn fetch_pool(rpc_client: &RpcClient, amm: &Pubkey) -> Result<(AmmInfo, PoolState)> {
+    let account = rpc_client.get_account(amm)?;
+    if !programs::decodes_with(&account.owner, ProgramDecoder::RaydiumV4) {
+        return Err(anyhow!("{} is not a Raydium V4 pool (owner {})", amm, account.owner));
+    }
+    let info = AmmInfo::unpack(&account.data)?;
+    let (coin, pc) = fetch_reserves(rpc_client, &info.coin_vault, &info.pc_vault)?;
+    let state = info.pool_state(coin, pc);
+    Ok((info, state))
+}
+
+/// Spot price of SOL in USDC from the reserves of [`SOL_USDC_AMM`].
+pub fn fetch_sol_usd_price(rpc_client: &RpcClient) -> Result<f64> {
+    let (info, state) = fetch_pool(rpc_client, &Pubkey::from_str(SOL_USDC_AMM)?)?;
+    let (sol, sol_decimals, usdc, usdc_decimals) = if info.coin_mint == spl_token::native_mint::id() {
+        (state.coin_reserve, info.coin_decimals, state.pc_reserve, info.pc_decimals)
+    } else {
+        (state.pc_reserve, info.pc_decimals, state.coin_reserve, info.coin_decimals)
+    };
+    if sol == 0 {
+        return Err(anyhow!("SOL reference pool is empty"));
+    }
+    Ok(usdc as f64 / sol as f64 * 10f64.powi(sol_decimals as i32 - usdc_decimals as i32))
+}
+
+const RAY_LOG_PREFIX: &str = "Program log: ray_log: ";
+const LOG_TYPE_SWAP_BASE_IN: u8 = 3;
+const LOG_TYPE_SWAP_BASE_OUT: u8 = 4;
+
+// layout shared by SwapBaseInLog and SwapBaseOutLog after the log type
+#[derive(BorshDeserialize)]
+struct SwapLogData {
+    // amount_in / max_in
+    first: u64,
+    // minimum_out / amount_out
+    second: u64,
+    direction: u64,
+    _user_source: u64,
+    pool_coin: u64,
+    pool_pc: u64,
+    // out_amount / deduct_in
+    last: u64,
+}
+
+/// A swap decoded from the program's `ray_log` line. The log doesn't name the
+/// pool; callers know it from the subscription.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapLog {
+    pub coin_to_pc: bool,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    // reserves before the swap
+    pub pool_coin: u64,
+    pub pool_pc: u64,
+}
+
+impl SwapLog {
+    /// Parses a program log line, returning None for anything but a swap.
+    pub fn parse(line: &str) -> Option<Self> {
+        let data = STANDARD.decode(line.strip_prefix(RAY_LOG_PREFIX)?).ok()?;
+        let (&log_type, rest) = data.split_first()?;
+        let log = SwapLogData::deserialize(&mut &rest[..]).ok()?;
+        let (amount_in, amount_out) = match log_type {
+            LOG_TYPE_SWAP_BASE_IN => (log.first, log.last),
+            LOG_TYPE_SWAP_BASE_OUT => (log.last, log.second),
+            _ => return None,
+        };
+        Some(Self { coin_to_pc: log.direction == 1, amount_in, amount_out, pool_coin: log.pool_coin, pool_pc: log.pool_pc })
+    }
+}
+
+/// Relative difference between declared and actual reserves tolerated by
+/// [`validate_new_pool`]; early swaps move reserves a little.
+const RESERVE_TOLERANCE: f64 = 0.01;
+
+/// Fetches the state account of a pool that was just created and returns
+/// the discrepancies with what its initialize2 instruction declared.
+pub fn validate_new_pool(rpc_client: &RpcClient, pool: &NewPool) -> Result<Vec<String>> {
+    let (info, state) = fetch_pool(rpc_client, &pool.accounts.amm)?;
+    let mut issues = Vec::new();
+    if info.status == 0 {
+        issues.push("pool is not initialized".to_string());
+    }
+    for (field, actual, declared) in [
+        ("coin mint", info.coin_mint, pool.accounts.coin_mint),
+        ("pc mint", info.pc_mint, pool.accounts.pc_mint),
+        ("coin vault", info.coin_vault, pool.accounts.coin_vault),
+        ("pc vault", info.pc_vault, pool.accounts.pc_vault),
+    ] {
+        if actual != declared {
+            issues.push(format!("{} is {}, instruction declared {}", field, actual, declared));
+        }
+    }
+    for (side, actual, declared) in [
+        ("coin", state.coin_reserve, pool.init_coin_amount),
+        ("pc", state.pc_reserve, pool.init_pc_amount),
+    ] {
+        let difference = (actual as f64 - declared as f64).abs() / (declared as f64).max(1.0);
+        if difference > RESERVE_TOLERANCE {
+            issues.push(format!("{} reserve is {}, instruction declared {}", side, actual, declared));
+        }
+    }
+    Ok(issues)
+}
+
+/// OpenBook market accounts required by the swap instruction.
+#[derive(Debug, Clone)]
+pub struct MarketAccounts {
+    pub bids: Pubkey,
+    pub asks: Pubkey,
+    pub event_queue: Pubkey,
+    pub coin_vault: Pubkey,
+    pub pc_vault: Pubkey,
+    pub vault_signer: Pubkey,
+}
+
+pub fn fetch_market_accounts(rpc_client: &RpcClient, market: &Pubkey, serum_program: &Pubkey) -> Result<MarketAccounts> {
+    market_accounts(market, serum_program, &rpc_client.get_account_data(market)?)
+}
+
+/// The swap accounts of a market from its MarketState data.
+pub fn market_accounts(market: &Pubkey, serum_program: &Pubkey, data: &[u8]) -> Result<MarketAccounts> {
+    // MarketState fields after the 5-byte padding and account_flags (8)
+    if data.len() < 349 {
+        return Err(anyhow!("Market account {} too short: {} bytes", market, data.len()));
+    }
+    let pubkey_at = |offset: usize| Pubkey::try_from(&data[offset..offset + 32]).unwrap();
+    let nonce = u64::from_le_bytes(data[45..53].try_into().unwrap());
+    let vault_signer = Pubkey::create_program_address(&[market.as_ref(), &nonce.to_le_bytes()], serum_program)?;
+
+    Ok(MarketAccounts {
+        coin_vault: pubkey_at(117),
+        pc_vault: pubkey_at(165),
+        event_queue: pubkey_at(253),
+        bids: pubkey_at(285),
+        asks: pubkey_at(317),
+        vault_signer,
+    })
+}
+
+pub fn swap_base_in(
+    pool: &Initialize2Accounts,
+    market: &MarketAccounts,
+    user_source: &Pubkey,
+    user_destination: &Pubkey,
+    owner: &Pubkey,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Instruction {
+    let mut data = Vec::with_capacity(17);
+    data.push(SWAP_BASE_IN_DISCRIMINATOR);
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&minimum_amount_out.to_le_bytes());
+
+    Instruction {
+        program_id: pool.program,
+        accounts: vec![
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(pool.amm, false),
+            AccountMeta::new_readonly(pool.amm_authority, false),
+            AccountMeta::new(pool.amm_open_orders, false),
+            AccountMeta::new(pool.amm_target_orders, false),
+            AccountMeta::new(pool.coin_vault, false),
+            AccountMeta::new(pool.pc_vault, false),
+            AccountMeta::new_readonly(pool.serum_program, false),
+            AccountMeta::new(pool.serum_market, false),
+            AccountMeta::new(market.bids, false),
+            AccountMeta::new(market.asks, false),
+            AccountMeta::new(market.event_queue, false),
+            AccountMeta::new(market.coin_vault, false),
+            AccountMeta::new(market.pc_vault, false),
+            AccountMeta::new_readonly(market.vault_signer, false),
+            AccountMeta::new(*user_source, false),
+            AccountMeta::new(*user_destination, false),
+            AccountMeta::new_readonly(*owner, true),
+        ],
+        data,
+    }
+}
@@ -0,0 +1,219 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use solana_client::{
+    client_error::Result as ClientResult,
+    rpc_client::{RpcClient, RpcClientConfig},
+    rpc_request::{RpcError, RpcRequest},
+    rpc_response::{Response, RpcLogsResponse},
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+use solana_sdk::commitment_config::CommitmentConfig;
+use tracing::{info, warn};
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+const WS_FILE: &str = "ws.jsonl";
+const RPC_FILE: &str = "rpc.jsonl";
+
+static RECORDER: OnceLock<Recorder> = OnceLock::new();
+
+/// One log notification from the WebSocket subscription.
+#[derive(Serialize, Deserialize)]
+struct WsEntry {
+    // milliseconds since the recording started
+    at_ms: u64,
+    log: Response<RpcLogsResponse>,
+}
+
+/// One RPC request and what the node answered.
+#[derive(Serialize, Deserialize)]
+struct RpcEntry {
+    at_ms: u64,
+    method: String,
+    params: Value,
+    #[serde(flatten)]
+    outcome: Outcome,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Outcome {
+    Result(Value),
+    // the client error as displayed, replayed as a request error
+    Error(String),
+}
+
+/// Appends every WebSocket log notification and RPC response to JSON lines
+/// files in one directory.
+struct Recorder {
+    started: Instant,
+    ws: Mutex<File>,
+    rpc: Mutex<File>,
+}
+
+/// Starts recording the session to `dir` (`ws.jsonl` and `rpc.jsonl`),
+/// replacing an earlier recording there.
+pub fn start(dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let create = |name: &str| {
+        let path = dir.join(name);
+        File::create(&path).with_context(|| format!("Failed to create {}", path.display()))
+    };
+    let recorder = Recorder { started: Instant::now(), ws: Mutex::new(create(WS_FILE)?), rpc: Mutex::new(create(RPC_FILE)?) };
+    let _ = RECORDER.set(recorder);
+    info!(dir = %dir.display(), "Recording WebSocket logs and RPC responses");
+    Ok(())
+}
+
+/// Whether `start` was called, so callers can skip preparing an entry.
+pub fn recording() -> bool {
+    RECORDER.get().is_some()
+}
+
+pub fn ws_log(log: &Response<RpcLogsResponse>) {
+    if let Some(recorder) = RECORDER.get() {
+        let entry = WsEntry { at_ms: recorder.at_ms(), log: log.clone() };
+        append(&recorder.ws, &entry);
+    }
+}
+
+pub fn rpc_response(method: &str, params: &Value, result: &ClientResult<Value>) {
+    if let Some(recorder) = RECORDER.get() {
+        let outcome = match result {
+            Ok(value) => Outcome::Result(value.clone()),
+            Err(e) => Outcome::Error(e.to_string()),
+        };
+        let entry = RpcEntry { at_ms: recorder.at_ms(), method: method.to_string(), params: params.clone(), outcome };
+        append(&recorder.rpc, &entry);
+    }
+}
+
+impl Recorder {
+    fn at_ms(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+}
+
+// one line per write, so an interrupted recording stays readable
+fn append(file: &Mutex<File>, entry: &impl Serialize) {
+    let result = serde_json::to_string(entry)
+        .map_err(std::io::Error::from)
+        .and_then(|line| file.lock().unwrap().write_all(format!("{}\n", line).as_bytes()));
+    if let Err(e) = result {
+        warn!(error = %e, "Failed to write to the recording");
+    }
+}
+
+/// A recorded session, fed back through the pipeline: the log notifications
+/// as a source and the RPC responses as the RPC client's answers.
+pub struct Replay {
+    dir: PathBuf,
+    logs: Vec<WsEntry>,
+    // (method, params) -> responses in recorded order
+    responses: Mutex<HashMap<(String, String), VecDeque<Outcome>>>,
+    // pace relative to the recording, 10.0 is ten times as fast; None
+    // replays as fast as possible
+    pub speed: Option<f64>,
+    // furthest the paced source fell behind the recording
+    max_lag_ms: AtomicU64,
+}
+
+impl Replay {
+    pub fn load(dir: &Path, speed: Option<f64>) -> Result<Arc<Self>> {
+        let logs = read_lines::<WsEntry>(&dir.join(WS_FILE))?;
+        let mut responses: HashMap<_, VecDeque<_>> = HashMap::new();
+        for entry in read_lines::<RpcEntry>(&dir.join(RPC_FILE))? {
+            responses.entry((entry.method, entry.params.to_string())).or_default().push_back(entry.outcome);
+        }
+        info!(dir = %dir.display(), logs = logs.len(), rpc_requests = responses.len(), "Loaded recording");
+        Ok(Arc::new(Self {
+            dir: dir.to_path_buf(),
+            logs,
+            responses: Mutex::new(responses),
+            speed,
+            max_lag_ms: AtomicU64::new(0),
+        }))
+    }
+
+    /// The recorded log notifications with their offsets from the start.
+    pub fn logs(&self) -> impl Iterator<Item = (Duration, &RpcLogsResponse)> {
+        self.logs.iter().map(|entry| (Duration::from_millis(entry.at_ms), &entry.log.value))
+    }
+
+    /// Notes that a notification reached the pipeline `lag` after its paced
+    /// time, because the pipeline wasn't taking signatures fast enough.
+    pub fn fell_behind(&self, lag: Duration) {
+        self.max_lag_ms.fetch_max(lag.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// The furthest a paced replay fell behind the recording.
+    pub fn max_lag(&self) -> Duration {
+        Duration::from_millis(self.max_lag_ms.load(Ordering::Relaxed))
+    }
+
+    /// An RPC client answering from the recording. Identical requests get
+    /// the recorded responses in order, the last one repeating once they run
+    /// out; requests that were never recorded fail.
+    pub fn rpc_client(self: &Arc<Self>) -> Arc<RpcClient> {
+        Arc::new(RpcClient::new_sender(
+            ReplaySender { replay: self.clone(), stats: RwLock::default() },
+            RpcClientConfig::with_commitment(CommitmentConfig::confirmed()),
+        ))
+    }
+
+    fn response(&self, method: &str, params: &Value) -> Option<Outcome> {
+        let mut responses = self.responses.lock().unwrap();
+        let queue = responses.get_mut(&(method.to_string(), params.to_string()))?;
+        match queue.len() {
+            1 => queue.front().cloned(),
+            _ => queue.pop_front(),
+        }
+    }
+}
+
+fn read_lines<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<Vec<T>> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut entries = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        match serde_json::from_str(&line) {
+            Ok(entry) => entries.push(entry),
+            // the last line of an interrupted recording may be cut off
+            Err(e) => warn!(file = %path.display(), line = index + 1, error = %e, "Skipping unreadable recording entry"),
+        }
+    }
+    Ok(entries)
+}
+
+struct ReplaySender {
+    replay: Arc<Replay>,
+    stats: RwLock<RpcTransportStats>,
+}
+
+#[async_trait]
+impl RpcSender for ReplaySender {
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        let method = request.to_string();
+        self.stats.write().unwrap().request_count += 1;
+        match self.replay.response(&method, &params) {
+            Some(Outcome::Result(value)) => Ok(value),
+            Some(Outcome::Error(message)) => Err(RpcError::RpcRequestError(message).into()),
+            None => Err(RpcError::RpcRequestError(format!("{} {} is not in the recording", method, params)).into()),
+        }
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.stats.read().unwrap().clone()
+    }
+
+    fn url(&self) -> String {
+        format!("replay:{}", self.replay.dir.display())
+    }
+}
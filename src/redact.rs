@@ -0,0 +1,203 @@
+use crate::config::Config;
+use std::borrow::Cow;
+use std::io::{self, Write};
+use std::sync::Arc;
+use tracing_subscriber::fmt::MakeWriter;
+use url::Url;
+
+const MASK: &str = "***";
+// shorter values would mask ordinary words
+const MIN_SECRET_LEN: usize = 6;
+// endings of config keys whose values are credentials (also their plural,
+// e.g. api_keys); not a bare "key", which names e.g. the leader lock
+const SECRET_KEYS: [&str; 5] = ["_key", "token", "password", "secret", "auth_header"];
+// credentials under a key too generic for SECRET_KEYS, by dotted path
+const SECRET_PATHS: [&str; 1] = ["api_keys.keys"];
+// URL query parameters carrying credentials, e.g. Helius' ?api-key=
+const SECRET_PARAMS: [&str; 5] = ["key", "token", "secret", "auth", "sig"];
+// path segments at least this long that mix letters and digits are tokens,
+// e.g. QuickNode's https://x.quiknode.pro/<token>/ or Triton's /<token>/whirligig
+const MIN_PATH_TOKEN_LEN: usize = 16;
+const SECRET_ENV: [&str; 2] = ["AWS_SECRET_ACCESS_KEY", "AWS_SESSION_TOKEN"];
+
+/// Masks known secrets (API keys in RPC URLs, tokens, passwords) in text
+/// before it is written to any log output.
+#[derive(Clone, Default)]
+pub struct Redactor {
+    // longest first, so a secret containing another is masked whole
+    secrets: Arc<Vec<String>>,
+}
+
+impl Redactor {
+    pub fn new(secrets: impl IntoIterator<Item = String>) -> Self {
+        let mut secrets = secrets.into_iter().filter(|secret| secret.len() >= MIN_SECRET_LEN).collect::<Vec<_>>();
+        secrets.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        secrets.dedup();
+        Self { secrets: Arc::new(secrets) }
+    }
+
+    /// The credentials found in the config file, `logging.redact` and the
+    /// AWS environment variables.
+    pub fn from_config(config: &Config) -> Self {
+        let env = SECRET_ENV.iter().filter_map(|name| std::env::var(name).ok());
+        Self::new(config.secrets.iter().chain(&config.logging.redact).cloned().chain(env))
+    }
+
+    pub fn redact<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut text = Cow::Borrowed(text);
+        for secret in self.secrets.iter() {
+            if text.contains(secret.as_str()) {
+                text = Cow::Owned(text.replace(secret.as_str(), MASK));
+            }
+        }
+        text
+    }
+
+    /// Wraps a log writer so everything written through it is redacted.
+    pub fn writer<M>(&self, inner: M) -> RedactingMakeWriter<M> {
+        RedactingMakeWriter { redactor: self.clone(), inner }
+    }
+}
+
+/// Credentials in a parsed config file: values of keys named like api_key,
+/// token or password, and the password and credential query parameters of
+/// every URL, and token-like segments of its path.
+pub fn secrets(value: &toml::Value) -> Vec<String> {
+    let mut secrets = Vec::new();
+    collect("", value, &mut secrets);
+    secrets
+}
+
+/// `path` is the dotted path of `value`'s key, empty at the top.
+fn collect(path: &str, value: &toml::Value, secrets: &mut Vec<String>) {
+    match value {
+        toml::Value::String(s) => {
+            if is_secret_key(path) {
+                secrets.push(s.clone());
+            } else if s.contains("://") {
+                secrets.extend(url_secrets(s));
+            }
+        }
+        toml::Value::Array(values) => values.iter().for_each(|value| collect(path, value, secrets)),
+        toml::Value::Table(table) => table.iter().for_each(|(key, value)| {
+            let path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+            collect(&path, value, secrets)
+        }),
+        _ => {}
+    }
+}
+
+fn is_secret_key(path: &str) -> bool {
+    if SECRET_PATHS.contains(&path) {
+        return true;
+    }
+    let key = path.rsplit('.').next().unwrap_or_default().to_lowercase();
+    let key = key.strip_suffix('s').unwrap_or(&key);
+    SECRET_KEYS.iter().any(|suffix| key.ends_with(suffix))
+}
+
+fn url_secrets(url: &str) -> Vec<String> {
+    let Ok(url) = Url::parse(url) else {
+        return Vec::new();
+    };
+    let mut secrets = url.password().map(|password| vec![password.to_string()]).unwrap_or_default();
+    for (name, value) in url.query_pairs() {
+        let name = name.to_lowercase();
+        if SECRET_PARAMS.iter().any(|param| name.contains(param)) {
+            secrets.push(value.into_owned());
+        }
+    }
+    let segments = url.path_segments().into_iter().flatten();
+    secrets.extend(segments.filter(|segment| is_path_token(segment)).map(str::to_string));
+    secrets
+}
+
+fn is_path_token(segment: &str) -> bool {
+    segment.len() >= MIN_PATH_TOKEN_LEN
+        && segment.bytes().all(|byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_')
+        && segment.bytes().any(|byte| byte.is_ascii_digit())
+        && segment.bytes().any(|byte| byte.is_ascii_alphabetic())
+}
+
+pub struct RedactingMakeWriter<M> {
+    redactor: Redactor,
+    inner: M,
+}
+
+impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for RedactingMakeWriter<M> {
+    type Writer = RedactingWriter<'a, M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter { redactor: &self.redactor, inner: self.inner.make_writer(), buf: Vec::new() }
+    }
+
+    fn make_writer_for(&'a self, meta: &tracing::Metadata<'_>) -> Self::Writer {
+        RedactingWriter { redactor: &self.redactor, inner: self.inner.make_writer_for(meta), buf: Vec::new() }
+    }
+}
+
+/// Buffers one formatted event and writes it redacted on drop, as a secret
+/// may span several writes.
+pub struct RedactingWriter<'a, W: Write> {
+    redactor: &'a Redactor,
+    inner: W,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> Write for RedactingWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<W: Write> Drop for RedactingWriter<'_, W> {
+    fn drop(&mut self) {
+        let text = String::from_utf8_lossy(&self.buf);
+        let _ = self.inner.write_all(self.redactor.redact(&text).as_bytes());
+        let _ = self.inner.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_credentials_but_not_the_leader_key() {
+        let config = toml::from_str::<toml::Value>(
+            r#"
+            rpc_url = "https://rpc.example.com/?api-key=url-secret"
+            ws_url = "wss://example.rpcpool.com/0a1b2c3d-4e5f-6789-abcd-ef0123456789/whirligig"
+            [sniper]
+            broadcast_rpc_urls = ["https://example.solana-mainnet.quiknode.pro/6b1f0c2e9d8a7b6c5d4e3f2a1b0c9d8e/"]
+            [notifications.aws]
+            queue_urls = ["https://sqs.us-east-1.amazonaws.com/123456789012/raydium-pools"]
+            [api_keys]
+            keys = ["rotated-1", "rotated-2"]
+            [leader]
+            key = "raydium_v4_monitor:leader"
+            [enrichment.birdeye]
+            api_key = "birdeye-secret"
+            "#,
+        )
+        .unwrap();
+        let mut secrets = secrets(&config);
+        secrets.sort();
+        assert_eq!(
+            secrets,
+            [
+                "0a1b2c3d-4e5f-6789-abcd-ef0123456789",
+                "6b1f0c2e9d8a7b6c5d4e3f2a1b0c9d8e",
+                "birdeye-secret",
+                "rotated-1",
+                "rotated-2",
+                "url-secret"
+            ]
+        );
+    }
+}
@@ -0,0 +1,408 @@
+use crate::faults::{self, Fault};
+use crate::config::{ApiKeysConfig, Config, QuotaPeriod, RpcLimitsConfig};
+use crate::metrics::Metrics;
+use crate::recording;
+use crate::sniper::unix_now;
+use crate::store::Store;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use reqwest::header::{CONTENT_TYPE, RETRY_AFTER};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::Value;
+use solana_client::{
+    client_error::Result as ClientResult,
+    rpc_client::{RpcClient, RpcClientConfig},
+    rpc_custom_error::{
+        NodeUnhealthyErrorData, JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY, JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE,
+    },
+    rpc_request::{RpcError, RpcRequest, RpcResponseErrorData},
+    rpc_response::RpcSimulateTransactionResult,
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+use solana_sdk::commitment_config::CommitmentConfig;
+use tokio::sync::Semaphore;
+use tracing::{error, info, warn};
+use url::Url;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+const PLACEHOLDER: &str = "{api_key}";
+// retries of a request while every key is rate limited
+const TOO_MANY_REQUESTS_RETRIES: usize = 5;
+const USAGE_FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+// as the stock HttpSender, so a hung request fails like any other instead of
+// holding its permit forever
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+struct Key {
+    // empty without [api_keys]
+    key: String,
+    // requests in the current quota period, including earlier runs
+    used: AtomicU64,
+    // requests not yet written to the store
+    unflushed: AtomicU64,
+    // skipped until this UNIX time after a 429
+    limited_until: AtomicU64,
+}
+
+impl Key {
+    /// `abcd…wxyz`, safe to log and store.
+    fn fingerprint(&self) -> String {
+        match self.key.len() {
+            0 => "default".to_string(),
+            len if len <= 8 => "…".to_string(),
+            len => format!("{}…{}", &self.key[..4], &self.key[len - 4..]),
+        }
+    }
+}
+
+/// The configured RPC provider keys, rotating away from keys that are rate
+/// limited or close to their quota. Without `[api_keys]` it holds a single
+/// key and the URLs are used as configured. Every call through it is also
+/// counted per endpoint and method.
+pub struct KeyRing {
+    rpc_url: String,
+    ws_url: String,
+    // hosts of the URLs, for usage accounting
+    rpc_endpoint: String,
+    ws_endpoint: String,
+    metrics: Arc<Metrics>,
+    // (endpoint, method) -> calls not yet written to the store
+    calls: Mutex<BTreeMap<(String, String), u64>>,
+    config: ApiKeysConfig,
+    keys: Vec<Key>,
+    current: AtomicUsize,
+    // quota period the `used` counts belong to
+    period: Mutex<String>,
+}
+
+impl KeyRing {
+    pub fn from_config(config: &Config, metrics: Arc<Metrics>) -> Result<Arc<Self>> {
+        let keys_config = config.api_keys.clone().unwrap_or_default();
+        let keys = match keys_config.keys.is_empty() {
+            true => vec![String::new()],
+            false if !config.rpc_url.contains(PLACEHOLDER) => {
+                return Err(anyhow!("[api_keys] requires {} in rpc_url", PLACEHOLDER));
+            }
+            false => keys_config.keys.clone(),
+        };
+        Ok(Arc::new(Self {
+            rpc_url: config.rpc_url.clone(),
+            ws_url: config.ws_url.clone(),
+            rpc_endpoint: endpoint(&config.rpc_url),
+            ws_endpoint: endpoint(&config.ws_url),
+            metrics,
+            calls: Mutex::default(),
+            period: Mutex::new(period(keys_config.period)),
+            config: keys_config,
+            keys: keys
+                .into_iter()
+                .map(|key| Key { key, used: AtomicU64::new(0), unflushed: AtomicU64::new(0), limited_until: AtomicU64::new(0) })
+                .collect(),
+            current: AtomicUsize::new(0),
+        }))
+    }
+
+    /// The WebSocket URL with the current key, counted as one request of
+    /// `method` (the subscription it is opened for).
+    pub fn ws_url(&self, method: &str) -> String {
+        let index = self.current();
+        self.record(index);
+        self.count(&self.ws_endpoint, method);
+        self.url(&self.ws_url, index)
+    }
+
+    fn count(&self, endpoint: &str, method: &str) {
+        self.metrics.rpc_call(endpoint, method);
+        *self.calls.lock().unwrap().entry((endpoint.to_string(), method.to_string())).or_default() += 1;
+    }
+
+    fn url(&self, template: &str, index: usize) -> String {
+        template.replace(PLACEHOLDER, &self.keys[index].key)
+    }
+
+    /// The key in use, moving on to the next one when it is rate limited or
+    /// near its quota. When no key is available the current one is kept.
+    fn current(&self) -> usize {
+        let now = unix_now();
+        let start = self.current.load(Ordering::Relaxed);
+        for offset in 0..self.keys.len() {
+            let index = (start + offset) % self.keys.len();
+            if self.available(&self.keys[index], now) {
+                if index != start && self.current.compare_exchange(start, index, Ordering::Relaxed, Ordering::Relaxed).is_ok() {
+                    let key = &self.keys[index];
+                    info!(key = %key.fingerprint(), used = key.used.load(Ordering::Relaxed), "Rotated to the next API key");
+                }
+                return index;
+            }
+        }
+        start
+    }
+
+    fn available(&self, key: &Key, now: u64) -> bool {
+        let near_quota = self.config.quota > 0
+            && key.used.load(Ordering::Relaxed) as f64 >= self.config.quota as f64 * self.config.threshold_percent / 100.0;
+        key.limited_until.load(Ordering::Relaxed) <= now && !near_quota
+    }
+
+    fn all_unavailable(&self) -> bool {
+        let now = unix_now();
+        !self.keys.iter().any(|key| self.available(key, now))
+    }
+
+    fn record(&self, index: usize) {
+        self.keys[index].used.fetch_add(1, Ordering::Relaxed);
+        self.keys[index].unflushed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn rate_limited(&self, index: usize, retry_after: Option<u64>) {
+        let key = &self.keys[index];
+        let cooldown = self.config.cooldown_secs.max(retry_after.unwrap_or(0));
+        key.limited_until.store(unix_now() + cooldown, Ordering::Relaxed);
+        if self.keys.len() > 1 {
+            warn!(key = %key.fingerprint(), cooldown_secs = cooldown, "API key rate limited");
+        }
+    }
+
+    /// Logs each key's usage and rate-limit state.
+    pub fn dump_state(&self) {
+        let now = unix_now();
+        let current = self.current.load(Ordering::Relaxed);
+        for (index, key) in self.keys.iter().enumerate() {
+            info!(
+                key = %key.fingerprint(),
+                current = index == current,
+                used = key.used.load(Ordering::Relaxed),
+                limited_for_secs = key.limited_until.load(Ordering::Relaxed).saturating_sub(now),
+                "API key state"
+            );
+        }
+    }
+
+    /// Loads the current period's usage from the store, then periodically
+    /// writes new requests and per-method calls back and resets the counts
+    /// when a period ends.
+    pub async fn track_usage(self: Arc<Self>, store: Arc<Store>) {
+        let current_period = self.period.lock().unwrap().clone();
+        match store.key_usage(&current_period) {
+            Ok(usage) => {
+                for key in &self.keys {
+                    key.used.fetch_add(usage.get(&key.fingerprint()).copied().unwrap_or(0), Ordering::Relaxed);
+                }
+            }
+            Err(e) => error!(error = %e, "Failed to load API key usage"),
+        }
+        let mut interval = tokio::time::interval(USAGE_FLUSH_INTERVAL);
+        loop {
+            interval.tick().await;
+            let flushed_period = self.period.lock().unwrap().clone();
+            for key in &self.keys {
+                let requests = key.unflushed.swap(0, Ordering::Relaxed);
+                if requests == 0 {
+                    continue;
+                }
+                if let Err(e) = store.add_key_usage(&flushed_period, &key.fingerprint(), requests) {
+                    key.unflushed.fetch_add(requests, Ordering::Relaxed);
+                    error!(key = %key.fingerprint(), error = %e, "Failed to save API key usage");
+                }
+            }
+            let day = Utc::now().format("%Y-%m-%d").to_string();
+            let calls = std::mem::take(&mut *self.calls.lock().unwrap());
+            for ((endpoint, method), count) in calls {
+                if let Err(e) = store.add_rpc_usage(&day, &endpoint, &method, count) {
+                    error!(endpoint, method, error = %e, "Failed to save RPC usage");
+                }
+            }
+            let new_period = period(self.config.period);
+            if new_period != flushed_period {
+                for key in &self.keys {
+                    key.used.store(0, Ordering::Relaxed);
+                }
+                info!(period = %new_period, "New API key quota period");
+                *self.period.lock().unwrap() = new_period;
+            }
+        }
+    }
+}
+
+// the host of an RPC or WebSocket URL, without the key
+fn endpoint(url: &str) -> String {
+    Url::parse(&url.replace(PLACEHOLDER, ""))
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+// "2024-05" or "2024-05-31" in UTC
+fn period(period: QuotaPeriod) -> String {
+    match period {
+        QuotaPeriod::Day => Utc::now().format("%Y-%m-%d").to_string(),
+        QuotaPeriod::Month => Utc::now().format("%Y-%m").to_string(),
+    }
+}
+
+/// An RPC client sending every request with the key ring's current key,
+/// with at most as many requests of each kind in flight as `limits` allow.
+pub fn client(keys: Arc<KeyRing>, limits: &RpcLimitsConfig) -> Arc<RpcClient> {
+    let semaphore = |limit: usize| (limit > 0).then(|| Semaphore::new(limit));
+    Arc::new(RpcClient::new_sender(
+        KeyedSender {
+            http: reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build().expect("build RPC HTTP client"),
+            keys,
+            request_id: AtomicU64::new(0),
+            stats: RwLock::default(),
+            transactions: semaphore(limits.transactions),
+            accounts: semaphore(limits.accounts),
+            other: semaphore(limits.other),
+        },
+        RpcClientConfig::with_commitment(CommitmentConfig::confirmed()),
+    ))
+}
+
+/// JSON-RPC over HTTP like the stock sender, except that a 429 moves on to
+/// the next key at once instead of waiting, and only waits for Retry-After
+/// when every key is rate limited.
+struct KeyedSender {
+    http: reqwest::Client,
+    keys: Arc<KeyRing>,
+    request_id: AtomicU64,
+    stats: RwLock<RpcTransportStats>,
+    // None when unlimited
+    transactions: Option<Semaphore>,
+    accounts: Option<Semaphore>,
+    other: Option<Semaphore>,
+}
+
+impl KeyedSender {
+    fn semaphore(&self, request: &RpcRequest) -> Option<&Semaphore> {
+        match request {
+            RpcRequest::GetTransaction
+            | RpcRequest::GetSignaturesForAddress
+            | RpcRequest::GetSignatureStatuses
+            | RpcRequest::GetBlock => self.transactions.as_ref(),
+            RpcRequest::GetAccountInfo
+            | RpcRequest::GetMultipleAccounts
+            | RpcRequest::GetProgramAccounts
+            | RpcRequest::GetBalance
+            | RpcRequest::GetTokenAccountBalance
+            | RpcRequest::GetTokenAccountsByOwner
+            | RpcRequest::GetTokenLargestAccounts
+            | RpcRequest::GetTokenSupply
+            | RpcRequest::Custom { method: "getAsset" } => self.accounts.as_ref(),
+            _ => self.other.as_ref(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ErrorObject {
+    code: i64,
+    message: String,
+}
+
+#[async_trait]
+impl RpcSender for KeyedSender {
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        let started = Instant::now();
+        let request_id = self.request_id.fetch_add(1, Ordering::Relaxed);
+        let method = request.to_string();
+        let recorded_params = recording::recording().then(|| params.clone());
+        let body = request.build_request_json(request_id, params).to_string();
+        // waiting for a permit is counted as rate-limited time
+        let _permit = match self.semaphore(&request) {
+            Some(semaphore) => semaphore.acquire().await.ok(),
+            None => None,
+        };
+        let mut rate_limited_time = started.elapsed();
+
+        let mut retries = TOO_MANY_REQUESTS_RETRIES;
+        let result = loop {
+            let index = self.keys.current();
+            self.keys.record(index);
+            self.keys.count(&self.keys.rpc_endpoint, &method);
+            if faults::inject(Fault::RpcTimeout) {
+                tokio::time::sleep(faults::rpc_timeout()).await;
+                break Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "injected timeout").into());
+            }
+            let response = match self
+                .http
+                .post(self.keys.url(&self.keys.rpc_url, index))
+                .header(CONTENT_TYPE, "application/json")
+                .body(body.clone())
+                .send()
+                .await
+            {
+                Ok(response) => response,
+                Err(e) => break Err(e.into()),
+            };
+            if response.status() == StatusCode::TOO_MANY_REQUESTS && retries > 0 {
+                retries -= 1;
+                let retry_after = response
+                    .headers()
+                    .get(RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.parse::<u64>().ok())
+                    .filter(|secs| *secs < 120);
+                self.keys.rate_limited(index, retry_after);
+                if self.keys.all_unavailable() {
+                    let wait = retry_after.map_or(Duration::from_millis(500), Duration::from_secs);
+                    tokio::time::sleep(wait).await;
+                    rate_limited_time += wait;
+                }
+                continue;
+            }
+            if let Err(e) = response.error_for_status_ref() {
+                break Err(e.into());
+            }
+            if faults::inject(Fault::RpcMalformed) {
+                break Err(serde_json::from_str::<Value>("{\"jsonrpc\":").unwrap_err().into());
+            }
+            break match response.json::<Value>().await {
+                Ok(json) if json["error"].is_object() => Err(response_error(&json["error"]).into()),
+                Ok(mut json) => Ok(json["result"].take()),
+                Err(e) => Err(e.into()),
+            };
+        };
+        if let Some(params) = &recorded_params {
+            recording::rpc_response(&method, params, &result);
+        }
+
+        let mut stats = self.stats.write().unwrap();
+        stats.request_count += 1;
+        stats.elapsed_time += started.elapsed();
+        stats.rate_limited_time += rate_limited_time;
+        result
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.stats.read().unwrap().clone()
+    }
+
+    fn url(&self) -> String {
+        self.keys.url(&self.keys.rpc_url, self.keys.current())
+    }
+}
+
+/// A JSON-RPC error object as the stock sender reports it.
+fn response_error(json: &Value) -> RpcError {
+    let error = match serde_json::from_value::<ErrorObject>(json.clone()) {
+        Ok(error) => error,
+        Err(e) => return RpcError::RpcRequestError(format!("Failed to deserialize RPC error response: {} [{}]", json, e)),
+    };
+    let data = match error.code {
+        JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE => {
+            serde_json::from_value::<RpcSimulateTransactionResult>(json["data"].clone())
+                .map_or(RpcResponseErrorData::Empty, RpcResponseErrorData::SendTransactionPreflightFailure)
+        }
+        JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY => serde_json::from_value::<NodeUnhealthyErrorData>(json["data"].clone())
+            .map_or(RpcResponseErrorData::Empty, |data| RpcResponseErrorData::NodeUnhealthy {
+                num_slots_behind: data.num_slots_behind,
+            }),
+        _ => RpcResponseErrorData::Empty,
+    };
+    RpcError::RpcResponseError { code: error.code, message: error.message, data }
+}
@@ -0,0 +1,113 @@
+use super::{envelope, Sink};
+use crate::config::AmqpConfig;
+use crate::events::Event;
+use crate::raydium::quote_label;
+use anyhow::Result;
+use futures::future::BoxFuture;
+use lapin::{
+    options::{BasicPublishOptions, ExchangeDeclareOptions},
+    types::FieldTable,
+    BasicProperties, Channel, Connection, ConnectionProperties, ExchangeKind,
+};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+// persistent messages survive a broker restart on durable queues
+const PERSISTENT: u8 = 2;
+
+/// Publishes events as JSON or MessagePack to a topic exchange. Routing keys are
+/// `<subject>.<event>.<quote>`, e.g. `pool.created.sol` or
+/// `pool.liquidity_pulled.usdc`; reports use `report.digest.all`,
+/// `report.summary.all` and `report.feed_stalled.all`.
+pub struct AmqpSink {
+    config: AmqpConfig,
+    // connected on first use and again after a failed publish
+    connection: tokio::sync::Mutex<Option<(Connection, Channel)>>,
+    // quote label by pool, remembered from new pool events for later events
+    quotes: Mutex<HashMap<String, &'static str>>,
+}
+
+impl AmqpSink {
+    pub fn new(config: &AmqpConfig) -> Self {
+        Self { config: config.clone(), connection: tokio::sync::Mutex::new(None), quotes: Mutex::new(HashMap::new()) }
+    }
+
+    async fn connect(&self) -> Result<(Connection, Channel)> {
+        let connection = Connection::connect(&self.config.url, ConnectionProperties::default()).await?;
+        let channel = connection.create_channel().await?;
+        if self.config.declare_exchange {
+            channel
+                .exchange_declare(
+                    &self.config.exchange,
+                    ExchangeKind::Topic,
+                    ExchangeDeclareOptions { durable: true, ..Default::default() },
+                    FieldTable::default(),
+                )
+                .await?;
+        }
+        Ok((connection, channel))
+    }
+
+    fn routing_key(&self, event: &Event) -> String {
+        let (subject, name, amm) = match event {
+            Event::NewPool(pool) => {
+                let quote = quote_label(pool.quote().mint);
+                self.quotes.lock().unwrap().insert(pool.amm.to_string(), quote);
+                return format!("pool.created.{}", quote);
+            }
+            Event::TokenLaunched(launched) => return format!("launch.created.{}", quote_label(&launched.quote_mint)),
+            Event::DexPool(pool) => return format!("dex.created.{}", quote_label(&pool.quote_mint)),
+            Event::LiquidityPulled(pulled) => ("pool", "liquidity_pulled", pulled.amm),
+            Event::DeveloperSold(sold) => ("pool", "developer_sold", sold.amm),
+            Event::PoolAdmin(admin) => ("pool", "admin", admin.amm),
+            Event::AuthorityChanged(changed) => ("pool", "authority_changed", changed.amm),
+            Event::LiquidityMigrated(migrated) => ("pool", "liquidity_migrated", migrated.amm),
+            Event::Graduated(graduated) => ("pool", "graduated", graduated.amm),
+            Event::DexScreenerListed(listed) => ("pool", "dexscreener_listed", listed.amm),
+            Event::DexScreenerProfile(profile) => ("pool", "dexscreener_profile", profile.amm),
+            Event::PerformanceReport(report) => ("pool", "performance", report.amm),
+            Event::Digest(_) => return "report.digest.all".to_string(),
+            Event::Summary(_) => return "report.summary.all".to_string(),
+            Event::FeedStalled(_) => return "report.feed_stalled.all".to_string(),
+        };
+        let quote = self.quotes.lock().unwrap().get(&amm.to_string()).copied().unwrap_or("other");
+        format!("{}.{}.{}", subject, name, quote)
+    }
+}
+
+impl Sink for AmqpSink {
+    fn name(&self) -> &str {
+        "amqp"
+    }
+
+    fn send<'a>(&'a self, event: &'a Event) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let routing_key = self.routing_key(event);
+            let payload = envelope(event, self.config.format)?;
+            let mut connection = self.connection.lock().await;
+            let channel = match connection.as_ref() {
+                Some((_, channel)) => channel.clone(),
+                None => {
+                    let (conn, channel) = self.connect().await?;
+                    *connection = Some((conn, channel.clone()));
+                    channel
+                }
+            };
+            let properties = BasicProperties::default()
+                .with_content_type(self.config.format.content_type().into())
+                .with_delivery_mode(PERSISTENT);
+            let published = async {
+                channel
+                    .basic_publish(&self.config.exchange, &routing_key, BasicPublishOptions::default(), &payload, properties)
+                    .await?
+                    .await?;
+                Ok::<_, lapin::Error>(())
+            }
+            .await;
+            if published.is_err() {
+                *connection = None;
+            }
+            Ok(published?)
+        })
+    }
+}
@@ -0,0 +1,138 @@
+use super::Sink;
+use crate::cloud::aws::Credentials;
+use crate::config::{AwsAttribute, AwsConfig};
+use crate::events::Event;
+use anyhow::{anyhow, Result};
+use futures::future::{self, BoxFuture};
+use serde_json::{json, Value};
+use tracing::warn;
+use url::{form_urlencoded, Url};
+
+const CONTENT_TYPE: &str = "application/x-www-form-urlencoded; charset=utf-8";
+
+/// Publishes events as JSON to SQS queues and SNS topics through their
+/// query APIs, with message attributes consumers can filter on.
+///
+/// A queue or topic that fails is only logged while others accepted the
+/// event; the event is queued for redelivery when every one failed, so
+/// healthy destinations don't receive it twice.
+pub struct AwsSink {
+    http: reqwest::Client,
+    config: AwsConfig,
+    credentials: Credentials,
+}
+
+impl AwsSink {
+    pub fn new(config: &AwsConfig) -> Result<Self> {
+        if config.queue_urls.is_empty() && config.topic_arns.is_empty() {
+            return Err(anyhow!("[notifications.aws] requires queue_urls or topic_arns"));
+        }
+        Ok(Self { http: reqwest::Client::new(), config: config.clone(), credentials: Credentials::from_env("[notifications.aws]")? })
+    }
+
+    /// (name, data type, value) for each configured attribute the event has.
+    fn attributes(&self, event: &Event, json: &Value) -> Vec<(&'static str, &'static str, String)> {
+        self.config
+            .attributes
+            .iter()
+            .filter_map(|attribute| match attribute {
+                AwsAttribute::Type => json["type"].as_str().map(|kind| ("type", "String", kind.to_string())),
+                AwsAttribute::Priority => {
+                    serde_json::to_value(event.priority()).ok()?.as_str().map(|priority| ("priority", "String", priority.to_string()))
+                }
+                AwsAttribute::Pool => json["amm"].as_str().map(|amm| ("pool", "String", amm.to_string())),
+                AwsAttribute::LiquiditySol => json["liquidity_sol"].as_f64().map(|sol| ("liquidity_sol", "Number", sol.to_string())),
+            })
+            .collect()
+    }
+
+    async fn send_message(&self, queue_url: &str, body: &str, attributes: &[(&str, &str, String)]) -> Result<()> {
+        let mut params = vec![
+            ("Action".to_string(), "SendMessage".to_string()),
+            ("Version".to_string(), "2012-11-05".to_string()),
+            ("MessageBody".to_string(), body.to_string()),
+        ];
+        for (i, (name, data_type, value)) in attributes.iter().enumerate() {
+            let prefix = format!("MessageAttribute.{}", i + 1);
+            params.push((format!("{}.Name", prefix), name.to_string()));
+            params.push((format!("{}.Value.DataType", prefix), data_type.to_string()));
+            params.push((format!("{}.Value.StringValue", prefix), value.clone()));
+        }
+        self.post(Url::parse(queue_url)?, "sqs", &params).await
+    }
+
+    async fn publish(&self, topic_arn: &str, body: &str, attributes: &[(&str, &str, String)]) -> Result<()> {
+        let mut params = vec![
+            ("Action".to_string(), "Publish".to_string()),
+            ("Version".to_string(), "2010-03-31".to_string()),
+            ("TopicArn".to_string(), topic_arn.to_string()),
+            ("Message".to_string(), body.to_string()),
+        ];
+        for (i, (name, data_type, value)) in attributes.iter().enumerate() {
+            let prefix = format!("MessageAttributes.entry.{}", i + 1);
+            params.push((format!("{}.Name", prefix), name.to_string()));
+            params.push((format!("{}.Value.DataType", prefix), data_type.to_string()));
+            params.push((format!("{}.Value.StringValue", prefix), value.clone()));
+        }
+        let url = Url::parse(&format!("https://sns.{}.amazonaws.com/", self.config.region))?;
+        self.post(url, "sns", &params).await
+    }
+
+    /// POSTs a signed query API request.
+    async fn post(&self, url: Url, service: &str, params: &[(String, String)]) -> Result<()> {
+        let body = form_urlencoded::Serializer::new(String::new()).extend_pairs(params).finish();
+        let headers = self.credentials.sign(
+            "POST",
+            &url,
+            &self.config.region,
+            service,
+            &[("content-type", CONTENT_TYPE.to_string())],
+            body.as_bytes(),
+        )?;
+        let mut request = self.http.post(url);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        let response = request.body(body).send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            return Err(anyhow!("{} returned {}: {}", service, status, response.text().await.unwrap_or_default()));
+        }
+        Ok(())
+    }
+}
+
+impl Sink for AwsSink {
+    fn name(&self) -> &str {
+        "aws"
+    }
+
+    fn send<'a>(&'a self, event: &'a Event) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let json = serde_json::to_value(event)?;
+            let attributes = self.attributes(event, &json);
+            let body = json!({ "priority": event.priority(), "event": json }).to_string();
+            let sends = self.config.queue_urls.iter().map(|queue_url| {
+                let send: BoxFuture<'_, Result<()>> = Box::pin(self.send_message(queue_url, &body, &attributes));
+                (queue_url, send)
+            });
+            let publishes = self.config.topic_arns.iter().map(|topic_arn| {
+                let publish: BoxFuture<'_, Result<()>> = Box::pin(self.publish(topic_arn, &body, &attributes));
+                (topic_arn, publish)
+            });
+            let (destinations, deliveries): (Vec<_>, Vec<_>) = sends.chain(publishes).unzip();
+            let results = future::join_all(deliveries).await;
+            let mut failed = 0;
+            for (destination, result) in destinations.iter().zip(results) {
+                if let Err(e) = result {
+                    warn!(destination = %destination, error = %e, "Failed to deliver event to AWS");
+                    failed += 1;
+                }
+            }
+            if failed == destinations.len() {
+                return Err(anyhow!("every queue and topic failed"));
+            }
+            Ok(())
+        })
+    }
+}
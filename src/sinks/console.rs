@@ -0,0 +1,154 @@
+use super::Sink;
+use crate::config::{ConsoleConfig, ConsoleFormat};
+use crate::events::{Event, PoolEvent};
+use crate::links::Links;
+use crate::time::{format_open_time, format_timestamp, opens_in};
+use anyhow::Result;
+use chrono_tz::Tz;
+use futures::future::BoxFuture;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const CYAN: &str = "\x1b[36m";
+
+/// Prints events to stdout: one line per pool in compact mode, a multi-line
+/// card in verbose mode.
+pub struct ConsoleSink {
+    format: ConsoleFormat,
+    color: bool,
+    timezone: Tz,
+    links: Links,
+}
+
+impl ConsoleSink {
+    pub fn new(config: &ConsoleConfig, timezone: Tz) -> Result<Self> {
+        Ok(Self { format: config.format, color: config.color, timezone, links: Links::from_config(&config.links)? })
+    }
+
+    fn paint(&self, code: &str, text: &str) -> String {
+        if self.color {
+            format!("{}{}{}", code, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Green without risks, yellow with one, red with more.
+    fn risk_color(pool: &PoolEvent) -> &'static str {
+        match pool.risks().len() {
+            0 => GREEN,
+            1 => YELLOW,
+            _ => RED,
+        }
+    }
+
+    fn compact(&self, pool: &PoolEvent) -> String {
+        let liquidity = pool.liquidity_sol.map_or("-".to_string(), |sol| format!("{:.2} SOL", sol));
+        let risks = pool.risks();
+        let risk = if risks.is_empty() { "ok".to_string() } else { risks.join(",") };
+        let now = crate::sniper::unix_now();
+        let opens = opens_in(pool.open_time, now).map_or(String::new(), |countdown| format!(" ({})", countdown));
+        // tags such as "verified" are rare for new pools, so stand out
+        let tags = pool.tags();
+        let tags = if tags.is_empty() { String::new() } else { format!(" {}", self.paint(CYAN, &self.paint(BOLD, &format!("<{}>", tags.join(","))))) };
+        let links = self.links.render(pool).into_iter().map(|(_, url)| url).collect::<Vec<_>>();
+        let links = if links.is_empty() { String::new() } else { format!(" {}", self.paint(DIM, &links.join(" "))) };
+        format!(
+            "{} {} {}{} {}{}{}",
+            self.paint(DIM, &format_timestamp(now, self.timezone)),
+            self.paint(BOLD, &pool.name()),
+            liquidity,
+            opens,
+            self.paint(Self::risk_color(pool), &format!("[{}]", risk)),
+            tags,
+            links,
+        )
+    }
+
+    fn verbose(&self, pool: &PoolEvent) -> String {
+        let color = Self::risk_color(pool);
+        let rule = self.paint(color, "----------------------------");
+        let now = crate::sniper::unix_now();
+        let mut lines = vec![
+            rule.clone(),
+            format!("{} {}", self.paint(BOLD, "New pool"), self.paint(DIM, &format_timestamp(now, self.timezone))),
+            format!("Pool:        {}", pool.amm),
+            format!("Base:        {} {} ({})", pool.base().init_amount, pool.base().name.unwrap_or("(no metadata)"), pool.base().mint),
+            format!("Quote:       {} {} ({})", pool.quote().init_amount, pool.quote().name.unwrap_or("(no metadata)"), pool.quote().mint),
+        ];
+        if let Some(sol) = pool.liquidity_sol {
+            lines.push(format!("Liquidity:   {:.2} SOL", sol));
+        }
+        if let Some(impact) = pool.price_impact {
+            lines.push(format!("Impact:      {:.2}%", impact * 100.0));
+        }
+        lines.push(format!("Open time:   {}", format_open_time(pool.open_time, now, self.timezone)));
+        if let Some(delay) = pool.block_delay_secs {
+            lines.push(format!("Delay:       {}s", delay));
+        }
+        let risks = pool.risks();
+        let risk = if risks.is_empty() { "none".to_string() } else { risks.join(", ") };
+        lines.push(format!("Risk:        {}", self.paint(color, &risk)));
+        let tags = pool.tags();
+        if !tags.is_empty() {
+            lines.push(format!("Tags:        {}", self.paint(CYAN, &self.paint(BOLD, &tags.join(", ")))));
+        }
+        for (provider, enrichment) in &pool.enrichment {
+            if let Some(price) = enrichment.price_usd {
+                lines.push(format!("{:<13}${}", format!("{}:", provider), price));
+            }
+        }
+        for (name, url) in self.links.render(pool) {
+            lines.push(format!("{:<13}{}", format!("{}:", name), url));
+        }
+        lines.push(rule);
+        lines.join("\n")
+    }
+}
+
+impl Sink for ConsoleSink {
+    fn name(&self) -> &str {
+        "console"
+    }
+
+    fn send<'a>(&'a self, event: &'a Event) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            match event {
+                Event::NewPool(pool) => match self.format {
+                    ConsoleFormat::Compact => println!("{}", self.compact(pool)),
+                    ConsoleFormat::Verbose => println!("{}", self.verbose(pool)),
+                },
+                Event::LiquidityPulled(_)
+                | Event::DeveloperSold(_)
+                | Event::PoolAdmin(_)
+                | Event::AuthorityChanged(_)
+                | Event::LiquidityMigrated(_)
+                | Event::FeedStalled(_) => println!(
+                    "{} {} {}",
+                    self.paint(DIM, &format_timestamp(crate::sniper::unix_now(), self.timezone)),
+                    self.paint(RED, &self.paint(BOLD, &event.title())),
+                    event.body().replace('\n', " | "),
+                ),
+                Event::TokenLaunched(_)
+                | Event::DexPool(_)
+                | Event::Graduated(_)
+                | Event::DexScreenerListed(_)
+                | Event::DexScreenerProfile(_)
+                | Event::PerformanceReport(_) => println!(
+                    "{} {} {}",
+                    self.paint(DIM, &format_timestamp(crate::sniper::unix_now(), self.timezone)),
+                    self.paint(BOLD, &event.title()),
+                    event.body().replace('\n', " | "),
+                ),
+                Event::Digest(digest) => println!("{}", digest.markdown()),
+                // summaries are already in the log
+                Event::Summary(_) => {}
+            }
+            Ok(())
+        })
+    }
+}
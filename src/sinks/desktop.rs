@@ -0,0 +1,49 @@
+use super::Sink;
+use crate::events::Event;
+use crate::links::Links;
+use anyhow::Result;
+use futures::future::BoxFuture;
+use notify_rust::{Notification, Timeout};
+
+/// OS desktop notifications.
+pub struct DesktopSink {
+    links: Links,
+}
+
+impl DesktopSink {
+    pub fn new(links: Links) -> Self {
+        Self { links }
+    }
+}
+
+impl Sink for DesktopSink {
+    fn name(&self) -> &str {
+        "desktop"
+    }
+
+    fn send<'a>(&'a self, event: &'a Event) -> BoxFuture<'a, Result<()>> {
+        let mut body = event.body();
+        if let Event::NewPool(pool) = event {
+            for (name, url) in self.links.render(pool) {
+                body.push_str(&format!("\n{}: {}", name, url));
+            }
+        }
+        let mut notification = Notification::new();
+        notification
+            .appname("Raydium V4 Monitor")
+            .summary(&event.title())
+            .body(&body)
+            .timeout(Timeout::Milliseconds(10_000));
+        #[cfg(all(unix, not(target_os = "macos")))]
+        notification.urgency(match event.priority() {
+            crate::events::Priority::High => notify_rust::Urgency::Critical,
+            crate::events::Priority::Normal => notify_rust::Urgency::Normal,
+            crate::events::Priority::Low => notify_rust::Urgency::Low,
+        });
+        Box::pin(async move {
+            // show() blocks on some platforms
+            tokio::task::spawn_blocking(move || notification.show().map(|_| ())).await??;
+            Ok(())
+        })
+    }
+}
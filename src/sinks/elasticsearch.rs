@@ -0,0 +1,185 @@
+use super::Sink;
+use crate::config::ElasticsearchConfig;
+use crate::events::Event;
+use anyhow::{anyhow, Result};
+use chrono::{SecondsFormat, Utc};
+use futures::future::BoxFuture;
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+// documents queued for the bulk task before new ones are dropped
+const QUEUE_LEN: usize = 10_000;
+
+/// Bulk-indexes events into daily indices (`<index_prefix>-YYYY.MM.DD`) on
+/// Elasticsearch or OpenSearch. An index template with the mapping below is
+/// installed on startup, so pools are searchable by token name, creator,
+/// risk flags and liquidity.
+pub struct ElasticsearchSink {
+    tx: mpsc::Sender<Value>,
+}
+
+impl ElasticsearchSink {
+    /// Starts the bulk task; must be called within the runtime.
+    pub fn new(config: &ElasticsearchConfig) -> Self {
+        let (tx, rx) = mpsc::channel(QUEUE_LEN);
+        tokio::spawn(Indexer { http: reqwest::Client::new(), config: config.clone() }.run(rx));
+        Self { tx }
+    }
+}
+
+impl Sink for ElasticsearchSink {
+    fn name(&self) -> &str {
+        "elasticsearch"
+    }
+
+    fn send<'a>(&'a self, event: &'a Event) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let document = document(event)?;
+            self.tx.try_send(document).map_err(|e| anyhow!("Elasticsearch queue unavailable: {}", e))
+        })
+    }
+}
+
+struct Indexer {
+    http: reqwest::Client,
+    config: ElasticsearchConfig,
+}
+
+impl Indexer {
+    async fn run(self, mut rx: mpsc::Receiver<Value>) {
+        match self.install_template().await {
+            Ok(()) => info!(url = %self.config.url, index_prefix = %self.config.index_prefix, "Installed Elasticsearch index template"),
+            Err(e) => warn!(url = %self.config.url, error = %e, "Failed to install Elasticsearch index template"),
+        }
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.flush_interval_secs.max(1)));
+        let mut batch = Vec::new();
+        loop {
+            tokio::select! {
+                document = rx.recv() => match document {
+                    Some(document) => {
+                        batch.push(document);
+                        if batch.len() < self.config.batch_size {
+                            continue;
+                        }
+                    }
+                    None => break,
+                },
+                _ = interval.tick() => {}
+            }
+            if batch.is_empty() {
+                continue;
+            }
+            let documents = std::mem::take(&mut batch);
+            if let Err(e) = self.bulk(&documents).await {
+                error!(documents = documents.len(), error = %e, "Failed to index events");
+            }
+        }
+        if !batch.is_empty() {
+            if let Err(e) = self.bulk(&batch).await {
+                error!(documents = batch.len(), error = %e, "Failed to index events");
+            }
+        }
+    }
+
+    fn request(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match (&self.config.api_key, &self.config.username) {
+            (Some(api_key), _) => request.header("authorization", format!("ApiKey {}", api_key)),
+            (None, Some(username)) => request.basic_auth(username, self.config.password.as_deref()),
+            (None, None) => request,
+        }
+    }
+
+    async fn install_template(&self) -> Result<()> {
+        let url = format!("{}/_index_template/{}", self.config.url.trim_end_matches('/'), self.config.index_prefix);
+        let template = json!({
+            "index_patterns": [format!("{}-*", self.config.index_prefix)],
+            "template": { "mappings": mapping() },
+        });
+        self.request(self.http.put(&url)).json(&template).send().await?.error_for_status()?;
+        Ok(())
+    }
+
+    async fn bulk(&self, documents: &[Value]) -> Result<()> {
+        let index = format!("{}-{}", self.config.index_prefix, Utc::now().format("%Y.%m.%d"));
+        let mut body = String::new();
+        for document in documents {
+            body.push_str(&json!({ "index": { "_index": index } }).to_string());
+            body.push('\n');
+            body.push_str(&document.to_string());
+            body.push('\n');
+        }
+        let url = format!("{}/_bulk", self.config.url.trim_end_matches('/'));
+        let response: Value = self
+            .request(self.http.post(&url))
+            .header("content-type", "application/x-ndjson")
+            .body(body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if response["errors"].as_bool() == Some(true) {
+            let failed = response["items"]
+                .as_array()
+                .map_or(0, |items| items.iter().filter(|item| !item["index"]["error"].is_null()).count());
+            return Err(anyhow!("{} of {} documents rejected", failed, documents.len()));
+        }
+        Ok(())
+    }
+}
+
+/// Searchable fields at the top level, with the full event kept unindexed
+/// under `event`.
+fn document(event: &Event) -> Result<Value> {
+    let json = serde_json::to_value(event)?;
+    let mut document = json!({
+        "@timestamp": Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        "type": json["type"],
+        "priority": event.priority(),
+        "title": event.title(),
+        "body": event.body(),
+        "amm": json["amm"],
+        "event": json,
+    });
+    match event {
+        Event::NewPool(pool) => {
+            let base = pool.base();
+            document["name"] = json!(pool.name());
+            document["base_mint"] = json!(base.mint.to_string());
+            document["base_name"] = json!(base.name);
+            document["quote_mint"] = json!(pool.quote().mint.to_string());
+            document["creator"] = json!(pool.creator.to_string());
+            document["risks"] = json!(pool.risks());
+            document["tags"] = json!(pool.tags());
+            document["liquidity_sol"] = json!(pool.liquidity_sol);
+        }
+        _ => document["name"] = document["event"]["name"].clone(),
+    }
+    Ok(document)
+}
+
+fn mapping() -> Value {
+    let keyword = json!({ "type": "keyword" });
+    json!({
+        "dynamic": false,
+        "properties": {
+            "@timestamp": { "type": "date" },
+            "type": keyword,
+            "priority": keyword,
+            "title": { "type": "text" },
+            "body": { "type": "text" },
+            "amm": keyword,
+            "name": { "type": "text", "fields": { "keyword": keyword } },
+            "base_mint": keyword,
+            "base_name": { "type": "text", "fields": { "keyword": keyword } },
+            "quote_mint": keyword,
+            "creator": keyword,
+            "risks": keyword,
+            "tags": keyword,
+            "liquidity_sol": { "type": "double" },
+            "event": { "type": "object", "enabled": false },
+        },
+    })
+}
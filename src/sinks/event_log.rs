@@ -0,0 +1,40 @@
+use super::Sink;
+use crate::events::Event;
+use crate::logging::unix_now_millis;
+use anyhow::Result;
+use futures::future::BoxFuture;
+use serde_json::json;
+use std::io::Write;
+use std::sync::Mutex;
+use tracing_appender::rolling::RollingFileAppender;
+
+/// Writes events as JSON Lines to rotating files, separate from the human
+/// readable log.
+pub struct EventLogSink {
+    writer: Mutex<RollingFileAppender>,
+}
+
+impl EventLogSink {
+    pub fn new(writer: RollingFileAppender) -> Self {
+        Self { writer: Mutex::new(writer) }
+    }
+}
+
+impl Sink for EventLogSink {
+    fn name(&self) -> &str {
+        "event log"
+    }
+
+    fn send<'a>(&'a self, event: &'a Event) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let mut line = serde_json::to_string(&json!({
+                "timestamp_ms": unix_now_millis(),
+                "priority": event.priority(),
+                "event": event,
+            }))?;
+            line.push('\n');
+            self.writer.lock().unwrap().write_all(line.as_bytes())?;
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,137 @@
+use super::Sink;
+use crate::config::InfluxConfig;
+use crate::events::Event;
+use crate::raydium::quote_label;
+use crate::sniper::unix_now;
+use anyhow::{anyhow, Result};
+use futures::future::BoxFuture;
+use tokio::sync::mpsc;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tracing::error;
+
+// points queued for the writer task before new ones are dropped
+const QUEUE_LEN: usize = 10_000;
+
+/// One new pool, written as a `launch` point.
+struct Launch {
+    amm: String,
+    quote: &'static str,
+    liquidity_sol: Option<f64>,
+    price_impact: Option<f64>,
+    block_delay_secs: Option<u64>,
+    timestamp: u64,
+}
+
+/// Writes launch activity to InfluxDB in line protocol: a `launch` point per
+/// new pool (initial liquidity, detection delay, price impact) and a
+/// `launches` count per quote token for every minute with launches.
+pub struct InfluxSink {
+    tx: mpsc::Sender<Launch>,
+}
+
+impl InfluxSink {
+    /// Starts the writer task; must be called within the runtime.
+    pub fn new(config: &InfluxConfig) -> Result<Self> {
+        if config.bucket.is_empty() {
+            return Err(anyhow!("[notifications.influxdb] requires bucket"));
+        }
+        let (tx, rx) = mpsc::channel(QUEUE_LEN);
+        tokio::spawn(Writer { http: reqwest::Client::new(), config: config.clone() }.run(rx));
+        Ok(Self { tx })
+    }
+}
+
+impl Sink for InfluxSink {
+    fn name(&self) -> &str {
+        "influxdb"
+    }
+
+    fn send<'a>(&'a self, event: &'a Event) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let Event::NewPool(pool) = event else {
+                return Ok(());
+            };
+            let launch = Launch {
+                amm: pool.amm.to_string(),
+                quote: quote_label(pool.quote().mint),
+                liquidity_sol: pool.liquidity_sol,
+                price_impact: pool.price_impact,
+                block_delay_secs: pool.block_delay_secs,
+                timestamp: unix_now(),
+            };
+            self.tx.try_send(launch).map_err(|e| anyhow!("InfluxDB queue unavailable: {}", e))
+        })
+    }
+}
+
+struct Writer {
+    http: reqwest::Client,
+    config: InfluxConfig,
+}
+
+impl Writer {
+    async fn run(self, mut rx: mpsc::Receiver<Launch>) {
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.flush_interval_secs.max(1)));
+        let mut lines = Vec::new();
+        // launches by (minute, quote), written once the minute is over
+        let mut counts = BTreeMap::<(u64, &'static str), u64>::new();
+        loop {
+            tokio::select! {
+                launch = rx.recv() => match launch {
+                    Some(launch) => {
+                        *counts.entry((launch.timestamp / 60 * 60, launch.quote)).or_default() += 1;
+                        lines.push(launch_line(&launch));
+                    }
+                    None => break,
+                },
+                _ = interval.tick() => {
+                    let current_minute = unix_now() / 60 * 60;
+                    let open = counts.split_off(&(current_minute, ""));
+                    for ((minute, quote), count) in std::mem::replace(&mut counts, open) {
+                        lines.push(format!("launches,quote={} count={}i {}", quote, count, minute));
+                    }
+                    if lines.is_empty() {
+                        continue;
+                    }
+                    let batch = std::mem::take(&mut lines);
+                    if let Err(e) = self.write(&batch).await {
+                        error!(points = batch.len(), error = %e, "Failed to write to InfluxDB");
+                    }
+                }
+            }
+        }
+    }
+
+    async fn write(&self, lines: &[String]) -> Result<()> {
+        let url = format!("{}/api/v2/write", self.config.url.trim_end_matches('/'));
+        let mut request = self
+            .http
+            .post(url)
+            .query(&[("org", self.config.org.as_str()), ("bucket", self.config.bucket.as_str()), ("precision", "s")])
+            .body(lines.join("\n"));
+        if let Some(token) = &self.config.token {
+            request = request.header("authorization", format!("Token {}", token));
+        }
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            return Err(anyhow!("InfluxDB returned {}: {}", status, response.text().await.unwrap_or_default()));
+        }
+        Ok(())
+    }
+}
+
+fn launch_line(launch: &Launch) -> String {
+    let mut fields = vec!["count=1i".to_string()];
+    if let Some(sol) = launch.liquidity_sol {
+        fields.push(format!("liquidity_sol={}", sol));
+    }
+    if let Some(impact) = launch.price_impact {
+        fields.push(format!("price_impact={}", impact));
+    }
+    if let Some(delay) = launch.block_delay_secs {
+        fields.push(format!("block_delay_secs={}i", delay));
+    }
+    format!("launch,quote={},pool={} {} {}", launch.quote, launch.amm, fields.join(","), launch.timestamp)
+}
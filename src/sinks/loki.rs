@@ -0,0 +1,41 @@
+use super::Sink;
+use crate::events::Event;
+use crate::loki::Loki;
+use crate::raydium::quote_label;
+use anyhow::Result;
+use futures::future::BoxFuture;
+use serde_json::json;
+
+/// Sends events to Loki as JSON lines, in streams labelled by event type,
+/// priority and, for new pools, quote token.
+pub struct LokiSink {
+    loki: Loki,
+}
+
+impl LokiSink {
+    pub fn new(loki: Loki) -> Self {
+        Self { loki }
+    }
+}
+
+impl Sink for LokiSink {
+    fn name(&self) -> &str {
+        "loki"
+    }
+
+    fn send<'a>(&'a self, event: &'a Event) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let json = serde_json::to_value(event)?;
+            let mut labels = vec![
+                ("kind", "event".to_string()),
+                ("type", json["type"].as_str().unwrap_or_default().to_string()),
+                ("severity", json!(event.priority()).as_str().unwrap_or_default().to_string()),
+            ];
+            if let Event::NewPool(pool) = event {
+                labels.push(("quote", quote_label(pool.quote().mint).to_string()));
+            }
+            let line = json!({ "title": event.title(), "event": json }).to_string();
+            self.loki.push(labels, line)
+        })
+    }
+}
@@ -0,0 +1,342 @@
+#[cfg(feature = "amqp")]
+mod amqp;
+mod aws;
+mod console;
+#[cfg(feature = "desktop-notifications")]
+mod desktop;
+mod elasticsearch;
+mod event_log;
+mod influxdb;
+mod loki;
+#[cfg(feature = "mongodb")]
+mod mongodb;
+mod pubsub;
+mod sheets;
+mod webhook;
+#[cfg(feature = "zmq")]
+mod zmq;
+
+use crate::config::{AmqpConfig, Config, DeliveryConfig, DesktopNotificationsConfig, EventFormat, MongoConfig, ZmqConfig};
+use crate::history::EventHistory;
+use crate::leader;
+use crate::logging;
+use crate::loki::Loki;
+use crate::events::{Event, Priority};
+use crate::sniper::unix_now;
+use crate::store::Store;
+use crate::webhooks::Webhooks;
+use anyhow::Result;
+use futures::future::BoxFuture;
+use serde::Serialize;
+use tracing::{debug, error, info, warn};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+// queued events read per store query while redelivering
+const REDELIVERY_BATCH: usize = 100;
+
+/// An event with its priority, as the message bus sinks publish it.
+#[derive(Serialize)]
+struct Envelope<'a> {
+    priority: Priority,
+    event: &'a Event,
+}
+
+/// `{"priority": ..., "event": ...}` in `format`.
+fn envelope(event: &Event, format: EventFormat) -> Result<Vec<u8>> {
+    let envelope = Envelope { priority: event.priority(), event };
+    Ok(match format {
+        EventFormat::Json => serde_json::to_vec(&envelope)?,
+        EventFormat::Msgpack => rmp_serde::to_vec_named(&envelope)?,
+    })
+}
+
+/// An output channel for events.
+pub trait Sink: Send + Sync {
+    fn name(&self) -> &str;
+    fn send<'a>(&'a self, event: &'a Event) -> BoxFuture<'a, Result<()>>;
+}
+
+struct Route {
+    sink: Box<dyn Sink>,
+    min_priority: Priority,
+    // events for the sink are waiting in the delivery queue
+    queued: AtomicBool,
+    // held while sending, so queued and new events arrive in order
+    order: tokio::sync::Mutex<()>,
+}
+
+impl Route {
+    fn new(sink: Box<dyn Sink>, min_priority: Priority) -> Self {
+        Self { sink, min_priority, queued: AtomicBool::new(false), order: tokio::sync::Mutex::new(()) }
+    }
+}
+
+/// Dispatches events to every sink whose minimum priority they meet. With a
+/// delivery queue, events a sink fails to accept are kept in the store and
+/// redelivered in order once it recovers.
+#[derive(Clone, Default)]
+pub struct Sinks {
+    routes: Arc<Vec<Route>>,
+    queue: Option<(Arc<Store>, DeliveryConfig)>,
+    history: Option<EventHistory>,
+}
+
+impl Sinks {
+    /// Nothing is printed to the terminal when `console` is false (e.g. in
+    /// dashboard mode).
+    pub fn from_config(config: &Config, console: bool) -> Result<Self> {
+        let mut routes = Vec::new();
+        if console && config.console.enabled {
+            routes.push(Route::new(Box::new(console::ConsoleSink::new(&config.console, config.timezone)?), Priority::Low));
+        }
+        if let Some(dir) = config.logging.dir.as_ref().filter(|_| config.logging.events) {
+            let writer = logging::appender(&config.logging, dir, "events", "jsonl")?;
+            routes.push(Route::new(Box::new(event_log::EventLogSink::new(writer)), Priority::Low));
+        }
+        if let Some(loki) = config.logging.loki.as_ref().filter(|loki| loki.events) {
+            routes.push(Route::new(Box::new(loki::LokiSink::new(Loki::new(loki)?)), loki.min_priority));
+        }
+        if let Some(sheets) = &config.notifications.sheets {
+            routes.push(Route::new(Box::new(sheets::SheetsSink::new(sheets, config.timezone)?), sheets.min_priority));
+        }
+        if let Some(aws) = &config.notifications.aws {
+            routes.push(Route::new(Box::new(aws::AwsSink::new(aws)?), aws.min_priority));
+        }
+        if let Some(pubsub) = &config.notifications.pubsub {
+            routes.push(Route::new(Box::new(pubsub::PubSubSink::new(pubsub)?), pubsub.min_priority));
+        }
+        if let Some(amqp) = &config.notifications.amqp {
+            Self::add_amqp(&mut routes, amqp)?;
+        }
+        if let Some(zmq) = &config.notifications.zmq {
+            Self::add_zmq(&mut routes, zmq)?;
+        }
+        if let Some(elasticsearch) = &config.notifications.elasticsearch {
+            let sink = elasticsearch::ElasticsearchSink::new(elasticsearch);
+            routes.push(Route::new(Box::new(sink), elasticsearch.min_priority));
+        }
+        if let Some(mongodb) = &config.notifications.mongodb {
+            Self::add_mongodb(&mut routes, mongodb)?;
+        }
+        if let Some(influxdb) = &config.notifications.influxdb {
+            // every new pool is counted, whatever its priority
+            routes.push(Route::new(Box::new(influxdb::InfluxSink::new(influxdb)?), Priority::Low));
+        }
+        if let Some(desktop) = &config.notifications.desktop {
+            Self::add_desktop(&mut routes, desktop)?;
+        }
+        Ok(Self { routes: Arc::new(routes), queue: None, history: None })
+    }
+
+    /// Queues undelivered events in `store`, picking up events left queued
+    /// by an earlier run. A no-op when the queue is disabled.
+    pub fn with_delivery_queue(mut self, store: Arc<Store>, config: &DeliveryConfig) -> Result<Self> {
+        if !config.enabled {
+            return Ok(self);
+        }
+        for route in self.routes.iter() {
+            let queued = store.delivery_queue_len(route.sink.name())?;
+            if queued > 0 {
+                info!(sink = route.sink.name(), queued, "Events waiting for redelivery");
+                route.queued.store(true, Ordering::Relaxed);
+            }
+        }
+        self.queue = Some((store, config.clone()));
+        Ok(self)
+    }
+
+    /// Delivers events to the webhooks registered at runtime. Call before
+    /// `with_delivery_queue` and before cloning.
+    pub fn with_webhooks(mut self, webhooks: Webhooks) -> Self {
+        let routes = Arc::get_mut(&mut self.routes).expect("Sinks are not shared yet");
+        routes.push(Route::new(Box::new(webhook::WebhookSink::new(webhooks)), Priority::Low));
+        self
+    }
+
+    /// Records every dispatched event in `history` for replay.
+    pub fn with_history(mut self, history: EventHistory) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    #[cfg(feature = "amqp")]
+    fn add_amqp(routes: &mut Vec<Route>, config: &AmqpConfig) -> Result<()> {
+        routes.push(Route::new(Box::new(amqp::AmqpSink::new(config)), config.min_priority));
+        Ok(())
+    }
+
+    #[cfg(not(feature = "amqp"))]
+    fn add_amqp(_routes: &mut Vec<Route>, _config: &AmqpConfig) -> Result<()> {
+        Err(anyhow::anyhow!("[notifications.amqp] requires building with --features amqp"))
+    }
+
+    #[cfg(feature = "mongodb")]
+    fn add_mongodb(routes: &mut Vec<Route>, config: &MongoConfig) -> Result<()> {
+        routes.push(Route::new(Box::new(mongodb::MongoSink::new(config)), config.min_priority));
+        Ok(())
+    }
+
+    #[cfg(not(feature = "mongodb"))]
+    fn add_mongodb(_routes: &mut Vec<Route>, _config: &MongoConfig) -> Result<()> {
+        Err(anyhow::anyhow!("[notifications.mongodb] requires building with --features mongodb"))
+    }
+
+    #[cfg(feature = "zmq")]
+    fn add_zmq(routes: &mut Vec<Route>, config: &ZmqConfig) -> Result<()> {
+        routes.push(Route::new(Box::new(zmq::ZmqSink::new(config)), config.min_priority));
+        Ok(())
+    }
+
+    #[cfg(not(feature = "zmq"))]
+    fn add_zmq(_routes: &mut Vec<Route>, _config: &ZmqConfig) -> Result<()> {
+        Err(anyhow::anyhow!("[notifications.zmq] requires building with --features zmq"))
+    }
+
+    #[cfg(feature = "desktop-notifications")]
+    fn add_desktop(routes: &mut Vec<Route>, config: &DesktopNotificationsConfig) -> Result<()> {
+        let sink = desktop::DesktopSink::new(crate::links::Links::from_config(&config.links)?);
+        routes.push(Route::new(Box::new(sink), config.min_priority));
+        Ok(())
+    }
+
+    #[cfg(not(feature = "desktop-notifications"))]
+    fn add_desktop(_routes: &mut Vec<Route>, _config: &DesktopNotificationsConfig) -> Result<()> {
+        Err(anyhow::anyhow!("[notifications.desktop] requires building with --features desktop-notifications"))
+    }
+
+    /// Sinks are independent; a failing sink only logs a warning, and its
+    /// events are queued when there is a delivery queue.
+    pub async fn dispatch(&self, event: &Event) {
+        // the leader sends it, followers must not queue it either
+        if !leader::is_leader() {
+            debug!(event = %event.title(), "Not the leader, skipping event");
+            return;
+        }
+        if let Some(history) = &self.history {
+            history.record(event);
+        }
+        let priority = event.priority();
+        for route in self.routes.iter().filter(|route| priority >= route.min_priority) {
+            let Some((store, _)) = &self.queue else {
+                if let Err(e) = route.sink.send(event).await {
+                    warn!(sink = route.sink.name(), error = %e, "Failed to send event");
+                }
+                continue;
+            };
+            let _order = route.order.lock().await;
+            // behind the events already waiting
+            if route.queued.load(Ordering::Relaxed) {
+                enqueue(store, route, event);
+                continue;
+            }
+            if let Err(e) = route.sink.send(event).await {
+                warn!(sink = route.sink.name(), error = %e, "Failed to send event, queued for redelivery");
+                enqueue(store, route, event);
+            }
+        }
+    }
+
+    /// Periodically replays queued events to their sinks, oldest first,
+    /// stopping at the first failure of a sink until the next attempt.
+    pub async fn redeliver(self) {
+        let Some((store, config)) = self.queue.clone() else {
+            return;
+        };
+        let mut interval = tokio::time::interval(Duration::from_secs(config.retry_interval_secs.max(1)));
+        loop {
+            interval.tick().await;
+            if !leader::is_leader() {
+                continue;
+            }
+            for route in self.routes.iter().filter(|route| route.queued.load(Ordering::Relaxed)) {
+                let _order = route.order.lock().await;
+                match redeliver(&store, &config, route).await {
+                    Ok(0) => {}
+                    Ok(delivered) => info!(sink = route.sink.name(), delivered, "Redelivered queued events"),
+                    Err(e) => error!(sink = route.sink.name(), error = %e, "Failed to read the delivery queue"),
+                }
+            }
+        }
+    }
+
+    /// Logs the number of queued events per sink.
+    pub fn dump_state(&self) {
+        let Some((store, _)) = &self.queue else {
+            return;
+        };
+        for route in self.routes.iter().filter(|route| route.queued.load(Ordering::Relaxed)) {
+            let queued = store.delivery_queue_len(route.sink.name()).ok();
+            info!(sink = route.sink.name(), queued, "Delivery queue");
+        }
+    }
+}
+
+fn enqueue(store: &Store, route: &Route, event: &Event) {
+    let result = serde_json::to_string(event)
+        .map_err(anyhow::Error::from)
+        .and_then(|json| store.enqueue_delivery(route.sink.name(), &json, unix_now()));
+    match result {
+        Ok(()) => route.queued.store(true, Ordering::Relaxed),
+        Err(e) => error!(sink = route.sink.name(), error = %e, "Failed to queue event, dropping it"),
+    }
+}
+
+/// Sends the route's queued events until the queue is empty or the sink
+/// fails again, returning how many were delivered. Expired and unreadable
+/// entries are dropped.
+async fn redeliver(store: &Store, config: &DeliveryConfig, route: &Route) -> Result<u64> {
+    let name = route.sink.name();
+    let max_age_secs = config.max_age_hours * 3600;
+    let mut delivered = 0;
+    loop {
+        let entries = store.queued_deliveries(name, REDELIVERY_BATCH)?;
+        if entries.is_empty() {
+            route.queued.store(false, Ordering::Relaxed);
+            return Ok(delivered);
+        }
+        for entry in entries {
+            if max_age_secs > 0 && unix_now().saturating_sub(entry.queued_at) > max_age_secs {
+                warn!(sink = name, queued_at = entry.queued_at, attempts = entry.attempts, "Dropping expired queued event");
+                store.remove_delivery(entry.id)?;
+                continue;
+            }
+            let event = match serde_json::from_str::<Event>(&entry.event) {
+                Ok(event) => event,
+                Err(e) => {
+                    warn!(sink = name, error = %e, "Dropping unreadable queued event");
+                    store.remove_delivery(entry.id)?;
+                    continue;
+                }
+            };
+            if let Err(e) = route.sink.send(&event).await {
+                warn!(sink = name, attempts = entry.attempts + 1, error = %e, "Sink still failing");
+                store.delivery_failed(entry.id)?;
+                return Ok(delivered);
+            }
+            store.remove_delivery(entry.id)?;
+            delivered += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::liquidity_pulled;
+    use serde_json::Value;
+    use solana_sdk::pubkey::Pubkey;
+
+    #[test]
+    fn msgpack_envelope_matches_json_in_fewer_bytes() {
+        let event = liquidity_pulled(Pubkey::new_unique(), "DOG/WSOL");
+        let json = envelope(&event, EventFormat::Json).unwrap();
+        let msgpack = envelope(&event, EventFormat::Msgpack).unwrap();
+        assert_eq!(
+            rmp_serde::from_slice::<Value>(&msgpack).unwrap(),
+            serde_json::from_slice::<Value>(&json).unwrap()
+        );
+        assert!(msgpack.len() < json.len());
+    }
+}
@@ -0,0 +1,78 @@
+use super::Sink;
+use crate::config::MongoConfig;
+use crate::events::Event;
+use anyhow::Result;
+use futures::future::BoxFuture;
+use mongodb::{
+    bson::{self, doc, DateTime, Document},
+    options::IndexOptions,
+    Client, Collection, IndexModel,
+};
+use tokio::sync::Mutex;
+
+/// Stores events as documents: the event under `event`, plus `type`,
+/// `priority` and, for new pools, the indexed `mint` (base token), `creator`
+/// and `slot` at the top level.
+pub struct MongoSink {
+    config: MongoConfig,
+    // connected, and the indexes created, on first use
+    collection: Mutex<Option<Collection<Document>>>,
+}
+
+impl MongoSink {
+    pub fn new(config: &MongoConfig) -> Self {
+        Self { config: config.clone(), collection: Mutex::new(None) }
+    }
+
+    async fn connect(&self) -> Result<Collection<Document>> {
+        let client = Client::with_uri_str(&self.config.uri).await?;
+        let collection = client.database(&self.config.database).collection::<Document>(&self.config.collection);
+        let indexes = ["mint", "creator", "slot", "amm"].map(|field| {
+            IndexModel::builder()
+                .keys(doc! { field: 1 })
+                .options(IndexOptions::builder().sparse(true).build())
+                .build()
+        });
+        collection.create_indexes(indexes, None).await?;
+        Ok(collection)
+    }
+}
+
+impl Sink for MongoSink {
+    fn name(&self) -> &str {
+        "mongodb"
+    }
+
+    fn send<'a>(&'a self, event: &'a Event) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let json = serde_json::to_value(event)?;
+            let mut document = doc! {
+                "timestamp": DateTime::now(),
+                "type": json["type"].as_str().unwrap_or_default(),
+                "priority": bson::to_bson(&event.priority())?,
+            };
+            if let Some(amm) = json["amm"].as_str() {
+                document.insert("amm", amm);
+            }
+            if let Event::NewPool(pool) = event {
+                document.insert("mint", pool.base().mint.to_string());
+                document.insert("creator", pool.creator.to_string());
+                document.insert("slot", pool.slot as i64);
+            }
+            document.insert("event", bson::to_bson(&json)?);
+
+            let mut collection = self.collection.lock().await;
+            let connected = match collection.as_ref() {
+                Some(connected) => connected.clone(),
+                None => {
+                    let connected = self.connect().await?;
+                    *collection = Some(connected.clone());
+                    connected
+                }
+            };
+            drop(collection);
+            connected.insert_one(document, None).await?;
+            Ok(())
+        })
+    }
+}
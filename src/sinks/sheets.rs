@@ -0,0 +1,75 @@
+use super::Sink;
+use crate::cloud::google::ServiceAccountAuth;
+use crate::config::SheetsConfig;
+use crate::events::Event;
+use crate::sniper::unix_now;
+use crate::time::format_timestamp;
+use anyhow::{anyhow, Result};
+use chrono_tz::Tz;
+use futures::future::BoxFuture;
+use serde_json::{json, Value};
+
+const SHEETS_SCOPE: &str = "https://www.googleapis.com/auth/spreadsheets";
+
+/// Appends one row per event to a Google Sheet through the Sheets API,
+/// authenticated as a service account the sheet is shared with.
+pub struct SheetsSink {
+    http: reqwest::Client,
+    spreadsheet_id: String,
+    sheet: String,
+    timezone: Tz,
+    auth: ServiceAccountAuth,
+}
+
+impl SheetsSink {
+    pub fn new(config: &SheetsConfig, timezone: Tz) -> Result<Self> {
+        if config.spreadsheet_id.is_empty() || config.credentials_path.is_empty() {
+            return Err(anyhow!("[notifications.sheets] requires spreadsheet_id and credentials_path"));
+        }
+        Ok(Self {
+            http: reqwest::Client::new(),
+            spreadsheet_id: config.spreadsheet_id.clone(),
+            sheet: config.sheet.clone(),
+            timezone,
+            auth: ServiceAccountAuth::from_file(&config.credentials_path, SHEETS_SCOPE)?,
+        })
+    }
+
+    /// Time, event type, priority, title and the body on one line.
+    fn row(&self, event: &Event) -> Result<Value> {
+        let kind = serde_json::to_value(event)?["type"].take();
+        Ok(json!([
+            format_timestamp(unix_now(), self.timezone),
+            kind,
+            event.priority(),
+            event.title(),
+            event.body().replace('\n', " | "),
+        ]))
+    }
+}
+
+impl Sink for SheetsSink {
+    fn name(&self) -> &str {
+        "google sheets"
+    }
+
+    fn send<'a>(&'a self, event: &'a Event) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let row = self.row(event)?;
+            let token = self.auth.access_token().await?;
+            let url = format!(
+                "https://sheets.googleapis.com/v4/spreadsheets/{}/values/{}:append",
+                self.spreadsheet_id, self.sheet
+            );
+            self.http
+                .post(&url)
+                .query(&[("valueInputOption", "RAW"), ("insertDataOption", "INSERT_ROWS")])
+                .bearer_auth(token)
+                .json(&json!({ "values": [row] }))
+                .send()
+                .await?
+                .error_for_status()?;
+            Ok(())
+        })
+    }
+}
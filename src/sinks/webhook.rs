@@ -0,0 +1,79 @@
+use super::{envelope, Sink};
+use crate::cloud::aws::hex;
+use crate::config::EventFormat;
+use crate::events::Event;
+use crate::webhooks::{Destination, Webhooks};
+use anyhow::{anyhow, Result};
+use futures::future::{self, BoxFuture};
+use ring::hmac;
+use std::time::Duration;
+use tracing::warn;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// POSTs the JSON envelope of each event to the webhooks registered through
+/// the REST API whose filter passes it. With a secret, `X-Signature-256`
+/// carries `sha256=<hex HMAC-SHA256 of the body>`.
+///
+/// A destination that fails is only logged while others accepted the event;
+/// the event is queued for redelivery when every matching destination failed,
+/// so healthy destinations don't receive it twice.
+pub struct WebhookSink {
+    http: reqwest::Client,
+    webhooks: Webhooks,
+}
+
+impl WebhookSink {
+    pub fn new(webhooks: Webhooks) -> Self {
+        Self { http: reqwest::Client::new(), webhooks }
+    }
+
+    async fn post(&self, destination: &Destination, body: &[u8]) -> Result<()> {
+        let webhook = &destination.webhook;
+        let mut request = self
+            .http
+            .post(&webhook.url)
+            .timeout(TIMEOUT)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header("X-Webhook-Id", webhook.id);
+        if let Some(secret) = &webhook.secret {
+            let signature = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes()), body);
+            request = request.header("X-Signature-256", format!("sha256={}", hex(signature.as_ref())));
+        }
+        // the error would otherwise quote the URL and its credential
+        let response = request.body(body.to_vec()).send().await.map_err(reqwest::Error::without_url)?;
+        if !response.status().is_success() {
+            return Err(anyhow!("HTTP {}", response.status()));
+        }
+        Ok(())
+    }
+}
+
+impl Sink for WebhookSink {
+    fn name(&self) -> &str {
+        "webhooks"
+    }
+
+    fn send<'a>(&'a self, event: &'a Event) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let destinations = self.webhooks.matching(event);
+            if destinations.is_empty() {
+                return Ok(());
+            }
+            let body = envelope(event, EventFormat::Json)?;
+            let results = future::join_all(destinations.iter().map(|destination| self.post(destination, &body))).await;
+            let mut failed = 0;
+            for (destination, result) in destinations.iter().zip(results) {
+                if let Err(e) = result {
+                    let webhook = &destination.webhook;
+                    warn!(id = webhook.id, host = %webhook.host(), error = %e, "Failed to deliver webhook");
+                    failed += 1;
+                }
+            }
+            if failed == destinations.len() {
+                return Err(anyhow!("every matching webhook failed"));
+            }
+            Ok(())
+        })
+    }
+}
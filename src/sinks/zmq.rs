@@ -0,0 +1,60 @@
+use super::{envelope, Sink};
+use crate::config::{EventFormat, ZmqConfig};
+use crate::events::Event;
+use anyhow::{anyhow, Result};
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use tokio::sync::mpsc;
+use tracing::{error, info};
+use zeromq::{PubSocket, Socket, SocketSend, ZmqMessage};
+
+// messages queued for the socket task before new ones are dropped
+const QUEUE_LEN: usize = 1024;
+
+/// Publishes events on a ZeroMQ PUB socket as two-frame messages: the event
+/// type (for SUB prefix filters) and the event as JSON or MessagePack.
+/// ZeroMQ frames carry their own length, so each payload arrives whole.
+pub struct ZmqSink {
+    format: EventFormat,
+    tx: mpsc::Sender<(String, Vec<u8>)>,
+}
+
+impl ZmqSink {
+    /// Binds the socket in a background task; must be called within the
+    /// runtime.
+    pub fn new(config: &ZmqConfig) -> Self {
+        let (tx, rx) = mpsc::channel(QUEUE_LEN);
+        tokio::spawn(publish(config.endpoint.clone(), rx));
+        Self { format: config.format, tx }
+    }
+}
+
+async fn publish(endpoint: String, mut rx: mpsc::Receiver<(String, Vec<u8>)>) {
+    let mut socket = PubSocket::new();
+    if let Err(e) = socket.bind(&endpoint).await {
+        error!(endpoint = %endpoint, error = %e, "Failed to bind ZeroMQ socket");
+        return;
+    }
+    info!(endpoint = %endpoint, "ZeroMQ PUB socket bound");
+    while let Some((topic, payload)) = rx.recv().await {
+        let mut message = ZmqMessage::from(topic);
+        message.push_back(Bytes::from(payload));
+        if let Err(e) = socket.send(message).await {
+            error!(endpoint = %endpoint, error = %e, "Failed to publish on ZeroMQ socket");
+        }
+    }
+}
+
+impl Sink for ZmqSink {
+    fn name(&self) -> &str {
+        "zeromq"
+    }
+
+    fn send<'a>(&'a self, event: &'a Event) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let topic = serde_json::to_value(event)?["type"].as_str().unwrap_or_default().to_string();
+            let payload = envelope(event, self.format)?;
+            self.tx.try_send((topic, payload)).map_err(|e| anyhow!("ZeroMQ queue unavailable: {}", e))
+        })
+    }
+}
@@ -0,0 +1,799 @@
+use crate::amm::{quote_swap, PoolState, SwapDirection};
+use crate::config::{ComputeBudgetConfig, SniperConfig, SniperMode};
+use crate::jito::JitoClient;
+use crate::markets::MarketWatcher;
+use crate::priority_fee::PriorityFeeEstimator;
+use crate::raydium::{self, Initialize2Accounts, MarketAccounts, NewPool};
+use crate::store::{Position, Store};
+use crate::wallet::{Wallet, Wallets};
+use anyhow::{anyhow, Result};
+use tracing::{error, info, warn};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::{
+        RpcSendTransactionConfig, RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig,
+        RpcTransactionConfig,
+    },
+    rpc_response::RpcSimulateTransactionResult,
+};
+use solana_program::program_pack::Pack;
+use solana_sdk::{
+    account::Account,
+    commitment_config::CommitmentConfig,
+    compute_budget::ComputeBudgetInstruction,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    native_token::{lamports_to_sol, sol_to_lamports},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    signature::Signature,
+    system_instruction, system_program,
+    transaction::Transaction,
+};
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, UiTransactionEncoding, UiTransactionStatusMeta, UiTransactionTokenBalance,
+};
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+const CONFIRM_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+// bundle status checks once a broadcast buy confirmed, while Jito has not
+// settled it yet
+const BUNDLE_STATUS_ATTEMPTS: u32 = 3;
+const BUNDLE_STATUS_RETRY: Duration = Duration::from_millis(500);
+// tuned limits never go below this, whatever the simulation reported
+const MIN_COMPUTE_UNITS: u64 = 10_000;
+
+/// Quote for a single buy.
+struct Quote {
+    token_mint: Pubkey,
+    amount_in: u64,
+    expected_out: u64,
+    minimum_out: u64,
+}
+
+/// A confirmed live buy.
+struct Fill {
+    signature: Signature,
+    // tokens actually received
+    tokens: u64,
+    // "rpc" or "jito"
+    route: &'static str,
+    // the routes that accepted it
+    routes: Vec<String>,
+}
+
+/// A sent transaction and the routes that accepted it: "rpc_url", broadcast
+/// endpoint hosts and "jito".
+#[derive(Debug, PartialEq)]
+struct Sent {
+    signature: Signature,
+    routes: Vec<String>,
+    bundle_id: Option<String>,
+}
+
+/// A sent sell as the RPC reports it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SellStatus {
+    Pending,
+    Failed(String),
+    // lamports received
+    Landed(u64),
+}
+
+/// Compute unit limit and price (micro-lamports per unit) of a trade.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ComputeBudget {
+    units: u32,
+    price: u64,
+}
+
+impl ComputeBudget {
+    fn instructions(&self) -> [Instruction; 2] {
+        [
+            ComputeBudgetInstruction::set_compute_unit_limit(self.units),
+            ComputeBudgetInstruction::set_compute_unit_price(self.price),
+        ]
+    }
+
+    /// The budget of a transaction that used `consumed` units in simulation:
+    /// the headroom above them, between MIN_COMPUTE_UNITS and this limit, at
+    /// the raised price capped by max_fee_lamports.
+    fn tuned(self, consumed: u64, config: &ComputeBudgetConfig) -> Self {
+        let units = consumed
+            .saturating_mul(100 + config.headroom_percent)
+            .div_ceil(100)
+            .max(MIN_COMPUTE_UNITS)
+            .min(self.units as u64) as u32;
+        let mut price = self.price.saturating_mul(100 + config.price_headroom_percent) / 100;
+        if let Some(max_fee_lamports) = config.max_fee_lamports {
+            price = price.min(max_fee_lamports.saturating_mul(1_000_000) / units.max(1) as u64);
+        }
+        Self { units, price }
+    }
+}
+
+pub struct Sniper {
+    config: SniperConfig,
+    // no keypair is loaded in paper mode
+    payer: Option<Wallet>,
+    rpc_client: Arc<RpcClient>,
+    // broadcast_rpc_urls by host, for logs without the API key
+    broadcast: Vec<(String, Arc<RpcClient>)>,
+    jito: Option<JitoClient>,
+    priority_fee: Option<PriorityFeeEstimator>,
+    store: Arc<Store>,
+    // markets seen before their pool, whose swap accounts need no fetch
+    markets: Option<Arc<MarketWatcher>>,
+}
+
+impl Sniper {
+    pub fn new(
+        config: SniperConfig,
+        rpc_client: Arc<RpcClient>,
+        wallets: &Wallets,
+        store: Arc<Store>,
+    ) -> Result<Self> {
+        let payer = match config.mode {
+            SniperMode::Live => {
+                let wallet = wallets.get(&config.wallet)?.clone();
+                info!(wallet = %wallet.label, pubkey = %wallet.pubkey(), "Sniper enabled");
+                Some(wallet)
+            }
+            SniperMode::Paper => {
+                info!(mode = "paper", "Sniper enabled, no transactions will be sent");
+                None
+            }
+        };
+        let broadcast = config
+            .broadcast_rpc_urls
+            .iter()
+            .map(|url| {
+                let host = reqwest::Url::parse(url)
+                    .map_err(|_| anyhow!("invalid [sniper] broadcast_rpc_urls entry"))?
+                    .host_str()
+                    .unwrap_or_default()
+                    .to_string();
+                Ok((host, Arc::new(RpcClient::new_with_commitment(url.clone(), CommitmentConfig::confirmed()))))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let jito = config.jito.as_ref().map(JitoClient::new);
+        let priority_fee = config
+            .priority_fee
+            .clone()
+            .map(|fee_config| PriorityFeeEstimator::new(fee_config, rpc_client.clone()));
+        Ok(Self { config, payer, rpc_client, broadcast, jito, priority_fee, store, markets: None })
+    }
+
+    pub fn with_markets(mut self, markets: Arc<MarketWatcher>) -> Self {
+        self.markets = Some(markets);
+        self
+    }
+
+    /// Startup work: refreshes the Jito tip accounts.
+    pub async fn prepare(&self) {
+        if let Some(jito) = &self.jito {
+            match jito.refresh_tip_accounts().await {
+                Ok(()) => info!("Loaded Jito tip accounts"),
+                Err(e) => warn!(error = %e, "Failed to load Jito tip accounts, using defaults"),
+            }
+        }
+    }
+
+    /// Checks the buy criteria, returning the initial SOL-side liquidity in
+    /// lamports.
+    fn check_criteria(&self, pool: &NewPool) -> Result<u64> {
+        let wsol = spl_token::native_mint::id();
+        let sol_liquidity = if pool.accounts.pc_mint == wsol {
+            pool.init_pc_amount
+        } else if pool.accounts.coin_mint == wsol {
+            pool.init_coin_amount
+        } else {
+            return Err(anyhow!("pool is not paired with SOL"));
+        };
+
+        if sol_liquidity < sol_to_lamports(self.config.min_liquidity_sol) {
+            return Err(anyhow!("SOL liquidity below {} SOL", self.config.min_liquidity_sol));
+        }
+        if let Some(max) = self.config.max_liquidity_sol {
+            if sol_liquidity > sol_to_lamports(max) {
+                return Err(anyhow!("SOL liquidity above {} SOL", max));
+            }
+        }
+
+        let now = unix_now();
+        if pool.open_time > now + self.config.max_open_wait_secs {
+            return Err(anyhow!("open_time is {} seconds away", pool.open_time - now));
+        }
+        Ok(sol_liquidity)
+    }
+
+    pub async fn on_new_pool(&self, pool: NewPool) {
+        if let Err(reason) = self.check_criteria(&pool) {
+            info!(pool = %pool.accounts.amm, reason = %reason, "Sniper skipped pool");
+            return;
+        }
+
+        // wait for the pool to open
+        let now = unix_now();
+        if pool.open_time > now {
+            info!(pool = %pool.accounts.amm, wait_secs = pool.open_time - now, "Waiting for pool to open");
+            tokio::time::sleep(Duration::from_secs(pool.open_time - now)).await;
+        }
+
+        let quote = self.quote(&pool);
+        let (wallet, fill) = match &self.payer {
+            Some(payer) => match self.buy(payer.keypair(), &pool, &quote).await {
+                Ok(fill) => (payer.label.clone(), Some(fill)),
+                Err(e) => {
+                    error!(pool = %pool.accounts.amm, error = %e, "Sniper buy failed");
+                    return;
+                }
+            },
+            // paper mode records a hypothetical fill at the quote
+            None => ("paper".to_string(), None),
+        };
+
+        let (entry_signature, tokens, entry_route, entry_routes) = match fill {
+            Some(fill) => (Some(fill.signature.to_string()), fill.tokens, Some(fill.route.to_string()), Some(fill.routes.join(","))),
+            None => (None, quote.expected_out, None, None),
+        };
+        let position = Position {
+            id: 0,
+            wallet,
+            paper: self.payer.is_none(),
+            amm: pool.accounts.amm.to_string(),
+            token_mint: quote.token_mint.to_string(),
+            pool_accounts: serde_json::to_string(&pool.accounts).unwrap_or_default(),
+            sol_is_pc: pool.accounts.pc_mint == spl_token::native_mint::id(),
+            cost_lamports: quote.amount_in,
+            tokens,
+            entry_signature,
+            entry_route,
+            entry_routes,
+            opened_at: unix_now(),
+            last_value_lamports: None,
+            last_marked_at: None,
+            closed_at: None,
+            proceeds_lamports: None,
+            peak_value_lamports: None,
+            exit_reason: None,
+            exit_signature: None,
+            sell_signature: None,
+            sell_sent_at: None,
+            sell_attempts: 0,
+            sell_retry_at: None,
+        };
+        match self.store.insert_position(&position) {
+            Ok(id) => info!(
+                position = id,
+                paper = position.paper,
+                pool = %position.amm,
+                cost_sol = lamports_to_sol(position.cost_lamports),
+                tokens = position.tokens,
+                route = ?position.entry_route,
+                "Opened position"
+            ),
+            Err(e) => error!(pool = %position.amm, error = %e, "Failed to record position"),
+        }
+    }
+
+    /// Expected token output at the initial reserves.
+    fn quote(&self, pool: &NewPool) -> Quote {
+        let accounts = &pool.accounts;
+        let (direction, token_mint) = if accounts.pc_mint == spl_token::native_mint::id() {
+            (SwapDirection::PcToCoin, accounts.coin_mint)
+        } else {
+            (SwapDirection::CoinToPc, accounts.pc_mint)
+        };
+        let state = PoolState::from_reserves(pool.init_coin_amount, pool.init_pc_amount);
+        let amount_in = sol_to_lamports(self.config.buy_amount_sol);
+        let swap = quote_swap(&state, amount_in, direction);
+        let minimum_out = swap.minimum_out(self.config.slippage_bps);
+        if minimum_out == 0 {
+            warn!(pool = %accounts.amm, "Minimum output rounds to zero");
+        }
+        Quote { token_mint, amount_in, expected_out: swap.amount_out, minimum_out }
+    }
+
+    /// Sends the buy and waits for confirmation.
+    async fn buy(&self, payer: &Keypair, pool: &NewPool, quote: &Quote) -> Result<Fill> {
+        let wsol = spl_token::native_mint::id();
+        let owner = payer.pubkey();
+        let accounts = &pool.accounts;
+        let market = self.market_accounts(accounts)?;
+        let wsol_account = associated_token_address(&owner, &wsol);
+        let token_account = associated_token_address(&owner, &quote.token_mint);
+        let budget = ComputeBudget { units: self.config.compute_unit_limit, price: self.compute_unit_price(accounts).await };
+
+        let instructions = vec![
+            create_associated_token_account_idempotent(&owner, &owner, &wsol),
+            system_instruction::transfer(&owner, &wsol_account, quote.amount_in),
+            spl_token::instruction::sync_native(&spl_token::id(), &wsol_account)?,
+            create_associated_token_account_idempotent(&owner, &owner, &quote.token_mint),
+            raydium::swap_base_in(accounts, &market, &wsol_account, &token_account, &owner, quote.amount_in, quote.minimum_out),
+            spl_token::instruction::close_account(&spl_token::id(), &wsol_account, &owner, &owner, &[])?,
+        ];
+
+        let blockhash = self.rpc_client.get_latest_blockhash()?;
+        let mut transaction = self.build_transaction(payer, budget, instructions.clone(), blockhash)?;
+        let consumed = if self.config.simulate {
+            self.simulate_buy(&transaction, &owner, quote)?
+        } else {
+            self.simulated_units(&transaction)
+        };
+        if let Some(tuned) = self.tuned_budget(budget, consumed) {
+            transaction = self.build_transaction(payer, tuned, instructions, blockhash)?;
+        }
+        let sent = self.send(&transaction, &format!("buy on pool {}", accounts.amm)).await?;
+        let meta = self.confirm(&sent.signature).await?;
+        let route = self.landed_via(&sent).await;
+        let received = token_balance(&meta.post_token_balances, &owner, &quote.token_mint)
+            .saturating_sub(token_balance(&meta.pre_token_balances, &owner, &quote.token_mint));
+        if received == 0 {
+            return Err(anyhow!("transaction {} received no tokens", sent.signature));
+        }
+        Ok(Fill { signature: sent.signature, tokens: received, route, routes: sent.routes })
+    }
+
+    fn market_accounts(&self, accounts: &Initialize2Accounts) -> Result<MarketAccounts> {
+        match self.markets.as_ref().and_then(|markets| markets.market_accounts(&accounts.serum_market)) {
+            Some(market) => Ok(market),
+            None => raydium::fetch_market_accounts(&self.rpc_client, &accounts.serum_market, &accounts.serum_program),
+        }
+    }
+
+    /// Signs a sell of the position's tokens still in the wallet, None when it
+    /// holds none, e.g. because an earlier sell landed.
+    pub async fn prepare_sell(&self, position: &Position) -> Result<Option<Transaction>> {
+        let payer = match &self.payer {
+            Some(payer) if payer.label == position.wallet => payer.keypair(),
+            _ => return Err(anyhow!("wallet '{}' is not the active sniper wallet", position.wallet)),
+        };
+        let wsol = spl_token::native_mint::id();
+        let owner = payer.pubkey();
+        let accounts: Initialize2Accounts = serde_json::from_str(&position.pool_accounts)?;
+        let token_mint = Pubkey::from_str(&position.token_mint)?;
+        let market = self.market_accounts(&accounts)?;
+        let wsol_account = associated_token_address(&owner, &wsol);
+        let token_account = associated_token_address(&owner, &token_mint);
+        // never more than the position, the wallet may hold the token otherwise
+        let tokens = self.token_account_balance(&token_account)?.min(position.tokens);
+        if tokens == 0 {
+            return Ok(None);
+        }
+
+        let (coin_reserve, pc_reserve) = raydium::fetch_vault_reserves(&self.rpc_client, &accounts)?;
+        let direction = if position.sol_is_pc { SwapDirection::CoinToPc } else { SwapDirection::PcToCoin };
+        let swap = quote_swap(&PoolState::from_reserves(coin_reserve, pc_reserve), tokens, direction);
+        let minimum_out = swap.minimum_out(self.config.slippage_bps);
+        let budget = ComputeBudget { units: self.config.compute_unit_limit, price: self.compute_unit_price(&accounts).await };
+
+        let instructions = vec![
+            create_associated_token_account_idempotent(&owner, &owner, &wsol),
+            raydium::swap_base_in(&accounts, &market, &token_account, &wsol_account, &owner, tokens, minimum_out),
+            spl_token::instruction::close_account(&spl_token::id(), &wsol_account, &owner, &owner, &[])?,
+        ];
+
+        let blockhash = self.rpc_client.get_latest_blockhash()?;
+        let mut transaction = self.build_transaction(payer, budget, instructions.clone(), blockhash)?;
+        if let Some(tuned) = self.tuned_budget(budget, self.simulated_units(&transaction)) {
+            transaction = self.build_transaction(payer, tuned, instructions, blockhash)?;
+        }
+        Ok(Some(transaction))
+    }
+
+    /// Sends a sell from `prepare_sell` without waiting for confirmation;
+    /// `sell_status` follows it up.
+    pub async fn send_sell(&self, position: &Position, transaction: &Transaction) -> Result<()> {
+        self.send(transaction, &format!("sell of position {}", position.id)).await?;
+        Ok(())
+    }
+
+    /// Where a sent sell stands, checked once. A transaction the RPC does not
+    /// know yet is pending.
+    pub fn sell_status(&self, signature: &Signature) -> SellStatus {
+        let Ok(tx) = self.rpc_client.get_transaction_with_config(signature, transaction_config()) else {
+            return SellStatus::Pending;
+        };
+        let Some(meta) = tx.transaction.meta else {
+            return SellStatus::Pending;
+        };
+        if let Some(err) = meta.err {
+            return SellStatus::Failed(err.to_string());
+        }
+        // proceeds are the payer's SOL delta plus the fee and tip paid
+        let tip = self.config.jito.as_ref().map(|jito| jito.tip_lamports).unwrap_or(0);
+        let delta = meta.post_balances[0] as i64 - meta.pre_balances[0] as i64 + meta.fee as i64 + tip as i64;
+        SellStatus::Landed(delta.max(0) as u64)
+    }
+
+    /// Raw balance of a token account, 0 when it does not exist.
+    fn token_account_balance(&self, token_account: &Pubkey) -> Result<u64> {
+        match self.rpc_client.get_account_with_commitment(token_account, CommitmentConfig::confirmed())?.value {
+            Some(account) => Ok(spl_token::state::Account::unpack(&account.data)?.amount),
+            None => Ok(0),
+        }
+    }
+
+    /// Priority fee estimated from the pool's writable accounts, or the fixed
+    /// compute_unit_price when no estimator is configured.
+    async fn compute_unit_price(&self, pool: &Initialize2Accounts) -> u64 {
+        match &self.priority_fee {
+            Some(estimator) => {
+                let accounts = [pool.amm, pool.coin_vault, pool.pc_vault, pool.amm_open_orders];
+                estimator.estimate(&accounts, self.config.compute_unit_price).await
+            }
+            None => self.config.compute_unit_price,
+        }
+    }
+
+    /// Signs the transaction behind its compute budget, adding a tip transfer
+    /// when Jito is configured.
+    fn build_transaction(&self, payer: &Keypair, budget: ComputeBudget, instructions: Vec<Instruction>, blockhash: Hash) -> Result<Transaction> {
+        let owner = payer.pubkey();
+        let mut instructions = [budget.instructions().to_vec(), instructions].concat();
+        if let (Some(jito), Some(jito_config)) = (&self.jito, &self.config.jito) {
+            instructions.push(system_instruction::transfer(&owner, &jito.next_tip_account(), jito_config.tip_lamports));
+        }
+        Ok(Transaction::new_signed_with_payer(&instructions, Some(&owner), &[payer], blockhash))
+    }
+
+    /// The compute units a trade used in simulation, when [sniper.compute_budget]
+    /// is configured. A failed simulation, or one that reports no units,
+    /// leaves the fixed budget.
+    fn simulated_units(&self, transaction: &Transaction) -> Option<u64> {
+        self.config.compute_budget.as_ref()?;
+        match self.simulate(transaction, Vec::new()) {
+            Ok(result) if result.err.is_none() => result.units_consumed.filter(|units| *units > 0),
+            Ok(result) => {
+                warn!(err = ?result.err, "Simulation failed, keeping the fixed compute budget");
+                None
+            }
+            Err(e) => {
+                warn!(error = %e, "Failed to simulate, keeping the fixed compute budget");
+                None
+            }
+        }
+    }
+
+    /// The budget tuned to the simulated consumption, when [sniper.compute_budget]
+    /// is configured and the simulation reported units.
+    fn tuned_budget(&self, budget: ComputeBudget, consumed: Option<u64>) -> Option<ComputeBudget> {
+        let (Some(config), Some(consumed)) = (&self.config.compute_budget, consumed.filter(|units| *units > 0)) else {
+            return None;
+        };
+        let tuned = budget.tuned(consumed, config);
+        info!(consumed, units = tuned.units, micro_lamports = tuned.price, "Tuned compute budget");
+        Some(tuned)
+    }
+
+    fn simulate(&self, transaction: &Transaction, addresses: Vec<String>) -> Result<RpcSimulateTransactionResult> {
+        Ok(self
+            .rpc_client
+            .simulate_transaction_with_config(
+                transaction,
+                RpcSimulateTransactionConfig {
+                    sig_verify: false,
+                    replace_recent_blockhash: true,
+                    commitment: Some(CommitmentConfig::processed()),
+                    accounts: Some(RpcSimulateTransactionAccountsConfig { encoding: Some(UiAccountEncoding::Base64), addresses }),
+                    ..RpcSimulateTransactionConfig::default()
+                },
+            )?
+            .value)
+    }
+
+    /// Simulates the buy before sending and rejects it when the simulation fails,
+    /// running out of compute included, or the tokens it adds to the wallet
+    /// deviate too far from the quote. Returns the compute units used, None
+    /// when the RPC does not report them.
+    fn simulate_buy(&self, transaction: &Transaction, owner: &Pubkey, quote: &Quote) -> Result<Option<u64>> {
+        let token_account = associated_token_address(owner, &quote.token_mint);
+        // the wallet may already hold the token
+        let balance_before = self.token_account_balance(&token_account)?;
+        let result = self.simulate(transaction, vec![token_account.to_string()])?;
+
+        let units = result.units_consumed.filter(|units| *units > 0);
+        let balance_after = result
+            .accounts
+            .and_then(|accounts| accounts.into_iter().next().flatten())
+            .and_then(|account| account.decode::<Account>())
+            .and_then(|account| spl_token::state::Account::unpack(&account.data).ok())
+            .map(|account| account.amount)
+            .unwrap_or(0);
+        let received = balance_after.saturating_sub(balance_before);
+        info!(
+            token = %quote.token_mint,
+            err = ?result.err,
+            units = ?units,
+            received,
+            quoted = quote.expected_out,
+            "Simulated buy"
+        );
+
+        if let Some(err) = result.err {
+            for line in result.logs.unwrap_or_default() {
+                warn!(log = %line, "Simulation log");
+            }
+            return Err(anyhow!("simulation failed: {}", err));
+        }
+        let deviation_bps = quote.expected_out.abs_diff(received) as u128 * 10_000 / quote.expected_out.max(1) as u128;
+        if deviation_bps > self.config.simulation_tolerance_bps as u128 {
+            return Err(anyhow!(
+                "simulated output {} deviates {} bps from quote {}",
+                received,
+                deviation_bps,
+                quote.expected_out
+            ));
+        }
+        Ok(units)
+    }
+
+    /// Sends a signed transaction. With broadcast_rpc_urls it goes out on
+    /// every route at once; otherwise as a Jito bundle waiting for it to land
+    /// when configured, or straight to the RPC.
+    async fn send(&self, transaction: &Transaction, what: &str) -> Result<Sent> {
+        let signature = transaction.signatures[0];
+        if !self.broadcast.is_empty() {
+            return self.broadcast(transaction, what).await;
+        }
+        let (Some(jito), Some(jito_config)) = (&self.jito, &self.config.jito) else {
+            self.rpc_client.send_transaction_with_config(transaction, send_config())?;
+            info!(what, signature = %signature, "Sniper transaction sent");
+            return Ok(Sent { signature, routes: vec!["rpc_url".to_string()], bundle_id: None });
+        };
+
+        let bundle_id = jito.send_bundle(std::slice::from_ref(transaction)).await?;
+        info!(
+            what,
+            bundle_id = %bundle_id,
+            tip_lamports = jito_config.tip_lamports,
+            signature = %signature,
+            "Sniper transaction sent as bundle"
+        );
+        let status = jito
+            .wait_for_bundle(&bundle_id, Duration::from_secs(jito_config.status_timeout_secs))
+            .await?;
+        if status != "Landed" {
+            return Err(anyhow!("bundle {} {}", bundle_id, status.to_lowercase()));
+        }
+        info!(bundle_id = %bundle_id, "Sniper bundle landed");
+        Ok(Sent { signature, routes: vec!["jito".to_string()], bundle_id: Some(bundle_id) })
+    }
+
+    /// Sends a signed transaction to rpc_url and each broadcast endpoint at
+    /// once, and as a Jito bundle when configured, without waiting for the
+    /// bundle.
+    async fn broadcast(&self, transaction: &Transaction, what: &str) -> Result<Sent> {
+        let endpoints = std::iter::once(("rpc_url".to_string(), self.rpc_client.clone())).chain(self.broadcast.iter().cloned());
+        let send = |rpc_client: Arc<RpcClient>| {
+            let transaction = transaction.clone();
+            async move {
+                tokio::task::spawn_blocking(move || -> Result<()> {
+                    rpc_client.send_transaction_with_config(&transaction, send_config())?;
+                    Ok(())
+                })
+                .await
+                .unwrap_or_else(|e| Err(e.into()))
+            }
+        };
+        let bundle = async {
+            match &self.jito {
+                Some(jito) => Some(jito.send_bundle(std::slice::from_ref(transaction)).await),
+                None => None,
+            }
+        };
+        send_all(transaction.signatures[0], endpoints.collect(), send, bundle, what).await
+    }
+
+    /// The route a confirmed transaction landed through. A broadcast bundle's
+    /// status is checked a few times while Jito has not settled it.
+    async fn landed_via(&self, sent: &Sent) -> &'static str {
+        let (Some(jito), Some(bundle_id)) = (&self.jito, &sent.bundle_id) else {
+            return landed_route(sent, None);
+        };
+        // without another route, the bundle is how it landed
+        if sent.routes.len() == 1 {
+            return landed_route(sent, None);
+        }
+        let mut status = None;
+        for attempt in 1..=BUNDLE_STATUS_ATTEMPTS {
+            match jito.bundle_status(bundle_id).await {
+                Ok(settled) if settled == "Landed" || settled == "Failed" => return landed_route(sent, Some(&settled)),
+                Ok(pending) => status = Some(pending),
+                Err(e) => warn!(bundle_id, attempt, error = %e, "Failed to get bundle status"),
+            }
+            if attempt < BUNDLE_STATUS_ATTEMPTS {
+                tokio::time::sleep(BUNDLE_STATUS_RETRY).await;
+            }
+        }
+        info!(bundle_id, status = ?status, "Bundle not settled, assuming an RPC landed the transaction");
+        landed_route(sent, status.as_deref())
+    }
+
+    /// Waits for confirmation and returns the transaction meta.
+    async fn confirm(&self, signature: &Signature) -> Result<UiTransactionStatusMeta> {
+        let config = transaction_config();
+        let started = std::time::Instant::now();
+        let tx = loop {
+            match self.rpc_client.get_transaction_with_config(signature, config) {
+                Ok(tx) => break tx,
+                Err(_) if started.elapsed() < CONFIRM_TIMEOUT => tokio::time::sleep(CONFIRM_POLL_INTERVAL).await,
+                Err(e) => return Err(anyhow!("transaction {} not confirmed: {}", signature, e)),
+            }
+        };
+        let meta = tx.transaction.meta.ok_or_else(|| anyhow!("transaction {} has no meta", signature))?;
+        if let Some(err) = &meta.err {
+            return Err(anyhow!("transaction {} failed: {}", signature, err));
+        }
+        Ok(meta)
+    }
+}
+
+fn transaction_config() -> RpcTransactionConfig {
+    RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Json),
+        commitment: Some(CommitmentConfig::confirmed()),
+        max_supported_transaction_version: Some(0),
+    }
+}
+
+/// Sends through every endpoint at once, alongside the bundle when there is
+/// one, and logs which accepted. Fails only when no route accepted.
+async fn send_all<C, F, S, B>(signature: Signature, endpoints: Vec<(String, C)>, send: F, bundle: B, what: &str) -> Result<Sent>
+where
+    F: Fn(C) -> S,
+    S: Future<Output = Result<()>>,
+    B: Future<Output = Option<Result<String>>>,
+{
+    let started = Instant::now();
+    let sends = endpoints.into_iter().map(|(endpoint, client)| {
+        let sent = send(client);
+        async move { (endpoint, sent.await, started.elapsed()) }
+    });
+    let (sent, bundle) = tokio::join!(futures::future::join_all(sends), bundle);
+
+    let mut routes = Vec::new();
+    for (endpoint, sent, elapsed) in sent {
+        match sent {
+            Ok(()) => {
+                info!(what, endpoint = %endpoint, elapsed_ms = elapsed.as_millis() as u64, "Sniper transaction accepted");
+                routes.push(endpoint);
+            }
+            Err(e) => warn!(what, endpoint = %endpoint, error = %e, "Endpoint rejected sniper transaction"),
+        }
+    }
+    let bundle_id = match bundle {
+        Some(Ok(bundle_id)) => {
+            info!(what, bundle_id = %bundle_id, "Sniper transaction accepted as bundle");
+            routes.push("jito".to_string());
+            Some(bundle_id)
+        }
+        Some(Err(e)) => {
+            warn!(what, error = %e, "Jito rejected sniper bundle");
+            None
+        }
+        None => None,
+    };
+    if routes.is_empty() {
+        return Err(anyhow!("no route accepted the {}", what));
+    }
+    info!(what, signature = %signature, routes = %routes.join(","), "Sniper transaction broadcast");
+    Ok(Sent { signature, routes, bundle_id })
+}
+
+/// "jito" when the transaction's bundle landed or was its only accepted
+/// route, otherwise "rpc".
+fn landed_route(sent: &Sent, bundle_status: Option<&str>) -> &'static str {
+    match (&sent.bundle_id, bundle_status) {
+        (None, _) => "rpc",
+        (Some(_), Some("Landed")) => "jito",
+        (Some(_), _) if sent.routes == ["jito"] => "jito",
+        _ => "rpc",
+    }
+}
+
+/// Sniper transactions are built for a single attempt: preflight would only
+/// add latency and retries would land them late.
+fn send_config() -> RpcSendTransactionConfig {
+    RpcSendTransactionConfig { skip_preflight: true, max_retries: Some(0), ..RpcSendTransactionConfig::default() }
+}
+
+/// Raw balance of `mint` held by `owner` in a transaction's token balances.
+pub fn token_balance(balances: &OptionSerializer<Vec<UiTransactionTokenBalance>>, owner: &Pubkey, mint: &Pubkey) -> u64 {
+    let (owner, mint) = (owner.to_string(), mint.to_string());
+    match balances {
+        OptionSerializer::Some(balances) => balances
+            .iter()
+            .find(|b| b.mint == mint && b.owner == OptionSerializer::Some(owner.clone()))
+            .and_then(|b| b.ui_token_amount.amount.parse().ok())
+            .unwrap_or(0),
+        _ => 0,
+    }
+}
+
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    let program_id = Pubkey::from_str(ASSOCIATED_TOKEN_PROGRAM_ID).unwrap();
+    Pubkey::find_program_address(&[owner.as_ref(), spl_token::id().as_ref(), mint.as_ref()], &program_id).0
+}
+
+fn create_associated_token_account_idempotent(payer: &Pubkey, owner: &Pubkey, mint: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: Pubkey::from_str(ASSOCIATED_TOKEN_PROGRAM_ID).unwrap(),
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(associated_token_address(owner, mint), false),
+            AccountMeta::new_readonly(*owner, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        // CreateIdempotent
+        data: vec![1],
+    }
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tunes_the_budget_to_the_simulation() {
+        let budget = ComputeBudget { units: 200_000, price: 100_000 };
+        let config = ComputeBudgetConfig { headroom_percent: 20, price_headroom_percent: 50, max_fee_lamports: None };
+        assert_eq!(budget.tuned(60_001, &config), ComputeBudget { units: 72_002, price: 150_000 });
+        // the configured limit stays the ceiling, MIN_COMPUTE_UNITS the floor
+        assert_eq!(budget.tuned(190_000, &config).units, 200_000);
+        assert_eq!(budget.tuned(0, &config).units, MIN_COMPUTE_UNITS as u32);
+
+        let capped = ComputeBudgetConfig { max_fee_lamports: Some(6_000), ..config };
+        assert_eq!(budget.tuned(50_000, &capped), ComputeBudget { units: 60_000, price: 100_000 });
+    }
+
+    #[tokio::test]
+    async fn broadcast_records_the_routes_that_accepted() {
+        let signature = Signature::new_unique();
+        let endpoints = || vec![("rpc_url".to_string(), true), ("fast.rpc".to_string(), false), ("other.rpc".to_string(), true)];
+        let send = |accepts: bool| async move { if accepts { Ok(()) } else { Err(anyhow!("rejected")) } };
+
+        let sent = send_all(signature, endpoints(), send, async { Some(Ok("bundle".to_string())) }, "buy").await.unwrap();
+        assert_eq!(sent.routes, ["rpc_url", "other.rpc", "jito"]);
+        assert_eq!(sent.bundle_id.as_deref(), Some("bundle"));
+
+        // a rejected bundle leaves the endpoints that accepted
+        let sent = send_all(signature, endpoints(), send, async { Some(Err(anyhow!("rate limited"))) }, "buy").await.unwrap();
+        assert_eq!((sent.routes, sent.bundle_id), (vec!["rpc_url".to_string(), "other.rpc".to_string()], None));
+
+        let rejected = vec![("rpc_url".to_string(), false)];
+        let only_jito = send_all(signature, rejected.clone(), send, async { Some(Ok("bundle".to_string())) }, "buy").await.unwrap();
+        assert_eq!(only_jito.routes, ["jito"]);
+        assert!(send_all(signature, rejected, send, async { Some(Err(anyhow!("rate limited"))) }, "buy").await.is_err());
+    }
+
+    #[test]
+    fn attributes_the_landing_route() {
+        let sent = |routes: &[&str], bundle_id: Option<&str>| Sent {
+            signature: Signature::default(),
+            routes: routes.iter().map(|route| route.to_string()).collect(),
+            bundle_id: bundle_id.map(str::to_string),
+        };
+        assert_eq!(landed_route(&sent(&["rpc_url"], None), None), "rpc");
+        assert_eq!(landed_route(&sent(&["jito"], Some("bundle")), None), "jito");
+        let raced = sent(&["rpc_url", "jito"], Some("bundle"));
+        assert_eq!(landed_route(&raced, Some("Landed")), "jito");
+        assert_eq!(landed_route(&raced, Some("Failed")), "rpc");
+        // still pending after the retries, or no status at all
+        assert_eq!(landed_route(&raced, Some("Pending")), "rpc");
+        assert_eq!(landed_route(&raced, None), "rpc");
+    }
+}
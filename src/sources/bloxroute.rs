@@ -0,0 +1,68 @@
+use super::stream::Subscription;
+use super::Source;
+use crate::config::BloxrouteConfig;
+use anyhow::{anyhow, Result};
+use futures::future::BoxFuture;
+use serde_json::json;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// bloXroute Trader API `GetNewRaydiumPoolsStream`. The stream carries pool
+/// addresses rather than transactions, so the initialize2 signature is
+/// looked up as the pool account's oldest signature.
+pub struct BloxrouteSource {
+    config: BloxrouteConfig,
+    rpc_client: Arc<RpcClient>,
+}
+
+impl BloxrouteSource {
+    pub fn new(config: BloxrouteConfig, rpc_client: Arc<RpcClient>) -> Self {
+        Self { config, rpc_client }
+    }
+
+    fn creation_signature(&self, amm: &Pubkey) -> Result<Signature> {
+        let signatures = self.rpc_client.get_signatures_for_address(amm)?;
+        let oldest = signatures.last().ok_or_else(|| anyhow!("no signatures for the pool yet"))?;
+        Ok(Signature::from_str(&oldest.signature)?)
+    }
+}
+
+impl Source for BloxrouteSource {
+    fn name(&self) -> &str {
+        "bloxroute"
+    }
+
+    fn run<'a>(&'a self, tx: mpsc::Sender<Signature>) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let subscribe = json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "subscribe",
+                "params": ["GetNewRaydiumPoolsStream", { "includeCPMM": false }],
+            });
+            let mut subscription = Subscription::open(&self.config.ws_url, Some(&self.config.auth_header), subscribe).await?;
+            info!(ws_url = %self.config.ws_url, "Subscribed to bloXroute new Raydium pools");
+
+            while let Some(result) = subscription.next().await {
+                let result = result?;
+                let Some(amm) = result["pool"]["poolAddress"].as_str().and_then(|amm| Pubkey::from_str(amm).ok()) else {
+                    warn!(%result, "bloXroute notification without a pool address");
+                    continue;
+                };
+                match self.creation_signature(&amm) {
+                    Ok(signature) => {
+                        if tx.send(signature).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => warn!(pool = %amm, error = %e, "Failed to find the pool's initialize2 signature"),
+                }
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,343 @@
+#[cfg(feature = "bloxroute")]
+mod bloxroute;
+#[cfg(any(feature = "bloxroute", feature = "triton"))]
+mod stream;
+#[cfg(feature = "triton")]
+mod triton;
+mod replay;
+#[cfg(test)]
+mod scripted;
+mod webhook;
+mod websocket;
+
+use crate::config::Config;
+use crate::programs;
+use crate::recording::Replay;
+use crate::rpc::KeyRing;
+use crate::tui::Dashboard;
+use anyhow::Result;
+use futures::future::BoxFuture;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::signature::Signature;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tracing::{debug, error, info, warn};
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+
+// signatures remembered to drop the same pool arriving from another source
+const RECENT_SIGNATURES: usize = 1000;
+// wait before reconnecting a feed that dropped
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+/// Signatures buffered ahead of the pipeline before sources are held back.
+pub const CHANNEL_CAPACITY: usize = 100;
+
+/// A feed of signatures of transactions creating a pool or opening a
+/// launchpad sale.
+pub trait Source: Send + Sync {
+    fn name(&self) -> &str;
+    /// Sends signatures until the feed ends or the receiver is dropped.
+    fn run<'a>(&'a self, tx: mpsc::Sender<Signature>) -> BoxFuture<'a, Result<()>>;
+}
+
+/// A transaction a feed notified, a creation or not. Every notification
+/// marks how far the feed got, for repairing the gap after a drop.
+#[derive(Debug, Clone, Copy)]
+pub struct Notification {
+    pub signature: Signature,
+    pub creation: bool,
+}
+
+/// A subscription that drops now and then and can look up what it missed,
+/// kept up by [`reconnecting`].
+pub trait Feed: Send + Sync {
+    /// Sends notifications until the connection drops, which is an error, or
+    /// returns Ok once the feed ends or the receiver is gone.
+    fn connect<'a>(&'a self, tx: mpsc::Sender<Notification>) -> BoxFuture<'a, Result<()>>;
+    /// The creations after `until` and before `before`, oldest first.
+    fn missed<'a>(&'a self, until: Signature, before: Signature) -> BoxFuture<'a, Result<Vec<Signature>>>;
+}
+
+/// Runs `feed` as a source, reconnecting after RECONNECT_DELAY whenever it
+/// drops. Once a reconnected feed notifies again, the creations between the
+/// last notification before the drop and that one are sent first, so a drop
+/// loses no pools; the merge drops those another source already delivered.
+pub async fn reconnecting(name: &str, feed: &dyn Feed, tx: mpsc::Sender<Signature>) -> Result<()> {
+    let mut last_seen = None;
+    // a drop after last_seen not repaired yet
+    let mut gap = false;
+    loop {
+        let (notification_tx, mut notifications) = mpsc::channel::<Notification>(CHANNEL_CAPACITY);
+        let forward = async {
+            while let Some(notification) = notifications.recv().await {
+                if let (true, Some(until)) = (std::mem::take(&mut gap), last_seen) {
+                    match feed.missed(until, notification.signature).await {
+                        Ok(missed) => {
+                            info!(source = name, missed = missed.len(), "Repaired the gap after reconnecting");
+                            for signature in missed {
+                                if tx.send(signature).await.is_err() {
+                                    return;
+                                }
+                            }
+                        }
+                        Err(e) => warn!(source = name, error = %e, "Failed to repair the gap after reconnecting"),
+                    }
+                }
+                last_seen = Some(notification.signature);
+                if notification.creation && tx.send(notification.signature).await.is_err() {
+                    return;
+                }
+            }
+        };
+        // a forwarder done early drops the receiver, which ends the connection
+        let (connection, ()) = tokio::join!(feed.connect(notification_tx), forward);
+        match connection {
+            Ok(()) => return Ok(()),
+            Err(e) => warn!(source = name, error = %e, retry_in_secs = RECONNECT_DELAY.as_secs(), "Feed dropped, reconnecting"),
+        }
+        gap = true;
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+/// Alerts once when no source delivered a new creation for `timeout`, and
+/// again after the next silence.
+pub struct Deadman {
+    pub timeout: Duration,
+    pub alert: Box<dyn Fn(Duration) + Send + Sync>,
+}
+
+/// Only the webhook endpoint when `[webhook]` is configured (server mode),
+/// otherwise a WebSocket log subscription per monitored program plus any
+/// configured commercial streams, racing each other.
+pub fn from_config(
+    config: &Config,
+    keys: Arc<KeyRing>,
+    rpc_client: Arc<RpcClient>,
+    dashboard: Option<Arc<Dashboard>>,
+) -> Result<Vec<Arc<dyn Source>>> {
+    if let Some(webhook) = &config.webhook {
+        return Ok(vec![Arc::new(webhook::WebhookSource::new(webhook.clone()))]);
+    }
+    let mut sources: Vec<Arc<dyn Source>> = programs::all()
+        .iter()
+        .map(|program| {
+            Arc::new(websocket::WebSocketSource::new(keys.clone(), rpc_client.clone(), program, dashboard.clone())) as Arc<dyn Source>
+        })
+        .collect();
+    if config.bloxroute.is_some() {
+        add_bloxroute(&mut sources, config, rpc_client)?;
+    }
+    if config.triton.is_some() {
+        add_triton(&mut sources, config)?;
+    }
+    Ok(sources)
+}
+
+/// The recorded WebSocket notifications in place of the live sources.
+pub fn replay(replay: Arc<Replay>) -> Vec<Arc<dyn Source>> {
+    vec![Arc::new(replay::ReplaySource::new(replay))]
+}
+
+/// Whether a log line shows one of the monitored programs creating a pool or
+/// opening a sale.
+fn is_creation_log(line: &str) -> bool {
+    programs::all().iter().any(|program| line.contains(program.creation_log()))
+}
+
+#[cfg(feature = "bloxroute")]
+fn add_bloxroute(sources: &mut Vec<Arc<dyn Source>>, config: &Config, rpc_client: Arc<RpcClient>) -> Result<()> {
+    if let Some(bloxroute) = &config.bloxroute {
+        if bloxroute.auth_header.is_empty() {
+            return Err(anyhow::anyhow!("[bloxroute] requires auth_header"));
+        }
+        sources.push(Arc::new(bloxroute::BloxrouteSource::new(bloxroute.clone(), rpc_client)));
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "bloxroute"))]
+fn add_bloxroute(_sources: &mut Vec<Arc<dyn Source>>, _config: &Config, _rpc_client: Arc<RpcClient>) -> Result<()> {
+    Err(anyhow::anyhow!("[bloxroute] requires building with --features bloxroute"))
+}
+
+#[cfg(feature = "triton")]
+fn add_triton(sources: &mut Vec<Arc<dyn Source>>, config: &Config) -> Result<()> {
+    if let Some(triton) = &config.triton {
+        sources.push(Arc::new(triton::TritonSource::new(triton.clone())));
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "triton"))]
+fn add_triton(_sources: &mut Vec<Arc<dyn Source>>, _config: &Config) -> Result<()> {
+    Err(anyhow::anyhow!("[triton] requires building with --features triton"))
+}
+
+/// Runs every source in its own task and merges them into one channel,
+/// passing each signature on only the first time it arrives, watched by the
+/// deadman when given.
+pub fn spawn(sources: Vec<Arc<dyn Source>>, deadman: Option<Deadman>) -> mpsc::Receiver<Signature> {
+    let (source_tx, mut source_rx) = mpsc::channel::<Signature>(CHANNEL_CAPACITY);
+    for source in sources {
+        let tx = source_tx.clone();
+        tokio::spawn(async move {
+            info!(source = source.name(), "Starting source");
+            if let Err(e) = source.run(tx).await {
+                error!(source = source.name(), error = %e, "Source failed");
+            }
+            warn!(source = source.name(), "Source ended");
+        });
+    }
+    drop(source_tx);
+
+    let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+    tokio::spawn(async move {
+        let mut seen = HashSet::new();
+        let mut order = VecDeque::new();
+        let (mut last_new, mut stalled) = (Instant::now(), false);
+        loop {
+            let received = match deadman.as_ref().filter(|_| !stalled) {
+                Some(deadman) => match tokio::time::timeout_at(last_new + deadman.timeout, source_rx.recv()).await {
+                    Ok(received) => received,
+                    Err(_) => {
+                        warn!(silent_secs = deadman.timeout.as_secs(), "No new pool from any source");
+                        (deadman.alert)(deadman.timeout);
+                        stalled = true;
+                        continue;
+                    }
+                },
+                None => source_rx.recv().await,
+            };
+            let Some(signature) = received else { break };
+            if !seen.insert(signature) {
+                debug!(signature = %signature, "Signature already received from another source");
+                continue;
+            }
+            if std::mem::take(&mut stalled) {
+                info!(silent_secs = last_new.elapsed().as_secs(), "Sources delivering new pools again");
+            }
+            last_new = Instant::now();
+            order.push_back(signature);
+            if order.len() > RECENT_SIGNATURES {
+                if let Some(oldest) = order.pop_front() {
+                    seen.remove(&oldest);
+                }
+            }
+            if tx.send(signature).await.is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::scripted::{ScriptedSource, Step};
+    use super::*;
+    use std::sync::Mutex;
+
+    fn scripted(name: &str, steps: Vec<Step>) -> Arc<dyn Source> {
+        Arc::new(ScriptedSource::new(name, steps))
+    }
+
+    /// Everything the merged channel yields until every source has ended,
+    /// with the virtual time each signature arrived at.
+    async fn received(sources: Vec<Arc<dyn Source>>) -> Vec<(Signature, Duration)> {
+        received_watched(sources, None).await
+    }
+
+    async fn received_watched(sources: Vec<Arc<dyn Source>>, deadman: Option<Deadman>) -> Vec<(Signature, Duration)> {
+        let started = Instant::now();
+        let mut rx = spawn(sources, deadman);
+        let mut received = Vec::new();
+        while let Some(signature) = rx.recv().await {
+            received.push((signature, started.elapsed()));
+        }
+        received
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn passes_each_signature_once_from_the_fastest_source() {
+        let (a, b) = (Signature::new_unique(), Signature::new_unique());
+        let sources = vec![
+            scripted("slow", vec![Step::Stall(Duration::from_millis(300)), Step::Emit(a), Step::Emit(b)]),
+            scripted("fast", vec![Step::Stall(Duration::from_millis(100)), Step::Emit(a), Step::Stall(Duration::from_millis(500)), Step::Emit(b)]),
+        ];
+
+        let received = received(sources).await;
+        assert_eq!(received, vec![(a, Duration::from_millis(100)), (b, Duration::from_millis(300))]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn drops_replayed_notifications() {
+        let signatures = [Signature::new_unique(), Signature::new_unique(), Signature::new_unique()];
+        let steps = vec![
+            Step::Emit(signatures[0]),
+            Step::Emit(signatures[1]),
+            Step::Stall(Duration::from_secs(2)),
+            Step::Replay(2),
+            Step::Emit(signatures[2]),
+        ];
+
+        let received = received(vec![scripted("websocket", steps)]).await;
+        let received = received.into_iter().map(|(signature, _)| signature).collect::<Vec<_>>();
+        assert_eq!(received, signatures);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn repairs_the_gap_after_a_reconnect() {
+        let (a, b, c, d) = (Signature::new_unique(), Signature::new_unique(), Signature::new_unique(), Signature::new_unique());
+        let sources = vec![
+            // b and c are created while the subscription is down
+            scripted("websocket", vec![Step::Emit(a), Step::Disconnect, Step::Missed(b), Step::Missed(c), Step::Emit(d)]),
+            scripted("stream", vec![Step::Stall(Duration::from_secs(2)), Step::Emit(b)]),
+        ];
+
+        let received = received(sources).await;
+        let reconnected = RECONNECT_DELAY;
+        assert_eq!(received, vec![(a, Duration::ZERO), (b, Duration::from_secs(2)), (c, reconnected), (d, reconnected)]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn alerts_once_per_silence() {
+        let (a, b) = (Signature::new_unique(), Signature::new_unique());
+        let minutes = |mins: u64| Duration::from_secs(mins * 60);
+        let steps = vec![
+            Step::Emit(a),
+            Step::Stall(minutes(20)),
+            // a redelivered pool is no sign of life
+            Step::Replay(1),
+            Step::Stall(minutes(20)),
+            Step::Emit(b),
+            Step::Stall(minutes(45)),
+        ];
+        let started = Instant::now();
+        let alerts = Arc::new(Mutex::new(Vec::new()));
+        let deadman = Deadman {
+            timeout: minutes(30),
+            alert: Box::new({
+                let alerts = alerts.clone();
+                move |silent| alerts.lock().unwrap().push((started.elapsed(), silent))
+            }),
+        };
+
+        let received = received_watched(vec![scripted("websocket", steps)], Some(deadman)).await;
+        assert_eq!(received, vec![(a, Duration::ZERO), (b, minutes(40))]);
+        assert_eq!(*alerts.lock().unwrap(), vec![(minutes(30), minutes(30)), (minutes(70), minutes(30))]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn forgets_signatures_beyond_the_dedup_window() {
+        let first = Signature::new_unique();
+        let mut steps = vec![Step::Emit(first)];
+        steps.extend((0..RECENT_SIGNATURES).map(|_| Step::Emit(Signature::new_unique())));
+        steps.push(Step::Emit(first));
+
+        let received = received(vec![scripted("websocket", steps)]).await;
+        assert_eq!(received.len(), RECENT_SIGNATURES + 2);
+        assert_eq!(received.last().map(|(signature, _)| *signature), Some(first));
+    }
+}
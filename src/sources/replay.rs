@@ -0,0 +1,50 @@
+use super::websocket::creation_signature;
+use super::Source;
+use crate::recording::Replay;
+use anyhow::Result;
+use futures::future::BoxFuture;
+use solana_sdk::signature::Signature;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use std::sync::Arc;
+
+/// The log notifications of a recorded session, filtered like the live
+/// WebSocket source, at the replay's speed. Ends after the last
+/// notification.
+pub struct ReplaySource {
+    replay: Arc<Replay>,
+}
+
+impl ReplaySource {
+    pub fn new(replay: Arc<Replay>) -> Self {
+        Self { replay }
+    }
+}
+
+impl Source for ReplaySource {
+    fn name(&self) -> &str {
+        "replay"
+    }
+
+    fn run<'a>(&'a self, tx: mpsc::Sender<Signature>) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let started = Instant::now();
+            for (at, log) in self.replay.logs() {
+                let due = self.replay.speed.map(|speed| started + at.div_f64(speed));
+                if let Some(due) = due {
+                    tokio::time::sleep_until(due).await;
+                }
+                if let Some(signature) = creation_signature(log, super::is_creation_log) {
+                    if tx.send(signature).await.is_err() {
+                        break;
+                    }
+                    // a full channel holds the source back
+                    if let Some(due) = due {
+                        self.replay.fell_behind(Instant::now().saturating_duration_since(due));
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,99 @@
+use super::{Feed, Notification, Source};
+use anyhow::{anyhow, Result};
+use futures::future::BoxFuture;
+use solana_sdk::signature::Signature;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// One step of a scripted feed.
+#[derive(Debug, Clone)]
+pub enum Step {
+    Emit(Signature),
+    // created while the feed is down, found only by repairing the gap
+    Missed(Signature),
+    // nothing arrives for this long on the (paused) tokio clock
+    Stall(Duration),
+    // sends the last n transactions again, like a resubscription that
+    // redelivers recent notifications
+    Replay(usize),
+    // the connection drops; the script resumes after the reconnect
+    Disconnect,
+}
+
+/// A fake feed playing a fixed script through [`super::reconnecting`], for
+/// deterministic tests of reconnects, gap repair, merging and the deadman.
+/// Run under `start_paused` so stalls and reconnect delays take no real time.
+pub struct ScriptedSource {
+    name: String,
+    steps: Vec<Step>,
+    // the next step, kept across reconnects
+    next: Mutex<usize>,
+    // every transaction so far, emitted or missed
+    chain: Mutex<Vec<Signature>>,
+}
+
+impl ScriptedSource {
+    pub fn new(name: &str, steps: Vec<Step>) -> Self {
+        Self { name: name.to_string(), steps, next: Mutex::new(0), chain: Mutex::new(Vec::new()) }
+    }
+
+    fn next_step(&self) -> Option<Step> {
+        let mut next = self.next.lock().unwrap();
+        let step = self.steps.get(*next).cloned();
+        *next += 1;
+        step
+    }
+}
+
+impl Feed for ScriptedSource {
+    fn connect<'a>(&'a self, tx: mpsc::Sender<Notification>) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            while let Some(step) = self.next_step() {
+                let batch = match step {
+                    Step::Emit(signature) => {
+                        self.chain.lock().unwrap().push(signature);
+                        vec![signature]
+                    }
+                    Step::Missed(signature) => {
+                        self.chain.lock().unwrap().push(signature);
+                        continue;
+                    }
+                    Step::Stall(duration) => {
+                        tokio::time::sleep(duration).await;
+                        continue;
+                    }
+                    Step::Replay(count) => {
+                        let chain = self.chain.lock().unwrap();
+                        chain[chain.len().saturating_sub(count)..].to_vec()
+                    }
+                    Step::Disconnect => return Err(anyhow!("{} disconnected", self.name)),
+                };
+                for signature in batch {
+                    if tx.send(Notification { signature, creation: true }).await.is_err() {
+                        return Ok(());
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn missed<'a>(&'a self, until: Signature, before: Signature) -> BoxFuture<'a, Result<Vec<Signature>>> {
+        Box::pin(async move {
+            let chain = self.chain.lock().unwrap();
+            let position = |signature| chain.iter().position(|seen| *seen == signature).ok_or_else(|| anyhow!("unknown signature"));
+            Ok(chain[position(until)? + 1..position(before)?].to_vec())
+        })
+    }
+}
+
+impl Source for ScriptedSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn run<'a>(&'a self, tx: mpsc::Sender<Signature>) -> BoxFuture<'a, Result<()>> {
+        Box::pin(super::reconnecting(&self.name, self, tx))
+    }
+}
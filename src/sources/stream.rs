@@ -0,0 +1,60 @@
+use anyhow::{anyhow, Result};
+use futures::{SinkExt, StreamExt};
+use serde_json::Value;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{client::IntoClientRequest, Message},
+    MaybeTlsStream, WebSocketStream,
+};
+
+/// A JSON-RPC subscription over a WebSocket, for the commercial streams.
+pub struct Subscription {
+    socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+}
+
+impl Subscription {
+    /// Connects and sends `subscribe`. `auth_header` goes out as
+    /// Authorization on the upgrade request.
+    pub async fn open(url: &str, auth_header: Option<&str>, subscribe: Value) -> Result<Self> {
+        let mut request = url.into_client_request()?;
+        if let Some(auth_header) = auth_header {
+            request.headers_mut().insert("Authorization", auth_header.parse()?);
+        }
+        let (mut socket, _) = connect_async(request).await?;
+        socket.send(Message::Text(subscribe.to_string())).await?;
+        Ok(Self { socket })
+    }
+
+    /// The `params.result` of the next notification, or None once the
+    /// connection closes. Replies to requests (with an `id`) are skipped
+    /// unless they carry an error.
+    pub async fn next(&mut self) -> Option<Result<Value>> {
+        while let Some(message) = self.socket.next().await {
+            let text = match message {
+                Ok(Message::Text(text)) => text,
+                Ok(Message::Ping(payload)) => {
+                    if let Err(e) = self.socket.send(Message::Pong(payload)).await {
+                        return Some(Err(e.into()));
+                    }
+                    continue;
+                }
+                Ok(Message::Close(_)) => return None,
+                Ok(_) => continue,
+                Err(e) => return Some(Err(e.into())),
+            };
+            let mut message: Value = match serde_json::from_str(&text) {
+                Ok(message) => message,
+                Err(e) => return Some(Err(e.into())),
+            };
+            if !message["error"].is_null() {
+                return Some(Err(anyhow!("subscription error: {}", message["error"])));
+            }
+            if !message["id"].is_null() {
+                continue;
+            }
+            return Some(Ok(message["params"]["result"].take()));
+        }
+        None
+    }
+}
@@ -0,0 +1,81 @@
+use super::stream::Subscription;
+use super::Source;
+use crate::config::TritonConfig;
+use crate::programs;
+use anyhow::Result;
+use futures::future::BoxFuture;
+use serde_json::{json, Value};
+use solana_sdk::signature::Signature;
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+use std::str::FromStr;
+
+/// Triton Whirligig `transactionSubscribe` on the monitored programs, keeping
+/// transactions whose logs show a pool created or a sale opened.
+pub struct TritonSource {
+    config: TritonConfig,
+}
+
+impl TritonSource {
+    pub fn new(config: TritonConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Source for TritonSource {
+    fn name(&self) -> &str {
+        "triton"
+    }
+
+    fn run<'a>(&'a self, tx: mpsc::Sender<Signature>) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let programs = programs::all().iter().map(|program| program.id.to_string()).collect::<Vec<_>>();
+            let subscribe = json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "transactionSubscribe",
+                "params": [
+                    { "vote": false, "failed": false, "accounts": { "include": programs } },
+                    {
+                        "commitment": "confirmed",
+                        "encoding": "json",
+                        "transactionDetails": "full",
+                        "maxSupportedTransactionVersion": 0,
+                    },
+                ],
+            });
+            let mut subscription = Subscription::open(&self.config.ws_url, None, subscribe).await?;
+            info!(programs = ?programs, "Subscribed to Triton transactions");
+
+            while let Some(result) = subscription.next().await {
+                let result = result?;
+                if !is_creation(&result) {
+                    continue;
+                }
+                let Some(signature) = signature(&result) else {
+                    warn!("Triton notification without a signature");
+                    continue;
+                };
+                match Signature::from_str(signature) {
+                    Ok(signature) => {
+                        if tx.send(signature).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => error!(signature, error = %e, "Failed to parse signature"),
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+fn signature(result: &Value) -> Option<&str> {
+    result["signature"].as_str().or_else(|| result["transaction"]["transaction"]["signatures"][0].as_str())
+}
+
+fn is_creation(result: &Value) -> bool {
+    result["transaction"]["meta"]["logMessages"]
+        .as_array()
+        .is_some_and(|logs| logs.iter().any(|log| log.as_str().is_some_and(super::is_creation_log)))
+}
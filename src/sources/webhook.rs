@@ -0,0 +1,109 @@
+use super::Source;
+use crate::config::WebhookConfig;
+use crate::decode;
+use crate::systemd;
+use anyhow::Result;
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use futures::future::BoxFuture;
+use serde_json::Value;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+use std::str::FromStr;
+
+/// Receives transactions POSTed by a Helius webhook on the monitored
+/// programs. Both enhanced and raw webhook payloads are accepted; only the
+/// signatures of initialize2 and launchpad initialize transactions are kept
+/// and the pipeline fetches them as usual.
+pub struct WebhookSource {
+    config: WebhookConfig,
+}
+
+impl WebhookSource {
+    pub fn new(config: WebhookConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[derive(Clone)]
+struct WebhookState {
+    tx: mpsc::Sender<Signature>,
+    // the webhook's authHeader, sent back verbatim as Authorization
+    auth_header: Option<String>,
+}
+
+impl Source for WebhookSource {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    fn run<'a>(&'a self, tx: mpsc::Sender<Signature>) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let app = Router::new()
+                .route(&self.config.path, post(receive))
+                .with_state(WebhookState { tx, auth_header: self.config.auth_header.clone() });
+            let listener = tokio::net::TcpListener::bind(&self.config.listen).await?;
+            info!(listen = %self.config.listen, path = %self.config.path, "Webhook endpoint listening");
+            systemd::ready();
+            axum::serve(listener, app).await?;
+            Ok(())
+        })
+    }
+}
+
+async fn receive(State(state): State<WebhookState>, headers: HeaderMap, Json(payload): Json<Value>) -> StatusCode {
+    if let Some(expected) = &state.auth_header {
+        if headers.get(header::AUTHORIZATION).and_then(|value| value.to_str().ok()) != Some(expected.as_str()) {
+            warn!("Rejected webhook request with a missing or wrong Authorization header");
+            return StatusCode::UNAUTHORIZED;
+        }
+    }
+    let Some(transactions) = payload.as_array() else {
+        return StatusCode::BAD_REQUEST;
+    };
+    debug!(transactions = transactions.len(), "Received webhook payload");
+    if let Some(slot) = transactions.iter().filter_map(|transaction| transaction["slot"].as_u64()).max() {
+        systemd::progress(slot);
+    }
+    for transaction in transactions.iter().filter(|transaction| is_creation(transaction)) {
+        let Some(signature) = signature(transaction) else {
+            warn!("Webhook transaction without a signature");
+            continue;
+        };
+        match Signature::from_str(signature) {
+            Ok(signature) => {
+                if state.tx.send(signature).await.is_err() {
+                    return StatusCode::SERVICE_UNAVAILABLE;
+                }
+            }
+            Err(e) => warn!(signature, error = %e, "Failed to parse webhook signature"),
+        }
+    }
+    StatusCode::OK
+}
+
+/// `signature` in enhanced payloads, the first of `transaction.signatures`
+/// in raw ones.
+fn signature(transaction: &Value) -> Option<&str> {
+    transaction["signature"].as_str().or_else(|| transaction["transaction"]["signatures"][0].as_str())
+}
+
+/// Enhanced payloads list instructions with their program and base58 data;
+/// raw payloads carry the log messages.
+fn is_creation(transaction: &Value) -> bool {
+    if let Some(instructions) = transaction["instructions"].as_array() {
+        return instructions.iter().any(|ix| {
+            let program = ix["programId"].as_str().and_then(|program| Pubkey::from_str(program).ok());
+            let data = ix["data"].as_str().and_then(|data| bs58::decode(data).into_vec().ok());
+            program.zip(data).is_some_and(|(program, data)| decode::is_creation(&program, &data))
+        });
+    }
+    transaction["meta"]["logMessages"]
+        .as_array()
+        .is_some_and(|logs| logs.iter().any(|log| log.as_str().is_some_and(super::is_creation_log)))
+}
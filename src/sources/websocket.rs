@@ -0,0 +1,169 @@
+use super::{Feed, Notification, Source};
+use crate::faults::{self, Fault};
+use crate::programs::Program;
+use crate::recording;
+use crate::rpc::KeyRing;
+use crate::systemd;
+use crate::tui::{ConnectionStatus, Dashboard};
+use anyhow::{anyhow, Result};
+use futures::future::BoxFuture;
+use solana_client::{
+    pubsub_client::PubsubClient,
+    rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+    rpc_config::{RpcTransactionConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter},
+    rpc_response::RpcLogsResponse,
+};
+use solana_sdk::{commitment_config::CommitmentConfig, signature::Signature};
+use solana_transaction_status::{option_serializer::OptionSerializer, UiTransactionEncoding};
+use tokio::sync::mpsc;
+use tracing::{debug, error, info, warn};
+use std::str::FromStr;
+use std::sync::Arc;
+
+// the most transactions looked up in a gap, getSignaturesForAddress's limit
+const GAP_REPAIR_LIMIT: usize = 1000;
+
+/// `logsSubscribe` on a monitored program, keeping transactions whose logs
+/// show it creating a pool or opening a sale (initialize2 for Raydium V4).
+/// Every notification counts as slot progress for the systemd watchdog. The
+/// subscription is renewed whenever it drops, and the program's transactions
+/// in between are fetched for the creations it missed.
+#[derive(Clone)]
+pub struct WebSocketSource {
+    keys: Arc<KeyRing>,
+    rpc_client: Arc<RpcClient>,
+    // one per subscription, which only takes a single address
+    program: &'static Program,
+    dashboard: Option<Arc<Dashboard>>,
+}
+
+impl WebSocketSource {
+    pub fn new(keys: Arc<KeyRing>, rpc_client: Arc<RpcClient>, program: &'static Program, dashboard: Option<Arc<Dashboard>>) -> Self {
+        Self { keys, rpc_client, program, dashboard }
+    }
+
+    fn set_connection(&self, status: ConnectionStatus) {
+        if let Some(dashboard) = &self.dashboard {
+            dashboard.set_connection(status);
+        }
+    }
+
+    /// Sends every notification until the subscription drops, or returns Ok
+    /// once the receiver is gone.
+    fn subscribe(&self, tx: &mpsc::Sender<Notification>) -> Result<()> {
+        let (_subscription, receiver) = PubsubClient::logs_subscribe(
+            &self.keys.ws_url("logsSubscribe"),
+            RpcTransactionLogsFilter::Mentions(vec![self.program.id.to_string()]),
+            RpcTransactionLogsConfig {
+                commitment: Some(CommitmentConfig::confirmed()),
+            },
+        )?;
+        info!(program = %self.program.id, name = %self.program.name, "Subscribed to program logs");
+        self.set_connection(ConnectionStatus::Connected);
+        systemd::ready();
+
+        let creation_log = self.program.creation_log();
+        while let Ok(log) = receiver.recv() {
+            systemd::progress(log.context.slot);
+            recording::ws_log(&log);
+            let notification = match creation_signature(&log.value, |line| line.contains(creation_log)) {
+                Some(signature) => Notification { signature, creation: true },
+                None => match Signature::from_str(&log.value.signature) {
+                    Ok(signature) => Notification { signature, creation: false },
+                    Err(_) => continue,
+                },
+            };
+            if tx.blocking_send(notification).is_err() {
+                return Ok(());
+            }
+            if notification.creation && faults::inject(Fault::WsDuplicate) && tx.blocking_send(notification).is_err() {
+                return Ok(());
+            }
+            if faults::inject(Fault::WsDisconnect) {
+                return Err(anyhow!("injected disconnect"));
+            }
+        }
+        Err(anyhow!("log subscription to {} closed", self.program.name))
+    }
+
+    /// The program's successful transactions after `until` and before
+    /// `before` whose logs show a creation, oldest first.
+    fn find_missed(&self, until: Signature, before: Signature) -> Result<Vec<Signature>> {
+        let gap = self.rpc_client.get_signatures_for_address_with_config(
+            &self.program.id,
+            GetConfirmedSignaturesForAddress2Config {
+                before: Some(before),
+                until: Some(until),
+                limit: Some(GAP_REPAIR_LIMIT),
+                commitment: Some(CommitmentConfig::confirmed()),
+            },
+        )?;
+        if gap.len() == GAP_REPAIR_LIMIT {
+            warn!(program = %self.program.id, limit = GAP_REPAIR_LIMIT, "Gap too long to repair, older creations in it are lost");
+        }
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        };
+        let creation_log = self.program.creation_log();
+        let mut missed = Vec::new();
+        for status in gap.iter().rev().filter(|status| status.err.is_none()) {
+            let signature = Signature::from_str(&status.signature)?;
+            let logs = match self.rpc_client.get_transaction_with_config(&signature, config) {
+                Ok(tx) => tx.transaction.meta.map(|meta| meta.log_messages),
+                Err(e) => {
+                    debug!(signature = %signature, error = %e, "Failed to fetch a transaction in the gap");
+                    continue;
+                }
+            };
+            if let Some(OptionSerializer::Some(logs)) = logs {
+                if logs.iter().any(|line| line.contains(creation_log)) {
+                    missed.push(signature);
+                }
+            }
+        }
+        Ok(missed)
+    }
+}
+
+impl Feed for WebSocketSource {
+    fn connect<'a>(&'a self, tx: mpsc::Sender<Notification>) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            // the pubsub client blocks
+            let source = self.clone();
+            let ended = tokio::task::spawn_blocking(move || source.subscribe(&tx)).await.unwrap_or_else(|e| Err(e.into()));
+            if ended.is_err() {
+                self.set_connection(ConnectionStatus::Disconnected);
+            }
+            ended
+        })
+    }
+
+    fn missed<'a>(&'a self, until: Signature, before: Signature) -> BoxFuture<'a, Result<Vec<Signature>>> {
+        Box::pin(async move {
+            let source = self.clone();
+            tokio::task::spawn_blocking(move || source.find_missed(until, before)).await?
+        })
+    }
+}
+
+impl Source for WebSocketSource {
+    fn name(&self) -> &str {
+        "websocket"
+    }
+
+    fn run<'a>(&'a self, tx: mpsc::Sender<Signature>) -> BoxFuture<'a, Result<()>> {
+        Box::pin(super::reconnecting(self.name(), self, tx))
+    }
+}
+
+/// The signature of a notification with a log line passing `is_creation`.
+pub(super) fn creation_signature(log: &RpcLogsResponse, is_creation: impl Fn(&str) -> bool) -> Option<Signature> {
+    if !log.logs.iter().any(|l| is_creation(l)) {
+        return None;
+    }
+    Signature::from_str(&log.signature)
+        .map_err(|e| error!(signature = %log.signature, error = %e, "Failed to parse signature"))
+        .ok()
+}
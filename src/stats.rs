@@ -0,0 +1,219 @@
+use crate::config::{ApiKeysConfig, RpcCostsConfig};
+use crate::positions::pnl_percent;
+use crate::raydium::KNOWN_QUOTES;
+use crate::store::Store;
+use crate::time::format_offset;
+use crate::volume::VOLUME_WINDOWS_SECS;
+use anyhow::Result;
+use chrono::DateTime;
+use solana_sdk::native_token::lamports_to_sol;
+use std::time::Duration;
+
+const TOP_CREATORS: usize = 10;
+
+fn sol(lamports: i64) -> f64 {
+    lamports.signum() as f64 * lamports_to_sol(lamports.unsigned_abs())
+}
+
+/// `stats positions`: prints positions and realized/unrealized PnL per wallet.
+pub fn print_positions(store: &Store, wallet: Option<&str>) -> Result<()> {
+    let summaries = store.pnl_summary(wallet)?;
+    if summaries.is_empty() {
+        println!("No positions recorded");
+        return Ok(());
+    }
+
+    println!("{:<16} {:>6} {:>8} {:>16} {:>16}", "WALLET", "OPEN", "CLOSED", "REALIZED SOL", "UNREALIZED SOL");
+    for summary in &summaries {
+        println!(
+            "{:<16} {:>6} {:>8} {:>16.6} {:>16.6}",
+            summary.wallet,
+            summary.open_positions,
+            summary.closed_positions,
+            sol(summary.realized_pnl_lamports),
+            sol(summary.unrealized_pnl_lamports)
+        );
+    }
+
+    let open = store.positions(wallet, true)?;
+    if !open.is_empty() {
+        println!();
+        println!("{:<6} {:<16} {:<44} {:>12} {:>12} {:>9}", "ID", "WALLET", "POOL", "COST SOL", "VALUE SOL", "PNL");
+        for position in open {
+            let value = position.last_value_lamports.map(lamports_to_sol);
+            println!(
+                "{:<6} {:<16} {:<44} {:>12.6} {:>12} {:>9}",
+                position.id,
+                position.wallet,
+                position.amm,
+                lamports_to_sol(position.cost_lamports),
+                value.map(|v| format!("{:.6}", v)).unwrap_or_else(|| "-".to_string()),
+                position
+                    .last_value_lamports
+                    .map(|v| format!("{:+.2}%", pnl_percent(position.cost_lamports, v)))
+                    .unwrap_or_else(|| "-".to_string())
+            );
+        }
+    }
+    Ok(())
+}
+
+/// `stats pools`: aggregates over the pools detected in [since, until].
+pub fn print_pools(store: &Store, since: u64, until: u64) -> Result<()> {
+    let days = store.pools_per_day(since, until)?;
+    if days.is_empty() {
+        println!("No pools recorded in this range");
+        return Ok(());
+    }
+    let total: u64 = days.iter().map(|(_, count)| count).sum();
+
+    println!("{:<12} {:>8}", "DAY", "POOLS");
+    for (day, count) in &days {
+        println!("{:<12} {:>8}", day, count);
+    }
+    println!("{:<12} {:>8}", "TOTAL", total);
+
+    let liquidities = store.sol_liquidities(since, until)?;
+    println!();
+    match median(&liquidities) {
+        Some(median) => println!(
+            "Median initial liquidity: {:.2} SOL ({} SOL pools)",
+            lamports_to_sol(median),
+            liquidities.len()
+        ),
+        None => println!("Median initial liquidity: - (no SOL pools)"),
+    }
+
+    println!();
+    println!("{:<12} {:>8} {:>8}", "QUOTE", "POOLS", "SHARE");
+    for (mint, count) in store.quote_breakdown(since, until)? {
+        let name = KNOWN_QUOTES.iter().find(|(known, _)| *known == mint).map_or(mint.as_str(), |(_, name)| name);
+        println!("{:<12} {:>8} {:>7.1}%", name, count, count as f64 / total as f64 * 100.0);
+    }
+
+    println!();
+    println!("{:<44} {:>8}", "CREATOR", "POOLS");
+    for (creator, count) in store.top_creators(since, until, TOP_CREATORS)? {
+        println!("{:<44} {:>8}", creator, count);
+    }
+
+    let buyers = store.first_buyer_counts(since, until)?;
+    if buyers.buyers > 0 {
+        let share = |count: u64| count as f64 / buyers.buyers as f64 * 100.0;
+        println!();
+        println!("First buyers of {} pools: {}", buyers.pools, buyers.buyers);
+        println!("  fresh wallets    {:>6} {:>6.1}%", buyers.fresh, share(buyers.fresh));
+        println!("  known bots       {:>6} {:>6.1}%", buyers.known_bots, share(buyers.known_bots));
+        println!("  deployer funded  {:>6} {:>6.1}%", buyers.deployer_funded, share(buyers.deployer_funded));
+    }
+
+    let snipes = store.snipe_counts(since, until)?;
+    if snipes.pools > 0 {
+        println!(
+            "Sniped pools: {} of {} ({:.1}%), {:.2}% of supply sniped on average",
+            snipes.sniped,
+            snipes.pools,
+            snipes.sniped as f64 / snipes.pools as f64 * 100.0,
+            snipes.avg_sniped_percent
+        );
+    }
+    Ok(())
+}
+
+/// `stats volume`: rolling trades and quote volume per pool ending at `now`.
+pub fn print_volume(store: &Store, now: u64, amm: Option<&str>) -> Result<()> {
+    let volumes = store.pool_volumes(now, amm, VOLUME_WINDOWS_SECS)?;
+    if volumes.is_empty() {
+        println!("No swaps recorded in the last {}s", VOLUME_WINDOWS_SECS[VOLUME_WINDOWS_SECS.len() - 1]);
+        return Ok(());
+    }
+    print!("{:<44}", "POOL");
+    for window in VOLUME_WINDOWS_SECS {
+        let label = format_offset(*window);
+        print!(" {:>8} {:>14}", format!("TRADES {}", label), format!("VOLUME {}", label));
+    }
+    println!();
+    for pool in volumes {
+        print!("{:<44}", pool.amm);
+        for window in &pool.windows {
+            print!(" {:>8} {:>14.4}", window.trades, window.quote_volume);
+        }
+        println!();
+    }
+    Ok(())
+}
+
+/// `stats rpc`: calls per endpoint and method since `since` before `now`,
+/// projected to 30 days from the days with recorded usage, with credits and
+/// cost when `[rpc_costs]` is configured.
+pub fn print_rpc_usage(store: &Store, now: u64, since: Duration, costs: Option<&RpcCostsConfig>) -> Result<()> {
+    let since_day = DateTime::from_timestamp(now.saturating_sub(since.as_secs()) as i64, 0)
+        .unwrap_or_default()
+        .format("%Y-%m-%d")
+        .to_string();
+    let usage = store.rpc_usage(&since_day)?;
+    if usage.is_empty() {
+        println!("No RPC usage recorded since {}", since_day);
+        return Ok(());
+    }
+    let days = store.rpc_usage_days(&since_day)?.max(1);
+    let monthly = |calls: f64| calls / days as f64 * 30.0;
+
+    println!("{:<32} {:<28} {:>10} {:>12} {:>14}", "ENDPOINT", "METHOD", "CALLS", "CALLS/30D", "CREDITS/30D");
+    let mut total_calls = 0;
+    let mut total_credits = 0.0;
+    for entry in &usage {
+        let credits = costs.map_or(entry.calls as f64, |costs| entry.calls as f64 * costs.credits(&entry.method));
+        total_calls += entry.calls;
+        total_credits += credits;
+        println!(
+            "{:<32} {:<28} {:>10} {:>12.0} {:>14.0}",
+            entry.endpoint,
+            entry.method,
+            entry.calls,
+            monthly(entry.calls as f64),
+            monthly(credits)
+        );
+    }
+    println!(
+        "{:<32} {:<28} {:>10} {:>12.0} {:>14.0}",
+        "TOTAL",
+        format!("({} days)", days),
+        total_calls,
+        monthly(total_calls as f64),
+        monthly(total_credits)
+    );
+    if let Some(costs) = costs.filter(|costs| costs.usd_per_million_credits > 0.0) {
+        println!();
+        println!("Estimated monthly cost: ${:.2}", monthly(total_credits) / 1_000_000.0 * costs.usd_per_million_credits);
+    }
+    Ok(())
+}
+
+/// `stats keys`: requests per API key (by fingerprint) in the latest quota
+/// periods, with the share of the configured quota.
+pub fn print_key_usage(store: &Store, config: Option<&ApiKeysConfig>, periods: usize) -> Result<()> {
+    let usage = store.key_usage_history(periods)?;
+    if usage.is_empty() {
+        println!("No API key usage recorded");
+        return Ok(());
+    }
+    let quota = config.map_or(0, |config| config.quota);
+    println!("{:<12} {:<12} {:>10} {:>8}", "PERIOD", "KEY", "REQUESTS", "QUOTA");
+    for (period, key, requests) in usage {
+        let share = match quota {
+            0 => "-".to_string(),
+            quota => format!("{:.1}%", requests as f64 / quota as f64 * 100.0),
+        };
+        println!("{:<12} {:<12} {:>10} {:>8}", period, key, requests, share);
+    }
+    Ok(())
+}
+
+fn median(sorted: &[u64]) -> Option<u64> {
+    match sorted.len() {
+        0 => None,
+        len if len % 2 == 1 => Some(sorted[len / 2]),
+        len => Some(((sorted[len / 2 - 1] as u128 + sorted[len / 2] as u128) / 2) as u64),
+    }
+}
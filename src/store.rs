@@ -0,0 +1,1731 @@
+use anyhow::{anyhow, Context, Result};
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS positions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    wallet TEXT NOT NULL,
+    paper INTEGER NOT NULL,
+    amm TEXT NOT NULL,
+    token_mint TEXT NOT NULL,
+    pool_accounts TEXT NOT NULL,
+    sol_is_pc INTEGER NOT NULL,
+    cost_lamports INTEGER NOT NULL,
+    tokens INTEGER NOT NULL,
+    entry_signature TEXT,
+    opened_at INTEGER NOT NULL,
+    last_value_lamports INTEGER,
+    last_marked_at INTEGER,
+    closed_at INTEGER,
+    proceeds_lamports INTEGER
+);
+CREATE INDEX IF NOT EXISTS positions_wallet ON positions (wallet, closed_at);
+CREATE TABLE IF NOT EXISTS pools (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    signature TEXT NOT NULL,
+    amm TEXT NOT NULL UNIQUE,
+    coin_mint TEXT NOT NULL,
+    pc_mint TEXT NOT NULL,
+    quote_mint TEXT NOT NULL,
+    creator TEXT NOT NULL,
+    init_coin_amount INTEGER NOT NULL,
+    init_pc_amount INTEGER NOT NULL,
+    sol_liquidity_lamports INTEGER,
+    open_time INTEGER NOT NULL,
+    detected_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS pools_detected_at ON pools (detected_at);
+CREATE INDEX IF NOT EXISTS pools_signature ON pools (signature);
+CREATE INDEX IF NOT EXISTS pools_pair ON pools (coin_mint, pc_mint);
+CREATE TABLE IF NOT EXISTS launches (
+    base_mint TEXT PRIMARY KEY,
+    launch_pool TEXT NOT NULL,
+    program TEXT NOT NULL,
+    signature TEXT NOT NULL,
+    creator TEXT NOT NULL,
+    quote_mint TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    detected_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS candles (
+    amm TEXT NOT NULL,
+    resolution_secs INTEGER NOT NULL,
+    start_time INTEGER NOT NULL,
+    open REAL NOT NULL,
+    high REAL NOT NULL,
+    low REAL NOT NULL,
+    close REAL NOT NULL,
+    volume REAL NOT NULL,
+    PRIMARY KEY (amm, resolution_secs, start_time)
+);
+CREATE TABLE IF NOT EXISTS first_buyers (
+    amm TEXT NOT NULL,
+    signature TEXT NOT NULL,
+    buyer TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    fresh INTEGER NOT NULL,
+    known_bot INTEGER NOT NULL,
+    deployer_funded INTEGER NOT NULL,
+    PRIMARY KEY (amm, signature)
+);
+CREATE TABLE IF NOT EXISTS snipes (
+    amm TEXT PRIMARY KEY,
+    early_buys INTEGER NOT NULL,
+    sniper_buys INTEGER NOT NULL,
+    identical_buys INTEGER NOT NULL,
+    jito_buys INTEGER NOT NULL,
+    bot_program_buys INTEGER NOT NULL,
+    sniped_amount INTEGER NOT NULL,
+    sniped_percent REAL NOT NULL,
+    sniped INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS swaps (
+    amm TEXT NOT NULL,
+    signature TEXT NOT NULL,
+    seq INTEGER NOT NULL,
+    time INTEGER NOT NULL,
+    buy INTEGER NOT NULL,
+    base_amount REAL NOT NULL,
+    quote_amount REAL NOT NULL,
+    PRIMARY KEY (amm, signature, seq)
+);
+CREATE INDEX IF NOT EXISTS swaps_time ON swaps (time);
+CREATE TABLE IF NOT EXISTS holder_snapshots (
+    amm TEXT NOT NULL,
+    offset_secs INTEGER NOT NULL,
+    taken_at INTEGER NOT NULL,
+    supply REAL NOT NULL,
+    pool_percent REAL NOT NULL,
+    top1_percent REAL NOT NULL,
+    top10_percent REAL NOT NULL,
+    top_holders TEXT NOT NULL,
+    PRIMARY KEY (amm, offset_secs)
+);
+CREATE TABLE IF NOT EXISTS performance (
+    amm TEXT NOT NULL,
+    offset_secs INTEGER NOT NULL,
+    taken_at INTEGER NOT NULL,
+    price REAL NOT NULL,
+    quote_liquidity REAL NOT NULL,
+    price_change_percent REAL NOT NULL,
+    liquidity_change_percent REAL NOT NULL,
+    PRIMARY KEY (amm, offset_secs)
+);
+CREATE TABLE IF NOT EXISTS rugs (
+    amm TEXT PRIMARY KEY,
+    detected_at INTEGER NOT NULL,
+    drop_percent REAL NOT NULL
+);
+CREATE TABLE IF NOT EXISTS retry_queue (
+    signature TEXT PRIMARY KEY,
+    attempts INTEGER NOT NULL,
+    first_seen_at INTEGER NOT NULL,
+    next_attempt_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS delivery_queue (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sink TEXT NOT NULL,
+    event TEXT NOT NULL,
+    queued_at INTEGER NOT NULL,
+    attempts INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS delivery_queue_sink ON delivery_queue (sink, id);
+CREATE TABLE IF NOT EXISTS events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    emitted_at INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    event TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS events_emitted_at ON events (emitted_at);
+CREATE INDEX IF NOT EXISTS events_slot ON events (slot);
+CREATE TABLE IF NOT EXISTS webhooks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    url TEXT NOT NULL,
+    secret TEXT,
+    filter TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS api_key_usage (
+    period TEXT NOT NULL,
+    key TEXT NOT NULL,
+    requests INTEGER NOT NULL,
+    PRIMARY KEY (period, key)
+);
+CREATE TABLE IF NOT EXISTS rpc_usage (
+    day TEXT NOT NULL,
+    endpoint TEXT NOT NULL,
+    method TEXT NOT NULL,
+    calls INTEGER NOT NULL,
+    PRIMARY KEY (day, endpoint, method)
+);
+";
+
+// columns added to existing databases: (table, column, definition)
+const COLUMNS: &[(&str, &str, &str)] = &[
+    ("positions", "peak_value_lamports", "INTEGER"),
+    ("positions", "exit_reason", "TEXT"),
+    ("positions", "exit_signature", "TEXT"),
+    ("positions", "entry_route", "TEXT"),
+    ("positions", "entry_routes", "TEXT"),
+    ("positions", "sell_signature", "TEXT"),
+    ("positions", "sell_sent_at", "INTEGER"),
+    ("positions", "sell_attempts", "INTEGER NOT NULL DEFAULT 0"),
+    ("positions", "sell_retry_at", "INTEGER"),
+    // 0 for pools recorded before it
+    ("pools", "slot", "INTEGER NOT NULL DEFAULT 0"),
+];
+
+// indexes on migrated columns, created once the columns exist
+const INDEXES: &str = "
+CREATE INDEX IF NOT EXISTS pools_slot ON pools (slot, signature);
+";
+
+/// A position opened by a buy, live or paper.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Position {
+    pub id: i64,
+    pub wallet: String,
+    pub paper: bool,
+    pub amm: String,
+    pub token_mint: String,
+    // serialized Initialize2Accounts, needed to sell
+    #[serde(skip)]
+    pub pool_accounts: String,
+    pub sol_is_pc: bool,
+    pub cost_lamports: u64,
+    pub tokens: u64,
+    pub entry_signature: Option<String>,
+    // "rpc" or "jito", the route the live buy landed through
+    pub entry_route: Option<String>,
+    // the routes that accepted the live buy, comma separated
+    pub entry_routes: Option<String>,
+    pub opened_at: u64,
+    pub last_value_lamports: Option<u64>,
+    pub last_marked_at: Option<u64>,
+    pub closed_at: Option<u64>,
+    pub proceeds_lamports: Option<u64>,
+    pub peak_value_lamports: Option<u64>,
+    pub exit_reason: Option<String>,
+    pub exit_signature: Option<String>,
+    // the sell sent and not yet seen landing or failing
+    pub sell_signature: Option<String>,
+    pub sell_sent_at: Option<u64>,
+    // failed sells so far, and when the next may be sent
+    pub sell_attempts: u32,
+    pub sell_retry_at: Option<u64>,
+}
+
+impl Position {
+    pub fn is_open(&self) -> bool {
+        self.closed_at.is_none()
+    }
+
+    pub fn unrealized_pnl_lamports(&self) -> Option<i64> {
+        match (self.is_open(), self.last_value_lamports) {
+            (true, Some(value)) => Some(value as i64 - self.cost_lamports as i64),
+            _ => None,
+        }
+    }
+
+    pub fn realized_pnl_lamports(&self) -> Option<i64> {
+        self.proceeds_lamports.map(|proceeds| proceeds as i64 - self.cost_lamports as i64)
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get("id")?,
+            wallet: row.get("wallet")?,
+            paper: row.get("paper")?,
+            amm: row.get("amm")?,
+            token_mint: row.get("token_mint")?,
+            pool_accounts: row.get("pool_accounts")?,
+            sol_is_pc: row.get("sol_is_pc")?,
+            cost_lamports: row.get::<_, i64>("cost_lamports")? as u64,
+            tokens: row.get::<_, i64>("tokens")? as u64,
+            entry_signature: row.get("entry_signature")?,
+            entry_route: row.get("entry_route")?,
+            entry_routes: row.get("entry_routes")?,
+            opened_at: row.get::<_, i64>("opened_at")? as u64,
+            last_value_lamports: row.get::<_, Option<i64>>("last_value_lamports")?.map(|v| v as u64),
+            last_marked_at: row.get::<_, Option<i64>>("last_marked_at")?.map(|v| v as u64),
+            closed_at: row.get::<_, Option<i64>>("closed_at")?.map(|v| v as u64),
+            proceeds_lamports: row.get::<_, Option<i64>>("proceeds_lamports")?.map(|v| v as u64),
+            peak_value_lamports: row.get::<_, Option<i64>>("peak_value_lamports")?.map(|v| v as u64),
+            exit_reason: row.get("exit_reason")?,
+            exit_signature: row.get("exit_signature")?,
+            sell_signature: row.get("sell_signature")?,
+            sell_sent_at: row.get::<_, Option<i64>>("sell_sent_at")?.map(|v| v as u64),
+            sell_attempts: row.get("sell_attempts")?,
+            sell_retry_at: row.get::<_, Option<i64>>("sell_retry_at")?.map(|v| v as u64),
+        })
+    }
+}
+
+/// A detected pool.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PoolRecord {
+    pub signature: String,
+    pub amm: String,
+    pub coin_mint: String,
+    pub pc_mint: String,
+    // the pc side unless only the coin side is a known quote token
+    pub quote_mint: String,
+    pub creator: String,
+    pub slot: u64,
+    pub init_coin_amount: u64,
+    pub init_pc_amount: u64,
+    pub sol_liquidity_lamports: Option<u64>,
+    pub open_time: u64,
+    pub detected_at: u64,
+}
+
+impl PoolRecord {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            signature: row.get("signature")?,
+            amm: row.get("amm")?,
+            coin_mint: row.get("coin_mint")?,
+            pc_mint: row.get("pc_mint")?,
+            quote_mint: row.get("quote_mint")?,
+            creator: row.get("creator")?,
+            slot: row.get::<_, i64>("slot")? as u64,
+            init_coin_amount: row.get::<_, i64>("init_coin_amount")? as u64,
+            init_pc_amount: row.get::<_, i64>("init_pc_amount")? as u64,
+            sol_liquidity_lamports: row.get::<_, Option<i64>>("sol_liquidity_lamports")?.map(|v| v as u64),
+            open_time: row.get::<_, i64>("open_time")? as u64,
+            detected_at: row.get::<_, i64>("detected_at")? as u64,
+        })
+    }
+
+    pub fn cursor(&self) -> PoolCursor {
+        PoolCursor { slot: self.slot, signature: self.signature.clone() }
+    }
+}
+
+/// A launchpad sale, by the token it mints.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LaunchRecord {
+    pub base_mint: String,
+    // the sale's pool state
+    pub launch_pool: String,
+    pub program: String,
+    pub signature: String,
+    pub creator: String,
+    pub quote_mint: String,
+    pub symbol: String,
+    pub slot: u64,
+    pub detected_at: u64,
+}
+
+impl LaunchRecord {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            base_mint: row.get("base_mint")?,
+            launch_pool: row.get("launch_pool")?,
+            program: row.get("program")?,
+            signature: row.get("signature")?,
+            creator: row.get("creator")?,
+            quote_mint: row.get("quote_mint")?,
+            symbol: row.get("symbol")?,
+            slot: row.get::<_, i64>("slot")? as u64,
+            detected_at: row.get::<_, i64>("detected_at")? as u64,
+        })
+    }
+}
+
+/// Where a newest-first page of pools ends: the slot and signature of its
+/// last pool, as `slot:signature`. Unlike an offset it stays valid while
+/// new pools are added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolCursor {
+    pub slot: u64,
+    pub signature: String,
+}
+
+impl fmt::Display for PoolCursor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.slot, self.signature)
+    }
+}
+
+impl FromStr for PoolCursor {
+    type Err = anyhow::Error;
+
+    fn from_str(cursor: &str) -> Result<Self> {
+        let (slot, signature) = cursor.split_once(':').ok_or_else(|| anyhow!("invalid cursor {}", cursor))?;
+        let slot = slot.parse().map_err(|_| anyhow!("invalid cursor {}", cursor))?;
+        Ok(Self { slot, signature: signature.to_string() })
+    }
+}
+
+/// Where a newest-first page of a pool's swaps ends, as
+/// `time:signature:seq`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapCursor {
+    pub time: u64,
+    pub signature: String,
+    pub seq: u32,
+}
+
+impl fmt::Display for SwapCursor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.time, self.signature, self.seq)
+    }
+}
+
+impl FromStr for SwapCursor {
+    type Err = anyhow::Error;
+
+    fn from_str(cursor: &str) -> Result<Self> {
+        let invalid = || anyhow!("invalid cursor {}", cursor);
+        let mut parts = cursor.splitn(3, ':');
+        let time = parts.next().and_then(|time| time.parse().ok()).ok_or_else(invalid)?;
+        let signature = parts.next().ok_or_else(invalid)?.to_string();
+        let seq = parts.next().and_then(|seq| seq.parse().ok()).ok_or_else(invalid)?;
+        Ok(Self { time, signature, seq })
+    }
+}
+
+/// The pools of one creator.
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatorSummary {
+    pub creator: String,
+    pub pools: u64,
+    // pools whose liquidity was pulled
+    pub rugs: u64,
+    pub first_detected_at: u64,
+    pub last_detected_at: u64,
+}
+
+/// A pool whose liquidity was pulled.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+pub struct Rug {
+    pub amm: String,
+    pub detected_at: u64,
+    pub drop_percent: f64,
+}
+
+/// PnL summary of one wallet.
+#[derive(Debug, Default, Clone, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PnlSummary {
+    pub wallet: String,
+    pub open_positions: u64,
+    pub closed_positions: u64,
+    pub realized_pnl_lamports: i64,
+    pub unrealized_pnl_lamports: i64,
+}
+
+/// Price candle of a tracked pool, in quote per base token (UI units).
+#[derive(Debug, Clone, Serialize)]
+pub struct Candle {
+    pub amm: String,
+    pub resolution_secs: u64,
+    pub start_time: u64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    // quote tokens moved in or out of the pool, estimated from reserve changes
+    pub volume: f64,
+}
+
+/// One of the first buyers of a pool.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+pub struct FirstBuyer {
+    pub amm: String,
+    pub signature: String,
+    pub buyer: String,
+    pub slot: u64,
+    // few transactions in the wallet's history
+    pub fresh: bool,
+    pub known_bot: bool,
+    // first transaction of the wallet paid by the pool creator
+    pub deployer_funded: bool,
+}
+
+/// Totals over the first buyers of pools in a range.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FirstBuyerCounts {
+    pub pools: u64,
+    pub buyers: u64,
+    pub fresh: u64,
+    pub known_bots: u64,
+    pub deployer_funded: u64,
+}
+
+/// Sniping activity in the first slots after a pool's first buy.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+pub struct SnipeReport {
+    pub amm: String,
+    pub early_buys: u64,
+    // early buys matching any of the patterns below, or by a known bot
+    pub sniper_buys: u64,
+    // same quote amount as another early buy
+    pub identical_buys: u64,
+    pub jito_buys: u64,
+    pub bot_program_buys: u64,
+    // base tokens bought by sniper buys, raw and as a share of supply
+    pub sniped_amount: u64,
+    pub sniped_percent: f64,
+    pub sniped: bool,
+}
+
+/// Sniping totals over the pools in a range.
+#[derive(Debug, Clone, Serialize)]
+pub struct SnipeCounts {
+    pub pools: u64,
+    pub sniped: u64,
+    pub avg_sniped_percent: f64,
+}
+
+/// A swap on a pool, amounts in UI units.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject), graphql(complex))]
+pub struct SwapRecord {
+    pub amm: String,
+    pub signature: String,
+    // position among the pool's swaps in the transaction
+    pub seq: u32,
+    pub time: u64,
+    // quote token in, base token out
+    pub buy: bool,
+    pub base_amount: f64,
+    pub quote_amount: f64,
+}
+
+/// Trades and quote volume of a pool over one rolling window.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct WindowVolume {
+    pub window_secs: u64,
+    pub trades: u64,
+    pub buys: u64,
+    pub quote_volume: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PoolVolume {
+    pub amm: String,
+    pub windows: Vec<WindowVolume>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TopHolder {
+    pub token_account: String,
+    pub amount: f64,
+    pub percent: f64,
+}
+
+/// Base-token distribution of a pool some time after detection. Percentages
+/// are of the total supply; the pool's own vault is counted separately.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct HolderSnapshot {
+    pub amm: String,
+    pub offset_secs: u64,
+    pub taken_at: u64,
+    pub supply: f64,
+    pub pool_percent: f64,
+    pub top1_percent: f64,
+    pub top10_percent: f64,
+    pub top_holders: Vec<TopHolder>,
+}
+
+/// Price and quote liquidity of a pool some time after launch, with the
+/// change from its initial reserves.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PerformancePoint {
+    pub amm: String,
+    pub offset_secs: u64,
+    pub taken_at: u64,
+    // quote per base, UI units
+    pub price: f64,
+    pub quote_liquidity: f64,
+    pub price_change_percent: f64,
+    pub liquidity_change_percent: f64,
+}
+
+/// A SOL pool and its initial liquidity.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Launch {
+    pub amm: String,
+    pub base_mint: String,
+    pub sol_liquidity_lamports: u64,
+}
+
+/// A transaction that could not be fetched yet.
+#[derive(Debug, Clone)]
+pub struct RetryEntry {
+    pub signature: String,
+    pub attempts: u32,
+    pub first_seen_at: u64,
+}
+
+/// An event waiting to be delivered to a sink, as JSON.
+#[derive(Debug, Clone)]
+pub struct QueuedDelivery {
+    pub id: i64,
+    pub event: String,
+    pub queued_at: u64,
+    pub attempts: u32,
+}
+
+/// An emitted event as kept for replay, as JSON. `slot` is the pool's slot
+/// for new pools and the latest slot seen when any other event was emitted.
+#[derive(Debug, Clone)]
+pub struct StoredEvent {
+    pub id: i64,
+    pub emitted_at: u64,
+    pub slot: u64,
+    pub event: String,
+}
+
+/// A webhook destination registered through the REST API, its filter as
+/// JSON.
+#[derive(Debug, Clone)]
+pub struct WebhookRecord {
+    pub id: i64,
+    pub url: String,
+    pub secret: Option<String>,
+    pub filter: String,
+    pub created_at: u64,
+}
+
+/// Calls of one method on one RPC or WebSocket endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RpcUsage {
+    pub endpoint: String,
+    pub method: String,
+    pub calls: u64,
+}
+
+/// SQLite-backed persistent store.
+pub struct Store {
+    conn: Mutex<Connection>,
+}
+
+impl Store {
+    pub fn open(path: &str) -> Result<Self> {
+        let conn = Connection::open(path).with_context(|| format!("Failed to open store {}", path))?;
+        conn.execute_batch(SCHEMA)?;
+        for (table, column, definition) in COLUMNS {
+            let exists = conn
+                .prepare(&format!("SELECT 1 FROM pragma_table_info('{}') WHERE name = ?1", table))?
+                .exists([column])?;
+            if !exists {
+                conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))?;
+            }
+        }
+        conn.execute_batch(INDEXES)?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    pub fn insert_position(&self, position: &Position) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO positions (wallet, paper, amm, token_mint, pool_accounts, sol_is_pc, cost_lamports, tokens, entry_signature, opened_at,
+                 entry_route, entry_routes)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                position.wallet,
+                position.paper,
+                position.amm,
+                position.token_mint,
+                position.pool_accounts,
+                position.sol_is_pc,
+                position.cost_lamports as i64,
+                position.tokens as i64,
+                position.entry_signature,
+                position.opened_at as i64,
+                position.entry_route,
+                position.entry_routes,
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Positions filtered by wallet and status, newest first.
+    pub fn positions(&self, wallet: Option<&str>, open_only: bool) -> Result<Vec<Position>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT * FROM positions
+             WHERE (?1 IS NULL OR wallet = ?1) AND (?2 = 0 OR closed_at IS NULL)
+             ORDER BY opened_at DESC, id DESC",
+        )?;
+        let positions = stmt
+            .query_map(params![wallet, open_only], Position::from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(positions)
+    }
+
+    pub fn mark_position(&self, id: i64, value_lamports: u64, marked_at: u64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE positions SET last_value_lamports = ?2, last_marked_at = ?3,
+                 peak_value_lamports = MAX(COALESCE(peak_value_lamports, 0), ?2)
+             WHERE id = ?1",
+            params![id, value_lamports as i64, marked_at as i64],
+        )?;
+        Ok(())
+    }
+
+    pub fn close_position(
+        &self,
+        id: i64,
+        proceeds_lamports: u64,
+        closed_at: u64,
+        reason: &str,
+        signature: Option<&str>,
+    ) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE positions SET proceeds_lamports = ?2, closed_at = ?3, exit_reason = ?4, exit_signature = ?5,
+                 sell_signature = NULL
+             WHERE id = ?1 AND closed_at IS NULL",
+            params![id, proceeds_lamports as i64, closed_at as i64, reason, signature],
+        )?;
+        Ok(())
+    }
+
+    /// Records a sell about to be sent, with the exit rule that triggered it.
+    pub fn sell_sent(&self, id: i64, signature: &str, sent_at: u64, reason: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE positions SET sell_signature = ?2, sell_sent_at = ?3, exit_reason = ?4 WHERE id = ?1",
+            params![id, signature, sent_at as i64, reason],
+        )?;
+        Ok(())
+    }
+
+    /// Clears the pending sell after it failed or expired, holding the next
+    /// one back until `retry_at`.
+    pub fn sell_failed(&self, id: i64, retry_at: u64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE positions SET sell_signature = NULL, sell_sent_at = NULL, sell_attempts = sell_attempts + 1,
+                 sell_retry_at = ?2
+             WHERE id = ?1",
+            params![id, retry_at as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Records a pool, ignoring pools already stored. Returns whether the pool
+    /// is new.
+    pub fn insert_pool(&self, pool: &PoolRecord) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO pools (signature, amm, coin_mint, pc_mint, quote_mint, creator, slot, init_coin_amount,
+                 init_pc_amount, sol_liquidity_lamports, open_time, detected_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                pool.signature,
+                pool.amm,
+                pool.coin_mint,
+                pool.pc_mint,
+                pool.quote_mint,
+                pool.creator,
+                pool.slot as i64,
+                pool.init_coin_amount as i64,
+                pool.init_pc_amount as i64,
+                pool.sol_liquidity_lamports.map(|v| v as i64),
+                pool.open_time as i64,
+                pool.detected_at as i64,
+            ],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    pub fn pool(&self, amm: &str) -> Result<Option<PoolRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT * FROM pools WHERE amm = ?1")?;
+        let pool = stmt.query_map([amm], PoolRecord::from_row)?.next().transpose()?;
+        Ok(pool)
+    }
+
+    /// Other pools of the pair of `coin_mint` and `pc_mint`, either way round,
+    /// detected since `since`, newest first.
+    pub fn pair_pools(&self, amm: &str, coin_mint: &str, pc_mint: &str, since: u64) -> Result<Vec<PoolRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT * FROM pools
+             WHERE ((coin_mint = ?2 AND pc_mint = ?3) OR (coin_mint = ?3 AND pc_mint = ?2)) AND amm != ?1
+                 AND detected_at >= ?4
+             ORDER BY slot DESC, signature DESC",
+        )?;
+        let pools = stmt
+            .query_map(params![amm, coin_mint, pc_mint, since as i64], PoolRecord::from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(pools)
+    }
+
+    /// Pools detected in [since, until], filtered by creator and quote mint,
+    /// newest (highest slot) first, starting after `after`.
+    pub fn pools(
+        &self,
+        creator: Option<&str>,
+        quote_mint: Option<&str>,
+        since: u64,
+        until: u64,
+        after: Option<&PoolCursor>,
+        limit: usize,
+    ) -> Result<Vec<PoolRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT * FROM pools
+             WHERE (?1 IS NULL OR creator = ?1) AND (?2 IS NULL OR quote_mint = ?2)
+                 AND detected_at >= ?3 AND detected_at <= ?4 AND (?5 IS NULL OR (slot, signature) < (?5, ?6))
+             ORDER BY slot DESC, signature DESC LIMIT ?7",
+        )?;
+        let pools = stmt
+            .query_map(
+                params![
+                    creator,
+                    quote_mint,
+                    since as i64,
+                    until.min(i64::MAX as u64) as i64,
+                    after.map(|after| after.slot as i64),
+                    after.map(|after| &after.signature),
+                    limit as i64,
+                ],
+                PoolRecord::from_row,
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(pools)
+    }
+
+    /// Pool and rug counts of a creator, None when it created no recorded
+    /// pool.
+    pub fn creator_summary(&self, creator: &str) -> Result<Option<CreatorSummary>> {
+        let conn = self.conn.lock().unwrap();
+        let (pools, rugs, first, last) = conn.query_row(
+            "SELECT COUNT(*), COUNT(r.amm), MIN(p.detected_at), MAX(p.detected_at)
+             FROM pools p LEFT JOIN rugs r ON r.amm = p.amm WHERE p.creator = ?1",
+            [creator],
+            |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, Option<i64>>(2)?, row.get::<_, Option<i64>>(3)?))
+            },
+        )?;
+        Ok(first.zip(last).filter(|_| pools > 0).map(|(first, last)| CreatorSummary {
+            creator: creator.to_string(),
+            pools: pools as u64,
+            rugs: rugs as u64,
+            first_detected_at: first as u64,
+            last_detected_at: last as u64,
+        }))
+    }
+
+    /// Whether a pool created by this transaction is already recorded.
+    pub fn has_pool_signature(&self, signature: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let exists = conn.prepare("SELECT 1 FROM pools WHERE signature = ?1")?.exists([signature])?;
+        Ok(exists)
+    }
+
+    /// Records a launchpad sale, ignoring tokens already recorded. Returns
+    /// whether the sale is new.
+    pub fn insert_launch(&self, launch: &LaunchRecord) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO launches (base_mint, launch_pool, program, signature, creator, quote_mint, symbol, slot,
+                 detected_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                launch.base_mint,
+                launch.launch_pool,
+                launch.program,
+                launch.signature,
+                launch.creator,
+                launch.quote_mint,
+                launch.symbol,
+                launch.slot as i64,
+                launch.detected_at as i64,
+            ],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    /// The sale that launched `base_mint`, if one was seen.
+    pub fn launch(&self, base_mint: &str) -> Result<Option<LaunchRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT * FROM launches WHERE base_mint = ?1")?;
+        let launch = stmt.query_map([base_mint], LaunchRecord::from_row)?.next().transpose()?;
+        Ok(launch)
+    }
+
+    pub fn insert_candle(&self, candle: &Candle) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO candles (amm, resolution_secs, start_time, open, high, low, close, volume)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                candle.amm,
+                candle.resolution_secs as i64,
+                candle.start_time as i64,
+                candle.open,
+                candle.high,
+                candle.low,
+                candle.close,
+                candle.volume,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn insert_first_buyer(&self, buyer: &FirstBuyer) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR IGNORE INTO first_buyers (amm, signature, buyer, slot, fresh, known_bot, deployer_funded)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                buyer.amm,
+                buyer.signature,
+                buyer.buyer,
+                buyer.slot as i64,
+                buyer.fresh,
+                buyer.known_bot,
+                buyer.deployer_funded,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn insert_snipe_report(&self, report: &SnipeReport) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO snipes (amm, early_buys, sniper_buys, identical_buys, jito_buys, bot_program_buys,
+                 sniped_amount, sniped_percent, sniped)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                report.amm,
+                report.early_buys as i64,
+                report.sniper_buys as i64,
+                report.identical_buys as i64,
+                report.jito_buys as i64,
+                report.bot_program_buys as i64,
+                report.sniped_amount as i64,
+                report.sniped_percent,
+                report.sniped,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Sniping totals over the pools detected in [since, until].
+    pub fn snipe_counts(&self, since: u64, until: u64) -> Result<SnipeCounts> {
+        let conn = self.conn.lock().unwrap();
+        let counts = conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(s.sniped), 0), COALESCE(AVG(s.sniped_percent), 0)
+             FROM snipes s JOIN pools p ON p.amm = s.amm
+             WHERE p.detected_at >= ?1 AND p.detected_at <= ?2",
+            params![since as i64, until as i64],
+            |row| {
+                Ok(SnipeCounts {
+                    pools: row.get::<_, i64>(0)? as u64,
+                    sniped: row.get::<_, i64>(1)? as u64,
+                    avg_sniped_percent: row.get(2)?,
+                })
+            },
+        )?;
+        Ok(counts)
+    }
+
+    /// First buyers of a pool funded by its deployer.
+    pub fn deployer_funded_buyers(&self, amm: &str) -> Result<Vec<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT DISTINCT buyer FROM first_buyers WHERE amm = ?1 AND deployer_funded = 1")?;
+        let buyers = stmt.query_map([amm], |row| row.get(0))?.collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(buyers)
+    }
+
+    /// First buyers of a pool in slot order.
+    pub fn first_buyers(&self, amm: &str) -> Result<Vec<FirstBuyer>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT amm, signature, buyer, slot, fresh, known_bot, deployer_funded
+             FROM first_buyers WHERE amm = ?1 ORDER BY slot, signature",
+        )?;
+        let buyers = stmt
+            .query_map([amm], |row| {
+                Ok(FirstBuyer {
+                    amm: row.get(0)?,
+                    signature: row.get(1)?,
+                    buyer: row.get(2)?,
+                    slot: row.get::<_, i64>(3)? as u64,
+                    fresh: row.get(4)?,
+                    known_bot: row.get(5)?,
+                    deployer_funded: row.get(6)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(buyers)
+    }
+
+    pub fn snipe_report(&self, amm: &str) -> Result<Option<SnipeReport>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT amm, early_buys, sniper_buys, identical_buys, jito_buys, bot_program_buys, sniped_amount,
+                 sniped_percent, sniped
+             FROM snipes WHERE amm = ?1",
+        )?;
+        let report = stmt
+            .query_map([amm], |row| {
+                Ok(SnipeReport {
+                    amm: row.get(0)?,
+                    early_buys: row.get::<_, i64>(1)? as u64,
+                    sniper_buys: row.get::<_, i64>(2)? as u64,
+                    identical_buys: row.get::<_, i64>(3)? as u64,
+                    jito_buys: row.get::<_, i64>(4)? as u64,
+                    bot_program_buys: row.get::<_, i64>(5)? as u64,
+                    sniped_amount: row.get::<_, i64>(6)? as u64,
+                    sniped_percent: row.get(7)?,
+                    sniped: row.get(8)?,
+                })
+            })?
+            .next()
+            .transpose()?;
+        Ok(report)
+    }
+
+    /// First-buyer totals over the pools detected in [since, until].
+    pub fn first_buyer_counts(&self, since: u64, until: u64) -> Result<FirstBuyerCounts> {
+        let conn = self.conn.lock().unwrap();
+        let counts = conn.query_row(
+            "SELECT COUNT(DISTINCT b.amm), COUNT(*), COALESCE(SUM(b.fresh), 0), COALESCE(SUM(b.known_bot), 0),
+                 COALESCE(SUM(b.deployer_funded), 0)
+             FROM first_buyers b JOIN pools p ON p.amm = b.amm
+             WHERE p.detected_at >= ?1 AND p.detected_at <= ?2",
+            params![since as i64, until as i64],
+            |row| {
+                Ok(FirstBuyerCounts {
+                    pools: row.get::<_, i64>(0)? as u64,
+                    buyers: row.get::<_, i64>(1)? as u64,
+                    fresh: row.get::<_, i64>(2)? as u64,
+                    known_bots: row.get::<_, i64>(3)? as u64,
+                    deployer_funded: row.get::<_, i64>(4)? as u64,
+                })
+            },
+        )?;
+        Ok(counts)
+    }
+
+    pub fn insert_swap(&self, swap: &SwapRecord) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR IGNORE INTO swaps (amm, signature, seq, time, buy, base_amount, quote_amount)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![swap.amm, swap.signature, swap.seq, swap.time as i64, swap.buy, swap.base_amount, swap.quote_amount],
+        )?;
+        Ok(())
+    }
+
+    /// Up to `limit` swaps of a pool, newest first, starting after `after`.
+    pub fn swaps(&self, amm: &str, after: Option<&SwapCursor>, limit: usize) -> Result<Vec<SwapRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT amm, signature, seq, time, buy, base_amount, quote_amount
+             FROM swaps WHERE amm = ?1 AND (?2 IS NULL OR (time, signature, seq) < (?2, ?3, ?4))
+             ORDER BY time DESC, signature DESC, seq DESC LIMIT ?5",
+        )?;
+        let swaps = stmt
+            .query_map(
+                params![
+                    amm,
+                    after.map(|after| after.time as i64),
+                    after.map(|after| &after.signature),
+                    after.map(|after| after.seq),
+                    limit as i64,
+                ],
+                |row| {
+                    Ok(SwapRecord {
+                        amm: row.get(0)?,
+                        signature: row.get(1)?,
+                        seq: row.get(2)?,
+                        time: row.get::<_, i64>(3)? as u64,
+                        buy: row.get(4)?,
+                        base_amount: row.get(5)?,
+                        quote_amount: row.get(6)?,
+                    })
+                },
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(swaps)
+    }
+
+    /// Rolling volume ending at `now` of every pool (or only `amm`) with
+    /// trades in the longest window, highest volume in that window first.
+    pub fn pool_volumes(&self, now: u64, amm: Option<&str>, windows_secs: &[u64]) -> Result<Vec<PoolVolume>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT amm, COUNT(*), SUM(buy), SUM(quote_amount) FROM swaps
+             WHERE time > ?1 AND (?2 IS NULL OR amm = ?2) GROUP BY amm",
+        )?;
+        let mut pools: BTreeMap<String, Vec<WindowVolume>> = BTreeMap::new();
+        for &window_secs in windows_secs {
+            let rows = stmt.query_map(params![now.saturating_sub(window_secs) as i64, amm], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    WindowVolume {
+                        window_secs,
+                        trades: row.get::<_, i64>(1)? as u64,
+                        buys: row.get::<_, i64>(2)? as u64,
+                        quote_volume: row.get(3)?,
+                    },
+                ))
+            })?;
+            for row in rows {
+                let (amm, volume) = row?;
+                pools.entry(amm).or_default().push(volume);
+            }
+        }
+        // fill windows without trades so every pool lists all of them
+        let mut volumes = pools
+            .into_iter()
+            .map(|(amm, found)| PoolVolume {
+                amm,
+                windows: windows_secs
+                    .iter()
+                    .map(|&window_secs| {
+                        found.iter().find(|volume| volume.window_secs == window_secs).cloned().unwrap_or(WindowVolume {
+                            window_secs,
+                            trades: 0,
+                            buys: 0,
+                            quote_volume: 0.0,
+                        })
+                    })
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+        volumes.sort_by(|a, b| {
+            let volume = |pool: &PoolVolume| pool.windows.last().map_or(0.0, |window| window.quote_volume);
+            volume(b).total_cmp(&volume(a))
+        });
+        Ok(volumes)
+    }
+
+    pub fn insert_holder_snapshot(&self, snapshot: &HolderSnapshot) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO holder_snapshots
+                 (amm, offset_secs, taken_at, supply, pool_percent, top1_percent, top10_percent, top_holders)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                snapshot.amm,
+                snapshot.offset_secs as i64,
+                snapshot.taken_at as i64,
+                snapshot.supply,
+                snapshot.pool_percent,
+                snapshot.top1_percent,
+                snapshot.top10_percent,
+                serde_json::to_string(&snapshot.top_holders)?,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Holder snapshots of a pool, earliest first.
+    pub fn holder_snapshots(&self, amm: &str) -> Result<Vec<HolderSnapshot>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT amm, offset_secs, taken_at, supply, pool_percent, top1_percent, top10_percent, top_holders
+             FROM holder_snapshots WHERE amm = ?1 ORDER BY offset_secs",
+        )?;
+        let rows = stmt.query_map([amm], |row| {
+            Ok((
+                HolderSnapshot {
+                    amm: row.get(0)?,
+                    offset_secs: row.get::<_, i64>(1)? as u64,
+                    taken_at: row.get::<_, i64>(2)? as u64,
+                    supply: row.get(3)?,
+                    pool_percent: row.get(4)?,
+                    top1_percent: row.get(5)?,
+                    top10_percent: row.get(6)?,
+                    top_holders: Vec::new(),
+                },
+                row.get::<_, String>(7)?,
+            ))
+        })?;
+        let mut snapshots = Vec::new();
+        for row in rows {
+            let (mut snapshot, top_holders) = row?;
+            snapshot.top_holders = serde_json::from_str(&top_holders)?;
+            snapshots.push(snapshot);
+        }
+        Ok(snapshots)
+    }
+
+    pub fn insert_performance_point(&self, point: &PerformancePoint) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO performance
+                 (amm, offset_secs, taken_at, price, quote_liquidity, price_change_percent, liquidity_change_percent)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                point.amm,
+                point.offset_secs as i64,
+                point.taken_at as i64,
+                point.price,
+                point.quote_liquidity,
+                point.price_change_percent,
+                point.liquidity_change_percent,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Performance report of a pool, earliest offset first.
+    pub fn performance(&self, amm: &str) -> Result<Vec<PerformancePoint>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT amm, offset_secs, taken_at, price, quote_liquidity, price_change_percent, liquidity_change_percent
+             FROM performance WHERE amm = ?1 ORDER BY offset_secs",
+        )?;
+        let points = stmt
+            .query_map([amm], |row| {
+                Ok(PerformancePoint {
+                    amm: row.get(0)?,
+                    offset_secs: row.get::<_, i64>(1)? as u64,
+                    taken_at: row.get::<_, i64>(2)? as u64,
+                    price: row.get(3)?,
+                    quote_liquidity: row.get(4)?,
+                    price_change_percent: row.get(5)?,
+                    liquidity_change_percent: row.get(6)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(points)
+    }
+
+    pub fn insert_rug(&self, amm: &str, detected_at: u64, drop_percent: f64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR IGNORE INTO rugs (amm, detected_at, drop_percent) VALUES (?1, ?2, ?3)",
+            params![amm, detected_at as i64, drop_percent],
+        )?;
+        Ok(())
+    }
+
+    pub fn rug(&self, amm: &str) -> Result<Option<Rug>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT amm, detected_at, drop_percent FROM rugs WHERE amm = ?1")?;
+        let rug = stmt
+            .query_map([amm], |row| {
+                Ok(Rug { amm: row.get(0)?, detected_at: row.get::<_, i64>(1)? as u64, drop_percent: row.get(2)? })
+            })?
+            .next()
+            .transpose()?;
+        Ok(rug)
+    }
+
+    /// Pools whose liquidity was pulled in [since, until].
+    pub fn rug_count(&self, since: u64, until: u64) -> Result<u64> {
+        let conn = self.conn.lock().unwrap();
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM rugs WHERE detected_at >= ?1 AND detected_at <= ?2",
+            params![since as i64, until as i64],
+            |row| row.get(0),
+        )?;
+        Ok(count as u64)
+    }
+
+    /// SOL pools detected in [since, until] with the most initial liquidity.
+    pub fn biggest_launches(&self, since: u64, until: u64, limit: usize) -> Result<Vec<Launch>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT amm, CASE WHEN quote_mint = coin_mint THEN pc_mint ELSE coin_mint END, sol_liquidity_lamports
+             FROM pools
+             WHERE detected_at >= ?1 AND detected_at <= ?2 AND sol_liquidity_lamports IS NOT NULL
+             ORDER BY sol_liquidity_lamports DESC LIMIT ?3",
+        )?;
+        let launches = stmt
+            .query_map(params![since as i64, until as i64, limit as i64], |row| {
+                Ok(Launch {
+                    amm: row.get(0)?,
+                    base_mint: row.get(1)?,
+                    sol_liquidity_lamports: row.get::<_, i64>(2)? as u64,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(launches)
+    }
+
+    /// Queues a signature for a later retry; already queued signatures keep
+    /// their schedule.
+    pub fn enqueue_retry(&self, signature: &str, first_seen_at: u64, next_attempt_at: u64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR IGNORE INTO retry_queue (signature, attempts, first_seen_at, next_attempt_at) VALUES (?1, 0, ?2, ?3)",
+            params![signature, first_seen_at as i64, next_attempt_at as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Queued signatures whose next attempt is due, oldest first.
+    pub fn due_retries(&self, now: u64) -> Result<Vec<RetryEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT signature, attempts, first_seen_at FROM retry_queue
+             WHERE next_attempt_at <= ?1 ORDER BY next_attempt_at",
+        )?;
+        let entries = stmt
+            .query_map([now as i64], |row| {
+                Ok(RetryEntry {
+                    signature: row.get(0)?,
+                    attempts: row.get(1)?,
+                    first_seen_at: row.get::<_, i64>(2)? as u64,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(entries)
+    }
+
+    pub fn reschedule_retry(&self, signature: &str, attempts: u32, next_attempt_at: u64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE retry_queue SET attempts = ?2, next_attempt_at = ?3 WHERE signature = ?1",
+            params![signature, attempts, next_attempt_at as i64],
+        )?;
+        Ok(())
+    }
+
+    pub fn retry_queue_len(&self) -> Result<u64> {
+        let conn = self.conn.lock().unwrap();
+        let len: i64 = conn.query_row("SELECT COUNT(*) FROM retry_queue", [], |row| row.get(0))?;
+        Ok(len as u64)
+    }
+
+    pub fn remove_retry(&self, signature: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM retry_queue WHERE signature = ?1", [signature])?;
+        Ok(())
+    }
+
+    pub fn enqueue_delivery(&self, sink: &str, event: &str, queued_at: u64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO delivery_queue (sink, event, queued_at, attempts) VALUES (?1, ?2, ?3, 0)",
+            params![sink, event, queued_at as i64],
+        )?;
+        Ok(())
+    }
+
+    /// The oldest `limit` events queued for `sink`, in queue order.
+    pub fn queued_deliveries(&self, sink: &str, limit: usize) -> Result<Vec<QueuedDelivery>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt =
+            conn.prepare("SELECT id, event, queued_at, attempts FROM delivery_queue WHERE sink = ?1 ORDER BY id LIMIT ?2")?;
+        let entries = stmt
+            .query_map(params![sink, limit as i64], |row| {
+                Ok(QueuedDelivery {
+                    id: row.get(0)?,
+                    event: row.get(1)?,
+                    queued_at: row.get::<_, i64>(2)? as u64,
+                    attempts: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(entries)
+    }
+
+    pub fn delivery_queue_len(&self, sink: &str) -> Result<u64> {
+        let conn = self.conn.lock().unwrap();
+        let len: i64 = conn.query_row("SELECT COUNT(*) FROM delivery_queue WHERE sink = ?1", [sink], |row| row.get(0))?;
+        Ok(len as u64)
+    }
+
+    pub fn delivery_failed(&self, id: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("UPDATE delivery_queue SET attempts = attempts + 1 WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    pub fn remove_delivery(&self, id: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM delivery_queue WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    pub fn insert_event(&self, emitted_at: u64, slot: u64, event: &str) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO events (emitted_at, slot, event) VALUES (?1, ?2, ?3)",
+            params![emitted_at as i64, slot as i64, event],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// The id of the first event at or after `slot` and `since` (unix
+    /// seconds), whichever are given.
+    pub fn first_event_id(&self, slot: Option<u64>, since: Option<u64>) -> Result<Option<i64>> {
+        let conn = self.conn.lock().unwrap();
+        let id = conn.query_row(
+            "SELECT MIN(id) FROM events WHERE (?1 IS NULL OR slot >= ?1) AND (?2 IS NULL OR emitted_at >= ?2)",
+            params![slot.map(|slot| slot as i64), since.map(|since| since as i64)],
+            |row| row.get(0),
+        )?;
+        Ok(id)
+    }
+
+    /// Up to `limit` events emitted after the event `after_id`, oldest first.
+    pub fn events_after(&self, after_id: i64, limit: usize) -> Result<Vec<StoredEvent>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT id, emitted_at, slot, event FROM events WHERE id > ?1 ORDER BY id LIMIT ?2")?;
+        let events = stmt
+            .query_map(params![after_id, limit as i64], |row| {
+                Ok(StoredEvent {
+                    id: row.get(0)?,
+                    emitted_at: row.get::<_, i64>(1)? as u64,
+                    slot: row.get::<_, i64>(2)? as u64,
+                    event: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(events)
+    }
+
+    /// Deletes events emitted before `before` (unix seconds), returning how
+    /// many.
+    pub fn prune_events(&self, before: u64) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        Ok(conn.execute("DELETE FROM events WHERE emitted_at < ?1", [before as i64])?)
+    }
+
+    pub fn insert_webhook(&self, url: &str, secret: Option<&str>, filter: &str, created_at: u64) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO webhooks (url, secret, filter, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![url, secret, filter, created_at as i64],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    pub fn webhooks(&self) -> Result<Vec<WebhookRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT id, url, secret, filter, created_at FROM webhooks ORDER BY id")?;
+        let webhooks = stmt
+            .query_map([], |row| {
+                Ok(WebhookRecord {
+                    id: row.get(0)?,
+                    url: row.get(1)?,
+                    secret: row.get(2)?,
+                    filter: row.get(3)?,
+                    created_at: row.get::<_, i64>(4)? as u64,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(webhooks)
+    }
+
+    /// False when there was no such webhook.
+    pub fn delete_webhook(&self, id: i64) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        Ok(conn.execute("DELETE FROM webhooks WHERE id = ?1", [id])? > 0)
+    }
+
+    /// Adds requests to a key's count for a quota period. Keys are stored as
+    /// fingerprints, never in full.
+    pub fn add_key_usage(&self, period: &str, key: &str, requests: u64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO api_key_usage (period, key, requests) VALUES (?1, ?2, ?3)
+             ON CONFLICT (period, key) DO UPDATE SET requests = requests + excluded.requests",
+            params![period, key, requests as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Requests per key in a quota period.
+    pub fn key_usage(&self, period: &str) -> Result<BTreeMap<String, u64>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT key, requests FROM api_key_usage WHERE period = ?1")?;
+        let usage = stmt
+            .query_map([period], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as u64)))?
+            .collect::<rusqlite::Result<BTreeMap<_, _>>>()?;
+        Ok(usage)
+    }
+
+    /// (period, key, requests) for the latest `periods` quota periods, newest
+    /// first.
+    pub fn key_usage_history(&self, periods: usize) -> Result<Vec<(String, String, u64)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT period, key, requests FROM api_key_usage
+             WHERE period IN (SELECT DISTINCT period FROM api_key_usage ORDER BY period DESC LIMIT ?1)
+             ORDER BY period DESC, key",
+        )?;
+        let usage = stmt
+            .query_map([periods as i64], |row| Ok((row.get(0)?, row.get(1)?, row.get::<_, i64>(2)? as u64)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(usage)
+    }
+
+    /// Adds calls of an RPC or WebSocket method on an endpoint to a UTC day's
+    /// count.
+    pub fn add_rpc_usage(&self, day: &str, endpoint: &str, method: &str, calls: u64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO rpc_usage (day, endpoint, method, calls) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (day, endpoint, method) DO UPDATE SET calls = calls + excluded.calls",
+            params![day, endpoint, method, calls as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Calls per endpoint and method on UTC days from `since_day` on, most
+    /// called first.
+    pub fn rpc_usage(&self, since_day: &str) -> Result<Vec<RpcUsage>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT endpoint, method, SUM(calls) FROM rpc_usage WHERE day >= ?1
+             GROUP BY endpoint, method ORDER BY SUM(calls) DESC, endpoint, method",
+        )?;
+        let usage = stmt
+            .query_map([since_day], |row| {
+                Ok(RpcUsage { endpoint: row.get(0)?, method: row.get(1)?, calls: row.get::<_, i64>(2)? as u64 })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(usage)
+    }
+
+    /// UTC days from `since_day` on with recorded RPC usage.
+    pub fn rpc_usage_days(&self, since_day: &str) -> Result<u64> {
+        let conn = self.conn.lock().unwrap();
+        let days: i64 =
+            conn.query_row("SELECT COUNT(DISTINCT day) FROM rpc_usage WHERE day >= ?1", [since_day], |row| row.get(0))?;
+        Ok(days as u64)
+    }
+
+    /// Pools detected per UTC day in [since, until], oldest first.
+    pub fn pools_per_day(&self, since: u64, until: u64) -> Result<Vec<(String, u64)>> {
+        self.grouped_counts(
+            "SELECT date(detected_at, 'unixepoch') AS day, COUNT(*) FROM pools
+             WHERE detected_at >= ?1 AND detected_at <= ?2 GROUP BY day ORDER BY day",
+            since,
+            until,
+        )
+    }
+
+    /// Pools per creator in [since, until], most active first.
+    pub fn top_creators(&self, since: u64, until: u64, limit: usize) -> Result<Vec<(String, u64)>> {
+        let mut creators = self.grouped_counts(
+            "SELECT creator, COUNT(*) AS pools FROM pools
+             WHERE detected_at >= ?1 AND detected_at <= ?2 GROUP BY creator ORDER BY pools DESC, creator",
+            since,
+            until,
+        )?;
+        creators.truncate(limit);
+        Ok(creators)
+    }
+
+    /// Pools per quote token in [since, until], most common first.
+    pub fn quote_breakdown(&self, since: u64, until: u64) -> Result<Vec<(String, u64)>> {
+        self.grouped_counts(
+            "SELECT quote_mint, COUNT(*) AS pools FROM pools
+             WHERE detected_at >= ?1 AND detected_at <= ?2 GROUP BY quote_mint ORDER BY pools DESC, quote_mint",
+            since,
+            until,
+        )
+    }
+
+    /// Initial SOL liquidity of the SOL pools in [since, until], ascending.
+    pub fn sol_liquidities(&self, since: u64, until: u64) -> Result<Vec<u64>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT sol_liquidity_lamports FROM pools
+             WHERE detected_at >= ?1 AND detected_at <= ?2 AND sol_liquidity_lamports IS NOT NULL
+             ORDER BY sol_liquidity_lamports",
+        )?;
+        let liquidities = stmt
+            .query_map(params![since as i64, until as i64], |row| row.get::<_, i64>(0).map(|v| v as u64))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(liquidities)
+    }
+
+    fn grouped_counts(&self, sql: &str, since: u64, until: u64) -> Result<Vec<(String, u64)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt
+            .query_map(params![since as i64, until as i64], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(rows)
+    }
+
+    pub fn pnl_summary(&self, wallet: Option<&str>) -> Result<Vec<PnlSummary>> {
+        let mut summaries: Vec<PnlSummary> = Vec::new();
+        for position in self.positions(wallet, false)? {
+            let index = match summaries.iter().position(|s| s.wallet == position.wallet) {
+                Some(index) => index,
+                None => {
+                    summaries.push(PnlSummary { wallet: position.wallet.clone(), ..PnlSummary::default() });
+                    summaries.len() - 1
+                }
+            };
+            let summary = &mut summaries[index];
+            if position.is_open() {
+                summary.open_positions += 1;
+                summary.unrealized_pnl_lamports += position.unrealized_pnl_lamports().unwrap_or(0);
+            } else {
+                summary.closed_positions += 1;
+                summary.realized_pnl_lamports += position.realized_pnl_lamports().unwrap_or(0);
+            }
+        }
+        summaries.sort_by(|a, b| a.wallet.cmp(&b.wallet));
+        Ok(summaries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(signature: &str, slot: u64) -> PoolRecord {
+        PoolRecord {
+            signature: signature.to_string(),
+            amm: format!("amm-{}", signature),
+            coin_mint: format!("mint-{}", signature),
+            pc_mint: "So11111111111111111111111111111111111111112".to_string(),
+            quote_mint: "So11111111111111111111111111111111111111112".to_string(),
+            creator: "creator".to_string(),
+            slot,
+            init_coin_amount: 200_000_000_000,
+            init_pc_amount: 5_000_000_000,
+            sol_liquidity_lamports: Some(5_000_000_000),
+            open_time: 0,
+            detected_at: 100,
+        }
+    }
+
+    #[test]
+    fn pool_pages_neither_skip_nor_repeat_pools_sharing_a_slot() {
+        let store = Store::open(":memory:").unwrap();
+        // pools recorded before the slot column all have slot 0
+        for (signature, slot) in [("a", 0), ("b", 0), ("c", 0), ("d", 7), ("e", 9)] {
+            store.insert_pool(&pool(signature, slot)).unwrap();
+        }
+        let mut seen = Vec::new();
+        let mut after = None;
+        loop {
+            let page = store.pools(None, None, 0, u64::MAX, after.as_ref(), 2).unwrap();
+            let Some(last) = page.last() else { break };
+            after = Some(last.cursor().to_string().parse::<PoolCursor>().unwrap());
+            seen.extend(page.into_iter().map(|pool| pool.signature));
+        }
+        assert_eq!(seen, ["e", "d", "c", "b", "a"]);
+    }
+
+    #[test]
+    fn pair_pools_match_either_side() {
+        let store = Store::open(":memory:").unwrap();
+        let first = pool("a", 1);
+        let flipped = PoolRecord {
+            amm: "amm-b".to_string(),
+            signature: "b".to_string(),
+            coin_mint: first.pc_mint.clone(),
+            pc_mint: first.coin_mint.clone(),
+            slot: 2,
+            ..first.clone()
+        };
+        for pool in [first.clone(), flipped, pool("c", 3)] {
+            store.insert_pool(&pool).unwrap();
+        }
+        let pools = store.pair_pools("amm-new", &first.coin_mint, &first.pc_mint, 0).unwrap();
+        assert_eq!(pools.iter().map(|pool| pool.amm.as_str()).collect::<Vec<_>>(), ["amm-b", "amm-a"]);
+        assert!(store.pair_pools("amm-a", &first.coin_mint, &first.pc_mint, 101).unwrap().is_empty());
+    }
+
+    #[test]
+    fn launches_are_recorded_once_per_token() {
+        let store = Store::open(":memory:").unwrap();
+        let launch = LaunchRecord {
+            base_mint: "mint".to_string(),
+            launch_pool: "launch-pool".to_string(),
+            program: "program".to_string(),
+            signature: "sig".to_string(),
+            creator: "creator".to_string(),
+            quote_mint: "wsol".to_string(),
+            symbol: "BDOG".to_string(),
+            slot: 7,
+            detected_at: 100,
+        };
+        assert!(store.insert_launch(&launch).unwrap());
+        assert!(!store.insert_launch(&LaunchRecord { signature: "other".to_string(), ..launch.clone() }).unwrap());
+        assert_eq!(store.launch("mint").unwrap(), Some(launch));
+        assert_eq!(store.launch("other-mint").unwrap(), None);
+    }
+
+    #[test]
+    fn pending_sells_are_kept_until_they_fail_or_close() {
+        let store = Store::open(":memory:").unwrap();
+        let position = Position {
+            id: 0,
+            wallet: "default".to_string(),
+            paper: false,
+            amm: "amm".to_string(),
+            token_mint: "mint".to_string(),
+            pool_accounts: "{}".to_string(),
+            sol_is_pc: true,
+            cost_lamports: 100_000_000,
+            tokens: 5_000,
+            entry_signature: Some("buy".to_string()),
+            entry_route: Some("rpc".to_string()),
+            entry_routes: Some("rpc_url,jito".to_string()),
+            opened_at: 100,
+            last_value_lamports: None,
+            last_marked_at: None,
+            closed_at: None,
+            proceeds_lamports: None,
+            peak_value_lamports: None,
+            exit_reason: None,
+            exit_signature: None,
+            sell_signature: None,
+            sell_sent_at: None,
+            sell_attempts: 0,
+            sell_retry_at: None,
+        };
+        let id = store.insert_position(&position).unwrap();
+        store.sell_sent(id, "sell-1", 200, "stop_loss").unwrap();
+        let pending = store.positions(None, true).unwrap().remove(0);
+        assert_eq!((pending.sell_signature.as_deref(), pending.sell_sent_at), (Some("sell-1"), Some(200)));
+        assert_eq!(pending.exit_reason.as_deref(), Some("stop_loss"));
+
+        store.sell_failed(id, 215).unwrap();
+        let failed = store.positions(None, true).unwrap().remove(0);
+        assert_eq!((failed.sell_signature, failed.sell_attempts, failed.sell_retry_at), (None, 1, Some(215)));
+
+        store.sell_sent(id, "sell-2", 220, "stop_loss").unwrap();
+        store.close_position(id, 90_000_000, 230, "stop_loss", Some("sell-2")).unwrap();
+        let closed = store.positions(None, false).unwrap().remove(0);
+        assert_eq!((closed.sell_signature, closed.exit_signature.as_deref()), (None, Some("sell-2")));
+    }
+
+    #[test]
+    fn cursors_round_trip() {
+        let cursor = PoolCursor { slot: 250_000_000, signature: "5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnb".to_string() };
+        assert_eq!(cursor.to_string().parse::<PoolCursor>().unwrap(), cursor);
+        let cursor = SwapCursor { time: 1_700_000_000, signature: "sig".to_string(), seq: 2 };
+        assert_eq!(cursor.to_string().parse::<SwapCursor>().unwrap(), cursor);
+        assert!("250000000".parse::<PoolCursor>().is_err());
+        assert!("1700000000:sig".parse::<SwapCursor>().is_err());
+    }
+}
@@ -0,0 +1,231 @@
+use crate::config::{SyslogConfig, SyslogTransport};
+use anyhow::{anyhow, Context, Result};
+use chrono::{SecondsFormat, Utc};
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::rustls::{self, ClientConfig, RootCertStore, ServerName};
+use tokio_rustls::TlsConnector;
+use tracing::warn;
+use tracing_subscriber::fmt::MakeWriter;
+use std::io::{self, BufReader, Write};
+use std::sync::Arc;
+use std::time::Duration;
+
+// messages queued for the transport task before new ones are dropped
+const QUEUE_LEN: usize = 10_000;
+// messages are dropped for this long after the collector was unreachable
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// The RFC 5424 header fields that are the same for every message.
+struct Header {
+    facility: u8,
+    hostname: String,
+    app_name: String,
+    procid: u32,
+}
+
+/// Sends formatted log lines to a syslog collector as RFC 5424 messages,
+/// with the level as the severity.
+pub struct SyslogWriter {
+    header: Arc<Header>,
+    tx: mpsc::Sender<String>,
+}
+
+impl SyslogWriter {
+    /// Starts the transport task; must be called within the runtime.
+    pub fn new(config: &SyslogConfig) -> Result<Self> {
+        let tls = match config.transport {
+            SyslogTransport::Tls => Some(tls_config(config)?),
+            _ => None,
+        };
+        let header = Header {
+            facility: config.facility.code(),
+            hostname: config.hostname.clone().unwrap_or_else(hostname),
+            app_name: config.app_name.clone(),
+            procid: std::process::id(),
+        };
+        let (tx, rx) = mpsc::channel(QUEUE_LEN);
+        tokio::spawn(Transport { config: config.clone(), tls }.run(rx));
+        Ok(Self { header: Arc::new(header), tx })
+    }
+}
+
+impl<'a> MakeWriter<'a> for SyslogWriter {
+    type Writer = MessageWriter;
+
+    fn make_writer(&'a self) -> MessageWriter {
+        MessageWriter { header: self.header.clone(), tx: self.tx.clone(), severity: 6, buf: Vec::new() }
+    }
+
+    fn make_writer_for(&'a self, meta: &tracing::Metadata<'_>) -> MessageWriter {
+        let severity = match *meta.level() {
+            tracing::Level::ERROR => 3,
+            tracing::Level::WARN => 4,
+            tracing::Level::INFO => 6,
+            tracing::Level::DEBUG | tracing::Level::TRACE => 7,
+        };
+        MessageWriter { header: self.header.clone(), tx: self.tx.clone(), severity, buf: Vec::new() }
+    }
+}
+
+/// Buffers one formatted event and queues it as a message on drop.
+pub struct MessageWriter {
+    header: Arc<Header>,
+    tx: mpsc::Sender<String>,
+    severity: u8,
+    buf: Vec<u8>,
+}
+
+impl Write for MessageWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for MessageWriter {
+    fn drop(&mut self) {
+        let msg = String::from_utf8_lossy(&self.buf);
+        let msg = msg.trim_end();
+        if msg.is_empty() {
+            return;
+        }
+        // <PRI>VERSION TIMESTAMP HOSTNAME APP-NAME PROCID MSGID STRUCTURED-DATA MSG
+        let message = format!(
+            "<{}>1 {} {} {} {} - - {}",
+            self.header.facility * 8 + self.severity,
+            Utc::now().to_rfc3339_opts(SecondsFormat::Micros, true),
+            self.header.hostname,
+            self.header.app_name,
+            self.header.procid,
+            msg
+        );
+        let _ = self.tx.try_send(message);
+    }
+}
+
+enum Connection {
+    Udp(UdpSocket),
+    Tcp(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>),
+}
+
+impl Connection {
+    async fn send(&mut self, message: &str) -> io::Result<()> {
+        match self {
+            Connection::Udp(socket) => socket.send(message.as_bytes()).await.map(|_| ()),
+            Connection::Tcp(stream) => stream.write_all(octet_counted(message).as_bytes()).await,
+            Connection::Tls(stream) => {
+                stream.write_all(octet_counted(message).as_bytes()).await?;
+                stream.flush().await
+            }
+        }
+    }
+}
+
+struct Transport {
+    config: SyslogConfig,
+    tls: Option<Arc<ClientConfig>>,
+}
+
+impl Transport {
+    async fn run(self, mut rx: mpsc::Receiver<String>) {
+        let mut connection = None;
+        let mut retry_at = Instant::now();
+        while let Some(message) = rx.recv().await {
+            let current = match &mut connection {
+                Some(current) => current,
+                None if Instant::now() < retry_at => continue,
+                None => match self.connect().await {
+                    Ok(new) => connection.insert(new),
+                    Err(e) => {
+                        retry_at = Instant::now() + RECONNECT_DELAY;
+                        warn!(address = %self.config.address, error = %e, "Failed to connect to syslog");
+                        continue;
+                    }
+                },
+            };
+            if let Err(e) = current.send(&message).await {
+                connection = None;
+                retry_at = Instant::now() + RECONNECT_DELAY;
+                warn!(address = %self.config.address, error = %e, "Lost syslog connection");
+            }
+        }
+    }
+
+    async fn connect(&self) -> Result<Connection> {
+        let address = tokio::net::lookup_host(&self.config.address)
+            .await?
+            .next()
+            .ok_or_else(|| anyhow!("{} did not resolve", self.config.address))?;
+        match self.config.transport {
+            SyslogTransport::Udp => {
+                let socket = UdpSocket::bind(if address.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" }).await?;
+                socket.connect(address).await?;
+                Ok(Connection::Udp(socket))
+            }
+            SyslogTransport::Tcp => Ok(Connection::Tcp(TcpStream::connect(address).await?)),
+            SyslogTransport::Tls => {
+                let tls = self.tls.clone().ok_or_else(|| anyhow!("TLS is not configured"))?;
+                let name = match &self.config.server_name {
+                    Some(name) => name.clone(),
+                    None => host(&self.config.address).to_string(),
+                };
+                let server_name = ServerName::try_from(name.as_str()).map_err(|_| anyhow!("Invalid server name {}", name))?;
+                let stream = TcpStream::connect(address).await?;
+                let stream = TlsConnector::from(tls).connect(server_name, stream).await?;
+                Ok(Connection::Tls(Box::new(stream)))
+            }
+        }
+    }
+}
+
+/// The public roots, or only the certificates in `ca_path`.
+fn tls_config(config: &SyslogConfig) -> Result<Arc<ClientConfig>> {
+    let mut roots = RootCertStore::empty();
+    match &config.ca_path {
+        Some(path) => {
+            let file = std::fs::File::open(path).with_context(|| format!("Failed to open {}", path))?;
+            let certs = rustls_pemfile::certs(&mut BufReader::new(file)).with_context(|| format!("Failed to read {}", path))?;
+            if certs.is_empty() {
+                return Err(anyhow!("No certificates in {}", path));
+            }
+            for cert in certs {
+                roots.add(&rustls::Certificate(cert)).with_context(|| format!("Invalid certificate in {}", path))?;
+            }
+        }
+        None => roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|ta| {
+            rustls::OwnedTrustAnchor::from_subject_spki_name_constraints(ta.subject, ta.spki, ta.name_constraints)
+        })),
+    }
+    Ok(Arc::new(
+        ClientConfig::builder().with_safe_defaults().with_root_certificates(roots).with_no_client_auth(),
+    ))
+}
+
+// RFC 6587 octet counting: the message length, a space and the message
+fn octet_counted(message: &str) -> String {
+    format!("{} {}", message.len(), message)
+}
+
+// "host:port" or "[v6]:port" without the port
+fn host(address: &str) -> &str {
+    let host = address.rsplit_once(':').map_or(address, |(host, _)| host);
+    host.trim_start_matches('[').trim_end_matches(']')
+}
+
+fn hostname() -> String {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "-".to_string())
+}
@@ -0,0 +1,88 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tracing::{info, warn};
+
+// highest slot a source has seen
+static SLOT: AtomicU64 = AtomicU64::new(0);
+
+/// Records that a source received a notification for `slot`; the watchdog
+/// is only pinged while this advances.
+pub fn progress(slot: u64) {
+    SLOT.fetch_max(slot, Ordering::Relaxed);
+}
+
+/// The highest slot a source has seen, 0 before the first notification.
+pub fn last_slot() -> u64 {
+    SLOT.load(Ordering::Relaxed)
+}
+
+/// Tells systemd (`Type=notify`) that the subscription is established. A
+/// no-op outside systemd.
+pub fn ready() {
+    notify("READY=1\nSTATUS=Subscribed, waiting for pools");
+}
+
+/// Pings the systemd watchdog at half of `WatchdogSec` for as long as the
+/// sources make slot progress, so systemd restarts the unit when the
+/// subscription or the pipeline stalls. Returns at once when the watchdog is
+/// not enabled for this process.
+pub async fn watchdog() {
+    let Some(timeout) = watchdog_timeout() else {
+        return;
+    };
+    info!(timeout_secs = timeout.as_secs(), "systemd watchdog enabled");
+    let mut interval = tokio::time::interval(timeout / 2);
+    let mut last_slot = 0;
+    loop {
+        interval.tick().await;
+        let slot = SLOT.load(Ordering::Relaxed);
+        if slot > last_slot {
+            notify(&format!("WATCHDOG=1\nSTATUS=At slot {}", slot));
+            last_slot = slot;
+        } else if slot > 0 {
+            warn!(slot, "No slot progress since the last watchdog ping");
+        }
+    }
+}
+
+fn watchdog_timeout() -> Option<Duration> {
+    if let Ok(pid) = std::env::var("WATCHDOG_PID") {
+        if pid.parse::<u32>().ok() != Some(std::process::id()) {
+            return None;
+        }
+    }
+    let usec = std::env::var("WATCHDOG_USEC").ok()?.parse::<u64>().ok()?;
+    (usec > 0).then(|| Duration::from_micros(usec))
+}
+
+fn notify(state: &str) {
+    #[cfg(unix)]
+    if let Some(path) = std::env::var_os("NOTIFY_SOCKET") {
+        if let Err(e) = send(&path, state) {
+            warn!(error = %e, "Failed to notify systemd");
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = state;
+}
+
+// NOTIFY_SOCKET is a datagram socket path, or an abstract name after "@"
+#[cfg(unix)]
+fn send(path: &std::ffi::OsStr, state: &str) -> std::io::Result<()> {
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::net::UnixDatagram;
+
+    let socket = UnixDatagram::unbound()?;
+    match path.as_bytes().strip_prefix(b"@") {
+        #[cfg(target_os = "linux")]
+        Some(name) => {
+            use std::os::linux::net::SocketAddrExt;
+            let addr = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+            socket.send_to_addr(state.as_bytes(), &addr)?;
+        }
+        _ => {
+            socket.send_to(state.as_bytes(), path)?;
+        }
+    }
+    Ok(())
+}
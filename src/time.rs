@@ -0,0 +1,52 @@
+use chrono::{DateTime, SecondsFormat};
+use chrono_tz::Tz;
+
+/// Formats a UNIX timestamp as `YYYY-MM-DD HH:MM:SS` in the given timezone.
+pub fn format_timestamp(timestamp: u64, tz: Tz) -> String {
+    match DateTime::from_timestamp(timestamp as i64, 0) {
+        Some(time) => time.with_timezone(&tz).format("%Y-%m-%d %H:%M:%S").to_string(),
+        None => timestamp.to_string(),
+    }
+}
+
+/// Formats a pool's `open_time` as RFC3339 in the given timezone, followed by
+/// a countdown while it is in the future. Zero means the pool opens
+/// immediately.
+pub fn format_open_time(open_time: u64, now: u64, tz: Tz) -> String {
+    if open_time == 0 {
+        return "immediately".to_string();
+    }
+    let time = match DateTime::from_timestamp(open_time as i64, 0) {
+        Some(time) => time.with_timezone(&tz).to_rfc3339_opts(SecondsFormat::Secs, true),
+        None => return open_time.to_string(),
+    };
+    match opens_in(open_time, now) {
+        Some(countdown) => format!("{} ({})", time, countdown),
+        None => time,
+    }
+}
+
+/// "opens in 1h 2m 3s" while `open_time` is in the future.
+pub fn opens_in(open_time: u64, now: u64) -> Option<String> {
+    if open_time <= now {
+        return None;
+    }
+    let secs = open_time - now;
+    let countdown = match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m {}s", secs / 60, secs % 60),
+        _ => format!("{}h {}m {}s", secs / 3600, secs % 3600 / 60, secs % 60),
+    };
+    Some(format!("opens in {}", countdown))
+}
+
+/// Short form of a whole number of days, hours, minutes or seconds: "1d",
+/// "1h", "5m", "90s".
+pub fn format_offset(secs: u64) -> String {
+    match secs {
+        s if s > 0 && s % 86400 == 0 => format!("{}d", s / 86400),
+        s if s > 0 && s % 3600 == 0 => format!("{}h", s / 3600),
+        s if s > 0 && s % 60 == 0 => format!("{}m", s / 60),
+        s => format!("{}s", s),
+    }
+}
@@ -0,0 +1,346 @@
+use crate::chain::ChainRpc;
+use crate::config::{Config, MetadataConfig, TokenInfoSource};
+use crate::metrics::Metrics;
+use anyhow::{anyhow, Result};
+use futures::future::BoxFuture;
+use serde_json::Value;
+use solana_program::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+use spl_token::state::Mint;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::debug;
+
+pub const TOKEN_METADATA_PROGRAM_ID: &str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s";
+
+#[derive(Debug, Clone)]
+pub struct TokenInfo {
+    // None without metadata
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    // from the off-chain metadata, through a gateway for IPFS / Arweave
+    pub image: Option<String>,
+    pub decimals: u8,
+    // mint / freeze authority still set
+    pub mint_authority: bool,
+    pub freeze_authority: bool,
+}
+
+/// Looks up the mint and metadata of a token.
+pub trait TokenInfoProvider: Send + Sync {
+    fn fetch<'a>(&'a self, mint: &'a Pubkey) -> BoxFuture<'a, Result<TokenInfo>>;
+}
+
+/// The provider selected by `token_info_source`, reading off-chain metadata
+/// when `[metadata]` is configured.
+pub fn provider(config: &Config, rpc: Arc<dyn ChainRpc>) -> Arc<dyn TokenInfoProvider> {
+    let offchain = config.metadata.clone().map(OffchainMetadata::new);
+    match config.token_info_source {
+        TokenInfoSource::Rpc => Arc::new(RpcTokenInfo { rpc, offchain }),
+        TokenInfoSource::Helius => {
+            Arc::new(HeliusTokenInfo { rpc, offchain })
+        }
+    }
+}
+
+/// Reads the mint and Metaplex metadata accounts.
+pub struct RpcTokenInfo {
+    rpc: Arc<dyn ChainRpc>,
+    offchain: Option<OffchainMetadata>,
+}
+
+impl TokenInfoProvider for RpcTokenInfo {
+    fn fetch<'a>(&'a self, mint: &'a Pubkey) -> BoxFuture<'a, Result<TokenInfo>> {
+        Box::pin(async move {
+            // Fetch the mint
+            let mint_account = self.rpc.get_account(mint)?.ok_or_else(|| anyhow!("mint account {} not found", mint))?;
+            let mint_state = Mint::unpack_from_slice(&mint_account.data)?;
+
+            // Derive the metadata PDA
+            let metadata_program_id = Pubkey::from_str(TOKEN_METADATA_PROGRAM_ID)?;
+            let seeds = &[b"metadata", metadata_program_id.as_ref(), mint.as_ref()];
+            let (metadata_address, _) = Pubkey::find_program_address(seeds, &metadata_program_id);
+
+            // Fetch the metadata; many new tokens have none
+            let metadata = self.rpc.get_account(&metadata_address)?;
+            let (name, symbol, uri) = match metadata {
+                Some(account) if account.owner == metadata_program_id => parse_metadata(&account.data),
+                Some(account) => {
+                    debug!(mint = %mint, owner = %account.owner, "Metadata PDA not owned by the metadata program");
+                    (None, None, None)
+                }
+                None => (None, None, None),
+            };
+            if name.is_none() {
+                debug!(mint = %mint, "No token metadata");
+            }
+            let image = match (&self.offchain, uri) {
+                (Some(offchain), Some(uri)) => offchain.image(mint, &uri).await,
+                _ => None,
+            };
+
+            Ok(TokenInfo {
+                name,
+                symbol,
+                image,
+                decimals: mint_state.decimals,
+                mint_authority: mint_state.mint_authority.is_some(),
+                freeze_authority: mint_state.freeze_authority.is_some(),
+            })
+        })
+    }
+}
+
+/// Name, symbol and uri from a Metaplex metadata account: key (1), update
+/// authority (32), mint (32), then the three as borsh strings padded to 32,
+/// 10 and 200 bytes.
+pub fn parse_metadata(data: &[u8]) -> (Option<String>, Option<String>, Option<String>) {
+    const NAME_OFFSET: usize = 65;
+    const MIN_LEN: usize = NAME_OFFSET + (4 + 32) + (4 + 10) + (4 + 200);
+    if data.len() < MIN_LEN {
+        return (None, None, None);
+    }
+    let Some((name, symbol_offset)) = borsh_string(data, NAME_OFFSET) else {
+        return (None, None, None);
+    };
+    let Some((symbol, uri_offset)) = borsh_string(data, symbol_offset) else {
+        return (name, None, None);
+    };
+    let uri = borsh_string(data, uri_offset).and_then(|(uri, _)| uri);
+    (name, symbol, uri)
+}
+
+/// The trimmed string at `offset` (None when empty) and the offset after it.
+fn borsh_string(data: &[u8], offset: usize) -> Option<(Option<String>, usize)> {
+    let length = u32::from_le_bytes(data.get(offset..offset + 4)?.try_into().unwrap()) as usize;
+    let end = offset + 4 + length;
+    let text = std::str::from_utf8(data.get(offset + 4..end)?).ok()?.trim_matches(char::from(0)).trim();
+    Some(((!text.is_empty()).then(|| text.to_string()), end))
+}
+
+/// Helius DAS getAsset through the RPC client: mint, metadata and image in
+/// one call.
+pub struct HeliusTokenInfo {
+    rpc: Arc<dyn ChainRpc>,
+    offchain: Option<OffchainMetadata>,
+}
+
+impl TokenInfoProvider for HeliusTokenInfo {
+    fn fetch<'a>(&'a self, mint: &'a Pubkey) -> BoxFuture<'a, Result<TokenInfo>> {
+        Box::pin(async move {
+            let asset = self.rpc.get_asset(mint)?;
+            let token_info = &asset["token_info"];
+            let decimals = token_info["decimals"]
+                .as_u64()
+                .ok_or_else(|| anyhow!("unexpected getAsset response: {}", asset))?;
+            let text = |value: &Value| value.as_str().map(str::trim).filter(|text| !text.is_empty()).map(str::to_string);
+            // Helius skips images it couldn't resolve; retry those through
+            // the configured gateways
+            let image = match (text(&asset["content"]["links"]["image"]), &self.offchain) {
+                (Some(image), Some(offchain)) => offchain.urls(&image).into_iter().next(),
+                (Some(image), None) => Some(image),
+                (None, Some(offchain)) => match text(&asset["content"]["json_uri"]) {
+                    Some(uri) => offchain.image(mint, &uri).await,
+                    None => None,
+                },
+                (None, None) => None,
+            };
+            Ok(TokenInfo {
+                name: text(&asset["content"]["metadata"]["name"]),
+                symbol: text(&asset["content"]["metadata"]["symbol"]),
+                image,
+                decimals: decimals as u8,
+                mint_authority: text(&token_info["mint_authority"]).is_some(),
+                freeze_authority: text(&token_info["freeze_authority"]).is_some(),
+            })
+        })
+    }
+}
+
+/// Fetches metadata JSON, rewriting IPFS and Arweave URIs through the
+/// configured gateways and trying each in turn.
+pub struct OffchainMetadata {
+    http: reqwest::Client,
+    config: MetadataConfig,
+}
+
+impl OffchainMetadata {
+    pub fn new(config: MetadataConfig) -> Self {
+        Self { http: reqwest::Client::new(), config }
+    }
+
+    /// URLs to try for a metadata or image URI. ipfs:// and ar:// URIs map to
+    /// every gateway; http URLs on an IPFS or Arweave gateway are tried as-is
+    /// first, then on the configured gateways in case theirs is dead.
+    pub fn urls(&self, uri: &str) -> Vec<String> {
+        let uri = uri.trim();
+        let (original, ipfs_path, arweave_path) = if let Some(path) = uri.strip_prefix("ipfs://") {
+            (None, Some(path.trim_start_matches("ipfs/")), None)
+        } else if let Some(path) = uri.strip_prefix("ar://") {
+            (None, None, Some(path))
+        } else if let Some((_, path)) = uri.split_once("/ipfs/") {
+            (Some(uri), Some(path), None)
+        } else if let Some(path) = uri.strip_prefix("https://arweave.net/").or_else(|| uri.strip_prefix("https://www.arweave.net/")) {
+            (Some(uri), None, Some(path))
+        } else {
+            (Some(uri), None, None)
+        };
+
+        let mut urls = original.map(str::to_string).into_iter().collect::<Vec<_>>();
+        let rewritten = match (ipfs_path, arweave_path) {
+            (Some(path), _) => self.config.ipfs_gateways.iter().map(|gateway| gateway_url(gateway, path)).collect(),
+            (_, Some(path)) => self.config.arweave_gateways.iter().map(|gateway| gateway_url(gateway, path)).collect(),
+            _ => Vec::new(),
+        };
+        for url in rewritten {
+            if !urls.contains(&url) {
+                urls.push(url);
+            }
+        }
+        urls
+    }
+
+    /// The metadata JSON from the first URL that answers within the timeout.
+    pub async fn fetch(&self, uri: &str) -> Result<Value> {
+        let mut last_error = anyhow!("no URL to fetch for {:?}", uri);
+        for url in self.urls(uri) {
+            let response = self.http.get(&url).timeout(Duration::from_millis(self.config.timeout_ms)).send().await;
+            match response.and_then(|response| response.error_for_status()) {
+                Ok(response) => match response.json().await {
+                    Ok(json) => return Ok(json),
+                    Err(e) => last_error = e.into(),
+                },
+                Err(e) => last_error = e.into(),
+            }
+            debug!(url = %url, error = %last_error, "Metadata gateway failed, trying the next");
+        }
+        Err(last_error)
+    }
+
+    /// The image URL from the metadata at `uri`, itself pointed at the first
+    /// gateway. None when the metadata can't be fetched from anywhere.
+    async fn image(&self, mint: &Pubkey, uri: &str) -> Option<String> {
+        match self.fetch(uri).await {
+            Ok(json) => json["image"].as_str().and_then(|image| self.urls(image).into_iter().next()),
+            Err(e) => {
+                debug!(mint = %mint, uri, error = %e, "Failed to fetch off-chain metadata");
+                None
+            }
+        }
+    }
+}
+
+fn gateway_url(gateway: &str, path: &str) -> String {
+    format!("{}/{}", gateway.trim_end_matches('/'), path.trim_start_matches('/'))
+}
+
+/// Token info by mint, so repeated quote tokens (WSOL, USDC) skip the
+/// lookups.
+pub struct TokenCache {
+    provider: Arc<dyn TokenInfoProvider>,
+    entries: Mutex<HashMap<Pubkey, TokenInfo>>,
+}
+
+impl TokenCache {
+    pub fn new(provider: Arc<dyn TokenInfoProvider>) -> Self {
+        Self { provider, entries: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn cached_mints(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub async fn get(&self, metrics: &Metrics, mint: &Pubkey) -> Result<TokenInfo> {
+        if let Some(info) = self.entries.lock().unwrap().get(mint).cloned() {
+            metrics.cache_hit();
+            return Ok(info);
+        }
+        metrics.cache_miss();
+        let info = self.provider.fetch(mint).await.inspect_err(|_| metrics.rpc_error())?;
+        self.entries.lock().unwrap().insert(*mint, info.clone());
+        Ok(info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::MockRpc;
+    use serde_json::json;
+    use solana_sdk::account::Account;
+    use solana_program::program_option::COption;
+
+    fn account(owner: Pubkey, data: Vec<u8>) -> Account {
+        Account { lamports: 1, data, owner, executable: false, rent_epoch: 0 }
+    }
+
+    fn mint_data(decimals: u8, freeze_authority: Option<Pubkey>) -> Vec<u8> {
+        let mint = Mint {
+            mint_authority: COption::None,
+            supply: 0,
+            decimals,
+            is_initialized: true,
+            freeze_authority: freeze_authority.into(),
+        };
+        let mut data = vec![0; Mint::LEN];
+        Mint::pack(mint, &mut data).unwrap();
+        data
+    }
+
+    // a borsh string padded with NULs the way Metaplex stores it
+    fn padded(text: &str, len: usize) -> Vec<u8> {
+        let mut bytes = (len as u32).to_le_bytes().to_vec();
+        bytes.extend(text.as_bytes());
+        bytes.resize(4 + len, 0);
+        bytes
+    }
+
+    #[tokio::test]
+    async fn reads_mint_and_metadata_accounts() {
+        let mint = Pubkey::new_unique();
+        let metadata_program_id = Pubkey::from_str(TOKEN_METADATA_PROGRAM_ID).unwrap();
+        let (metadata_address, _) =
+            Pubkey::find_program_address(&[b"metadata", metadata_program_id.as_ref(), mint.as_ref()], &metadata_program_id);
+        let mut metadata = vec![4];
+        metadata.extend([0; 32]);
+        metadata.extend(mint.to_bytes());
+        metadata.extend(padded("Dog Coin", 32));
+        metadata.extend(padded("DOG", 10));
+        metadata.extend(padded("", 200));
+        let rpc = MockRpc::new()
+            .with_account(mint, account(spl_token::id(), mint_data(6, Some(Pubkey::new_unique()))))
+            .with_account(metadata_address, account(metadata_program_id, metadata));
+
+        let info = provider(&Config::default(), Arc::new(rpc)).fetch(&mint).await.unwrap();
+        assert_eq!(info.name.as_deref(), Some("Dog Coin"));
+        assert_eq!(info.symbol.as_deref(), Some("DOG"));
+        assert_eq!(info.image, None);
+        assert_eq!(info.decimals, 6);
+        assert!(!info.mint_authority);
+        assert!(info.freeze_authority);
+    }
+
+    #[tokio::test]
+    async fn reads_helius_asset() {
+        let mint = Pubkey::new_unique();
+        let rpc = MockRpc::new().with_asset(
+            mint,
+            json!({
+                "content": {
+                    "metadata": { "name": "Dog Coin ", "symbol": "DOG" },
+                    "links": { "image": "https://example.com/dog.png" }
+                },
+                "token_info": { "decimals": 6, "mint_authority": "", "freeze_authority": null }
+            }),
+        );
+        let config = Config { token_info_source: TokenInfoSource::Helius, ..Config::default() };
+
+        let info = provider(&config, Arc::new(rpc)).fetch(&mint).await.unwrap();
+        assert_eq!(info.name.as_deref(), Some("Dog Coin"));
+        assert_eq!(info.image.as_deref(), Some("https://example.com/dog.png"));
+        assert_eq!(info.decimals, 6);
+        assert!(!info.mint_authority && !info.freeze_authority);
+    }
+}
@@ -0,0 +1,249 @@
+use crate::events::PoolEvent;
+use anyhow::Result;
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, Paragraph, Row, Table},
+    Frame,
+};
+use solana_sdk::pubkey::Pubkey;
+use tracing_subscriber::fmt::MakeWriter;
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const MAX_POOLS: usize = 50;
+const MAX_LOGS: usize = 500;
+const MAX_LATENCIES: usize = 200;
+const TICK: Duration = Duration::from_millis(250);
+
+/// State of the WebSocket subscription.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionStatus {
+    Connecting,
+    Connected,
+    Disconnected,
+}
+
+/// A new pool shown on the dashboard.
+#[derive(Debug, Clone)]
+pub struct PoolRow {
+    pub name: String,
+    pub amm: Pubkey,
+    // initial SOL liquidity for SOL pairs
+    pub liquidity_sol: Option<f64>,
+    pub flags: Vec<&'static str>,
+    pub detected_at: Instant,
+}
+
+impl PoolRow {
+    pub fn from_event(event: &PoolEvent) -> Self {
+        Self {
+            name: event.name(),
+            amm: event.amm,
+            liquidity_sol: event.liquidity_sol,
+            flags: event.flags.clone(),
+            detected_at: Instant::now(),
+        }
+    }
+}
+
+struct State {
+    connection: ConnectionStatus,
+    pools: VecDeque<PoolRow>,
+    // time from receiving the log to parsing the transaction
+    latencies: VecDeque<Duration>,
+    logs: VecDeque<String>,
+}
+
+/// State shared between the monitor tasks and the UI thread in `--tui` mode.
+pub struct Dashboard {
+    state: Mutex<State>,
+}
+
+impl Dashboard {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            state: Mutex::new(State {
+                connection: ConnectionStatus::Connecting,
+                pools: VecDeque::new(),
+                latencies: VecDeque::new(),
+                logs: VecDeque::new(),
+            }),
+        })
+    }
+
+    pub fn set_connection(&self, status: ConnectionStatus) {
+        self.state.lock().unwrap().connection = status;
+    }
+
+    pub fn record_pool(&self, pool: PoolRow) {
+        let mut state = self.state.lock().unwrap();
+        state.pools.push_front(pool);
+        state.pools.truncate(MAX_POOLS);
+    }
+
+    pub fn record_latency(&self, latency: Duration) {
+        let mut state = self.state.lock().unwrap();
+        if state.latencies.len() == MAX_LATENCIES {
+            state.latencies.pop_front();
+        }
+        state.latencies.push_back(latency);
+    }
+
+    fn push_log(&self, line: String) {
+        let mut state = self.state.lock().unwrap();
+        if state.logs.len() == MAX_LOGS {
+            state.logs.pop_front();
+        }
+        state.logs.push_back(line);
+    }
+}
+
+/// Sends formatted log lines to the dashboard's log pane instead of the
+/// terminal.
+pub struct DashboardWriter {
+    dashboard: Arc<Dashboard>,
+}
+
+impl DashboardWriter {
+    pub fn new(dashboard: Arc<Dashboard>) -> Self {
+        Self { dashboard }
+    }
+}
+
+impl<'a> MakeWriter<'a> for DashboardWriter {
+    type Writer = LineWriter;
+
+    fn make_writer(&'a self) -> LineWriter {
+        LineWriter { dashboard: self.dashboard.clone(), buf: Vec::new() }
+    }
+}
+
+/// Buffers one formatted event and pushes its lines to the log pane on drop.
+pub struct LineWriter {
+    dashboard: Arc<Dashboard>,
+    buf: Vec<u8>,
+}
+
+impl Write for LineWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for LineWriter {
+    fn drop(&mut self) {
+        for line in String::from_utf8_lossy(&self.buf).lines().filter(|line| !line.is_empty()) {
+            self.dashboard.push_log(line.to_string());
+        }
+    }
+}
+
+/// Runs the UI until q or Esc is pressed, blocking the current thread.
+pub fn run(dashboard: Arc<Dashboard>) -> Result<()> {
+    let mut terminal = ratatui::init();
+    let result = (|| -> Result<()> {
+        loop {
+            terminal.draw(|frame| draw(frame, &dashboard))?;
+            if event::poll(TICK)? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    })();
+    ratatui::restore();
+    result
+}
+
+fn draw(frame: &mut Frame, dashboard: &Dashboard) {
+    let state = dashboard.state.lock().unwrap();
+    let [header, pools, logs] = Layout::vertical([Constraint::Length(3), Constraint::Percentage(50), Constraint::Min(5)])
+        .areas(frame.area());
+
+    let (status, color) = match state.connection {
+        ConnectionStatus::Connecting => ("connecting", Color::Yellow),
+        ConnectionStatus::Connected => ("connected", Color::Green),
+        ConnectionStatus::Disconnected => ("disconnected", Color::Red),
+    };
+    let status_line = Line::from(vec![
+        Span::raw("WebSocket: "),
+        Span::styled(status, Style::default().fg(color).add_modifier(Modifier::BOLD)),
+        Span::raw(format!("   Pools: {}   Latency: {}   (q to quit)", state.pools.len(), latency_summary(&state.latencies))),
+    ]);
+    frame.render_widget(
+        Paragraph::new(status_line).block(Block::default().borders(Borders::ALL).title("Raydium V4 Monitor")),
+        header,
+    );
+
+    let rows = state.pools.iter().map(|pool| {
+        let flags = if pool.flags.is_empty() { "-".to_string() } else { pool.flags.join(",") };
+        let style = if pool.flags.is_empty() { Style::default() } else { Style::default().fg(Color::Red) };
+        Row::new(vec![
+            pool.name.clone(),
+            pool.amm.to_string(),
+            pool.liquidity_sol.map_or("-".to_string(), |sol| format!("{:.2} SOL", sol)),
+            flags,
+            format_age(pool.detected_at.elapsed()),
+        ])
+        .style(style)
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(25),
+            Constraint::Percentage(40),
+            Constraint::Percentage(13),
+            Constraint::Percentage(12),
+            Constraint::Percentage(10),
+        ],
+    )
+    .header(Row::new(vec!["Name", "Pool", "Liquidity", "Risk", "Age"]).style(Style::default().add_modifier(Modifier::BOLD)))
+    .block(Block::default().borders(Borders::ALL).title("Recent pools"));
+    frame.render_widget(table, pools);
+
+    let visible = logs.height.saturating_sub(2) as usize;
+    let items = state
+        .logs
+        .iter()
+        .skip(state.logs.len().saturating_sub(visible))
+        .map(|line| ListItem::new(line.as_str()))
+        .collect::<Vec<_>>();
+    frame.render_widget(List::new(items).block(Block::default().borders(Borders::ALL).title("Log")), logs);
+}
+
+fn latency_summary(latencies: &VecDeque<Duration>) -> String {
+    if latencies.is_empty() {
+        return "-".to_string();
+    }
+    let mut sorted = latencies.iter().copied().collect::<Vec<_>>();
+    sorted.sort_unstable();
+    let avg = sorted.iter().sum::<Duration>() / sorted.len() as u32;
+    let p95 = sorted[(sorted.len() - 1) * 95 / 100];
+    format!(
+        "avg {}ms p95 {}ms max {}ms",
+        avg.as_millis(),
+        p95.as_millis(),
+        sorted[sorted.len() - 1].as_millis()
+    )
+}
+
+fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m{}s", secs / 60, secs % 60),
+        _ => format!("{}h{}m", secs / 3600, secs % 3600 / 60),
+    }
+}
@@ -0,0 +1,110 @@
+use crate::config::VolumeConfig;
+use crate::raydium::{self, NewPool, SwapLog};
+use crate::rpc::KeyRing;
+use crate::sniper::unix_now;
+use crate::store::{Store, SwapRecord};
+use anyhow::{anyhow, Result};
+use solana_client::{
+    pubsub_client::PubsubClient,
+    rpc_client::RpcClient,
+    rpc_config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter},
+};
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Rolling windows reported by `stats volume` and `GET /volume`: 1m, 5m, 1h.
+pub const VOLUME_WINDOWS_SECS: &[u64] = &[60, 300, 3600];
+
+/// Subscribes to the logs of each new pool for a while and records its swaps
+/// from the `ray_log` lines, for rolling volume and trade counts.
+pub struct VolumeTracker {
+    keys: Arc<KeyRing>,
+    rpc_client: Arc<RpcClient>,
+    store: Arc<Store>,
+    config: VolumeConfig,
+    // pools currently subscribed to
+    subscriptions: AtomicUsize,
+}
+
+impl VolumeTracker {
+    pub fn new(keys: Arc<KeyRing>, rpc_client: Arc<RpcClient>, store: Arc<Store>, config: VolumeConfig) -> Self {
+        Self { keys, rpc_client, store, config, subscriptions: AtomicUsize::new(0) }
+    }
+
+    pub fn subscriptions(&self) -> usize {
+        self.subscriptions.load(Ordering::Relaxed)
+    }
+
+    pub async fn watch(self: Arc<Self>, pool: NewPool) {
+        let amm = pool.accounts.amm;
+        // the pubsub client blocks
+        let tracker = self.clone();
+        self.subscriptions.fetch_add(1, Ordering::Relaxed);
+        let result = tokio::task::spawn_blocking(move || tracker.run(&pool)).await;
+        self.subscriptions.fetch_sub(1, Ordering::Relaxed);
+        match result {
+            Ok(Ok(trades)) => info!(pool = %amm, trades, "Finished recording swaps"),
+            Ok(Err(e)) => warn!(pool = %amm, error = %e, "Stopped recording swaps"),
+            Err(e) => warn!(pool = %amm, error = %e, "Swap recorder panicked"),
+        }
+    }
+
+    fn run(&self, pool: &NewPool) -> Result<u64> {
+        let amm = pool.accounts.amm;
+        let (info, _) = raydium::fetch_pool(&self.rpc_client, &amm)?;
+        let quote_is_coin = pool.accounts.quote_mint() == pool.accounts.coin_mint;
+        let (base_unit, quote_unit) = if quote_is_coin {
+            (10f64.powi(info.pc_decimals as i32), 10f64.powi(info.coin_decimals as i32))
+        } else {
+            (10f64.powi(info.coin_decimals as i32), 10f64.powi(info.pc_decimals as i32))
+        };
+
+        let (mut subscription, receiver) = PubsubClient::logs_subscribe(
+            &self.keys.ws_url("logsSubscribe"),
+            RpcTransactionLogsFilter::Mentions(vec![amm.to_string()]),
+            RpcTransactionLogsConfig { commitment: Some(CommitmentConfig::confirmed()) },
+        )?;
+        info!(pool = %amm, window_mins = self.config.window_mins, "Recording swaps");
+
+        let end = Instant::now() + Duration::from_secs(self.config.window_mins * 60);
+        let mut trades = 0;
+        let result = loop {
+            let Some(remaining) = end.checked_duration_since(Instant::now()) else {
+                break Ok(trades);
+            };
+            let log = match receiver.recv_timeout(remaining) {
+                Ok(log) => log,
+                Err(e) if e.is_timeout() => break Ok(trades),
+                Err(_) => break Err(anyhow!("log subscription closed")),
+            };
+            if log.value.err.is_some() {
+                continue;
+            }
+            // routed swaps through several pools log one ray_log per hop and
+            // are all attributed to this pool
+            for (seq, swap) in log.value.logs.iter().filter_map(|line| SwapLog::parse(line)).enumerate() {
+                // buying spends the quote token
+                let buy = swap.coin_to_pc == quote_is_coin;
+                let (base, quote) = if buy { (swap.amount_out, swap.amount_in) } else { (swap.amount_in, swap.amount_out) };
+                let record = SwapRecord {
+                    amm: amm.to_string(),
+                    signature: log.value.signature.clone(),
+                    seq: seq as u32,
+                    time: unix_now(),
+                    buy,
+                    base_amount: base as f64 / base_unit,
+                    quote_amount: quote as f64 / quote_unit,
+                };
+                match self.store.insert_swap(&record) {
+                    Ok(()) => trades += 1,
+                    Err(e) => warn!(pool = %amm, error = %e, "Failed to record swap"),
+                }
+            }
+        };
+        let _ = subscription.shutdown();
+        result
+    }
+}
@@ -0,0 +1,257 @@
+use crate::config::WalletConfig;
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Nonce,
+};
+use aes_gcm::aead::rand_core::RngCore;
+use anyhow::{anyhow, Context, Result};
+use tracing::info;
+use serde::{Deserialize, Serialize};
+use solana_sdk::signature::{Keypair, Signer};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use zeroize::Zeroizing;
+
+const ENCRYPTED_WALLET_VERSION: u32 = 1;
+// scrypt parameters: N = 2^15, r = 8, p = 1
+const SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+// caps on the parameters read from a file, so a corrupt one can't make
+// scrypt allocate gigabytes (128 * r * N bytes) or run for hours
+const MAX_SCRYPT_MEMORY: u128 = 256 << 20;
+const MAX_SCRYPT_P: u32 = 16;
+
+/// A labelled wallet. `Debug` only shows the label and public key, never the
+/// secret key.
+#[derive(Clone)]
+pub struct Wallet {
+    pub label: String,
+    keypair: Arc<Keypair>,
+}
+
+impl Wallet {
+    pub fn pubkey(&self) -> solana_sdk::pubkey::Pubkey {
+        self.keypair.pubkey()
+    }
+
+    pub fn keypair(&self) -> &Keypair {
+        &self.keypair
+    }
+}
+
+impl fmt::Debug for Wallet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Wallet")
+            .field("label", &self.label)
+            .field("pubkey", &self.pubkey())
+            .finish()
+    }
+}
+
+/// Encrypted wallet file format.
+#[derive(Serialize, Deserialize)]
+struct EncryptedKeypair {
+    version: u32,
+    kdf: String,
+    log_n: u8,
+    r: u32,
+    p: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Wallets {
+    wallets: HashMap<String, Wallet>,
+}
+
+impl Wallets {
+    pub fn load(configs: &[WalletConfig]) -> Result<Self> {
+        let mut wallets = HashMap::new();
+        for config in configs {
+            let keypair = load_keypair(config).with_context(|| format!("Failed to load wallet '{}'", config.label))?;
+            let wallet = Wallet { label: config.label.clone(), keypair: Arc::new(keypair) };
+            info!(label = %wallet.label, pubkey = %wallet.pubkey(), "Loaded wallet");
+            if wallets.insert(config.label.clone(), wallet).is_some() {
+                return Err(anyhow!("Duplicate wallet label '{}'", config.label));
+            }
+        }
+        Ok(Self { wallets })
+    }
+
+    pub fn get(&self, label: &str) -> Result<&Wallet> {
+        self.wallets.get(label).ok_or_else(|| anyhow!("Unknown wallet '{}'", label))
+    }
+}
+
+fn load_keypair(config: &WalletConfig) -> Result<Keypair> {
+    match (&config.path, &config.env) {
+        (Some(path), None) => {
+            let contents = Zeroizing::new(std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path))?);
+            if config.encrypted {
+                let password = read_password(config)?;
+                decrypt_keypair(&contents, &password)
+            } else {
+                parse_keypair(&contents)
+            }
+        }
+        (None, Some(env)) => {
+            let contents = Zeroizing::new(std::env::var(env).map_err(|_| anyhow!("Environment variable {} is not set", env))?);
+            parse_keypair(&contents)
+        }
+        _ => Err(anyhow!("Exactly one of `path` or `env` must be set")),
+    }
+}
+
+/// Parses a JSON byte array or a base58 secret key. Errors never include the
+/// input.
+fn parse_keypair(contents: &str) -> Result<Keypair> {
+    let contents = contents.trim();
+    let bytes = Zeroizing::new(if contents.starts_with('[') {
+        serde_json::from_str::<Vec<u8>>(contents).map_err(|_| anyhow!("Invalid keypair JSON"))?
+    } else {
+        bs58::decode(contents).into_vec().map_err(|_| anyhow!("Invalid base58 keypair"))?
+    });
+    Keypair::from_bytes(&bytes).map_err(|_| anyhow!("Invalid keypair bytes"))
+}
+
+fn read_password(config: &WalletConfig) -> Result<Zeroizing<String>> {
+    if let Some(env) = &config.password_env {
+        if let Ok(password) = std::env::var(env) {
+            return Ok(Zeroizing::new(password));
+        }
+    }
+    let password = rpassword::prompt_password(format!("Password for wallet '{}': ", config.label))?;
+    Ok(Zeroizing::new(password))
+}
+
+fn check_scrypt_params(log_n: u8, r: u32, p: u32) -> Result<()> {
+    let memory = 1u128.checked_shl(log_n.into()).and_then(|n| n.checked_mul(128 * u128::from(r)));
+    if memory.is_none_or(|memory| memory > MAX_SCRYPT_MEMORY) || p > MAX_SCRYPT_P {
+        return Err(anyhow!("Unsupported scrypt parameters log_n={} r={} p={}", log_n, r, p));
+    }
+    Ok(())
+}
+
+fn derive_key(password: &str, salt: &[u8], log_n: u8, r: u32, p: u32) -> Result<Zeroizing<[u8; 32]>> {
+    let params = scrypt::Params::new(log_n, r, p, 32).map_err(|e| anyhow!("Invalid scrypt parameters: {}", e))?;
+    let mut key = Zeroizing::new([0u8; 32]);
+    scrypt::scrypt(password.as_bytes(), salt, &params, key.as_mut()).map_err(|e| anyhow!("scrypt failed: {}", e))?;
+    Ok(key)
+}
+
+pub fn encrypt_keypair(keypair: &Keypair, password: &str) -> Result<String> {
+    encrypt_keypair_with(keypair, password, SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P)
+}
+
+fn encrypt_keypair_with(keypair: &Keypair, password: &str, log_n: u8, r: u32, p: u32) -> Result<String> {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let key = derive_key(password, &salt, log_n, r, p)?;
+    let cipher = Aes256Gcm::new_from_slice(key.as_ref())?;
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let secret = Zeroizing::new(keypair.to_bytes());
+    let ciphertext = cipher
+        .encrypt(&nonce, secret.as_ref())
+        .map_err(|_| anyhow!("Failed to encrypt keypair"))?;
+
+    let file = EncryptedKeypair {
+        version: ENCRYPTED_WALLET_VERSION,
+        kdf: "scrypt".to_string(),
+        log_n,
+        r,
+        p,
+        salt: bs58::encode(salt).into_string(),
+        nonce: bs58::encode(nonce).into_string(),
+        ciphertext: bs58::encode(ciphertext).into_string(),
+    };
+    Ok(serde_json::to_string_pretty(&file)?)
+}
+
+pub fn decrypt_keypair(contents: &str, password: &str) -> Result<Keypair> {
+    let file: EncryptedKeypair = serde_json::from_str(contents).context("Invalid encrypted wallet file")?;
+    if file.version != ENCRYPTED_WALLET_VERSION || file.kdf != "scrypt" {
+        return Err(anyhow!("Unsupported encrypted wallet version {} ({})", file.version, file.kdf));
+    }
+    let salt = bs58::decode(&file.salt).into_vec()?;
+    let nonce = bs58::decode(&file.nonce).into_vec()?;
+    let ciphertext = bs58::decode(&file.ciphertext).into_vec()?;
+    if nonce.len() != 12 {
+        return Err(anyhow!("Invalid nonce length"));
+    }
+    check_scrypt_params(file.log_n, file.r, file.p)?;
+
+    let key = derive_key(password, &salt, file.log_n, file.r, file.p)?;
+    let cipher = Aes256Gcm::new_from_slice(key.as_ref())?;
+    let secret = Zeroizing::new(
+        cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+            .map_err(|_| anyhow!("Wrong password or corrupted wallet file"))?,
+    );
+    Keypair::from_bytes(&secret).map_err(|_| anyhow!("Invalid keypair bytes"))
+}
+
+/// `wallet encrypt`: encrypts a plain keypair file with a password.
+pub fn encrypt_file(input: &str, output: &str) -> Result<()> {
+    let contents = Zeroizing::new(std::fs::read_to_string(input).with_context(|| format!("Failed to read {}", input))?);
+    let keypair = parse_keypair(&contents)?;
+    let password = Zeroizing::new(rpassword::prompt_password("New wallet password: ")?);
+    let confirm = Zeroizing::new(rpassword::prompt_password("Confirm password: ")?);
+    if *password != *confirm {
+        return Err(anyhow!("Passwords do not match"));
+    }
+    std::fs::write(output, encrypt_keypair(&keypair, &password)?)?;
+    println!("Encrypted wallet {} written to {}", keypair.pubkey(), output);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // N = 2^4 keeps the tests fast
+    fn encrypted(keypair: &Keypair) -> String {
+        encrypt_keypair_with(keypair, "hunter2", 4, 8, 1).unwrap()
+    }
+
+    fn edited(contents: &str, edit: impl FnOnce(&mut EncryptedKeypair)) -> String {
+        let mut file: EncryptedKeypair = serde_json::from_str(contents).unwrap();
+        edit(&mut file);
+        serde_json::to_string(&file).unwrap()
+    }
+
+    #[test]
+    fn decrypts_what_it_encrypted() {
+        let keypair = Keypair::new();
+        let contents = encrypted(&keypair);
+        assert_eq!(decrypt_keypair(&contents, "hunter2").unwrap().to_bytes(), keypair.to_bytes());
+        assert!(decrypt_keypair(&contents, "hunter3").is_err());
+    }
+
+    #[test]
+    fn rejects_tampered_and_unsupported_files() {
+        let contents = encrypted(&Keypair::new());
+        let tampered = edited(&contents, |file| {
+            let mut ciphertext = bs58::decode(&file.ciphertext).into_vec().unwrap();
+            ciphertext[0] ^= 1;
+            file.ciphertext = bs58::encode(ciphertext).into_string();
+        });
+        assert!(decrypt_keypair(&tampered, "hunter2").is_err());
+        assert!(decrypt_keypair(&edited(&contents, |file| file.version = 2), "hunter2").is_err());
+        assert!(decrypt_keypair(&edited(&contents, |file| file.kdf = "argon2".to_string()), "hunter2").is_err());
+    }
+
+    #[test]
+    fn caps_the_scrypt_parameters() {
+        let contents = encrypted(&Keypair::new());
+        for (log_n, r, p) in [(22, 8, 1), (15, 1 << 20, 1), (255, u32::MAX, 1), (4, 8, 1 << 20)] {
+            let oversized = edited(&contents, |file| (file.log_n, file.r, file.p) = (log_n, r, p));
+            let error = decrypt_keypair(&oversized, "hunter2").unwrap_err();
+            assert!(error.to_string().contains("Unsupported scrypt parameters"), "{}", error);
+        }
+        assert!(check_scrypt_params(SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P).is_ok());
+    }
+}
@@ -0,0 +1,140 @@
+use crate::events::Event;
+use crate::filter::{EventFilter, FilterParams};
+use crate::sniper::unix_now;
+use crate::store::{Store, WebhookRecord};
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::sync::{Arc, RwLock};
+use tracing::{info, warn};
+
+/// A webhook destination, as listed by the REST API.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Webhook {
+    pub id: i64,
+    pub url: String,
+    // signs each body with HMAC-SHA256 when set
+    #[serde(skip)]
+    pub secret: Option<String>,
+    pub filter: FilterParams,
+    pub created_at: u64,
+}
+
+impl Webhook {
+    /// The destination's host, for logs: chat webhook URLs carry their
+    /// credential in the path.
+    pub fn host(&self) -> String {
+        reqwest::Url::parse(&self.url).ok().and_then(|url| url.host_str().map(str::to_string)).unwrap_or_default()
+    }
+}
+
+/// A destination with its parsed filter.
+pub struct Destination {
+    pub webhook: Webhook,
+    pub filter: EventFilter,
+}
+
+/// The webhook destinations registered at runtime, kept in the store and
+/// mirrored in memory for the sink. Clones share the destinations, so the
+/// API's changes apply to the next event dispatched.
+#[derive(Clone)]
+pub struct Webhooks {
+    store: Arc<Store>,
+    destinations: Arc<RwLock<Vec<Arc<Destination>>>>,
+}
+
+impl Webhooks {
+    /// Loads the destinations registered by earlier runs.
+    pub fn load(store: Arc<Store>) -> Result<Self> {
+        let mut destinations = Vec::new();
+        for record in store.webhooks()? {
+            match destination(record) {
+                Ok(destination) => destinations.push(Arc::new(destination)),
+                Err(e) => warn!(error = %e, "Skipping unreadable webhook"),
+            }
+        }
+        if !destinations.is_empty() {
+            info!(webhooks = destinations.len(), "Loaded webhooks");
+        }
+        Ok(Self { store, destinations: Arc::new(RwLock::new(destinations)) })
+    }
+
+    /// An http(s) URL and a filter that parses, or why not.
+    pub fn validate(url: &str, filter: &FilterParams) -> Result<()> {
+        let parsed = reqwest::Url::parse(url).map_err(|e| anyhow!("invalid url {}: {}", url, e))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(anyhow!("invalid url {}: expected http or https", url));
+        }
+        filter.parse()?;
+        Ok(())
+    }
+
+    pub fn register(&self, url: &str, secret: Option<&str>, filter: FilterParams) -> Result<Webhook> {
+        Self::validate(url, &filter)?;
+        let created_at = unix_now();
+        let id = self.store.insert_webhook(url, secret, &serde_json::to_string(&filter)?, created_at)?;
+        let webhook = Webhook { id, url: url.to_string(), secret: secret.map(str::to_string), filter, created_at };
+        let destination = Destination { filter: webhook.filter.parse()?, webhook: webhook.clone() };
+        self.destinations.write().unwrap().push(Arc::new(destination));
+        info!(id, host = %webhook.host(), "Registered webhook");
+        Ok(webhook)
+    }
+
+    pub fn list(&self) -> Vec<Webhook> {
+        self.destinations.read().unwrap().iter().map(|destination| destination.webhook.clone()).collect()
+    }
+
+    /// False when there was no such webhook.
+    pub fn remove(&self, id: i64) -> Result<bool> {
+        let removed = self.store.delete_webhook(id)?;
+        self.destinations.write().unwrap().retain(|destination| destination.webhook.id != id);
+        if removed {
+            info!(id, "Removed webhook");
+        }
+        Ok(removed)
+    }
+
+    /// The destinations whose filter passes `event`.
+    pub fn matching(&self, event: &Event) -> Vec<Arc<Destination>> {
+        let destinations = self.destinations.read().unwrap();
+        destinations.iter().filter(|destination| destination.filter.matches(event)).cloned().collect()
+    }
+}
+
+fn destination(record: WebhookRecord) -> Result<Destination> {
+    let filter = serde_json::from_str::<FilterParams>(&record.filter)?;
+    let webhook = Webhook { id: record.id, url: record.url, secret: record.secret, filter, created_at: record.created_at };
+    Ok(Destination { filter: webhook.filter.parse()?, webhook })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::liquidity_pulled;
+    use solana_sdk::pubkey::Pubkey;
+
+    #[test]
+    fn registered_webhooks_persist_until_removed() {
+        let store = Arc::new(Store::open(":memory:").unwrap());
+        let webhooks = Webhooks::load(store.clone()).unwrap();
+        let pools = FilterParams { types: Some("new_pool".to_string()), ..FilterParams::default() };
+        let first = webhooks.register("https://example.com/pools", Some("secret"), pools.clone()).unwrap();
+        let second = webhooks.register("http://localhost:9000/all", None, FilterParams::default()).unwrap();
+        assert!(webhooks.register("ftp://example.com", None, FilterParams::default()).is_err());
+        let unknown_type = FilterParams { types: Some("new_pools".to_string()), ..FilterParams::default() };
+        assert!(webhooks.register("https://example.com", None, unknown_type).is_err());
+
+        let matching = webhooks.matching(&liquidity_pulled(Pubkey::new_unique(), "DOG/WSOL"));
+        assert_eq!(matching.iter().map(|destination| destination.webhook.id).collect::<Vec<_>>(), [second.id]);
+
+        let reloaded = Webhooks::load(store).unwrap();
+        let listed = reloaded.list();
+        assert_eq!(listed.iter().map(|webhook| webhook.id).collect::<Vec<_>>(), [first.id, second.id]);
+        assert_eq!(listed[0].filter, pools);
+        assert_eq!(listed[0].secret.as_deref(), Some("secret"));
+
+        assert!(reloaded.remove(first.id).unwrap());
+        assert!(!reloaded.remove(first.id).unwrap());
+        assert_eq!(reloaded.list().len(), 1);
+    }
+}
@@ -0,0 +1,163 @@
+//! Account resolution of v0 transactions whose pool accounts come from
+//! address lookup tables. The fixtures are getTransaction responses in both
+//! encodings: one calling initialize2 directly, one where a launchpad
+//! creates the pool through CPI. Both load the mints and vaults from two
+//! tables, so writable and readonly addresses interleave across them.
+//! Admin instructions are built by hand.
+
+use raydium_v4_monitor::decode::{
+    admin_instruction, compiled_instructions, initialize2, initialize2_instructions, parsed_instructions,
+    AdminInstruction, ResolvedInstruction, SetParams,
+};
+use raydium_v4_monitor::raydium::Initialize2Accounts;
+use raydium_v4_monitor::RAYDIUM_V4_PROGRAM_ID;
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
+use std::str::FromStr;
+
+const DIRECT: &str = include_str!("fixtures/v0_alt_initialize2.json");
+const DIRECT_PARSED: &str = include_str!("fixtures/v0_alt_initialize2_parsed.json");
+const CPI: &str = include_str!("fixtures/v0_alt_cpi_initialize2.json");
+const CPI_PARSED: &str = include_str!("fixtures/v0_alt_cpi_initialize2_parsed.json");
+
+const COIN_MINT: &str = "84G7MvymDDLQV7rFYpVRbuSh6wrD2skmr1DDuqPysUok";
+const PC_MINT: &str = "So11111111111111111111111111111111111111112";
+const COIN_VAULT: &str = "7WgZbzmoWz6ruAkrNfDz4FD5McNXccv4rD6EFzGWuW7w";
+const PC_VAULT: &str = "CCwBx2QdsHMxfpG9mU6BhBp62KWXKmvem1obzPzyC3dx";
+const AMM: &str = "HpzrRhsZnzrtL8a6wSvPsLzHjptjDKPQUSBeWk8Ap16s";
+const AMM_OPEN_ORDERS: &str = "7eqaAHZy6f5PhooF3SdbXRaZiQ8DrTkLZDZXaVfSxiSC";
+const AMM_TARGET_ORDERS: &str = "2uvSYBHwJPoJV961Hpug8xJQnCmwKuaGfVwvyffThh64";
+const SERUM_MARKET: &str = "Dy3WB9qoekLcdLRTtJnqUHK5tgEJ2P6aCw7do7h68XSj";
+const CREATOR: &str = "3VA193cvZsGYCfES7jynbsnSmEzpJgczFk73EPZCNbnw";
+const LAUNCHPAD: &str = "LanMV9sAd7wArD4vJFi2qDdfnVhFxYSUg6eADduJ3uj";
+
+fn fixture(json: &str) -> EncodedConfirmedTransactionWithStatusMeta {
+    serde_json::from_str(json).expect("fixture is a getTransaction response")
+}
+
+fn key(address: &str) -> Pubkey {
+    Pubkey::from_str(address).unwrap()
+}
+
+fn assert_pool_accounts(accounts: &Initialize2Accounts) {
+    assert_eq!(accounts.coin_mint, key(COIN_MINT));
+    assert_eq!(accounts.pc_mint, key(PC_MINT));
+    assert_eq!(accounts.coin_vault, key(COIN_VAULT));
+    assert_eq!(accounts.pc_vault, key(PC_VAULT));
+    assert_eq!(accounts.amm, key(AMM));
+    assert_eq!(accounts.amm_open_orders, key(AMM_OPEN_ORDERS));
+    assert_eq!(accounts.amm_target_orders, key(AMM_TARGET_ORDERS));
+    assert_eq!(accounts.serum_market, key(SERUM_MARKET));
+    assert_eq!(accounts.creator, key(CREATOR));
+}
+
+/// The single initialize2 of `json`, decoded from either encoding.
+fn pool_accounts(json: &str) -> Initialize2Accounts {
+    let tx = fixture(json);
+    let instructions = match compiled_instructions(&tx).unwrap() {
+        Some(instructions) => instructions,
+        None => parsed_instructions(&tx).unwrap(),
+    };
+    let mut pools = initialize2_instructions(&instructions);
+    let ix = pools.next().expect("an initialize2 instruction");
+    assert!(pools.next().is_none());
+    let (data, accounts) = initialize2(ix).unwrap();
+    assert_eq!(data.open_time, 1_712_345_678);
+    assert_eq!(data.init_pc_amount, 79_005_359_057);
+    assert_eq!(data.init_coin_amount, 206_900_000_000_000);
+    accounts
+}
+
+#[test]
+fn resolves_lookup_table_accounts_of_initialize2() {
+    assert_pool_accounts(&pool_accounts(DIRECT));
+}
+
+#[test]
+fn resolves_lookup_table_accounts_of_initialize2_through_cpi() {
+    assert_pool_accounts(&pool_accounts(CPI));
+}
+
+#[test]
+fn parsed_encoding_resolves_the_same_accounts() {
+    assert_pool_accounts(&pool_accounts(DIRECT_PARSED));
+    assert_pool_accounts(&pool_accounts(CPI_PARSED));
+}
+
+#[test]
+fn inner_instructions_follow_their_caller() {
+    // the Raydium program itself is loaded from a lookup table
+    let tx = fixture(CPI);
+    let instructions = compiled_instructions(&tx).unwrap().unwrap();
+    let programs = instructions.iter().map(|ix| ix.program_id).collect::<Vec<_>>();
+    assert_eq!(
+        programs,
+        [
+            solana_sdk::compute_budget::id(),
+            solana_sdk::compute_budget::id(),
+            key(LAUNCHPAD),
+            spl_token::id(),
+            key(RAYDIUM_V4_PROGRAM_ID),
+        ]
+    );
+
+    let tx = fixture(CPI_PARSED);
+    let instructions = parsed_instructions(&tx).unwrap();
+    let programs = instructions.iter().map(|ix| ix.program_id).collect::<Vec<_>>();
+    // the fully parsed Token instruction is left out
+    assert_eq!(
+        programs,
+        [
+            solana_sdk::compute_budget::id(),
+            solana_sdk::compute_budget::id(),
+            key(LAUNCHPAD),
+            key(RAYDIUM_V4_PROGRAM_ID),
+        ]
+    );
+}
+
+#[test]
+fn unresolved_lookup_tables_need_the_parsed_encoding() {
+    for json in [DIRECT, CPI] {
+        let mut tx: Value = serde_json::from_str(json).unwrap();
+        tx["meta"].as_object_mut().unwrap().remove("loadedAddresses");
+        let tx: EncodedConfirmedTransactionWithStatusMeta = serde_json::from_value(tx).unwrap();
+        assert!(compiled_instructions(&tx).unwrap().is_none());
+    }
+}
+
+#[test]
+fn rejects_indexes_past_the_loaded_addresses() {
+    let mut tx: Value = serde_json::from_str(CPI).unwrap();
+    tx["meta"]["loadedAddresses"]["readonly"].as_array_mut().unwrap().pop();
+    let tx: EncodedConfirmedTransactionWithStatusMeta = serde_json::from_value(tx).unwrap();
+    let error = compiled_instructions(&tx).err().expect("an out of range account index");
+    assert!(error.to_string().contains("out of range"), "{}", error);
+}
+
+#[test]
+fn decodes_admin_instructions_on_the_pool() {
+    let instruction = |data: Vec<u8>| ResolvedInstruction {
+        program_id: key(RAYDIUM_V4_PROGRAM_ID),
+        accounts: vec![spl_token::id(), key(AMM), Pubkey::new_unique()],
+        data,
+    };
+    let disable = instruction([vec![6, 0], 3u64.to_le_bytes().to_vec()].concat());
+    assert_eq!(
+        admin_instruction(&disable, &key(AMM)),
+        Some(AdminInstruction::SetParams(SetParams { param: 0, value: Some(3), swap_fee: None, new_owner: None }))
+    );
+    assert_eq!(admin_instruction(&disable, &key(CREATOR)), None);
+
+    let fees = [5u64, 100, 25, 10_000, 12, 100, 30, 10_000].iter().flat_map(|fee| fee.to_le_bytes()).collect::<Vec<_>>();
+    let Some(AdminInstruction::SetParams(set)) = admin_instruction(&instruction([vec![6, 9], fees].concat()), &key(AMM))
+    else {
+        panic!("a setParams instruction");
+    };
+    assert_eq!((set.param_name(), set.swap_fee), ("fees", Some((30, 10_000))));
+
+    assert_eq!(admin_instruction(&instruction(vec![7]), &key(AMM)), Some(AdminInstruction::WithdrawPnl));
+    // a swap
+    assert_eq!(admin_instruction(&instruction(vec![9; 17]), &key(AMM)), None);
+}
@@ -0,0 +1,351 @@
+//! End to end against a local validator: clones Raydium V4 and OpenBook from
+//! mainnet into `solana-test-validator`, lists an OpenBook market, creates a
+//! pool on it and checks that the running monitor records the pool and that
+//! `decode` reports it correctly.
+//!
+//! Needs `solana-test-validator` on PATH and access to mainnet for the
+//! clones, so it is ignored by default:
+//!
+//! ```bash
+//! cargo test --test localnet -- --ignored
+//! ```
+
+use raydium_v4_monitor::store::Store;
+use raydium_v4_monitor::RAYDIUM_V4_PROGRAM_ID;
+use serde_json::Value;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    compute_budget::ComputeBudgetInstruction,
+    instruction::{AccountMeta, Instruction},
+    native_token::sol_to_lamports,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    system_instruction, system_program, sysvar,
+    transaction::Transaction,
+};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+const RPC_URL: &str = "http://127.0.0.1:8899";
+const WS_URL: &str = "ws://127.0.0.1:8900";
+// the OpenBook (Serum v3) program Raydium V4 markets live on
+const OPENBOOK_PROGRAM_ID: &str = "srmqPvymJeFKQ4zGQed1GFppgkRHL9kaELCbyksJtPX";
+// Raydium's AMM config and pool creation fee accounts, checked by initialize2
+const AMM_CONFIG: &str = "9DCxsMizn3H1hprZ7xWe6LDzeUeZBksYFqjNNr5tW9sY";
+const CREATE_FEE_DESTINATION: &str = "7YttLkHDoNj9wyDur5pM1ejNaAvT9X4eqaYcHQqtj2G5";
+const ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(120);
+const DETECTION_TIMEOUT: Duration = Duration::from_secs(60);
+
+const COIN_DECIMALS: u8 = 6;
+const INIT_COIN_AMOUNT: u64 = 1_000_000_000_000;
+const INIT_PC_SOL: f64 = 10.0;
+
+/// A child process killed when the test ends, passing or not.
+struct Process(Child);
+
+impl Drop for Process {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+fn scratch_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("raydium-localnet-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn start_validator(dir: &Path) -> Process {
+    let child = Command::new("solana-test-validator")
+        .arg("--reset")
+        .arg("--quiet")
+        .args(["--ledger", dir.join("ledger").to_str().unwrap()])
+        .args(["--url", "mainnet-beta"])
+        .args(["--clone-upgradeable-program", RAYDIUM_V4_PROGRAM_ID])
+        .args(["--clone-upgradeable-program", OPENBOOK_PROGRAM_ID])
+        .args(["--clone", AMM_CONFIG, "--clone", CREATE_FEE_DESTINATION])
+        .stdout(Stdio::null())
+        .spawn()
+        .expect("solana-test-validator must be on PATH");
+    let validator = Process(child);
+    let rpc = RpcClient::new(RPC_URL.to_string());
+    let started = Instant::now();
+    while rpc.get_health().is_err() {
+        assert!(started.elapsed() < STARTUP_TIMEOUT, "validator did not become healthy");
+        std::thread::sleep(Duration::from_secs(1));
+    }
+    validator
+}
+
+/// Runs the monitor binary against the validator with its own store.
+fn start_monitor(dir: &Path, store_path: &Path) -> Process {
+    let config = dir.join("config.toml");
+    std::fs::write(
+        &config,
+        format!(
+            "rpc_url = \"{}\"\nws_url = \"{}\"\n\n[store]\npath = \"{}\"\n\n[clock]\ninterval_secs = 0\n",
+            RPC_URL,
+            WS_URL,
+            store_path.display()
+        ),
+    )
+    .unwrap();
+    let log = std::fs::File::create(dir.join("monitor.log")).unwrap();
+    let child = Command::new(env!("CARGO_BIN_EXE_raydium_v4_monitor"))
+        .env("RAYDIUM_MONITOR_CONFIG", &config)
+        .stdout(log.try_clone().unwrap())
+        .stderr(log)
+        .spawn()
+        .unwrap();
+    // time to subscribe before the pool is created
+    std::thread::sleep(Duration::from_secs(5));
+    Process(child)
+}
+
+fn send(rpc: &RpcClient, payer: &Keypair, instructions: &[Instruction], signers: &[&Keypair]) -> Signature {
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    let blockhash = rpc.get_latest_blockhash().unwrap();
+    let tx = Transaction::new_signed_with_payer(instructions, Some(&payer.pubkey()), &all_signers, blockhash);
+    rpc.send_and_confirm_transaction(&tx).unwrap()
+}
+
+fn create_account(rpc: &RpcClient, payer: &Keypair, account: &Keypair, space: usize, owner: &Pubkey) -> Instruction {
+    let lamports = rpc.get_minimum_balance_for_rent_exemption(space).unwrap();
+    system_instruction::create_account(&payer.pubkey(), &account.pubkey(), lamports, space as u64, owner)
+}
+
+/// A new SPL token account holding `lamports` on top of rent; for the
+/// native mint that is its balance.
+fn create_token_account(rpc: &RpcClient, payer: &Keypair, account: &Keypair, mint: &Pubkey, owner: &Pubkey, lamports: u64) -> Vec<Instruction> {
+    let space = spl_token::state::Account::LEN;
+    let rent = rpc.get_minimum_balance_for_rent_exemption(space).unwrap();
+    vec![
+        system_instruction::create_account(&payer.pubkey(), &account.pubkey(), rent + lamports, space as u64, &spl_token::id()),
+        spl_token::instruction::initialize_account(&spl_token::id(), &account.pubkey(), mint, owner).unwrap(),
+    ]
+}
+
+struct Market {
+    address: Pubkey,
+}
+
+/// Lists a permissionless OpenBook market for `coin_mint`/`pc_mint`, with
+/// the smallest queues and order books OpenBook accepts.
+fn create_market(rpc: &RpcClient, payer: &Keypair, coin_mint: &Pubkey, pc_mint: &Pubkey) -> Market {
+    let openbook = Pubkey::from_str(OPENBOOK_PROGRAM_ID).unwrap();
+    let market = Keypair::new();
+    let (vault_signer, vault_signer_nonce) = (0u64..)
+        .find_map(|nonce| {
+            Pubkey::create_program_address(&[market.pubkey().as_ref(), &nonce.to_le_bytes()], &openbook)
+                .ok()
+                .map(|signer| (signer, nonce))
+        })
+        .unwrap();
+
+    let coin_vault = Keypair::new();
+    let pc_vault = Keypair::new();
+    let mut instructions = create_token_account(rpc, payer, &coin_vault, coin_mint, &vault_signer, 0);
+    instructions.extend(create_token_account(rpc, payer, &pc_vault, pc_mint, &vault_signer, 0));
+    send(rpc, payer, &instructions, &[&coin_vault, &pc_vault]);
+
+    let request_queue = Keypair::new();
+    let event_queue = Keypair::new();
+    let bids = Keypair::new();
+    let asks = Keypair::new();
+    // sizes include OpenBook's 5 byte header and 7 bytes of padding
+    let instructions = [
+        create_account(rpc, payer, &market, 388, &openbook),
+        create_account(rpc, payer, &request_queue, 764, &openbook),
+        create_account(rpc, payer, &event_queue, 11_308, &openbook),
+        create_account(rpc, payer, &bids, 14_524, &openbook),
+        create_account(rpc, payer, &asks, 14_524, &openbook),
+    ];
+    send(rpc, payer, &instructions, &[&market, &request_queue, &event_queue, &bids, &asks]);
+
+    // version 0, InitializeMarket (tag 0): coin_lot_size, pc_lot_size,
+    // fee_rate_bps, vault_signer_nonce, pc_dust_threshold
+    let mut data = vec![0u8];
+    data.extend(0u32.to_le_bytes());
+    data.extend(1_000_000u64.to_le_bytes());
+    data.extend(1_000u64.to_le_bytes());
+    data.extend(0u16.to_le_bytes());
+    data.extend(vault_signer_nonce.to_le_bytes());
+    data.extend(100u64.to_le_bytes());
+    let accounts = vec![
+        AccountMeta::new(market.pubkey(), false),
+        AccountMeta::new(request_queue.pubkey(), false),
+        AccountMeta::new(event_queue.pubkey(), false),
+        AccountMeta::new(bids.pubkey(), false),
+        AccountMeta::new(asks.pubkey(), false),
+        AccountMeta::new(coin_vault.pubkey(), false),
+        AccountMeta::new(pc_vault.pubkey(), false),
+        AccountMeta::new_readonly(*coin_mint, false),
+        AccountMeta::new_readonly(*pc_mint, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+    send(rpc, payer, &[Instruction { program_id: openbook, accounts, data }], &[]);
+    Market { address: market.pubkey() }
+}
+
+/// The pool's accounts, derived the way Raydium V4 derives them from the
+/// market.
+struct Pool {
+    amm: Pubkey,
+    authority: Pubkey,
+    nonce: u8,
+    open_orders: Pubkey,
+    lp_mint: Pubkey,
+    coin_vault: Pubkey,
+    pc_vault: Pubkey,
+    target_orders: Pubkey,
+}
+
+impl Pool {
+    fn derive(market: &Pubkey) -> Self {
+        let raydium = Pubkey::from_str(RAYDIUM_V4_PROGRAM_ID).unwrap();
+        let associated =
+            |seed: &[u8]| Pubkey::find_program_address(&[raydium.as_ref(), market.as_ref(), seed], &raydium).0;
+        let (authority, nonce) = Pubkey::find_program_address(&[b"amm authority"], &raydium);
+        Self {
+            amm: associated(b"amm_associated_seed"),
+            authority,
+            nonce,
+            open_orders: associated(b"open_order_associated_seed"),
+            lp_mint: associated(b"lp_mint_associated_seed"),
+            coin_vault: associated(b"coin_vault_associated_seed"),
+            pc_vault: associated(b"pc_vault_associated_seed"),
+            target_orders: associated(b"target_associated_seed"),
+        }
+    }
+}
+
+fn associated_token_address(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    let program = Pubkey::from_str(ASSOCIATED_TOKEN_PROGRAM_ID).unwrap();
+    Pubkey::find_program_address(&[owner.as_ref(), spl_token::id().as_ref(), mint.as_ref()], &program).0
+}
+
+struct Launch {
+    signature: Signature,
+    amm: Pubkey,
+    coin_mint: Pubkey,
+    creator: Pubkey,
+}
+
+/// Mints a new token, lists it against WSOL and creates the Raydium pool
+/// with initialize2.
+fn launch_pool(rpc: &RpcClient) -> Launch {
+    let payer = Keypair::new();
+    let airdrop = rpc.request_airdrop(&payer.pubkey(), sol_to_lamports(100.0)).unwrap();
+    let started = Instant::now();
+    while !rpc.confirm_transaction(&airdrop).unwrap() {
+        assert!(started.elapsed() < STARTUP_TIMEOUT, "airdrop not confirmed");
+        std::thread::sleep(Duration::from_millis(500));
+    }
+
+    let wsol = spl_token::native_mint::id();
+    let coin_mint = Keypair::new();
+    let user_coin = Keypair::new();
+    let user_pc = Keypair::new();
+    let mut instructions = vec![
+        create_account(rpc, &payer, &coin_mint, spl_token::state::Mint::LEN, &spl_token::id()),
+        spl_token::instruction::initialize_mint(&spl_token::id(), &coin_mint.pubkey(), &payer.pubkey(), None, COIN_DECIMALS)
+            .unwrap(),
+    ];
+    instructions.extend(create_token_account(rpc, &payer, &user_coin, &coin_mint.pubkey(), &payer.pubkey(), 0));
+    instructions.push(
+        spl_token::instruction::mint_to(&spl_token::id(), &coin_mint.pubkey(), &user_coin.pubkey(), &payer.pubkey(), &[], INIT_COIN_AMOUNT)
+            .unwrap(),
+    );
+    instructions.extend(create_token_account(rpc, &payer, &user_pc, &wsol, &payer.pubkey(), sol_to_lamports(INIT_PC_SOL)));
+    send(rpc, &payer, &instructions, &[&coin_mint, &user_coin, &user_pc]);
+
+    let market = create_market(rpc, &payer, &coin_mint.pubkey(), &wsol);
+    let pool = Pool::derive(&market.address);
+
+    // discriminator, nonce, open_time (0 opens at once), init_pc_amount,
+    // init_coin_amount
+    let mut data = vec![1, pool.nonce];
+    data.extend(0u64.to_le_bytes());
+    data.extend(sol_to_lamports(INIT_PC_SOL).to_le_bytes());
+    data.extend(INIT_COIN_AMOUNT.to_le_bytes());
+    let accounts = vec![
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(Pubkey::from_str(ASSOCIATED_TOKEN_PROGRAM_ID).unwrap(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new(pool.amm, false),
+        AccountMeta::new_readonly(pool.authority, false),
+        AccountMeta::new(pool.open_orders, false),
+        AccountMeta::new(pool.lp_mint, false),
+        AccountMeta::new_readonly(coin_mint.pubkey(), false),
+        AccountMeta::new_readonly(wsol, false),
+        AccountMeta::new(pool.coin_vault, false),
+        AccountMeta::new(pool.pc_vault, false),
+        AccountMeta::new(pool.target_orders, false),
+        AccountMeta::new_readonly(Pubkey::from_str(AMM_CONFIG).unwrap(), false),
+        AccountMeta::new(Pubkey::from_str(CREATE_FEE_DESTINATION).unwrap(), false),
+        AccountMeta::new_readonly(Pubkey::from_str(OPENBOOK_PROGRAM_ID).unwrap(), false),
+        AccountMeta::new_readonly(market.address, false),
+        AccountMeta::new(payer.pubkey(), true),
+        AccountMeta::new(user_coin.pubkey(), false),
+        AccountMeta::new(user_pc.pubkey(), false),
+        AccountMeta::new(associated_token_address(&payer.pubkey(), &pool.lp_mint), false),
+    ];
+    let initialize2 = Instruction { program_id: Pubkey::from_str(RAYDIUM_V4_PROGRAM_ID).unwrap(), accounts, data };
+    let signature = send(rpc, &payer, &[ComputeBudgetInstruction::set_compute_unit_limit(400_000), initialize2], &[]);
+    Launch { signature, amm: pool.amm, coin_mint: coin_mint.pubkey(), creator: payer.pubkey() }
+}
+
+#[test]
+#[ignore = "needs solana-test-validator and mainnet access for the program clones"]
+fn detects_and_decodes_new_pool() {
+    let dir = scratch_dir();
+    let store_path = dir.join("monitor.db");
+    let _validator = start_validator(&dir);
+    let _monitor = start_monitor(&dir, &store_path);
+    let rpc = RpcClient::new_with_commitment(RPC_URL.to_string(), CommitmentConfig::confirmed());
+
+    let launch = launch_pool(&rpc);
+
+    // detected through the log subscription and recorded in the store
+    let store = Store::open(store_path.to_str().unwrap()).unwrap();
+    let started = Instant::now();
+    while !store.has_pool_signature(&launch.signature.to_string()).unwrap() {
+        assert!(
+            started.elapsed() < DETECTION_TIMEOUT,
+            "pool not detected, see {}",
+            dir.join("monitor.log").display()
+        );
+        std::thread::sleep(Duration::from_secs(1));
+    }
+
+    // decoded like the live monitor does
+    let output = Command::new(env!("CARGO_BIN_EXE_raydium_v4_monitor"))
+        .args(["decode", &launch.signature.to_string()])
+        .env("RAYDIUM_MONITOR_CONFIG", dir.join("config.toml"))
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "decode failed: {}", String::from_utf8_lossy(&output.stderr));
+    let event: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let amount = |field: &str| event[field].as_str().and_then(|amount| amount.parse::<f64>().ok());
+    assert_eq!(event["type"], "new_pool");
+    assert_eq!(event["amm"], launch.amm.to_string());
+    assert_eq!(event["creator"], launch.creator.to_string());
+    assert_eq!(event["coin_mint"], launch.coin_mint.to_string());
+    assert_eq!(event["pc_mint"], spl_token::native_mint::id().to_string());
+    assert_eq!(event["quote_side"], "pc");
+    assert_eq!(event["pair_type"], "sol");
+    assert_eq!(amount("init_coin_amount"), Some(INIT_COIN_AMOUNT as f64 / 10f64.powi(COIN_DECIMALS as i32)));
+    assert_eq!(amount("init_pc_amount"), Some(INIT_PC_SOL));
+    assert_eq!(event["liquidity_sol"].as_f64(), Some(INIT_PC_SOL));
+    assert_eq!(event["flags"], serde_json::json!(["mint"]));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}